@@ -0,0 +1,62 @@
+use binary_mirror::{DynamicField, DynamicMirror, FieldKind, LayoutError};
+use serde::Deserialize;
+use serde_json::Value;
+
+/// Record layout loaded from JSON, one entry per field in wire order
+#[derive(Debug, Deserialize)]
+pub struct Layout {
+    pub name: String,
+    pub fields: Vec<LayoutField>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LayoutField {
+    pub name: String,
+    pub size: usize,
+    /// Same names as the `type` attribute of `#[bm(...)]`, untyped fields are skipped
+    #[serde(rename = "type", default)]
+    pub type_name: Option<String>,
+    #[serde(default)]
+    pub format: Option<String>,
+}
+
+/// A decoded field, `None` for fields the mirror leaves out such as padding, null when the
+/// bytes could not be parsed
+pub struct Decoded<'a> {
+    pub field: &'a DynamicField,
+    pub bytes: &'a [u8],
+    pub value: Option<Value>,
+}
+
+impl Layout {
+    /// The fields one after the other as a `DynamicMirror`, Err for an unknown type
+    pub fn to_mirror(&self) -> Result<DynamicMirror, LayoutError> {
+        let mut mirror = DynamicMirror::new();
+        for field in &self.fields {
+            let kind = match field.type_name.as_deref() {
+                Some(type_name) => FieldKind::from_type_name(type_name).ok_or_else(|| LayoutError {
+                    line: 0,
+                    message: format!("unknown type \"{}\" of field `{}`", type_name, field.name),
+                })?,
+                None => FieldKind::Untyped,
+            };
+            let offset = mirror.size();
+            mirror = mirror.field_at(&field.name, offset, field.size, kind, field.format.as_deref())?;
+        }
+        Ok(mirror)
+    }
+}
+
+/// Decode a record of `mirror.size()` bytes, every field of the layout in order
+pub fn decode<'a>(mirror: &'a DynamicMirror, record: &'a [u8]) -> Result<Vec<Decoded<'a>>, String> {
+    let mut values = mirror.decode(record).map_err(|e| e.to_string())?;
+    Ok(mirror
+        .fields()
+        .iter()
+        .map(|field| Decoded {
+            field,
+            bytes: &record[field.offset..field.offset + field.size],
+            value: values.remove(&field.name),
+        })
+        .collect())
+}
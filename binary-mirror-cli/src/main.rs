@@ -0,0 +1,183 @@
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use binary_mirror::DynamicMirror;
+use clap::{Args, Parser, Subcommand};
+use serde_json::Value;
+
+mod layout;
+
+use layout::{Decoded, Layout};
+
+/// Decode and inspect fixed-length binary records described by a JSON layout
+#[derive(Parser)]
+#[command(version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print each record as one JSON object per line
+    Decode(Input),
+    /// Print each record as `field: value` lines
+    Pretty(Input),
+    /// Report fields that fail to parse, exiting with 1 if any do
+    Validate(Input),
+    /// Print offsets, hex and ASCII of each record, labelled with field names
+    Hexdump(Input),
+}
+
+#[derive(Args)]
+struct Input {
+    /// JSON layout: {"name": .., "fields": [{"name": .., "size": .., "type": .., "format": ..}]}
+    #[arg(short, long)]
+    layout: PathBuf,
+    /// Records are separated by newlines instead of being concatenated
+    #[arg(long)]
+    lines: bool,
+    /// Input file, stdin when omitted
+    file: Option<PathBuf>,
+}
+
+impl Input {
+    fn load(&self) -> Result<(Layout, DynamicMirror, Vec<u8>), String> {
+        let layout = std::fs::read_to_string(&self.layout)
+            .map_err(|e| format!("failed to read {}: {}", self.layout.display(), e))?;
+        let layout: Layout = serde_json::from_str(&layout)
+            .map_err(|e| format!("invalid layout {}: {}", self.layout.display(), e))?;
+        let mirror = layout
+            .to_mirror()
+            .map_err(|e| format!("invalid layout {}: {}", self.layout.display(), e.message))?;
+        let data = match &self.file {
+            Some(path) => std::fs::read(path).map_err(|e| format!("failed to read {}: {}", path.display(), e))?,
+            None => {
+                let mut data = Vec::new();
+                std::io::stdin()
+                    .read_to_end(&mut data)
+                    .map_err(|e| format!("failed to read stdin: {}", e))?;
+                data
+            }
+        };
+        Ok((layout, mirror, data))
+    }
+
+    fn records<'a>(&self, layout: &Layout, mirror: &DynamicMirror, data: &'a [u8]) -> Result<Vec<&'a [u8]>, String> {
+        let size = mirror.size();
+        let records: Vec<&[u8]> = if self.lines {
+            data.split(|&b| b == b'\n')
+                .map(|line| line.strip_suffix(b"\r").unwrap_or(line))
+                .filter(|line| !line.is_empty())
+                .collect()
+        } else if size == 0 {
+            Vec::new()
+        } else {
+            data.chunks(size).collect()
+        };
+        match records.iter().position(|record| record.len() != size) {
+            Some(i) => Err(format!(
+                "record {} is {} bytes but {} expects {} bytes, content: \"{}\"",
+                i,
+                records[i].len(),
+                layout.name,
+                size,
+                binary_mirror::to_bytes_repr(records[i])
+            )),
+            None => Ok(records),
+        }
+    }
+}
+
+fn is_failure(decoded: &Decoded) -> bool {
+    decoded.value.as_ref().is_some_and(Value::is_null) && !binary_mirror::is_blank(decoded.bytes)
+}
+
+fn hexdump(record: &[u8], fields: &[Decoded]) -> String {
+    let specs = fields
+        .iter()
+        .map(|decoded| {
+            let spec = binary_mirror::FieldSpec {
+                offset: decoded.field.offset,
+                limit: decoded.field.offset + decoded.field.size,
+                size: decoded.field.size,
+                type_name: None,
+            };
+            (decoded.field.name.as_str(), spec)
+        })
+        .collect::<Vec<_>>();
+    binary_mirror::hexdump(record, &specs)
+}
+
+fn run(cli: Cli) -> Result<bool, String> {
+    let input = match &cli.command {
+        Command::Decode(input)
+        | Command::Pretty(input)
+        | Command::Validate(input)
+        | Command::Hexdump(input) => input,
+    };
+    let (layout, mirror, data) = input.load()?;
+    let records = input.records(&layout, &mirror, &data)?;
+    let mut out = std::io::stdout().lock();
+    let mut valid = true;
+    for (index, record) in records.iter().enumerate() {
+        let fields = layout::decode(&mirror, record)?;
+        let result = match &cli.command {
+            Command::Decode(_) => {
+                let entries = fields
+                    .iter()
+                    .filter_map(|decoded| Some((&decoded.field.name, decoded.value.as_ref()?)));
+                // Serialized as a map in layout order, a `serde_json::Map` would sort the keys
+                serde::Serializer::collect_map(&mut serde_json::Serializer::new(&mut out), entries)
+                    .map_err(std::io::Error::from)
+                    .and_then(|_| writeln!(out))
+            }
+            Command::Pretty(_) => {
+                let width = fields.iter().map(|decoded| decoded.field.name.len()).max().unwrap_or(0);
+                writeln!(out, "{} #{}", layout.name, index).and_then(|_| {
+                    fields.iter().try_for_each(|decoded| match &decoded.value {
+                        Some(value) if !value.is_null() => writeln!(out, "  {:width$}: {}", decoded.field.name, value),
+                        _ if is_failure(decoded) => writeln!(
+                            out,
+                            "  {:width$}: <unparsed \"{}\">",
+                            decoded.field.name,
+                            binary_mirror::to_bytes_repr(decoded.bytes)
+                        ),
+                        _ => writeln!(
+                            out,
+                            "  {:width$}: \"{}\"",
+                            decoded.field.name,
+                            binary_mirror::to_bytes_repr(decoded.bytes)
+                        ),
+                    })
+                })
+            }
+            Command::Validate(_) => fields.iter().filter(|decoded| is_failure(decoded)).try_for_each(|decoded| {
+                valid = false;
+                writeln!(
+                    out,
+                    "record {}: failed to parse {} as {}, content: \"{}\"",
+                    index,
+                    decoded.field.name,
+                    decoded.field.kind.type_name().unwrap_or_default(),
+                    binary_mirror::to_bytes_repr(decoded.bytes)
+                )
+            }),
+            Command::Hexdump(_) => write!(out, "{} #{}\n{}", layout.name, index, hexdump(record, &fields)),
+        };
+        result.map_err(|e| format!("failed to write output: {}", e))?;
+    }
+    Ok(valid)
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse()) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(message) => {
+            eprintln!("error: {}", message);
+            ExitCode::from(2)
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+const LAYOUT: &str = r#"{"name": "Trade", "fields": [
+    {"name": "name", "size": 10, "type": "str"},
+    {"name": "value", "size": 4, "type": "i32"},
+    {"name": "pad", "size": 2},
+    {"name": "dt", "size": 14, "type": "datetime", "format": "%Y%m%d%H%M%S"}
+]}"#;
+
+fn run(args: &[&str], input: &[u8]) -> (i32, String) {
+    run_layout(LAYOUT, args, input)
+}
+
+fn run_layout(layout_json: &str, args: &[&str], input: &[u8]) -> (i32, String) {
+    static COUNT: AtomicUsize = AtomicUsize::new(0);
+    let layout = std::env::temp_dir().join(format!(
+        "binary_mirror_cli_{}_{}.json",
+        std::process::id(),
+        COUNT.fetch_add(1, Ordering::Relaxed)
+    ));
+    std::fs::write(&layout, layout_json).unwrap();
+    let mut child = Command::new(env!("CARGO_BIN_EXE_binary-mirror-cli"))
+        .args(args)
+        .arg("--layout")
+        .arg(&layout)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(input).unwrap();
+    let output = child.wait_with_output().unwrap();
+    (
+        output.status.code().unwrap(),
+        String::from_utf8(output.stdout).unwrap(),
+    )
+}
+
+#[test]
+fn test_decode() {
+    let (code, stdout) = run(
+        &["decode"],
+        b"Hello     123   20240101123456World     abc   20240101123456",
+    );
+    assert_eq!(code, 0);
+    assert_eq!(
+        stdout,
+        "{\"name\":\"Hello\",\"value\":123,\"dt\":\"2024-01-01T12:34:56\"}\n\
+         {\"name\":\"World\",\"value\":null,\"dt\":\"2024-01-01T12:34:56\"}\n"
+    );
+}
+
+#[test]
+fn test_decode_like_dynamic_mirror() {
+    let layout = r#"{"name": "Price", "fields": [{"name": "price", "size": 8, "type": "decimal"}]}"#;
+    let (code, stdout) = run_layout(layout, &["decode"], b"0012.500");
+    assert_eq!(code, 0);
+    let mirror = binary_mirror::DynamicMirror::new()
+        .field("price", binary_mirror::FieldKind::Decimal, 8)
+        .unwrap();
+    assert_eq!(stdout.trim_end(), mirror.decode_value(b"0012.500").unwrap().to_string());
+    assert_eq!(stdout, "{\"price\":\"12.5\"}\n");
+}
+
+#[test]
+fn test_validate_and_lines() {
+    let (code, stdout) = run(
+        &["validate", "--lines"],
+        b"Hello     123   20240101123456\nWorld     abc   20240101123456\n",
+    );
+    assert_eq!(code, 1);
+    assert_eq!(stdout, "record 1: failed to parse value as i32, content: \"abc \"\n");
+}
+
+#[test]
+fn test_validate_range() {
+    let layout = r#"{"name": "Qty", "fields": [{"name": "qty", "size": 5, "type": "i16"}]}"#;
+    let (code, stdout) = run_layout(layout, &["validate"], b"1234599999");
+    assert_eq!(code, 1);
+    assert_eq!(stdout, "record 1: failed to parse qty as i16, content: \"99999\"\n");
+}
+
+#[test]
+fn test_unknown_type() {
+    let layout = r#"{"name": "Qty", "fields": [{"name": "qty", "size": 5, "type": "i23"}]}"#;
+    let (code, stdout) = run_layout(layout, &["validate"], b"12345");
+    assert_eq!(code, 2);
+    assert!(stdout.is_empty());
+}
+
+#[test]
+fn test_hexdump() {
+    let (code, stdout) = run(&["hexdump"], b"Hello     123   20240101123456");
+    assert_eq!(code, 0);
+    assert!(stdout.starts_with("Trade #0\n00000000  48 65 6c 6c 6f 20 20 20 20 20"));
+    assert!(stdout.contains("0000000a  31 32 33 20"));
+    assert!(stdout.trim_end().ends_with("|20240101123456  |  dt"));
+}
+
+#[test]
+fn test_size_mismatch() {
+    let (code, stdout) = run(&["decode"], b"too short");
+    assert_eq!(code, 2);
+    assert!(stdout.is_empty());
+}
@@ -1,31 +1,156 @@
 use proc_macro::TokenStream;
-use quote::quote;
+use quote::{quote, ToTokens};
+use syn::spanned::Spanned;
 use syn::{parse_macro_input, Data, DeriveInput, Fields, LitStr};
 
+/// Derive the native struct, conversions and tooling of a `#[repr(C)]` record of byte arrays
+///
+/// A struct with type parameters, one per nested payload, only gets the methods that don't
+/// need its offsets: it leaves out the layout table, field specs, `diff`, `inspect`, the raw
+/// builder and default, `to_raw()`, CSV, SQL, JSON, FIX, Arrow, schema exports,
+/// `native_struct_code`, `Reflect` and `Arbitrary`. `size`, `serde_raw`, `version`, `cast` and `fix_tag` are rejected on it.
 #[proc_macro_derive(BinaryMirror, attributes(bm))]
 pub fn binary_mirror_derive(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     impl_binary_mirror(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+#[proc_macro_derive(BinaryMessage, attributes(bmsg))]
+pub fn binary_message_derive(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    impl_binary_message(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
 }
 
 #[proc_macro_derive(BinaryEnum, attributes(bv))]
 pub fn binary_enum_derive(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     impl_binary_enum(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
 }
 
 #[derive(Debug, Clone)]
 struct FieldAttrs {
     type_name: String,
+    type_span: proc_macro2::Span,
     alias: Option<String>,
     format: Option<String>,
     datetime_with: Option<String>,
+    subsec_with: Option<String>,
+    assume_tz: Option<String>,
+    to_tz: Option<String>,
+    precision: Option<usize>,
+    rounding: Option<String>,
+    sign: Option<String>,
+    numeric_locale: Option<NumericLocaleAttrs>,
+    fill: Option<u8>,
     skip: bool,
     skip_native: bool,
+    /// Keep the bytes of a `skip_native` field in a hidden native field so `from_native` writes them back
+    carry_raw: bool,
     enum_type: Option<String>,
     default_byte: Option<u8>,
     ignore_warn: bool,
     default_func: Option<String>,
+    validate: Option<ValidateAttrs>,
+    algo: Option<String>,
+    over: Option<String>,
+    parse_with: Option<String>,
+    native_type: Option<String>,
+    write_with: Option<String>,
+    required: bool,
+    none_value: Option<Vec<u8>>,
+    none_if_blank: bool,
+    native_serde: Option<proc_macro2::TokenStream>,
+    /// Attributes other than `serde` copied onto the native field by `native_attrs`
+    native_attrs: Vec<syn::Meta>,
+    /// How a `bytes` field serializes in the native struct, `hex` or `base64`
+    bytes_repr: Option<String>,
+    /// FIX tag the field maps to in `to_fix`
+    fix_tag: Option<u32>,
+    /// Shown as `***` by the generated Debug and Display
+    redact: bool,
+    /// Also serialized as `"***"` by the native struct, implies `redact`
+    redact_serde: bool,
+    flatten: Option<String>,
+    overlay: Option<String>,
+    /// The overlay is read only while the named field holds these bytes
+    when: Option<(String, Vec<u8>)>,
+    since: Option<u32>,
+    upgrade_with: Option<String>,
+    /// A field typed with one of the struct's type parameters, a nested struct chosen by the caller
+    generic: bool,
+}
+
+impl FieldAttrs {
+    fn new(type_span: proc_macro2::Span) -> Self {
+        FieldAttrs {
+            type_name: String::new(),
+            type_span,
+            alias: None,
+            format: None,
+            datetime_with: None,
+            subsec_with: None,
+            assume_tz: None,
+            to_tz: None,
+            precision: None,
+            rounding: None,
+            sign: None,
+            numeric_locale: None,
+            fill: None,
+            skip: false,
+            skip_native: false,
+            carry_raw: false,
+            enum_type: None,
+            default_byte: None,
+            ignore_warn: false,
+            default_func: None,
+            validate: None,
+            algo: None,
+            over: None,
+            parse_with: None,
+            native_type: None,
+            write_with: None,
+            required: false,
+            none_value: None,
+            none_if_blank: false,
+            native_serde: None,
+            native_attrs: Vec::new(),
+            bytes_repr: None,
+            fix_tag: None,
+            redact: false,
+            redact_serde: false,
+            flatten: None,
+            overlay: None,
+            when: None,
+            since: None,
+            upgrade_with: None,
+            generic: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct ValidateAttrs {
+    min: Option<String>,
+    max: Option<String>,
+    min_len: Option<usize>,
+    max_len: Option<usize>,
+    regex: Option<String>,
+    ascii: bool,
+}
+
+/// Separators of `numeric_locale(decimal = ',', thousands = '.', group)`
+#[derive(Debug, Clone)]
+struct NumericLocaleAttrs {
+    decimal: char,
+    thousands: Option<char>,
+    /// Write `thousands` separators too, rather than only accepting them
+    group: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -33,6 +158,10 @@ struct OriginField {
     name: syn::Ident,
     size: usize,
     attrs: Option<FieldAttrs>,
+    /// Byte filling the field when it has no value, from the field or the struct
+    default_byte: u8,
+    /// The `///` comments on the raw field, one line each
+    doc: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -43,6 +172,8 @@ struct NativeField {
     pure_ty: proc_macro2::TokenStream,
     origin_fields: Vec<OriginField>,
     is_combined_datetime: bool,
+    /// Time zones the raw bytes and the native value are in, from `assume_tz` and `to`
+    tz: Option<(proc_macro2::TokenStream, proc_macro2::TokenStream)>,
     default_func: Option<String>,
     skip_native: bool,
 }
@@ -53,52 +184,407 @@ struct NativeField2OriginFieldMap {
     native_field: Option<NativeField>,
 }
 
+/// Type parameters of the derived struct, every part is empty for a plain struct
+struct StructGenerics {
+    /// The type parameters, each the type of a generic field
+    params: Vec<syn::Ident>,
+    /// `<P>` for `impl` headers of the raw struct
+    impl_generics: proc_macro2::TokenStream,
+    /// The bounds generic fields need, `where P: FromBytes + ToNative + ...`
+    where_clause: proc_macro2::TokenStream,
+    /// The raw struct, `Envelope<P>`
+    ty: proc_macro2::TokenStream,
+    /// The native struct of `ty`, `EnvelopeNative<<P as ToNative>::Native>`
+    native_ty: proc_macro2::TokenStream,
+    /// `<P>` of the native struct, which takes the native payloads as its parameters
+    native_params: proc_macro2::TokenStream,
+}
+
 #[derive(Debug, Clone)]
 struct StructAttrs {
     derives: Vec<syn::Path>,
+    size: Option<usize>,
+    serde_raw: bool,
+    frame: Option<proc_macro2::TokenStream>,
+    version: Option<u32>,
+    previous: Option<syn::Path>,
+    version_field: Option<syn::Ident>,
+    log: LogBackend,
+    on_error: Option<syn::Path>,
+    /// Fallback `default_byte` for fields that do not set their own
+    default_byte: Option<u8>,
+    computed: Vec<ComputedField>,
+    /// Carry the original bytes in the native struct so unchanged fields write back as they were
+    preserve_unknown: bool,
+    views: Vec<ViewStruct>,
+    /// Traits implemented over the raw bytes, from `raw_derive(...)`
+    raw_derives: Vec<syn::Ident>,
+    /// Library the byte casts go through instead of this crate's own, from `cast = "..."`
+    cast: Option<CastBackend>,
+    /// Style of the generated `Display`, None with `display = false`
+    display: Option<DisplayFormat>,
+    /// `Default` and `new` for the raw struct, from `raw_default = true`
+    raw_default: bool,
+}
+
+/// `#[bm(display_format = "...")]`: `Name { a: 1, b: 2 }`, one field per line, or `a=1 b=2`
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DisplayFormat {
+    Compact,
+    Multiline,
+    KeyValue,
+}
+
+/// `#[bm(cast = "bytemuck")]` implements `Pod` for the raw struct, `#[bm(cast = "zerocopy")]`
+/// relies on zerocopy's own derives
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CastBackend {
+    Bytemuck,
+    Zerocopy,
+}
+
+/// A lightweight native struct from `#[bm(view(name = "...", fields(...)))]`, converting
+/// only the listed fields
+#[derive(Debug, Clone)]
+struct ViewStruct {
+    name: syn::Ident,
+    fields: Vec<syn::Ident>,
+}
+
+/// A native-only field from `#[bm(computed(name = "...", type = "...", with = "..."))]`,
+/// set by calling `with` on the native struct once the other fields are converted
+#[derive(Debug, Clone)]
+struct ComputedField {
+    name: syn::Ident,
+    ty: syn::Type,
+    with: syn::Path,
+}
+
+/// Where the `_with_warn` methods report parse failures, set with `#[bm(log = "...")]`
+#[derive(Debug, Clone, Copy)]
+enum LogBackend {
+    Tracing,
+    Log,
+    None,
+}
+
+/// Statements run when a field fails to parse: the log warning (left out for `ignore_warn`
+/// fields), the metrics counter, then the struct's `on_error` function or the global parse
+/// failure hook
+fn get_parse_failure(
+    struct_name: &syn::Ident,
+    struct_attrs: &StructAttrs,
+    field: &NativeField,
+) -> proc_macro2::TokenStream {
+    let ignore_warn = field.origin_fields[0].attrs.as_ref().is_some_and(|attrs| attrs.ignore_warn);
+    let warn = if ignore_warn {
+        quote! {}
+    } else {
+        struct_attrs.log.warn(&field.name)
+    };
+    let report = get_parse_failure_report(struct_name, struct_attrs, field);
+    quote! {
+        #warn
+        #report
+    }
+}
+
+/// `get_parse_failure` without the log warning, for `to_native_with_report()` which returns
+/// the failures instead of logging them
+fn get_parse_failure_report(
+    struct_name: &syn::Ident,
+    struct_attrs: &StructAttrs,
+    field: &NativeField,
+) -> proc_macro2::TokenStream {
+    let name = &field.name;
+    let origin_field = &field.origin_fields[0].name;
+    let hook = match &struct_attrs.on_error {
+        Some(on_error) => quote! { #on_error },
+        None => quote! { binary_mirror::parse_failure },
+    };
+    quote! {
+        binary_mirror::count_parse_failure(stringify!(#struct_name), stringify!(#name));
+        #hook(stringify!(#struct_name), stringify!(#name), &self.#origin_field);
+    }
+}
+
+impl LogBackend {
+    fn warn(self, name: &syn::Ident) -> proc_macro2::TokenStream {
+        match self {
+            LogBackend::Tracing => {
+                quote! { tracing::warn!("Failed to parse {} in {:?}", stringify!(#name), self); }
+            }
+            LogBackend::Log => {
+                quote! { log::warn!("Failed to parse {} in {:?}", stringify!(#name), self); }
+            }
+            LogBackend::None => quote! {},
+        }
+    }
 }
 
-fn get_struct_attrs(input: &DeriveInput) -> StructAttrs {
+fn get_struct_attrs(input: &DeriveInput) -> syn::Result<StructAttrs> {
     let attrs = &input.attrs;
-    let mut struct_attrs = StructAttrs { derives: vec![] };
+    let mut struct_attrs = StructAttrs {
+        derives: vec![],
+        size: None,
+        serde_raw: false,
+        frame: None,
+        version: None,
+        previous: None,
+        version_field: None,
+        log: LogBackend::Tracing,
+        on_error: None,
+        default_byte: None,
+        computed: vec![],
+        preserve_unknown: false,
+        views: vec![],
+        raw_derives: vec![],
+        cast: None,
+        display: Some(DisplayFormat::Compact),
+        raw_default: false,
+    };
+    let mut display = true;
+    let mut display_format = None;
     for attr in attrs {
         if attr.path().is_ident("bm") {
-            let _ = attr.parse_nested_meta(|meta| {
+            attr.parse_nested_meta(|meta| {
                 if meta.path.is_ident("derive") {
                     let content;
                     syn::parenthesized!(content in meta.input);
-                    let derives: syn::punctuated::Punctuated<syn::Path, syn::Token![,]> = content
-                        .parse_terminated(syn::parse::Parse::parse, syn::Token![,])
-                        .expect("derive");
+                    let derives: syn::punctuated::Punctuated<syn::Path, syn::Token![,]> =
+                        content.parse_terminated(syn::parse::Parse::parse, syn::Token![,])?;
                     struct_attrs.derives = derives.into_iter().collect();
+                } else if meta.path.is_ident("raw_derive") {
+                    let content;
+                    syn::parenthesized!(content in meta.input);
+                    let derives: syn::punctuated::Punctuated<syn::Ident, syn::Token![,]> =
+                        content.parse_terminated(syn::parse::Parse::parse, syn::Token![,])?;
+                    for derive in derives {
+                        if !RAW_DERIVES.contains(&derive.to_string().as_str()) {
+                            return Err(syn::Error::new_spanned(
+                                &derive,
+                                unknown_message("raw_derive", &derive.to_string(), RAW_DERIVES),
+                            ));
+                        }
+                        struct_attrs.raw_derives.push(derive);
+                    }
+                } else if meta.path.is_ident("cast") {
+                    let lit = meta.value()?.parse::<syn::LitStr>()?;
+                    struct_attrs.cast = match lit.value().as_str() {
+                        "bytemuck" => Some(CastBackend::Bytemuck),
+                        "zerocopy" => Some(CastBackend::Zerocopy),
+                        _ => {
+                            return Err(syn::Error::new_spanned(
+                                lit,
+                                "cast must be one of \"bytemuck\" or \"zerocopy\"",
+                            ))
+                        }
+                    };
+                } else if meta.path.is_ident("display") {
+                    display = meta.value()?.parse::<syn::LitBool>()?.value();
+                } else if meta.path.is_ident("display_format") {
+                    let lit = meta.value()?.parse::<syn::LitStr>()?;
+                    display_format = match lit.value().as_str() {
+                        "compact" => Some((lit, DisplayFormat::Compact)),
+                        "multiline" => Some((lit, DisplayFormat::Multiline)),
+                        "keyvalue" => Some((lit, DisplayFormat::KeyValue)),
+                        value => {
+                            return Err(syn::Error::new_spanned(
+                                &lit,
+                                unknown_message("display_format", value, &["compact", "multiline", "keyvalue"]),
+                            ))
+                        }
+                    };
+                } else if meta.path.is_ident("size") {
+                    let lit = meta.value()?.parse::<syn::LitInt>()?;
+                    struct_attrs.size = Some(lit.base10_parse()?);
+                } else if meta.path.is_ident("serde_raw") {
+                    struct_attrs.serde_raw = meta.value()?.parse::<syn::LitBool>()?.value();
+                } else if meta.path.is_ident("frame") {
+                    let lit = meta.value()?.parse::<syn::LitStr>()?;
+                    struct_attrs.frame = Some(parse_frame(&lit)?);
+                } else if meta.path.is_ident("version") {
+                    let lit = meta.value()?.parse::<syn::LitInt>()?;
+                    struct_attrs.version = Some(lit.base10_parse()?);
+                } else if meta.path.is_ident("previous") {
+                    let lit = meta.value()?.parse::<syn::LitStr>()?;
+                    struct_attrs.previous = Some(lit.parse()?);
+                } else if meta.path.is_ident("version_field") {
+                    let lit = meta.value()?.parse::<syn::LitStr>()?;
+                    struct_attrs.version_field = Some(lit.parse()?);
+                } else if meta.path.is_ident("on_error") {
+                    let lit = meta.value()?.parse::<syn::LitStr>()?;
+                    struct_attrs.on_error = Some(lit.parse()?);
+                } else if meta.path.is_ident("default_byte") {
+                    let lit = meta.value()?.parse::<syn::LitByte>()?;
+                    struct_attrs.default_byte = Some(lit.value());
+                } else if meta.path.is_ident("raw_default") {
+                    struct_attrs.raw_default = meta.value()?.parse::<syn::LitBool>()?.value();
+                } else if meta.path.is_ident("preserve_unknown") {
+                    struct_attrs.preserve_unknown = meta.value()?.parse::<syn::LitBool>()?.value();
+                } else if meta.path.is_ident("computed") {
+                    let (mut name, mut ty, mut with) = (None, None, None);
+                    meta.parse_nested_meta(|meta| {
+                        let lit = meta.value()?.parse::<syn::LitStr>()?;
+                        if meta.path.is_ident("name") {
+                            name = Some(lit.parse::<syn::Ident>()?);
+                        } else if meta.path.is_ident("type") {
+                            ty = Some(lit.parse::<syn::Type>()?);
+                        } else if meta.path.is_ident("with") {
+                            with = Some(lit.parse::<syn::Path>()?);
+                        } else {
+                            return Err(meta.error(format!(
+                                "unknown computed option `{}`",
+                                meta.path.to_token_stream()
+                            )));
+                        }
+                        Ok(())
+                    })?;
+                    let (Some(name), Some(ty)) = (name, ty) else {
+                        return Err(meta.error("computed requires name and type"));
+                    };
+                    // `with` defaults to a function named after the field
+                    let with = with.unwrap_or_else(|| name.clone().into());
+                    struct_attrs.computed.push(ComputedField { name, ty, with });
+                } else if meta.path.is_ident("view") {
+                    let (mut name, mut fields) = (None, None);
+                    meta.parse_nested_meta(|meta| {
+                        if meta.path.is_ident("name") {
+                            let lit = meta.value()?.parse::<syn::LitStr>()?;
+                            name = Some(lit.parse::<syn::Ident>()?);
+                        } else if meta.path.is_ident("fields") {
+                            let content;
+                            syn::parenthesized!(content in meta.input);
+                            let names: syn::punctuated::Punctuated<syn::Ident, syn::Token![,]> =
+                                content.parse_terminated(syn::parse::Parse::parse, syn::Token![,])?;
+                            fields = Some(names.into_iter().collect());
+                        } else {
+                            return Err(meta.error(format!(
+                                "unknown view option `{}`",
+                                meta.path.to_token_stream()
+                            )));
+                        }
+                        Ok(())
+                    })?;
+                    let (Some(name), Some(fields)) = (name, fields) else {
+                        return Err(meta.error("view requires name and fields"));
+                    };
+                    struct_attrs.views.push(ViewStruct { name, fields });
+                } else if meta.path.is_ident("log") {
+                    let lit = meta.value()?.parse::<syn::LitStr>()?;
+                    struct_attrs.log = match lit.value().as_str() {
+                        "tracing" => LogBackend::Tracing,
+                        "log" => LogBackend::Log,
+                        "none" => LogBackend::None,
+                        _ => {
+                            return Err(syn::Error::new_spanned(
+                                lit,
+                                "log must be one of \"tracing\", \"log\" or \"none\"",
+                            ))
+                        }
+                    };
+                } else {
+                    return Err(meta.error(format!(
+                        "unknown bm attribute `{}`",
+                        meta.path.to_token_stream()
+                    )));
                 }
                 Ok(())
-            });
+            })?;
         }
     }
-    struct_attrs
+    struct_attrs.display = match (display, display_format) {
+        (true, format) => Some(format.map_or(DisplayFormat::Compact, |(_, format)| format)),
+        (false, None) => None,
+        (false, Some((lit, _))) => {
+            return Err(syn::Error::new_spanned(lit, "display_format has no effect with display = false"))
+        }
+    };
+    Ok(struct_attrs)
+}
+
+/// Parse `frame = "..."`: `lf`, `crlf`, `soh_etx`, `stx_etx`, `ascii_len(N)` or `bcd_len(N)`
+fn parse_frame(lit: &syn::LitStr) -> syn::Result<proc_macro2::TokenStream> {
+    let value = lit.value();
+    let length_prefixed = |prefix: &str, encoding: proc_macro2::TokenStream| {
+        let width = value.strip_prefix(prefix)?.strip_suffix(')')?.parse::<usize>().ok()?;
+        Some(quote! {
+            binary_mirror::Frame::LengthPrefixed {
+                width: #width,
+                encoding: binary_mirror::LengthEncoding::#encoding,
+            }
+        })
+    };
+    let frame = match value.as_str() {
+        "lf" => Some(quote! { binary_mirror::Frame::Delimited(b"\n") }),
+        "crlf" => Some(quote! { binary_mirror::Frame::Delimited(b"\r\n") }),
+        "soh_etx" => Some(quote! { binary_mirror::Frame::Wrapped { start: b"\x01", end: b"\x03" } }),
+        "stx_etx" => Some(quote! { binary_mirror::Frame::Wrapped { start: b"\x02", end: b"\x03" } }),
+        _ => length_prefixed("ascii_len(", quote!(Ascii)).or_else(|| length_prefixed("bcd_len(", quote!(Bcd))),
+    };
+    frame.ok_or_else(|| {
+        syn::Error::new_spanned(
+            lit,
+            "frame must be one of \"lf\", \"crlf\", \"soh_etx\", \"stx_etx\", \"ascii_len(N)\" or \"bcd_len(N)\"",
+        )
+    })
+}
+
+/// `from_bytes` casts the byte slice to the struct, which is only sound with `#[repr(C)]`
+fn check_repr_c(input: &DeriveInput) -> syn::Result<()> {
+    let mut is_repr_c = false;
+    for attr in &input.attrs {
+        if attr.path().is_ident("repr") {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("C") {
+                    is_repr_c = true;
+                } else if meta.path.is_ident("align") {
+                    return Err(meta.error(
+                        "BinaryMirror structs must keep an alignment of 1, from_bytes casts unaligned bytes to the struct",
+                    ));
+                } else if meta.input.peek(syn::token::Paren) {
+                    // e.g. packed(1)
+                    let content;
+                    syn::parenthesized!(content in meta.input);
+                    content.parse::<proc_macro2::TokenStream>()?;
+                }
+                Ok(())
+            })?;
+        }
+    }
+    if is_repr_c {
+        Ok(())
+    } else {
+        Err(syn::Error::new_spanned(
+            &input.ident,
+            format!(
+                "BinaryMirror requires `#[repr(C)]` on `{}`, from_bytes casts the bytes to the struct",
+                input.ident
+            ),
+        ))
+    }
 }
 
-fn get_field_attrs(attrs: &[syn::Attribute]) -> Option<FieldAttrs> {
+/// Parse the `#[bm(...)]` attribute of a field
+fn get_field_attrs(attrs: &[syn::Attribute]) -> syn::Result<Option<FieldAttrs>> {
     for attr in attrs {
         if attr.path().is_ident("bm") {
-            let mut field_attrs = FieldAttrs {
-                type_name: String::new(),
-                alias: None,
-                format: None,
-                datetime_with: None,
-                skip: false,
-                skip_native: false,
-                enum_type: None,
-                default_byte: None,
-                ignore_warn: false,
-                default_func: None,
-            };
+            let mut field_attrs = FieldAttrs::new(attr.span());
 
-            let _ = attr.parse_nested_meta(|meta| {
+            attr.parse_nested_meta(|meta| {
                 if meta.path.is_ident("type") {
                     let lit = meta.value()?.parse::<LitStr>()?;
+                    if field_attrs.type_name == "pad" {
+                        return Err(meta.error("pad cannot be combined with type"));
+                    }
                     field_attrs.type_name = lit.value();
+                    field_attrs.type_span = lit.span();
+                    if !FIELD_TYPES.contains(&field_attrs.type_name.as_str()) {
+                        return Err(syn::Error::new(
+                            lit.span(),
+                            unknown_message("type", &field_attrs.type_name, FIELD_TYPES),
+                        ));
+                    }
                 } else if meta.path.is_ident("alias") {
                     let lit = meta.value()?.parse::<LitStr>()?;
                     field_attrs.alias = Some(lit.value());
@@ -108,10 +594,30 @@ fn get_field_attrs(attrs: &[syn::Attribute]) -> Option<FieldAttrs> {
                 } else if meta.path.is_ident("datetime_with") {
                     let lit = meta.value()?.parse::<LitStr>()?;
                     field_attrs.datetime_with = Some(lit.value());
+                } else if meta.path.is_ident("subsec_with") {
+                    let lit = meta.value()?.parse::<LitStr>()?;
+                    field_attrs.subsec_with = Some(lit.value());
+                } else if meta.path.is_ident("assume_tz") {
+                    let lit = meta.value()?.parse::<LitStr>()?;
+                    field_attrs.assume_tz = Some(lit.value());
+                } else if meta.path.is_ident("to") {
+                    let lit = meta.value()?.parse::<LitStr>()?;
+                    field_attrs.to_tz = Some(lit.value());
+                } else if meta.path.is_ident("precision") {
+                    let lit = meta.value()?.parse::<syn::LitInt>()?;
+                    field_attrs.precision = Some(lit.base10_parse()?);
+                } else if meta.path.is_ident("rounding") {
+                    let lit = meta.value()?.parse::<LitStr>()?;
+                    field_attrs.rounding = Some(lit.value());
+                } else if meta.path.is_ident("sign") {
+                    let lit = meta.value()?.parse::<LitStr>()?;
+                    field_attrs.sign = Some(lit.value());
                 } else if meta.path.is_ident("skip") {
                     field_attrs.skip = meta.value()?.parse::<syn::LitBool>()?.value();
                 } else if meta.path.is_ident("skip_native") {
                     field_attrs.skip_native = meta.value()?.parse::<syn::LitBool>()?.value();
+                } else if meta.path.is_ident("carry_raw") {
+                    field_attrs.carry_raw = meta.value()?.parse::<syn::LitBool>()?.value();
                 } else if meta.path.is_ident("enum_type") {
                     let lit = meta.value()?.parse::<LitStr>()?;
                     field_attrs.enum_type = Some(lit.value());
@@ -120,120 +626,1192 @@ fn get_field_attrs(attrs: &[syn::Attribute]) -> Option<FieldAttrs> {
                     field_attrs.default_byte = Some(lit.value());
                 } else if meta.path.is_ident("ignore_warn") {
                     field_attrs.ignore_warn = meta.value()?.parse::<syn::LitBool>()?.value();
+                } else if meta.path.is_ident("fill") {
+                    let lit = meta.value()?.parse::<syn::LitByte>()?;
+                    field_attrs.fill = Some(lit.value());
                 } else if meta.path.is_ident("default_func") {
                     let lit = meta.value()?.parse::<syn::LitStr>()?;
                     field_attrs.default_func = Some(lit.value());
+                } else if meta.path.is_ident("validate") {
+                    let mut validate = ValidateAttrs::default();
+                    meta.parse_nested_meta(|meta| {
+                        if meta.path.is_ident("min") {
+                            validate.min = Some(parse_number_lit(&meta.value()?.parse()?)?);
+                        } else if meta.path.is_ident("max") {
+                            validate.max = Some(parse_number_lit(&meta.value()?.parse()?)?);
+                        } else if meta.path.is_ident("min_len") {
+                            let lit = meta.value()?.parse::<syn::LitInt>()?;
+                            validate.min_len = Some(lit.base10_parse()?);
+                        } else if meta.path.is_ident("max_len") {
+                            let lit = meta.value()?.parse::<syn::LitInt>()?;
+                            validate.max_len = Some(lit.base10_parse()?);
+                        } else if meta.path.is_ident("regex") {
+                            let lit = meta.value()?.parse::<LitStr>()?;
+                            if let Err(err) = regex::Regex::new(&lit.value()) {
+                                return Err(syn::Error::new(lit.span(), format!("invalid regex: {}", err)));
+                            }
+                            validate.regex = Some(lit.value());
+                        } else if meta.path.is_ident("ascii") {
+                            validate.ascii = true;
+                        } else {
+                            return Err(meta.error(format!(
+                                "unknown validate option `{}`",
+                                meta.path.to_token_stream()
+                            )));
+                        }
+                        Ok(())
+                    })?;
+                    field_attrs.validate = Some(validate);
+                } else if meta.path.is_ident("numeric_locale") {
+                    let mut locale = NumericLocaleAttrs {
+                        decimal: '.',
+                        thousands: None,
+                        group: false,
+                    };
+                    meta.parse_nested_meta(|meta| {
+                        if meta.path.is_ident("decimal") {
+                            locale.decimal = meta.value()?.parse::<syn::LitChar>()?.value();
+                        } else if meta.path.is_ident("thousands") {
+                            locale.thousands = Some(meta.value()?.parse::<syn::LitChar>()?.value());
+                        } else if meta.path.is_ident("group") {
+                            locale.group = true;
+                        } else {
+                            return Err(meta.error(format!(
+                                "unknown numeric_locale option `{}`",
+                                meta.path.to_token_stream()
+                            )));
+                        }
+                        Ok(())
+                    })?;
+                    field_attrs.numeric_locale = Some(locale);
+                } else if meta.path.is_ident("algo") {
+                    let lit = meta.value()?.parse::<LitStr>()?;
+                    field_attrs.algo = Some(lit.value());
+                } else if meta.path.is_ident("over") {
+                    let lit = meta.value()?.parse::<LitStr>()?;
+                    field_attrs.over = Some(lit.value());
+                } else if meta.path.is_ident("parse_with") {
+                    let lit = meta.value()?.parse::<LitStr>()?;
+                    field_attrs.parse_with = Some(lit.value());
+                } else if meta.path.is_ident("native_type") {
+                    let lit = meta.value()?.parse::<LitStr>()?;
+                    field_attrs.native_type = Some(lit.value());
+                } else if meta.path.is_ident("write_with") {
+                    let lit = meta.value()?.parse::<LitStr>()?;
+                    field_attrs.write_with = Some(lit.value());
+                } else if meta.path.is_ident("required") {
+                    field_attrs.required = meta.value()?.parse::<syn::LitBool>()?.value();
+                } else if meta.path.is_ident("none_value") {
+                    let lit = meta.value()?.parse::<syn::LitByteStr>()?;
+                    field_attrs.none_value = Some(lit.value());
+                } else if meta.path.is_ident("none_if_blank") {
+                    field_attrs.none_if_blank = meta.value()?.parse::<syn::LitBool>()?.value();
+                } else if meta.path.is_ident("flatten") {
+                    // A nested BinaryMirror struct, inlined into the parent's native struct
+                    let lit = meta.value()?.parse::<LitStr>()?;
+                    field_attrs.type_name = "flatten".to_string();
+                    field_attrs.type_span = lit.span();
+                    field_attrs.flatten = Some(lit.value());
+                } else if meta.path.is_ident("pad") {
+                    if meta.input.peek(syn::Token![=]) {
+                        return Err(meta.error(
+                            "pad takes no value, declare the gap as a field, e.g. `#[bm(pad)] reserved: [u8; 12]`",
+                        ));
+                    }
+                    if !field_attrs.type_name.is_empty() {
+                        return Err(meta.error("pad cannot be combined with type or flatten"));
+                    }
+                    field_attrs.type_name = "pad".to_string();
+                    field_attrs.type_span = meta.path.span();
+                } else if meta.path.is_ident("since") {
+                    let lit = meta.value()?.parse::<syn::LitInt>()?;
+                    field_attrs.since = Some(lit.base10_parse()?);
+                } else if meta.path.is_ident("upgrade_with") {
+                    let lit = meta.value()?.parse::<LitStr>()?;
+                    field_attrs.upgrade_with = Some(lit.value());
+                } else if meta.path.is_ident("overlay") {
+                    // Another reading of the bytes of the named field
+                    let lit = meta.value()?.parse::<LitStr>()?;
+                    field_attrs.overlay = Some(lit.value());
+                } else if meta.path.is_ident("when") {
+                    // `when(status = b"P")`, the condition under which an overlay applies
+                    meta.parse_nested_meta(|when| {
+                        let name = when.path.get_ident().ok_or_else(|| when.error("expected a field name"))?;
+                        let lit = when.value()?.parse::<syn::LitByteStr>()?;
+                        field_attrs.when = Some((name.to_string(), lit.value()));
+                        Ok(())
+                    })?;
+                } else if meta.path.is_ident("native_serde") {
+                    let content;
+                    syn::parenthesized!(content in meta.input);
+                    let args = content.parse::<proc_macro2::TokenStream>()?;
+                    field_attrs.native_serde = Some(match field_attrs.native_serde.take() {
+                        Some(serde) => quote!(#serde, #args),
+                        None => args,
+                    });
+                } else if meta.path.is_ident("native_attrs") {
+                    // `serde(...)` items join `native_serde` so serialization sees them
+                    let content;
+                    syn::parenthesized!(content in meta.input);
+                    let metas: syn::punctuated::Punctuated<syn::Meta, syn::Token![,]> =
+                        content.parse_terminated(syn::parse::Parse::parse, syn::Token![,])?;
+                    for item in metas {
+                        match item {
+                            syn::Meta::List(list) if list.path.is_ident("serde") => {
+                                let args = list.tokens;
+                                field_attrs.native_serde = Some(match field_attrs.native_serde.take() {
+                                    Some(serde) => quote!(#serde, #args),
+                                    None => args,
+                                });
+                            }
+                            item => field_attrs.native_attrs.push(item),
+                        }
+                    }
+                } else if meta.path.is_ident("bytes_repr") {
+                    let lit = meta.value()?.parse::<LitStr>()?;
+                    field_attrs.bytes_repr = Some(lit.value());
+                } else if meta.path.is_ident("fix_tag") {
+                    let lit = meta.value()?.parse::<syn::LitInt>()?;
+                    field_attrs.fix_tag = Some(lit.base10_parse()?);
+                } else if meta.path.is_ident("redact") {
+                    field_attrs.redact = meta.value()?.parse::<syn::LitBool>()?.value();
+                } else if meta.path.is_ident("redact_serde") {
+                    field_attrs.redact_serde = meta.value()?.parse::<syn::LitBool>()?.value();
+                } else {
+                    let key = meta.path.to_token_stream().to_string();
+                    return Err(meta.error(unknown_message("bm attribute", &key, FIELD_ATTRIBUTES)));
                 }
                 Ok(())
-            });
+            })?;
 
+            if field_attrs.redact_serde {
+                field_attrs.redact = true;
+                let mask = quote! { serialize_with = "binary_mirror::redact::serialize" };
+                field_attrs.native_serde = Some(match field_attrs.native_serde.take() {
+                    Some(serde) => quote!(#serde, #mask),
+                    None => mask,
+                });
+            }
             if !field_attrs.type_name.is_empty() {
-                return Some(field_attrs);
+                return Ok(Some(field_attrs));
             }
         }
     }
-    None
+    Ok(None)
+}
+
+const RAW_DERIVES: &[&str] = &["Clone", "Copy", "PartialEq", "Eq", "PartialOrd", "Ord", "Hash", "AsRef", "Borrow"];
+
+const FIELD_TYPES: &[&str] = &[
+    "str", "compact_str", "bytes", "i16", "i32", "i64", "u16", "u32", "u64", "f32", "f64",
+    "decimal", "datetime", "date", "time", "enum", "custom", "checksum", "rest",
+];
+
+const FIELD_ATTRIBUTES: &[&str] = &[
+    "type", "alias", "format", "datetime_with", "subsec_with", "assume_tz", "to", "precision",
+    "rounding", "sign", "numeric_locale", "fill", "skip", "skip_native", "carry_raw", "enum_type",
+    "default_byte", "ignore_warn", "default_func", "validate", "algo", "over", "parse_with",
+    "native_type", "write_with", "required", "none_value", "none_if_blank",
+    "native_serde", "native_attrs", "bytes_repr", "fix_tag", "flatten", "pad", "overlay", "when", "since",
+    "upgrade_with",
+    "redact", "redact_serde",
+];
+
+/// Error message for an unknown name, suggesting the closest known one
+fn unknown_message(kind: &str, name: &str, known: &[&str]) -> String {
+    let suggestion = known
+        .iter()
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, candidate)| *distance <= (candidate.len() + 2) / 3)
+        .min_by_key(|(distance, _)| *distance);
+    match suggestion {
+        Some((_, candidate)) => format!("unknown {} `{}`, did you mean `{}`?", kind, name, candidate),
+        None => format!("unknown {} `{}`, expected one of: {}", kind, name, known.join(", ")),
+    }
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut prev = (0..=b.len()).collect::<Vec<_>>();
+    for (i, ca) in a.chars().enumerate() {
+        let mut curr = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = if ca == *cb { 0 } else { 1 };
+            curr[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(curr[j] + 1);
+        }
+        prev = curr;
+    }
+    prev[b.len()]
+}
+
+/// Read a (possibly negative) numeric literal such as `0`, `-5` or `1.5` as its digits
+fn parse_number_lit(expr: &syn::Expr) -> syn::Result<String> {
+    match expr {
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Int(lit),
+            ..
+        }) => Ok(lit.base10_digits().to_string()),
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Float(lit),
+            ..
+        }) => Ok(lit.base10_digits().to_string()),
+        syn::Expr::Unary(syn::ExprUnary {
+            op: syn::UnOp::Neg(_),
+            expr,
+            ..
+        }) => Ok(format!("-{}", parse_number_lit(expr)?)),
+        _ => Err(syn::Error::new_spanned(expr, "expected a numeric literal")),
+    }
+}
+
+fn is_u8(ty: &syn::Type) -> bool {
+    matches!(ty, syn::Type::Path(path) if path.qself.is_none() && path.path.is_ident("u8"))
 }
 
-fn get_origin_fields(input: &DeriveInput) -> Vec<OriginField> {
+fn get_origin_fields(input: &DeriveInput, struct_attrs: &StructAttrs) -> syn::Result<Vec<OriginField>> {
     let fields = match &input.data {
         Data::Struct(data) => match &data.fields {
             Fields::Named(fields) => &fields.named,
-            _ => panic!("Only named fields are supported"),
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &data.fields,
+                    "BinaryMirror only supports structs with named fields",
+                ))
+            }
         },
-        _ => panic!("Only structs are supported"),
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "BinaryMirror can only be derived for structs",
+            ))
+        }
     };
 
-    fields
-        .iter()
-        .map(|field| {
-            let name = field.ident.clone().unwrap();
+    let mut errors: Option<syn::Error> = None;
+    let mut origin_fields = Vec::new();
+    for field in fields {
+        let name = field.ident.clone().unwrap();
 
-            // Check if field is [u8] array and get size
-            let size = if let syn::Type::Array(array) = &field.ty {
-                if let syn::Expr::Lit(syn::ExprLit {
-                    lit: syn::Lit::Int(ref lit_int),
-                    ..
-                }) = array.len
-                {
-                    lit_int
-                        .base10_parse::<usize>()
-                        .expect("Could not parse array length")
-                } else {
-                    panic!("Field {} array length must be a literal integer", name);
+        // A field typed with a type parameter holds a nested struct in place, like `flatten`
+        if let Some(param) = get_type_param(&input.generics, &field.ty) {
+            match get_field_attrs(&field.attrs) {
+                Ok(None) => {
+                    let mut attrs = FieldAttrs::new(param.span());
+                    attrs.type_name = "flatten".to_string();
+                    attrs.flatten = Some(param.to_string());
+                    attrs.generic = true;
+                    origin_fields.push(OriginField {
+                        name,
+                        size: 0,
+                        attrs: Some(attrs),
+                        default_byte: b' ',
+                        doc: get_doc(&field.attrs),
+                    });
                 }
-            } else {
-                panic!("Field {} must be a [u8] array", name);
-            };
+                Ok(Some(_)) => combine_error(
+                    &mut errors,
+                    syn::Error::new_spanned(
+                        &field.ty,
+                        format!("generic field `{}` cannot have bm attributes", name),
+                    ),
+                ),
+                Err(error) => combine_error(&mut errors, error),
+            }
+            continue;
+        }
 
-            OriginField {
-                name,
-                size,
-                attrs: get_field_attrs(&field.attrs),
+        // Check if field is [u8] array and get size
+        let size = match &field.ty {
+            syn::Type::Array(array) if !is_u8(&array.elem) => Err(syn::Error::new_spanned(
+                &field.ty,
+                format!("field `{}` must be a [u8; N] array", name),
+            )),
+            syn::Type::Array(array) => match &array.len {
+                syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Int(lit_int),
+                    ..
+                }) => lit_int.base10_parse::<usize>(),
+                len => Err(syn::Error::new_spanned(
+                    len,
+                    format!("field `{}` array length must be a literal integer", name),
+                )),
+            },
+            // Only the trailing `rest` field may be unsized, its size is the minimum of 0
+            syn::Type::Slice(slice) if is_u8(&slice.elem) => Ok(0),
+            ty => Err(syn::Error::new_spanned(
+                ty,
+                format!("field `{}` must be a [u8; N] array", name),
+            )),
+        };
+        let attrs = get_field_attrs(&field.attrs);
+        let is_slice = matches!(field.ty, syn::Type::Slice(_));
+        let is_rest = matches!(&attrs, Ok(Some(attrs)) if attrs.type_name == "rest");
+        let size = match size {
+            Ok(_) if is_slice && !is_rest => Err(syn::Error::new_spanned(
+                &field.ty,
+                format!("field `{}` must be a [u8; N] array, only a \"rest\" field can be [u8]", name),
+            )),
+            Ok(_) if is_rest && !is_slice => Err(syn::Error::new_spanned(
+                &field.ty,
+                format!("field `{}` of type \"rest\" must be a [u8] slice", name),
+            )),
+            size => size,
+        };
+        match (size, attrs) {
+            (Ok(size), Ok(mut attrs)) => {
+                if let Some(attrs) = attrs.as_mut() {
+                    if let Err(error) = fit_subsec_format(&name, attrs, size) {
+                        combine_error(&mut errors, error);
+                    }
+                }
+                let default_byte = attrs
+                    .as_ref()
+                    .and_then(|attrs| attrs.default_byte)
+                    .or(struct_attrs.default_byte)
+                    .unwrap_or(b' ');
+                origin_fields.push(OriginField {
+                    name,
+                    size,
+                    attrs,
+                    default_byte,
+                    doc: get_doc(&field.attrs),
+                })
             }
-        })
-        .collect()
+            (size, attrs) => {
+                for error in [size.err(), attrs.err()].into_iter().flatten() {
+                    combine_error(&mut errors, error);
+                }
+            }
+        }
+    }
+    if let Some(errors) = errors {
+        return Err(errors);
+    }
+    check_origin_fields(&origin_fields)?;
+    Ok(origin_fields)
 }
 
-fn get_native_fields_and_map(origin_fields: &[OriginField]) -> (Vec<NativeField>, Vec<NativeField2OriginFieldMap>) {
-    let mut native_fields = Vec::new();
-    let mut native_field_map = Vec::new();
-    let mut processed = std::collections::HashSet::new();
+/// The struct's type parameter when the field type is exactly that parameter
+fn get_type_param<'a>(generics: &'a syn::Generics, ty: &syn::Type) -> Option<&'a syn::Ident> {
+    let syn::Type::Path(path) = ty else {
+        return None;
+    };
+    if path.qself.is_some() {
+        return None;
+    }
+    let ident = path.path.get_ident()?;
+    generics.type_params().map(|param| &param.ident).find(|param| *param == ident)
+}
+
+/// Type parameters are the only generics supported, and the layout attributes need a fixed size
+fn check_generics(input: &DeriveInput, struct_attrs: &StructAttrs) -> syn::Result<()> {
+    let mut errors = None;
+    for param in &input.generics.params {
+        match param {
+            syn::GenericParam::Type(_) => {}
+            param => combine_error(
+                &mut errors,
+                syn::Error::new_spanned(param, "BinaryMirror only supports type parameters"),
+            ),
+        }
+    }
+    if input.generics.type_params().next().is_some()
+        && (struct_attrs.size.is_some() || struct_attrs.serde_raw || struct_attrs.version.is_some())
+    {
+        combine_error(
+            &mut errors,
+            syn::Error::new_spanned(
+                &input.generics,
+                "size, serde_raw and version are not supported on generic structs",
+            ),
+        );
+    }
+    match errors {
+        Some(errors) => Err(errors),
+        None => Ok(()),
+    }
+}
 
+/// Field attributes asking for methods a generic struct leaves out, since its offsets are
+/// only known once its parameters are
+fn check_generic_fields(origin_fields: &[OriginField], is_generic: bool) -> syn::Result<()> {
+    if !is_generic {
+        return Ok(());
+    }
+    let mut errors = None;
     for field in origin_fields {
-        if let Some(attrs) = &field.attrs {
-            // Skip if this field has already been processed
-            if processed.contains(&field.name.to_string()) {
-                continue;
-            }
+        let Some(attrs) = &field.attrs else { continue };
+        if attrs.fix_tag.is_some() {
+            combine_error(
+                &mut errors,
+                syn::Error::new_spanned(
+                    &field.name,
+                    format!("fix_tag on `{}` is not supported on generic structs, they have no to_fix", field.name),
+                ),
+            );
+        }
+    }
+    match errors {
+        Some(errors) => Err(errors),
+        None => Ok(()),
+    }
+}
 
-            let field_name = if let Some(alias) = &attrs.alias {
-                quote::format_ident!("{}", alias)
-            } else {
-                field.name.clone()
-            };
+/// The `binary_mirror::Sign` variant of a field with a sign other than the leading default
+fn get_sign(attrs: &FieldAttrs) -> Option<syn::Ident> {
+    match attrs.sign.as_deref()? {
+        "trailing" => Some(quote::format_ident!("Trailing")),
+        "paren" => Some(quote::format_ident!("Paren")),
+        _ => None,
+    }
+}
 
-            match attrs.type_name.as_str() {
-                "date" | "time" if attrs.datetime_with.is_some() => {
-                    let other_field_name = attrs.datetime_with.as_ref().unwrap();
-                    let other_field = origin_fields
-                        .iter()
-                        .find(|f| f.name == quote::format_ident!("{}", other_field_name))
-                        .expect("Could not find datetime pair field");
+/// Statements binding `digits` to the trimmed bytes of a numeric field, its sign moved to the
+/// front and its locale's separators rewritten
+fn get_number_digits(attrs: &FieldAttrs, size: usize, origin_field: &syn::Ident) -> proc_macro2::TokenStream {
+    let delocalize = attrs.numeric_locale.as_ref().map(|locale| {
+        let thousands = match locale.thousands {
+            Some(c) => {
+                let b = c as u8;
+                quote!(Some(#b))
+            }
+            None => quote!(None),
+        };
+        let decimal = locale.decimal as u8;
+        quote! {
+            let mut locale_buf = [0u8; #size];
+            let digits = binary_mirror::delocalize_number(digits, #thousands, #decimal, &mut locale_buf)?;
+        }
+    });
+    let trim_fill = attrs.fill.map(|fill| {
+        quote! { let digits = binary_mirror::trim_fill(digits, #fill); }
+    });
+    let normalize_sign = get_sign(attrs).map(|sign| {
+        quote! {
+            let mut buf = [0u8; #size];
+            let digits = binary_mirror::normalize_sign(digits, binary_mirror::Sign::#sign, &mut buf)?;
+        }
+    });
+    quote! {
+        let digits = binary_mirror::trim_ascii(&self.#origin_field);
+        #trim_fill
+        #normalize_sign
+        #delocalize
+    }
+}
 
-                    // Mark both fields as processed
-                    processed.insert(field.name.to_string());
-                    processed.insert(other_field.name.to_string());
+/// A time zone spec as a chrono `TimeZone` value: `utc`, a fixed offset like `+08:00`,
+/// or an IANA name like `Asia/Taipei` from `chrono-tz`
+fn get_tz(spec: &str) -> Result<proc_macro2::TokenStream, String> {
+    if spec.eq_ignore_ascii_case("utc") {
+        return Ok(quote!(chrono::Utc));
+    }
+    if let Some(sign) = spec.chars().next().filter(|c| *c == '+' || *c == '-') {
+        let digits = spec[1..].replace(':', "");
+        let offset = (digits.len() == 4 && digits.bytes().all(|b| b.is_ascii_digit()))
+            .then(|| digits[..2].parse::<i32>().unwrap() * 3600 + digits[2..].parse::<i32>().unwrap() * 60)
+            .filter(|offset| *offset < 86_400)
+            .ok_or_else(|| format!("time zone offset \"{}\" must look like +08:00", spec))?;
+        let offset = if sign == '-' { -offset } else { offset };
+        return Ok(quote! { chrono::FixedOffset::east_opt(#offset).expect("offset checked at compile time") });
+    }
+    let valid = spec.contains('/')
+        && spec.bytes().all(|b| b.is_ascii_alphanumeric() || b"/_+-".contains(&b));
+    if !valid {
+        return Err(format!(
+            "unknown time zone \"{}\", use \"utc\", an offset like \"+08:00\" or a name like \"Asia/Taipei\"",
+            spec
+        ));
+    }
+    // The variant names chrono-tz generates for the tz database names
+    let mut variant = spec.replace('/', "__").replace('+', "Plus");
+    if let Some(position) = variant.find('-') {
+        let before_digit = variant[position + 1..].starts_with(|c: char| c.is_ascii_digit());
+        variant = variant.replace('-', if before_digit { "Minus" } else { "" });
+    }
+    let variant = quote::format_ident!("{}", variant);
+    Ok(quote!(chrono_tz::Tz::#variant))
+}
 
-                    // Determine which is date and which is time
-                    let (date_field, time_field) = if attrs.type_name == "date" {
-                        (field, other_field)
-                    } else {
-                        (other_field, field)
-                    };
+/// The zones a datetime field converts between, the raw bytes are in `assume_tz` and the
+/// native value in `to`, UTC by default
+fn get_tz_conversion(attrs: &FieldAttrs) -> Option<(proc_macro2::TokenStream, proc_macro2::TokenStream)> {
+    // Both specs are checked in check_origin_fields
+    let from = get_tz(attrs.assume_tz.as_ref()?).ok()?;
+    let to = get_tz(attrs.to_tz.as_deref().unwrap_or("utc")).ok()?;
+    Some((from, to))
+}
 
-                    let native_field = NativeField {
-                        name: field_name,
-                        ty: quote!(Option<chrono::NaiveDateTime>),
-                        type_name: "datetime".to_string(),
-                        pure_ty: quote!(chrono::NaiveDateTime),
-                        origin_fields: vec![date_field.clone(), time_field.clone()],
-                        is_combined_datetime: true,
-                        default_func: attrs.default_func.clone(),
-                        skip_native: attrs.skip_native,
-                    };
+/// Statements re-reading `dt`, a `NaiveDateTime` in zone `from`, in zone `to`
+/// A local time skipped by a daylight saving change fails, a repeated one takes the earlier instant
+fn get_tz_convert(from: &proc_macro2::TokenStream, to: &proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    quote! {
+        let dt = chrono::TimeZone::from_local_datetime(&#from, &dt)
+            .earliest()?
+            .with_timezone(&#to)
+            .naive_local();
+    }
+}
 
-                    native_fields.push(native_field.clone());
-                    native_field_map.push(NativeField2OriginFieldMap {
-                        origin_field: field.clone(),
-                        native_field: Some(native_field.clone()),
-                    });
-                    native_field_map.push(NativeField2OriginFieldMap {
-                        origin_field: other_field.clone(),
-                        native_field: Some(native_field),
-                    });
-                }
-                _ => {
+/// Fit the fraction of a second in a time or datetime format to the field width
+///
+/// chrono reads a bare `%f` as nanoseconds and writes all nine digits, so it becomes `%3f`,
+/// `%6f` or `%9f` for the digits left in the field. A fixed number of digits must fit.
+fn fit_subsec_format(name: &syn::Ident, attrs: &mut FieldAttrs, size: usize) -> syn::Result<()> {
+    if !matches!(attrs.type_name.as_str(), "time" | "datetime") {
+        return Ok(());
+    }
+    let Some(format) = attrs.format.clone() else {
+        return Ok(());
+    };
+    let sample = chrono::NaiveDate::from_ymd_opt(2024, 1, 1)
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .expect("valid sample datetime");
+    // None for a format chrono rejects, parsing reports it at runtime
+    let width = |format: &str| {
+        use std::fmt::Write;
+        let mut rendered = String::new();
+        write!(rendered, "{}", sample.format(format)).ok().map(|_| rendered.len())
+    };
+    let error = |message: String| Err(syn::Error::new(attrs.type_span, message));
+    if format.contains("%f") {
+        let Some(rest) = width(&format.replacen("%f", "", 1)) else {
+            return Ok(());
+        };
+        return match size.checked_sub(rest) {
+            Some(digits @ (3 | 6 | 9)) => {
+                attrs.format = Some(format.replacen("%f", &format!("%{}f", digits), 1));
+                Ok(())
+            }
+            digits => error(format!(
+                "format \"{}\" leaves {} digits for %f in field `{}`, it must leave 3, 6 or 9",
+                format,
+                digits.map_or("no".to_string(), |digits| digits.to_string()),
+                name
+            )),
+        };
+    }
+    let fixed_digits = ["%.3f", "%.6f", "%.9f", "%3f", "%6f", "%9f"];
+    if fixed_digits.iter().any(|spec| format.contains(spec)) {
+        if let Some(width) = width(&format).filter(|width| *width > size) {
+            return error(format!(
+                "format \"{}\" writes {} bytes but field `{}` holds {}, the fraction of a second would be cut",
+                format, width, name, size
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn combine_error(errors: &mut Option<syn::Error>, error: syn::Error) {
+    match errors {
+        Some(errors) => errors.combine(error),
+        None => *errors = Some(error),
+    }
+}
+
+/// Check the field attributes up front so code generation can rely on them
+fn check_origin_fields(origin_fields: &[OriginField]) -> syn::Result<()> {
+    let mut errors = None;
+    // Only the fields before the generic ones have a fixed offset
+    if let Some(first_generic) = origin_fields.iter().position(is_generic) {
+        for field in origin_fields[first_generic..].iter().filter(|field| !is_generic(field)) {
+            combine_error(
+                &mut errors,
+                syn::Error::new(
+                    field.name.span(),
+                    format!("field `{}` must come before the generic fields", field.name),
+                ),
+            );
+        }
+    }
+    for field in origin_fields {
+        let Some(attrs) = &field.attrs else {
+            continue;
+        };
+        let span = attrs.type_span;
+        let mut error = |message: String| combine_error(&mut errors, syn::Error::new(span, message));
+        let find_field = |name: &str| origin_fields.iter().any(|f| f.name == name);
+
+        match attrs.type_name.as_str() {
+            "enum" if attrs.enum_type.is_none() => {
+                error(format!("field `{}` of type \"enum\" requires enum_type", field.name))
+            }
+            "custom" => {
+                if attrs.native_type.is_none() {
+                    error(format!("field `{}` of type \"custom\" requires native_type", field.name));
+                }
+                if attrs.parse_with.is_none() {
+                    error(format!("field `{}` of type \"custom\" requires parse_with", field.name));
+                }
+            }
+            "checksum"
+                if attrs.over.is_none()
+                    && origin_fields.iter().take_while(|f| !std::ptr::eq(*f, field)).all(|f| f.size == 0) =>
+            {
+                error(format!("checksum field `{}` has no preceding bytes to cover", field.name))
+            }
+            _ => {}
+        }
+        if attrs.type_name == "checksum" {
+            match attrs.algo.as_deref() {
+                None => error(format!("checksum field `{}` requires algo", field.name)),
+                Some("lrc" | "crc16" | "mod10") => {}
+                Some(algo) => error(unknown_message("checksum algo", algo, &["lrc", "crc16", "mod10"])),
+            }
+        }
+        if let Some(flatten) = &attrs.flatten {
+            if syn::parse_str::<syn::Path>(flatten).is_err() {
+                error(format!("could not parse flatten `{}`", flatten));
+            }
+            if attrs.none_value.is_some() || attrs.none_if_blank || attrs.datetime_with.is_some() {
+                error(format!(
+                    "flattened field `{}` cannot have none_value, none_if_blank or datetime_with",
+                    field.name
+                ));
+            }
+        }
+        if let Some(native_type) = &attrs.native_type {
+            if syn::parse_str::<syn::Type>(native_type).is_err() {
+                error(format!("could not parse native_type `{}`", native_type));
+            }
+        }
+        for (key, path) in [
+            ("parse_with", &attrs.parse_with),
+            ("write_with", &attrs.write_with),
+            ("upgrade_with", &attrs.upgrade_with),
+        ] {
+            if let Some(path) = path {
+                if syn::parse_str::<syn::Path>(path).is_err() {
+                    error(format!("could not parse {} `{}`", key, path));
+                }
+            }
+        }
+        if let Some(other) = &attrs.datetime_with {
+            match origin_fields.iter().find(|f| f.name == other) {
+                None => error(format!("datetime_with field `{}` not found", other)),
+                Some(_) if !matches!(attrs.type_name.as_str(), "date" | "time") => error(format!(
+                    "datetime_with is only supported on \"date\" and \"time\" fields, `{}` is \"{}\"",
+                    field.name, attrs.type_name
+                )),
+                Some(_) => {}
+            }
+        }
+        if let Some(subsec) = &attrs.subsec_with {
+            match origin_fields.iter().find(|f| f.name == subsec) {
+                _ if attrs.datetime_with.is_none() => {
+                    error(format!("subsec_with on field `{}` requires datetime_with", field.name))
+                }
+                None => error(format!("subsec_with field `{}` not found", subsec)),
+                Some(target) if target.attrs.is_some() => error(format!(
+                    "subsec_with field `{}` is part of the datetime and cannot have bm attributes",
+                    subsec
+                )),
+                Some(target) if !(1..=9).contains(&target.size) => error(format!(
+                    "subsec_with field `{}` must hold 1 to 9 digits, got {}",
+                    subsec, target.size
+                )),
+                Some(_) => {}
+            }
+        }
+        if attrs.carry_raw && !attrs.skip_native {
+            error(format!("carry_raw on field `{}` requires skip_native", field.name));
+        } else if attrs.carry_raw && (attrs.datetime_with.is_some() || attrs.overlay.is_some()) {
+            error(format!(
+                "carry_raw on field `{}` cannot be combined with datetime_with or overlay",
+                field.name
+            ));
+        }
+        if attrs.to_tz.is_some() && attrs.assume_tz.is_none() {
+            error(format!("`to` on field `{}` requires assume_tz", field.name));
+        }
+        if attrs.assume_tz.is_some() && attrs.type_name != "datetime" && attrs.datetime_with.is_none() {
+            error(format!(
+                "assume_tz is only supported on \"datetime\" fields and fields with datetime_with, `{}` is \"{}\"",
+                field.name, attrs.type_name
+            ));
+        }
+        for spec in [&attrs.assume_tz, &attrs.to_tz].into_iter().flatten() {
+            if let Err(message) = get_tz(spec) {
+                error(message);
+            }
+        }
+        if let Some(precision) = attrs.precision {
+            if !matches!(attrs.type_name.as_str(), "f32" | "f64" | "decimal") {
+                error(format!(
+                    "precision is only supported on \"f32\", \"f64\" and \"decimal\" fields, `{}` is \"{}\"",
+                    field.name, attrs.type_name
+                ));
+            } else if attrs.format.is_some() {
+                error(format!("field `{}` takes either format or precision, not both", field.name));
+            } else if precision + usize::from(precision > 0) + 1 > field.size {
+                error(format!(
+                    "precision {} of field `{}` leaves no room for an integer digit in {} bytes",
+                    precision, field.name, field.size
+                ));
+            }
+        }
+        match attrs.rounding.as_deref() {
+            Some(_) if attrs.precision.is_none() => {
+                error(format!("rounding on field `{}` requires precision", field.name))
+            }
+            Some("half_up" | "truncate") | None => {}
+            Some(rounding) => error(unknown_message("rounding", rounding, &["half_up", "truncate"])),
+        }
+        if let Some(sign) = &attrs.sign {
+            let numeric = matches!(
+                attrs.type_name.as_str(),
+                "i16" | "i32" | "i64" | "u16" | "u32" | "u64" | "f32" | "f64" | "decimal"
+            );
+            if !numeric {
+                error(format!(
+                    "sign is only supported on numeric fields, `{}` is \"{}\"",
+                    field.name, attrs.type_name
+                ));
+            } else if !matches!(sign.as_str(), "leading" | "trailing" | "paren") {
+                error(unknown_message("sign", sign, &["leading", "trailing", "paren"]));
+            }
+        }
+        if let Some(repr) = &attrs.bytes_repr {
+            if attrs.type_name != "bytes" {
+                error(format!(
+                    "bytes_repr is only supported on \"bytes\" fields, `{}` is \"{}\"",
+                    field.name, attrs.type_name
+                ));
+            } else if !matches!(repr.as_str(), "hex" | "base64") {
+                error(unknown_message("bytes_repr", repr, &["hex", "base64"]));
+            }
+        }
+        if attrs.redact_serde && (attrs.bytes_repr.is_some() || attrs.type_name == "flatten") {
+            error(format!(
+                "redact_serde cannot be combined with bytes_repr or flatten, `{}` already has a serializer",
+                field.name
+            ));
+        }
+        if let Some(tag) = attrs.fix_tag {
+            let merged = origin_fields.iter().filter_map(|f| f.attrs.as_ref()).any(|other| {
+                [&other.datetime_with, &other.subsec_with].contains(&&Some(field.name.to_string()))
+            });
+            let mut earlier = origin_fields.iter().take_while(|f| !core::ptr::eq(*f, field));
+            if attrs.skip
+                || attrs.skip_native
+                || merged
+                || matches!(attrs.type_name.as_str(), "pad" | "checksum" | "flatten")
+            {
+                error(format!("fix_tag on field `{}` which has no native value", field.name));
+            } else if let Some(other) =
+                earlier.find(|f| f.attrs.as_ref().is_some_and(|other| other.fix_tag == Some(tag)))
+            {
+                error(format!("fix_tag {} of field `{}` is also on `{}`", tag, field.name, other.name));
+            }
+        }
+        if let Some(locale) = &attrs.numeric_locale {
+            let numeric = matches!(
+                attrs.type_name.as_str(),
+                "i16" | "i32" | "i64" | "u16" | "u32" | "u64" | "f32" | "f64" | "decimal"
+            );
+            let separators = [Some(locale.decimal), locale.thousands];
+            if !numeric {
+                error(format!(
+                    "numeric_locale is only supported on numeric fields, `{}` is \"{}\"",
+                    field.name, attrs.type_name
+                ));
+            } else if separators
+                .iter()
+                .flatten()
+                .any(|c| !(c.is_ascii_punctuation() || *c == ' ') || "+-()".contains(*c))
+            {
+                error(format!(
+                    "numeric_locale separators of field `{}` must be ASCII punctuation or a space, other than a sign",
+                    field.name
+                ));
+            } else if locale.thousands == Some(locale.decimal) {
+                error(format!(
+                    "numeric_locale of field `{}` uses '{}' for both decimal and thousands",
+                    field.name, locale.decimal
+                ));
+            } else if locale.group && locale.thousands.is_none() {
+                error(format!("numeric_locale group on field `{}` requires thousands", field.name));
+            }
+        }
+        if let Some(fill) = attrs.fill {
+            let numeric = matches!(
+                attrs.type_name.as_str(),
+                "i16" | "i32" | "i64" | "u16" | "u32" | "u64" | "f32" | "f64" | "decimal"
+            );
+            let separators = attrs
+                .numeric_locale
+                .as_ref()
+                .map_or(vec!['.'], |locale| [Some(locale.decimal), locale.thousands].into_iter().flatten().collect());
+            if !numeric {
+                error(format!(
+                    "fill is only supported on numeric fields, `{}` is \"{}\"",
+                    field.name, attrs.type_name
+                ));
+            } else if fill != b'0' && (fill.is_ascii_alphanumeric() || b"+-()".contains(&fill))
+                || separators.contains(&(fill as char))
+            {
+                error(format!(
+                    "fill of field `{}` must not be a digit, sign or separator, got b'{}'",
+                    field.name,
+                    fill.escape_ascii()
+                ));
+            }
+        }
+        if let Some(over) = &attrs.over {
+            match over.split_once("..") {
+                Some((first, last)) => {
+                    let position = |name: &str| origin_fields.iter().position(|f| f.name == name);
+                    match (position(first.trim()), position(last.trim())) {
+                        (Some(first), Some(last)) if first > last => {
+                            error(format!("checksum range \"{}\" is empty", over))
+                        }
+                        (Some(first), Some(last))
+                            if origin_fields[first..=last].iter().any(is_generic) =>
+                        {
+                            error(format!("checksum range \"{}\" cannot cover a generic field", over))
+                        }
+                        (Some(first), Some(last)) if origin_fields[first..=last].iter().all(|f| f.size == 0) => {
+                            error(format!("checksum range \"{}\" covers no bytes", over))
+                        }
+                        (Some(_), Some(_)) => {}
+                        _ => {
+                            for name in [first.trim(), last.trim()] {
+                                if !find_field(name) {
+                                    error(format!("checksum range field `{}` not found", name));
+                                }
+                            }
+                        }
+                    }
+                }
+                None => error(format!(
+                    "over must be in the form \"first_field..last_field\", got \"{}\"",
+                    over
+                )),
+            }
+        }
+        if let Some(validate) = &attrs.validate {
+            let numeric = matches!(
+                attrs.type_name.as_str(),
+                "i16" | "i32" | "i64" | "u16" | "u32" | "u64" | "f32" | "f64" | "decimal"
+            );
+            if (validate.min.is_some() || validate.max.is_some()) && !numeric {
+                error(format!(
+                    "validate(min/max) is only supported for numeric fields, `{}` is \"{}\"",
+                    field.name, attrs.type_name
+                ));
+            } else {
+                for (key, bound) in [("min", &validate.min), ("max", &validate.max)] {
+                    match bound {
+                        Some(bound) if get_validate_bound(&attrs.type_name, bound).is_none() => error(format!(
+                            "validate {} {} of field `{}` is not a valid {} value",
+                            key, bound, field.name, attrs.type_name
+                        )),
+                        _ => {}
+                    }
+                }
+            }
+        }
+        if let Some(none_value) = &attrs.none_value {
+            if none_value.len() != field.size {
+                error(format!(
+                    "none_value of field `{}` must be {} bytes, got {}",
+                    field.name,
+                    field.size,
+                    none_value.len()
+                ));
+            }
+        }
+        if let Some(overlay) = &attrs.overlay {
+            match origin_fields.iter().find(|f| f.name == overlay) {
+                None => error(format!("overlay target `{}` not found", overlay)),
+                Some(target) => match target.attrs.as_ref() {
+                    Some(target_attrs) if target_attrs.overlay.is_some() => {
+                        error(format!("overlay target `{}` is itself an overlay", overlay))
+                    }
+                    Some(target_attrs) if matches!(target_attrs.type_name.as_str(), "rest" | "pad") => {
+                        error(format!(
+                            "overlay target `{}` cannot be a \"{}\" field",
+                            overlay, target_attrs.type_name
+                        ))
+                    }
+                    _ => {}
+                },
+            }
+            if field.size != 0 {
+                error(format!(
+                    "overlay field `{}` must be declared as [u8; 0], it reads the bytes of `{}`",
+                    field.name, overlay
+                ));
+            }
+            if matches!(attrs.type_name.as_str(), "bytes" | "flatten" | "checksum" | "rest" | "pad") {
+                error(format!("overlay field `{}` cannot be of type \"{}\"", field.name, attrs.type_name));
+            }
+            if attrs.required || attrs.none_value.is_some() || attrs.datetime_with.is_some() {
+                error(format!(
+                    "overlay field `{}` cannot have required, none_value or datetime_with",
+                    field.name
+                ));
+            }
+        }
+        if let Some((when, value)) = &attrs.when {
+            match origin_fields.iter().find(|f| f.name == when) {
+                _ if attrs.overlay.is_none() => {
+                    error(format!("when on field `{}` requires overlay", field.name))
+                }
+                None => error(format!("when field `{}` not found", when)),
+                Some(target) if target.size != value.len() => error(format!(
+                    "when value for `{}` must be {} bytes, got {}",
+                    when,
+                    target.size,
+                    value.len()
+                )),
+                Some(_) => {}
+            }
+        }
+        if attrs.type_name == "pad" {
+            let has_other = attrs.alias.is_some()
+                || attrs.format.is_some()
+                || attrs.datetime_with.is_some()
+                || attrs.enum_type.is_some()
+                || attrs.default_func.is_some()
+                || attrs.validate.is_some()
+                || attrs.parse_with.is_some()
+                || attrs.write_with.is_some()
+                || attrs.required
+                || attrs.none_value.is_some()
+                || attrs.none_if_blank
+                || attrs.native_serde.is_some()
+                || !attrs.native_attrs.is_empty()
+                || attrs.redact;
+            if has_other {
+                error(format!("pad field `{}` only supports default_byte", field.name));
+            }
+        }
+        if attrs.type_name == "rest" {
+            if !std::ptr::eq(field, origin_fields.last().unwrap()) {
+                error(format!("rest field `{}` must be the last field", field.name));
+            }
+            if attrs.none_value.is_some() || attrs.none_if_blank || attrs.validate.is_some() {
+                error(format!(
+                    "rest field `{}` cannot have none_value, none_if_blank or validate",
+                    field.name
+                ));
+            }
+            if attrs.parse_with.is_some() || attrs.write_with.is_some() || attrs.default_func.is_some() {
+                error(format!(
+                    "rest field `{}` cannot have parse_with, write_with or default_func",
+                    field.name
+                ));
+            }
+        }
+        if let Some((_, last)) = attrs.over.as_ref().and_then(|over| over.split_once("..")) {
+            let last = origin_fields.iter().find(|f| f.name == last.trim());
+            if last.and_then(|f| f.attrs.as_ref()).is_some_and(|attrs| attrs.type_name == "rest") {
+                error(format!("checksum `{}` cannot cover the rest field", field.name));
+            }
+        }
+        if (attrs.none_value.is_some() || attrs.none_if_blank) && attrs.type_name == "bytes" {
+            error(format!("field `{}` of type \"bytes\" cannot be None", field.name));
+        }
+    }
+    match errors {
+        Some(errors) => Err(errors),
+        None => Ok(()),
+    }
+}
+
+/// Whether the field is unused filler, declared with `#[bm(pad)]`
+fn is_pad(field: &OriginField) -> bool {
+    field.attrs.as_ref().is_some_and(|attrs| attrs.type_name == "pad")
+}
+
+/// The field an overlay reads its bytes from
+fn overlay_target(field: &OriginField) -> Option<&String> {
+    field.attrs.as_ref()?.overlay.as_ref()
+}
+
+/// Whether the mapping writes an overlay's value into the bytes of its target
+fn is_overlay_view(mapping: &NativeField2OriginFieldMap) -> bool {
+    mapping.origin_field.attrs.as_ref().is_some_and(|attrs| attrs.overlay.is_some())
+}
+
+/// Offset and size of every origin field, an overlay reports the range of its target
+fn get_field_ranges(origin_fields: &[OriginField]) -> Vec<(usize, usize)> {
+    let mut offset = 0;
+    let ranges = origin_fields
+        .iter()
+        .map(|field| {
+            let range = (offset, field.size);
+            offset += field.size;
+            range
+        })
+        .collect::<Vec<_>>();
+    origin_fields
+        .iter()
+        .zip(&ranges)
+        .map(|(field, range)| {
+            match overlay_target(field) {
+                Some(overlay) => origin_fields
+                    .iter()
+                    .position(|f| f.name == overlay)
+                    .map_or(*range, |position| ranges[position]),
+                None => *range,
+            }
+        })
+        .collect()
+}
+
+/// Whether the field is the variable-length trailing `rest` field
+fn is_rest(field: &OriginField) -> bool {
+    field.attrs.as_ref().is_some_and(|attrs| attrs.type_name == "rest")
+}
+
+/// Whether the field is typed with one of the struct's type parameters
+fn is_generic(field: &OriginField) -> bool {
+    field.attrs.as_ref().is_some_and(|attrs| attrs.generic)
+}
+
+/// The nested raw struct of a `flatten` field
+fn get_flatten_ty(attrs: &FieldAttrs) -> syn::Path {
+    syn::parse_str(attrs.flatten.as_ref().expect("flatten field")).expect("Could not parse flatten")
+}
+
+/// The native struct of a `flatten` field, `Header` -> `HeaderNative`, `P` -> `<P as ToNative>::Native`
+fn get_flatten_native_ty(attrs: &FieldAttrs) -> syn::Type {
+    let mut path = get_flatten_ty(attrs);
+    if attrs.generic {
+        return syn::parse_quote!(<#path as binary_mirror::ToNative>::Native);
+    }
+    let last = path.segments.last_mut().expect("flatten path");
+    last.ident = quote::format_ident!("{}Native", last.ident);
+    syn::parse_quote!(#path)
+}
+
+fn get_native_fields_and_map(origin_fields: &[OriginField]) -> (Vec<NativeField>, Vec<NativeField2OriginFieldMap>) {
+    let mut native_fields = Vec::new();
+    let mut native_field_map = Vec::new();
+    let mut processed = std::collections::HashSet::new();
+    // Sub-second fields are mapped with the datetime they belong to, wherever they are declared
+    let subsec_fields = origin_fields
+        .iter()
+        .filter_map(|field| field.attrs.as_ref()?.subsec_with.clone())
+        .collect::<std::collections::HashSet<_>>();
+
+    for field in origin_fields {
+        if subsec_fields.contains(&field.name.to_string()) {
+            continue;
+        }
+        if let Some(attrs) = &field.attrs {
+            // Skip if this field has already been processed
+            if processed.contains(&field.name.to_string()) {
+                continue;
+            }
+
+            let field_name = if let Some(alias) = &attrs.alias {
+                quote::format_ident!("{}", alias)
+            } else {
+                field.name.clone()
+            };
+
+            // An overlay reads and writes the bytes of its target, the zero-sized field holds nothing
+            let source = match &attrs.overlay {
+                Some(overlay) => {
+                    let target = origin_fields
+                        .iter()
+                        .find(|f| f.name == overlay)
+                        .expect("Could not find overlay target");
+                    native_field_map.push(NativeField2OriginFieldMap {
+                        origin_field: OriginField {
+                            name: field.name.clone(),
+                            size: 0,
+                            attrs: None,
+                            default_byte: field.default_byte,
+                            doc: field.doc.clone(),
+                        },
+                        native_field: None,
+                    });
+                    OriginField {
+                        name: target.name.clone(),
+                        size: target.size,
+                        attrs: field.attrs.clone(),
+                        default_byte: field.default_byte,
+                        doc: field.doc.clone(),
+                    }
+                }
+                None => field.clone(),
+            };
+
+            match attrs.type_name.as_str() {
+                "date" | "time" if attrs.datetime_with.is_some() => {
+                    let other_field_name = attrs.datetime_with.as_ref().unwrap();
+                    let other_field = origin_fields
+                        .iter()
+                        .find(|f| f.name == quote::format_ident!("{}", other_field_name))
+                        .expect("Could not find datetime pair field");
+
+                    // Mark both fields as processed
+                    processed.insert(field.name.to_string());
+                    processed.insert(other_field.name.to_string());
+
+                    // Determine which is date and which is time
+                    let (date_field, time_field) = if attrs.type_name == "date" {
+                        (field, other_field)
+                    } else {
+                        (other_field, field)
+                    };
+
+                    let mut combined = vec![date_field.clone(), time_field.clone()];
+                    let subsec_field = attrs.subsec_with.as_ref().map(|subsec| {
+                        let subsec = origin_fields
+                            .iter()
+                            .find(|f| f.name == subsec)
+                            .expect("Could not find subsec field");
+                        let mut subsec_attrs = FieldAttrs::new(subsec.name.span());
+                        subsec_attrs.type_name = "subsec".to_string();
+                        OriginField {
+                            attrs: Some(subsec_attrs),
+                            ..subsec.clone()
+                        }
+                    });
+                    combined.extend(subsec_field.clone());
+
+                    let native_field = NativeField {
+                        name: field_name,
+                        ty: quote!(Option<chrono::NaiveDateTime>),
+                        type_name: "datetime".to_string(),
+                        pure_ty: quote!(chrono::NaiveDateTime),
+                        origin_fields: combined,
+                        is_combined_datetime: true,
+                        tz: get_tz_conversion(attrs),
+                        default_func: attrs.default_func.clone(),
+                        skip_native: attrs.skip_native,
+                    };
+
+                    native_fields.push(native_field.clone());
+                    native_field_map.push(NativeField2OriginFieldMap {
+                        origin_field: field.clone(),
+                        native_field: Some(native_field.clone()),
+                    });
+                    native_field_map.push(NativeField2OriginFieldMap {
+                        origin_field: other_field.clone(),
+                        native_field: Some(native_field.clone()),
+                    });
+                    if let Some(subsec_field) = subsec_field {
+                        native_field_map.push(NativeField2OriginFieldMap {
+                            origin_field: subsec_field,
+                            native_field: Some(native_field),
+                        });
+                    }
+                }
+                "checksum" | "pad" => {
+                    // Computed from the other fields or filler, never part of the native struct
+                    native_field_map.push(NativeField2OriginFieldMap {
+                        origin_field: field.clone(),
+                        native_field: None,
+                    });
+                }
+                _ => {
                     let (ty, pure_ty) = match attrs.type_name.as_str() {
                         "str" => (quote!(Option<String>), quote!(String)),
                         "compact_str" => (
@@ -262,15 +1840,26 @@ fn get_native_fields_and_map(origin_fields: &[OriginField]) -> (Vec<NativeField>
                         ),
                         "date" => (quote!(Option<chrono::NaiveDate>), quote!(chrono::NaiveDate)),
                         "time" => (quote!(Option<chrono::NaiveTime>), quote!(chrono::NaiveTime)),
+                        // enum_type and native_type are checked in check_origin_fields
                         "enum" => {
-                            let enum_type = attrs.enum_type.as_ref();
-                            match enum_type {
-                                Some(enum_type) => {
-                                    let enum_ident = quote::format_ident!("{}", enum_type);
-                                    (quote!(Option<#enum_ident>), quote!(#enum_ident))
-                                }
-                                None => panic!("enum_type is required for enum field"),
-                            }
+                            let Some(enum_type) = &attrs.enum_type else { continue };
+                            let enum_ident = quote::format_ident!("{}", enum_type);
+                            (quote!(Option<#enum_ident>), quote!(#enum_ident))
+                        }
+                        // The native struct takes the payload's native type as its own parameter
+                        "flatten" if attrs.generic => {
+                            let param = get_flatten_ty(attrs);
+                            (quote!(#param), quote!(#param))
+                        }
+                        "flatten" => {
+                            let native_ty = get_flatten_native_ty(attrs);
+                            (quote!(#native_ty), quote!(#native_ty))
+                        }
+                        "rest" => (quote!(Vec<u8>), quote!(Vec<u8>)),
+                        "custom" => {
+                            let native_type = attrs.native_type.as_deref().map(syn::parse_str::<syn::Type>);
+                            let Some(Ok(native_type)) = native_type else { continue };
+                            (quote!(Option<#native_type>), quote!(#native_type))
                         }
                         _ => continue,
                     };
@@ -279,20 +1868,21 @@ fn get_native_fields_and_map(origin_fields: &[OriginField]) -> (Vec<NativeField>
                         ty,
                         type_name: attrs.type_name.clone(),
                         pure_ty,
-                        origin_fields: vec![field.clone()],
+                        origin_fields: vec![source.clone()],
                         is_combined_datetime: false,
+                        tz: get_tz_conversion(attrs),
                         default_func: attrs.default_func.clone(),
                         skip_native: attrs.skip_native,
                     };
                     if !attrs.skip {
                         native_fields.push(native_field.clone());
                         native_field_map.push(NativeField2OriginFieldMap {
-                            origin_field: field.clone(),
+                            origin_field: source.clone(),
                             native_field: Some(native_field),
                         });
                     } else {
                         native_field_map.push(NativeField2OriginFieldMap {
-                            origin_field: field.clone(),
+                            origin_field: source.clone(),
                             native_field: None,
                         });
                     }
@@ -312,8 +1902,15 @@ fn get_native_fields_and_map(origin_fields: &[OriginField]) -> (Vec<NativeField>
 fn get_debug_fields(origin_fields: &[OriginField]) -> Vec<proc_macro2::TokenStream> {
     origin_fields
         .iter()
+        .filter(|field| !is_pad(field) && overlay_target(field).is_none())
         .map(|field| {
             let field_name = &field.name;
+            if is_generic(field) {
+                return quote! { .field(stringify!(#field_name), &self.#field_name) };
+            }
+            if field.attrs.as_ref().is_some_and(|attrs| attrs.redact) {
+                return quote! { .field(stringify!(#field_name), &format_args!("{}", binary_mirror::redact::MASK)) };
+            }
             quote! {
                 .field(
                     stringify!(#field_name),
@@ -327,7 +1924,50 @@ fn get_debug_fields(origin_fields: &[OriginField]) -> Vec<proc_macro2::TokenStre
         .collect()
 }
 
-fn get_methods(native_fields: &[NativeField]) -> Vec<proc_macro2::TokenStream> {
+/// Condition that is true when the field bytes hold the `none_value` sentinel or are blank
+/// with `none_if_blank`, returns None when neither is set
+fn get_none_condition(field: &OriginField) -> Option<proc_macro2::TokenStream> {
+    let attrs = field.attrs.as_ref()?;
+    let origin_field = &field.name;
+    // The length of none_value is checked in check_origin_fields
+    let none_value_check = attrs.none_value.as_ref().map(|none_value| {
+        let none_value = syn::LitByteStr::new(none_value, proc_macro2::Span::call_site());
+        quote! { (&self.#origin_field == #none_value) }
+    });
+    let blank_check = attrs
+        .none_if_blank
+        .then(|| quote! { binary_mirror::is_blank(&self.#origin_field) });
+    // An overlay whose condition does not hold has no value, its bytes belong to the target
+    let when_check = attrs.when.as_ref().map(|(when, value)| {
+        let when = quote::format_ident!("{}", when);
+        let value = syn::LitByteStr::new(value, proc_macro2::Span::call_site());
+        quote! { (&self.#when != #value) }
+    });
+    let checks = [none_value_check, blank_check, when_check].into_iter().flatten().collect::<Vec<_>>();
+    match checks.len() {
+        0 => None,
+        1 => checks.into_iter().next(),
+        _ => Some(quote! { (#(#checks)||*) }),
+    }
+}
+
+/// Early `return None` for fields whose bytes hold a sentinel
+fn get_none_check(field: &OriginField) -> proc_macro2::TokenStream {
+    match get_none_condition(field) {
+        Some(condition) => quote! {
+            if #condition {
+                return None;
+            }
+        },
+        None => quote! {},
+    }
+}
+
+fn get_methods(
+    struct_name: &syn::Ident,
+    struct_attrs: &StructAttrs,
+    native_fields: &[NativeField],
+) -> Vec<proc_macro2::TokenStream> {
     native_fields
         .iter()
         .map(|field| {
@@ -336,9 +1976,9 @@ fn get_methods(native_fields: &[NativeField]) -> Vec<proc_macro2::TokenStream> {
 
             let method_with_warn_name = quote::format_ident!("{}_with_warn", name);
 
-            let debug_bytes = quote! {
-                tracing::warn!("Failed to parse {} in {:?}", stringify!(#name), self);
-            };
+            let debug_bytes = get_parse_failure(struct_name, struct_attrs, field);
+
+            let none_check = get_none_check(&field.origin_fields[0]);
 
             if field.is_combined_datetime {
                 let date_field = &field.origin_fields[0].name;
@@ -355,21 +1995,45 @@ fn get_methods(native_fields: &[NativeField]) -> Vec<proc_macro2::TokenStream> {
                     .and_then(|attrs| attrs.format.as_ref())
                     .map(String::as_str)
                     .unwrap_or("%H%M%S");
+                // Digits of a fraction of a second, scaled to nanoseconds, a blank field is 0.
+                // They are the leading digits, so trailing blanks read as zeros, "5  " is 500ms
+                let subsec = field.origin_fields.get(2).map(|subsec| {
+                    let subsec_field = &subsec.name;
+                    let size = subsec.size;
+                    let scale = 10u32.pow(9 - subsec.size as u32);
+                    quote! {
+                        let time = {
+                            let digits = binary_mirror::trim_ascii_end(&self.#subsec_field);
+                            let fraction = if digits.is_empty() {
+                                0
+                            } else {
+                                binary_mirror::parse_number::<u32>(digits)? * 10u32.pow((#size - digits.len()) as u32)
+                            };
+                            chrono::Timelike::with_nanosecond(&time, fraction * #scale)?
+                        };
+                    }
+                });
+                let tz_convert = field.tz.as_ref().map(|(from, to)| get_tz_convert(from, to));
 
                 quote! {
                     pub fn #name(&self) -> Option<chrono::NaiveDateTime> {
+                        #none_check
                         let date = chrono::NaiveDate::parse_from_str(
-                            std::str::from_utf8(&self.#date_field.trim_ascii()).ok()?,
+                            ::core::str::from_utf8(binary_mirror::trim_ascii(&self.#date_field)).ok()?,
                             #date_format
                         ).ok()?;
                         let time = chrono::NaiveTime::parse_from_str(
-                            std::str::from_utf8(&self.#time_field.trim_ascii()).ok()?,
+                            ::core::str::from_utf8(binary_mirror::trim_ascii(&self.#time_field)).ok()?,
                             #time_format
                         ).ok()?;
-                        Some(chrono::NaiveDateTime::new(date, time))
+                        #subsec
+                        let dt = chrono::NaiveDateTime::new(date, time);
+                        #tz_convert
+                        Some(dt)
                     }
 
                     pub fn #method_with_warn_name(&self) -> Option<chrono::NaiveDateTime> {
+                        #none_check
                         match self.#name() {
                             Some(dt) => Some(dt),
                             None => {
@@ -387,10 +2051,12 @@ fn get_methods(native_fields: &[NativeField]) -> Vec<proc_macro2::TokenStream> {
                 match attrs.type_name.as_str() {
                     "str" => quote! {
                         pub fn #name(&self) -> Option<String> {
-                            std::str::from_utf8(&self.#origin_field.trim_ascii()).ok().map(|s| s.to_string())
+                            #none_check
+                            ::core::str::from_utf8(binary_mirror::trim_ascii(&self.#origin_field)).ok().map(|s| s.to_string())
                         }
 
                         pub fn #method_with_warn_name(&self) -> Option<String> {
+                            #none_check
                             match self.#name() {
                                 Some(s) => Some(s),
                                 None => {
@@ -403,10 +2069,12 @@ fn get_methods(native_fields: &[NativeField]) -> Vec<proc_macro2::TokenStream> {
                     "compact_str" => {
                         quote! {
                             pub fn #name(&self) -> Option<compact_str::CompactString> {
-                                compact_str::CompactString::from_utf8(&self.#origin_field.trim_ascii()).ok()
+                                #none_check
+                                compact_str::CompactString::from_utf8(binary_mirror::trim_ascii(&self.#origin_field)).ok()
                             }
 
                             pub fn #method_with_warn_name(&self) -> Option<compact_str::CompactString> {
+                                #none_check
                                 match self.#name() {
                                     Some(s) => Some(s),
                                     None => {
@@ -420,11 +2088,11 @@ fn get_methods(native_fields: &[NativeField]) -> Vec<proc_macro2::TokenStream> {
                     // "hipstr" => {
                     //     quote! {
                     //         pub fn #name(&self) -> hipstr::HipStr {
-                    //             hipstr::HipStr::from_utf8_lossy(hipstr::HipByt::borrowed(&self.#origin_field.trim_ascii()))
+                    //             hipstr::HipStr::from_utf8_lossy(hipstr::HipByt::borrowed(binary_mirror::trim_ascii(&self.#origin_field)))
                     //         }
 
                     //         pub fn #method_with_warn_name(&self) -> hipstr::HipStr {
-                    //             hipstr::HipStr::from_utf8_lossy(hipstr::HipByt::borrowed(&self.#origin_field.trim_ascii()))
+                    //             hipstr::HipStr::from_utf8_lossy(hipstr::HipByt::borrowed(binary_mirror::trim_ascii(&self.#origin_field)))
                     //         }
                     //     }
                     // },
@@ -442,15 +2110,16 @@ fn get_methods(native_fields: &[NativeField]) -> Vec<proc_macro2::TokenStream> {
                     }
                     "i16" | "i32" | "i64" | "u16" | "u32" | "u64" | "f32" | "f64" => {
                         let type_ident = quote::format_ident!("{}", attrs.type_name);
+                        let digits = get_number_digits(attrs, field.origin_fields[0].size, origin_field);
                         quote! {
                             pub fn #name(&self) -> Option<#type_ident> {
-                                std::str::from_utf8(&self.#origin_field.trim_ascii())
-                                    .ok()?
-                                    .parse::<#type_ident>()
-                                    .ok()
+                                #none_check
+                                #digits
+                                binary_mirror::parse_number::<#type_ident>(digits)
                             }
 
                             pub fn #method_with_warn_name(&self) -> Option<#type_ident> {
+                                #none_check
                                 match self.#name() {
                                     Some(val) => Some(val),
                                     None => {
@@ -461,40 +2130,53 @@ fn get_methods(native_fields: &[NativeField]) -> Vec<proc_macro2::TokenStream> {
                             }
                         }
                     }
-                    "decimal" => quote! {
-                        pub fn #name(&self) -> Option<rust_decimal::Decimal> {
-                            std::str::from_utf8(&self.#origin_field.trim_ascii())
-                                .ok()?
-                                .parse::<rust_decimal::Decimal>()
-                                .ok()
-                                .map(|d| d.normalize())
-                        }
-                        pub fn #method_with_warn_name(&self) -> Option<rust_decimal::Decimal> {
-                            match self.#name() {
-                                Some(d) => Some(d),
-                                None => {
-                                    #debug_bytes
-                                    None
+                    "decimal" => {
+                        let digits = get_number_digits(attrs, field.origin_fields[0].size, origin_field);
+                        quote! {
+                            pub fn #name(&self) -> Option<rust_decimal::Decimal> {
+                                #none_check
+                                #digits
+                                ::core::str::from_utf8(digits)
+                                    .ok()?
+                                    .parse::<rust_decimal::Decimal>()
+                                    .ok()
+                                    .map(|d| d.normalize())
+                            }
+                            pub fn #method_with_warn_name(&self) -> Option<rust_decimal::Decimal> {
+                                #none_check
+                                match self.#name() {
+                                    Some(d) => Some(d),
+                                    None => {
+                                        #debug_bytes
+                                        None
+                                    }
                                 }
                             }
-                        }
 
-                    },
+                        }
+                    }
                     "datetime" => {
-                        let format = attrs
-                            .format
-                            .as_ref()
-                            .map(String::as_str)
-                            .unwrap_or("%Y%m%d%H%M%S");
+                        let format = attrs.format.as_deref().unwrap_or("%Y%m%d%H%M%S");
+                        let tz_convert = field.tz.as_ref().map(|(from, to)| get_tz_convert(from, to));
+                        let method_with_format = quote::format_ident!("{}_with_format", name);
                         quote! {
                             pub fn #name(&self) -> Option<chrono::NaiveDateTime> {
-                                chrono::NaiveDateTime::parse_from_str(
-                                    std::str::from_utf8(&self.#origin_field.trim_ascii()).ok()?,
-                                    #format
-                                ).ok()
+                                self.#method_with_format(#format)
+                            }
+
+                            /// Parse with `format` in place of the format declared on the field
+                            pub fn #method_with_format(&self, format: &str) -> Option<chrono::NaiveDateTime> {
+                                #none_check
+                                let dt = chrono::NaiveDateTime::parse_from_str(
+                                    ::core::str::from_utf8(binary_mirror::trim_ascii(&self.#origin_field)).ok()?,
+                                    format
+                                ).ok()?;
+                                #tz_convert
+                                Some(dt)
                             }
 
                             pub fn #method_with_warn_name(&self) -> Option<chrono::NaiveDateTime> {
+                                #none_check
                                 match self.#name() {
                                     Some(dt) => Some(dt),
                                     None => {
@@ -508,20 +2190,24 @@ fn get_methods(native_fields: &[NativeField]) -> Vec<proc_macro2::TokenStream> {
                         }
                     }
                     "date" => {
-                        let format = attrs
-                            .format
-                            .as_ref()
-                            .map(String::as_str)
-                            .unwrap_or("%Y%m%d");
+                        let format = attrs.format.as_deref().unwrap_or("%Y%m%d");
+                        let method_with_format = quote::format_ident!("{}_with_format", name);
                         quote! {
                             pub fn #name(&self) -> Option<chrono::NaiveDate> {
+                                self.#method_with_format(#format)
+                            }
+
+                            /// Parse with `format` in place of the format declared on the field
+                            pub fn #method_with_format(&self, format: &str) -> Option<chrono::NaiveDate> {
+                                #none_check
                                 chrono::NaiveDate::parse_from_str(
-                                    std::str::from_utf8(&self.#origin_field.trim_ascii()).ok()?,
-                                    #format
+                                    ::core::str::from_utf8(binary_mirror::trim_ascii(&self.#origin_field)).ok()?,
+                                    format
                                 )
                                 .ok()
                             }
                             pub fn #method_with_warn_name(&self) -> Option<chrono::NaiveDate> {
+                                #none_check
                                 match self.#name() {
                                     Some(d) => Some(d),
                                     None => {
@@ -533,20 +2219,24 @@ fn get_methods(native_fields: &[NativeField]) -> Vec<proc_macro2::TokenStream> {
                         }
                     }
                     "time" => {
-                        let format = attrs
-                            .format
-                            .as_ref()
-                            .map(String::as_str)
-                            .unwrap_or("%H%M%S");
+                        let format = attrs.format.as_deref().unwrap_or("%H%M%S");
+                        let method_with_format = quote::format_ident!("{}_with_format", name);
                         quote! {
                             pub fn #name(&self) -> Option<chrono::NaiveTime> {
+                                self.#method_with_format(#format)
+                            }
+
+                            /// Parse with `format` in place of the format declared on the field
+                            pub fn #method_with_format(&self, format: &str) -> Option<chrono::NaiveTime> {
+                                #none_check
                                 chrono::NaiveTime::parse_from_str(
-                                    std::str::from_utf8(&self.#origin_field.trim_ascii()).ok()?,
-                                    #format
+                                    ::core::str::from_utf8(binary_mirror::trim_ascii(&self.#origin_field)).ok()?,
+                                    format
                                 )
                                 .ok()
                             }
                             pub fn #method_with_warn_name(&self) -> Option<chrono::NaiveTime> {
+                                #none_check
                                 match self.#name() {
                                     Some(t) => Some(t),
                                     None => {
@@ -562,10 +2252,12 @@ fn get_methods(native_fields: &[NativeField]) -> Vec<proc_macro2::TokenStream> {
                         let enum_ident = quote::format_ident!("{}", enum_type);
                         quote! {
                             pub fn #name(&self) -> Option<#enum_ident> {
+                                #none_check
                                 #enum_ident::from_bytes(&self.#origin_field)
                             }
 
                             pub fn #method_with_warn_name(&self) -> Option<#enum_ident> {
+                                #none_check
                                 match self.#name() {
                                     Some(v) => Some(v),
                                     None => {
@@ -577,6 +2269,57 @@ fn get_methods(native_fields: &[NativeField]) -> Vec<proc_macro2::TokenStream> {
 
                         }
                     }
+                    "rest" => quote! {
+                        /// The trailing bytes after the fixed fields
+                        pub fn #name(&self) -> &[u8] {
+                            &self.#origin_field
+                        }
+                    },
+                    "flatten" if attrs.generic => {
+                        let ty = get_flatten_ty(attrs);
+                        quote! {
+                            /// The nested struct
+                            pub fn #name(&self) -> &#ty {
+                                &self.#origin_field
+                            }
+                        }
+                    }
+                    "flatten" => {
+                        let ty = get_flatten_ty(attrs);
+                        quote! {
+                            /// The nested struct, viewed in place
+                            pub fn #name(&self) -> &#ty {
+                                <#ty as binary_mirror::FromBytes>::from_bytes(&self.#origin_field)
+                                    .expect("size checked at compile time")
+                            }
+                        }
+                    }
+                    "custom" => {
+                        let ty = &field.pure_ty;
+                        let parse_with = attrs
+                            .parse_with
+                            .as_ref()
+                            .expect("parse_with is required for custom field");
+                        let parse_with = syn::parse_str::<syn::Path>(parse_with)
+                            .expect("Could not parse parse_with");
+                        quote! {
+                            pub fn #name(&self) -> Option<#ty> {
+                                #none_check
+                                #parse_with(&self.#origin_field)
+                            }
+
+                            pub fn #method_with_warn_name(&self) -> Option<#ty> {
+                                #none_check
+                                match self.#name() {
+                                    Some(v) => Some(v),
+                                    None => {
+                                        #debug_bytes
+                                        None
+                                    }
+                                }
+                            }
+                        }
+                    }
                     _ => panic!("Unsupported type: {}", attrs.type_name),
                 }
             }
@@ -584,7 +2327,11 @@ fn get_methods(native_fields: &[NativeField]) -> Vec<proc_macro2::TokenStream> {
         .collect()
 }
 
-fn get_display_fields(native_fields: &[NativeField]) -> Vec<proc_macro2::TokenStream> {
+fn get_display_fields(native_fields: &[NativeField], format: DisplayFormat) -> Vec<proc_macro2::TokenStream> {
+    let sep = match format {
+        DisplayFormat::KeyValue => "=",
+        DisplayFormat::Compact | DisplayFormat::Multiline => ": ",
+    };
     native_fields
         .iter()
         .filter_map(|field| {
@@ -599,6 +2346,14 @@ fn get_display_fields(native_fields: &[NativeField]) -> Vec<proc_macro2::TokenSt
             }
 
             Some(match attrs.type_name.as_str() {
+                "str" | "compact_str" | "i16" | "i32" | "i64" | "u16" | "u32" | "u64" | "f32" | "f64" | "decimal"
+                | "datetime" | "date" | "time" | "flatten" | "enum" | "custom"
+                    if is_redacted(field) =>
+                {
+                    quote! {
+                        write!(f, "{}{}{}", stringify!(#name), #sep, binary_mirror::redact::MASK)?;
+                    }
+                }
                 // | "hipstr" 
                 // "str" | "compact_str" => quote! {
                 //     write!(f, "{}: {}", stringify!(#name), self.#method_name())?;
@@ -606,57 +2361,356 @@ fn get_display_fields(native_fields: &[NativeField]) -> Vec<proc_macro2::TokenSt
                 "str" | "compact_str" | "i16" | "i32" | "i64" | "u16" | "u32" | "u64" | "f32" | "f64" | "decimal"
                 | "datetime" | "date" | "time" => quote! {
                     match self.#method_name() {
-                        Some(val) => write!(f, "{}: {}", stringify!(#name), val)?,
-                        None => write!(f, "{}: Error<bytes: \"{}\">",
+                        Some(val) => write!(f, "{}{}{}", stringify!(#name), #sep, val)?,
+                        None => write!(f, "{}{}Error<bytes: \"{}\">",
                             stringify!(#name),
+                            #sep,
                             binary_mirror::to_bytes_repr(&self.#origin_field)
                         )?,
                     }
                 },
-                "enum" => quote! {
+                "flatten" => quote! {
+                    write!(f, "{}{}{}", stringify!(#name), #sep, self.#method_name())?;
+                },
+                "enum" | "custom" => quote! {
                     match self.#method_name() {
-                        Some(val) => write!(f, "{}: {:?}", stringify!(#name), val)?,
-                        None => write!(f, "{}: Error<bytes: \"{}\">",
+                        Some(val) => write!(f, "{}{}{:?}", stringify!(#name), #sep, val)?,
+                        None => write!(f, "{}{}Error<bytes: \"{}\">",
                             stringify!(#name),
+                            #sep,
                             binary_mirror::to_bytes_repr(&self.#origin_field)
                         )?,
                     }
                 },
-                _ => quote! {},
+                // Nothing to show, so no separator either
+                _ => return None,
             })
         })
         .collect()
 }
 
+/// `Display` in the struct's `display_format`, nothing with `display = false`
+fn get_display_impl(
+    name: &syn::Ident,
+    generics: &StructGenerics,
+    native_fields: &[NativeField],
+    format: Option<DisplayFormat>,
+) -> proc_macro2::TokenStream {
+    let Some(format) = format else {
+        return quote! {};
+    };
+    let StructGenerics { impl_generics, where_clause, ty, .. } = generics;
+    let display_fields_token = get_display_fields(native_fields, format);
+    let (open, before, between, close) = match format {
+        DisplayFormat::Compact => (quote! { write!(f, "{} {{ ", stringify!(#name))?; }, "", ", ", " }"),
+        DisplayFormat::Multiline => (quote! { write!(f, "{} {{\n", stringify!(#name))?; }, "    ", ",\n    ", "\n}"),
+        DisplayFormat::KeyValue => (quote! {}, "", " ", ""),
+    };
+    quote! {
+        impl #impl_generics ::core::fmt::Display for #ty #where_clause {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                #open
+                let mut first = true;
+                #(
+                    if first {
+                        first = false;
+                        f.write_str(#before)?;
+                    } else {
+                        f.write_str(#between)?;
+                    }
+                    #display_fields_token
+                )*
+                f.write_str(#close)
+            }
+        }
+    }
+}
+
+/// Path of the serde adapter a `bytes_repr` field serializes with
+fn get_bytes_repr_with(field: &NativeField) -> Option<String> {
+    let repr = field.origin_fields[0].attrs.as_ref()?.bytes_repr.as_ref()?;
+    Some(format!("binary_mirror::bytes_repr::{}", repr))
+}
+
+/// The text of `#[doc = "..."]` attributes, i.e. `///` comments, joined by newlines
+fn get_doc(attrs: &[syn::Attribute]) -> Option<String> {
+    let lines = attrs
+        .iter()
+        .filter_map(|attr| match &attr.meta {
+            syn::Meta::NameValue(syn::MetaNameValue {
+                path,
+                value: syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(doc), ..
+                }),
+                ..
+            }) if path.is_ident("doc") => Some(doc.value()),
+            _ => None,
+        })
+        .map(|line| line.strip_prefix(' ').map(str::to_string).unwrap_or(line))
+        .collect::<Vec<_>>();
+    (!lines.is_empty()).then(|| lines.join("\n"))
+}
+
+/// Doc comment of the native field, from the first raw field it is built from that has one
+fn get_native_doc(field: &NativeField) -> Option<&str> {
+    field.origin_fields.iter().find_map(|origin| origin.doc.as_deref())
+}
+
+/// Whether any raw field a native field is built from has `redact`
+fn is_redacted(field: &NativeField) -> bool {
+    field
+        .origin_fields
+        .iter()
+        .any(|origin| origin.attrs.as_ref().is_some_and(|attrs| attrs.redact))
+}
+
+/// `Debug` of a native or view struct with redacted fields, which can't derive it
+///
+/// Hidden fields carrying raw bytes are left out, they would show what `redact` masks.
+fn get_redacted_debug_impl(
+    name: &syn::Ident,
+    generics: Option<&StructGenerics>,
+    fields: &[NativeField],
+    computed: &[ComputedField],
+) -> proc_macro2::TokenStream {
+    let debug_fields = fields.iter().filter(|field| !field.skip_native).map(|field| {
+        let field_name = &field.name;
+        if is_redacted(field) {
+            quote! { .field(stringify!(#field_name), &format_args!("{}", binary_mirror::redact::MASK)) }
+        } else {
+            quote! { .field(stringify!(#field_name), &self.#field_name) }
+        }
+    });
+    let computed_fields = computed.iter().map(|field| {
+        let field_name = &field.name;
+        quote! { .field(stringify!(#field_name), &self.#field_name) }
+    });
+    let (impl_generics, native_params) = match generics {
+        Some(generics) if !generics.params.is_empty() => {
+            let params = &generics.params;
+            (quote!(<#(#params: ::core::fmt::Debug),*>), generics.native_params.clone())
+        }
+        _ => (quote! {}, quote! {}),
+    };
+    quote! {
+        impl #impl_generics ::core::fmt::Debug for #name #native_params {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                f.debug_struct(stringify!(#name))
+                    #(#debug_fields)*
+                    #(#computed_fields)*
+                    .finish()
+            }
+        }
+    }
+}
+
+/// Attributes from `native_attrs` of the raw fields a native field is built from
+fn get_native_attrs(field: &NativeField) -> Vec<&syn::Meta> {
+    field
+        .origin_fields
+        .iter()
+        .filter_map(|origin| origin.attrs.as_ref())
+        .flat_map(|attrs| &attrs.native_attrs)
+        .collect()
+}
+
+/// Serde attribute arguments copied verbatim onto the native field
+fn get_native_serde(field: &NativeField) -> Option<&proc_macro2::TokenStream> {
+    field
+        .origin_fields
+        .iter()
+        .find_map(|origin| origin.attrs.as_ref()?.native_serde.as_ref())
+}
+
+/// The type of a native field in a struct definition, which can't take the prelude of
+/// `with_alloc_prelude`, so the `alloc` types are spelled out
+fn get_native_def_ty(field: &NativeField) -> proc_macro2::TokenStream {
+    match field.type_name.as_str() {
+        "str" => quote!(Option<binary_mirror::__private::String>),
+        "rest" => quote!(binary_mirror::__private::Vec<u8>),
+        _ => field.ty.clone(),
+    }
+}
+
 fn get_native_fields_token(native_fields: &[NativeField]) -> Vec<proc_macro2::TokenStream> {
     native_fields
         .iter()
         .filter(|field| !field.skip_native)
         .map(|field| {
             let name = &field.name;
-            let ty = &field.ty;
+            let ty = get_native_def_ty(field);
+            let serde_attr = get_native_serde(field).map(|args| quote! { #[serde(#args)] });
+            let flatten_attr = (field.type_name == "flatten").then(|| quote! { #[serde(flatten)] });
+            let repr_attr = get_bytes_repr_with(field).map(|with| quote! { #[serde(with = #with)] });
+            let doc_attr = get_native_doc(field).map(|doc| quote! { #[doc = #doc] });
+            let native_attrs = get_native_attrs(field);
 
             quote! {
+                #doc_attr
+                #flatten_attr
+                #repr_attr
+                #serde_attr
+                #(#[#native_attrs])*
                 pub #name: #ty
             }
         })
         .collect()
 }
 
+/// The hidden native field carrying the bytes of a `skip_native` field with `carry_raw`
+fn get_carry_name(origin: &OriginField) -> syn::Ident {
+    quote::format_ident!("__raw_{}", origin.name)
+}
+
+/// One item per `carry_raw` field, built from its hidden native field and raw field
+fn get_carried_raw<T, F>(native_fields: &[NativeField], token: F) -> Vec<T>
+where
+    F: Fn(&syn::Ident, &OriginField) -> T,
+{
+    native_fields
+        .iter()
+        .filter(|field| field.skip_native)
+        .map(|field| &field.origin_fields[0])
+        .filter(|origin| origin.attrs.as_ref().is_some_and(|attrs| attrs.carry_raw))
+        .map(|origin| token(&get_carry_name(origin), origin))
+        .collect()
+}
+
+/// Declarations of the computed fields, serde leaves them out of the input as they are derived
+fn get_computed_fields_token(struct_attrs: &StructAttrs) -> Vec<proc_macro2::TokenStream> {
+    struct_attrs
+        .computed
+        .iter()
+        .map(|field| {
+            let ComputedField { name, ty, .. } = field;
+            quote! {
+                #[serde(default)]
+                pub #name: #ty
+            }
+        })
+        .collect()
+}
+
+/// Initializers holding the computed fields at their default until they are computed
+fn get_computed_defaults(struct_attrs: &StructAttrs) -> Vec<proc_macro2::TokenStream> {
+    struct_attrs
+        .computed
+        .iter()
+        .map(|field| {
+            let name = &field.name;
+            quote! { #name: Default::default() }
+        })
+        .collect()
+}
+
+/// Statements computing each computed field of `native` from the fields before it
+fn get_computed_assigns(struct_attrs: &StructAttrs, native: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    let assigns = struct_attrs.computed.iter().map(|field| {
+        let ComputedField { name, with, .. } = field;
+        quote! { native.#name = #with(#native); }
+    });
+    quote! { #(#assigns)* }
+}
+
+/// The hidden native field a `preserve_unknown` struct carries its original bytes in
+fn get_preserved_field_token(struct_attrs: &StructAttrs) -> Vec<proc_macro2::TokenStream> {
+    let token = quote! {
+        #[doc(hidden)]
+        #[serde(skip)]
+        pub __raw: binary_mirror::PreservedBytes
+    };
+    struct_attrs.preserve_unknown.then_some(token).into_iter().collect()
+}
+
+/// Initializer of the preserved bytes for native structs not converted from a record
+fn get_preserved_default(struct_attrs: &StructAttrs) -> Vec<proc_macro2::TokenStream> {
+    let token = quote! { __raw: Default::default() };
+    struct_attrs.preserve_unknown.then_some(token).into_iter().collect()
+}
+
+/// The view structs and their `to_<view>` methods, converting only the fields each lists
+fn get_views(
+    struct_attrs: &StructAttrs,
+    native_fields: &[NativeField],
+) -> syn::Result<(Vec<proc_macro2::TokenStream>, Vec<proc_macro2::TokenStream>)> {
+    let mut structs = Vec::new();
+    let mut methods = Vec::new();
+    for view in &struct_attrs.views {
+        let mut fields: Vec<NativeField> = Vec::new();
+        for name in &view.fields {
+            let field = native_fields
+                .iter()
+                .find(|field| &field.name == name)
+                .filter(|field| !field.skip_native)
+                .ok_or_else(|| {
+                    syn::Error::new_spanned(name, format!("view field `{}` is not a native field", name))
+                })?;
+            if is_generic(&field.origin_fields[0]) {
+                return Err(syn::Error::new_spanned(
+                    name,
+                    format!("generic field `{}` cannot be part of a view", name),
+                ));
+            }
+            if fields.iter().any(|field| &field.name == name) {
+                return Err(syn::Error::new_spanned(name, format!("view field `{}` is listed twice", name)));
+            }
+            fields.push(field.clone());
+        }
+        let view_name = &view.name;
+        let fields_token = get_native_fields_token(&fields);
+        let to_view_fields = get_to_native_fields(&fields);
+        let method_name = quote::format_ident!("to_{}", to_snake_case(&view_name.to_string()));
+        let doc = format!("Convert only the fields of the `{}` view", view_name);
+        let redacted = needs_redacted_debug(struct_attrs, &fields);
+        let native_derives = get_native_derives(struct_attrs, redacted);
+        structs.push(quote! {
+            #[derive(#native_derives)]
+            pub struct #view_name {
+                #(#fields_token,)*
+            }
+        });
+        if redacted {
+            structs.push(get_redacted_debug_impl(view_name, None, &fields, &[]));
+        }
+        methods.push(quote! {
+            #[doc = #doc]
+            pub fn #method_name(&self) -> #view_name {
+                #view_name {
+                    #(#to_view_fields,)*
+                }
+            }
+        });
+    }
+    Ok((structs, methods))
+}
+
+/// `OrderKey` as `order_key`
+fn to_snake_case(name: &str) -> String {
+    let mut out = String::with_capacity(name.len() + 4);
+    for (i, c) in name.chars().enumerate() {
+        if c.is_ascii_uppercase() {
+            if i > 0 {
+                out.push('_');
+            }
+            out.push(c.to_ascii_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
 fn get_to_native_fields(native_fields: &[NativeField]) -> Vec<proc_macro2::TokenStream> {
     native_fields
         .iter()
         .filter(|field| !field.skip_native)
         .map(|field| {
             let name = &field.name;
-            let ignore_warn = field.origin_fields[0]
-                .attrs
-                .as_ref()
-                .map(|attrs| attrs.ignore_warn)
-                .unwrap_or(false);
 
-            if ignore_warn {
-                quote! { #name: self.#name() }
+            if field.type_name == "flatten" {
+                quote! { #name: binary_mirror::ToNative::to_native(self.#name()) }
+            } else if field.type_name == "rest" {
+                quote! { #name: self.#name().to_vec() }
             } else {
                 let method_name = quote::format_ident!("{}_with_warn", name);
                 quote! { #name: self.#method_name() }
@@ -665,440 +2719,3659 @@ fn get_to_native_fields(native_fields: &[NativeField]) -> Vec<proc_macro2::Token
         .collect()
 }
 
-fn get_from_native_fields(
-    native_field_map: &[NativeField2OriginFieldMap],
+/// `get_to_native_fields`, with the date and time fields named in `overrides` parsed with the
+/// format given there
+fn get_to_native_formats_fields(
+    struct_name: &syn::Ident,
+    struct_attrs: &StructAttrs,
+    native_fields: &[NativeField],
 ) -> Vec<proc_macro2::TokenStream> {
-    native_field_map.iter().map(|mapping| {
-        let field_name = &mapping.origin_field.name;
-        let size = mapping.origin_field.size;
-        let default_byte = mapping.origin_field.attrs
-            .as_ref()
-            .and_then(|attrs| attrs.default_byte)
-            .unwrap_or(b' ');
-        
-
-        if let Some(native_field) = &mapping.native_field {
-            let native_name = &native_field.name;
-            let attrs = mapping.origin_field.attrs.as_ref().unwrap();
-            let format = attrs.format.as_ref().map(String::as_str);
-            let skip_native = native_field.skip_native;
-            if skip_native {
-                return quote! {
-                    #field_name: [#default_byte; #size]
-                };
-            }
-            match attrs.type_name.as_str() {
-                // | "hipstr" 
-                "str" | "compact_str" => quote! {
-                    #field_name: {
-                        let mut bytes = [#default_byte; #size];  // Use default_byte here
-                        if let Some(s) = &native.#native_name {
-                            let s = s.as_bytes();
-                            bytes[..s.len().min(#size)].copy_from_slice(&s[..s.len().min(#size)]);
-                        }
-                        bytes
-                    }
-                },
-                "enum" => quote! {
-                    #field_name: {
-                        let mut bytes = [#default_byte; #size];
-                        if let Some(enum_val) = &native.#native_name {
-                            let s = enum_val.as_bytes();
-                            bytes[..s.len().min(#size)].copy_from_slice(&s[..s.len().min(#size)]);
-                        }
-                        bytes
-                    }
-                },
-                "datetime" => {
-                    let format = attrs.format.as_ref()
-                        .map(String::as_str)
-                        .unwrap_or("%Y-%m-%d %H:%M:%S");
-                    quote! {
-                        #field_name: {
-                            let mut bytes = [#default_byte; #size];
-                            if let Some(dt) = native.#native_name {
-                                let s = dt.format(#format).to_string();
-                                let b = s.as_bytes();
-                                bytes[..b.len().min(#size)].copy_from_slice(&b[..b.len().min(#size)]);
-                            }
-                            bytes
-                        }
-                    }
+    let defaults = get_to_native_fields(native_fields);
+    native_fields
+        .iter()
+        .filter(|field| !field.skip_native)
+        .zip(defaults)
+        .map(|(field, default)| {
+            let name = &field.name;
+            let attrs = field.origin_fields[0].attrs.as_ref();
+            match field.type_name.as_str() {
+                "flatten" if !attrs.is_some_and(|attrs| attrs.generic) => {
+                    quote! { #name: self.#name().to_native_with_formats(overrides) }
                 }
-                "date" => {
-                    let format = attrs.format.as_ref()
-                        .map(String::as_str)
-                        .unwrap_or("%Y-%m-%d");
-                    quote! {
-                        #field_name: {
-                            let mut bytes = [#default_byte; #size];
-                            if let Some(dt) = native.#native_name {
-                                let s = dt.format(#format).to_string();
-                                let b = s.as_bytes();
-                                bytes[..b.len().min(#size)].copy_from_slice(&b[..b.len().min(#size)]);
-                            }
-                            bytes
+                "date" | "time" | "datetime" if !field.is_combined_datetime => {
+                    let method_with_format = quote::format_ident!("{}_with_format", name);
+                    let method_with_warn = quote::format_ident!("{}_with_warn", name);
+                    let debug_bytes = get_parse_failure(struct_name, struct_attrs, field);
+                    let condition = match get_none_condition(&field.origin_fields[0]) {
+                        Some(condition) => quote! { value.is_none() && !#condition },
+                        None => quote! { value.is_none() },
+                    };
+                    let warn = quote! {
+                        if #condition {
+                            #debug_bytes
                         }
-                    }
-                },
-                "time" => {
-                    let format = attrs.format.as_ref()
-                        .map(String::as_str)
-                        .unwrap_or("%H%M%S");
+                    };
+                    let parse = quote! { self.#method_with_warn() };
                     quote! {
-                        #field_name: {
-                            let mut bytes = [#default_byte; #size];
-                            if let Some(dt) = native.#native_name {
-                                let s = dt.format(#format).to_string();
-                                let b = s.as_bytes();
-                                bytes[..b.len().min(#size)].copy_from_slice(&b[..b.len().min(#size)]);
-                            }
-                            bytes
-                        }
-                    }
-                },
-                "i16" | "i32" | "i64" | "u16" | "u32" | "u64" | "f32" | "f64" | "decimal" => {
-                    if let Some(fmt) = format {
-                        quote! {
-                            #field_name: {
-                                let mut bytes = [#default_byte; #size];
-                                if let Some(val) = &native.#native_name {
-                                    let s = format!(#fmt, val);
-                                    let b = s.as_bytes();
-                                    bytes[..b.len().min(#size)].copy_from_slice(&b[..b.len().min(#size)]);
-                                }
-                                bytes
+                        #name: match overrides.get(stringify!(#name)) {
+                            Some(format) => {
+                                let value = self.#method_with_format(format);
+                                #warn
+                                value
                             }
+                            None => #parse,
                         }
-                    } else {
-                        quote! {
-                            #field_name: {
-                                let mut bytes = [#default_byte; #size];
-                                if let Some(val) = &native.#native_name {
-                                    let s = val.to_string();
-                                    let b = s.as_bytes();
-                                    bytes[..b.len().min(#size)].copy_from_slice(&b[..b.len().min(#size)]);
-                                }
-                                bytes
-                            }
-                        }
-                    }
-                },
-                "bytes" => quote! {
-                    #field_name: native.#native_name
-                },
-                _ => quote! {
-                    #field_name: {
-                        let mut bytes = [#default_byte; #size];
-                        if let Some(val) = &native.#native_name {
-                            let s = val.to_string();
-                            let b = s.as_bytes();
-                            bytes[..b.len().min(#size)].copy_from_slice(&b[..b.len().min(#size)]);
-                        }
-                        bytes
                     }
                 }
+                _ => default,
             }
-        } else {
-            // Field without attributes, use default byte
-            quote! {
-                #field_name: [#default_byte; #size]
-            }
-        }
-    }).collect()
+        })
+        .collect()
 }
 
-fn get_native_methods(native_fields: &[NativeField]) -> Vec<proc_macro2::TokenStream> {
+fn get_to_native_into_fields(
+    struct_name: &syn::Ident,
+    struct_attrs: &StructAttrs,
+    native_fields: &[NativeField],
+) -> Vec<proc_macro2::TokenStream> {
     native_fields
         .iter()
         .filter(|field| !field.skip_native)
         .map(|field| {
             let name = &field.name;
-            let method_name = quote::format_ident!("with_{}", name);
-            let ty = &field.pure_ty;
-            let type_name = &field.type_name;
-
-            match type_name.as_str() {
-                "str" => quote! {
-                    pub fn #method_name(mut self, value: impl Into<String>) -> Self {
-                        self.#name = Some(value.into());
-                        self
+            let origin_field = &field.origin_fields[0].name;
+            let buf_ty = match field.type_name.as_str() {
+                "str" => quote!(String),
+                "compact_str" => quote!(compact_str::CompactString),
+                "flatten" if field.origin_fields[0].attrs.as_ref().is_some_and(|attrs| attrs.generic) => {
+                    return quote! { native.#name = binary_mirror::ToNative::to_native(self.#name()); }
+                }
+                "flatten" => return quote! { self.#name().to_native_into(&mut native.#name); },
+                "rest" => {
+                    return quote! {
+                        native.#name.clear();
+                        native.#name.extend_from_slice(self.#name());
                     }
-                },
-                "compact_str" => quote! {
-                    pub fn #method_name(mut self, value: impl Into<compact_str::CompactString>) -> Self {
-                        self.#name = Some(value.into());
-                        self
+                }
+                _ => {
+                    let method_name = quote::format_ident!("{}_with_warn", name);
+                    return quote! { native.#name = self.#method_name(); };
+                }
+            };
+            let warn = get_parse_failure(struct_name, struct_attrs, field);
+            // Reuse the existing buffer instead of allocating a new string
+            let parse = quote! {
+                match ::core::str::from_utf8(binary_mirror::trim_ascii(&self.#origin_field)) {
+                    Ok(s) => {
+                        let buf = native.#name.get_or_insert_with(#buf_ty::default);
+                        buf.clear();
+                        buf.push_str(s);
                     }
-                },
-                // "hipstr" => quote! {
-                //     pub fn #method_name<'borrow>(mut self, value: impl Into<hipstr::HipStr<'borrow>>) -> Self {
-                //         self.#name = value.into();
-                //         self
-                //     }
-                // },
-                "i16" | "i32" | "i64" | "u16" | "u32" | "u64" | "f32" | "f64" | "decimal"
-                | "datetime" | "date" | "time" | "enum" => {
-                    quote! {
-                        pub fn #method_name(mut self, value: #ty) -> Self {
-                            self.#name = Some(value);
-                            self
-                        }
+                    Err(_) => {
+                        #warn
+                        native.#name = None;
                     }
                 }
-                _ => quote! {
-                    pub fn #method_name(mut self, value: #ty) -> Self {
-                        self.#name = value;
-                        self
+            };
+            match get_none_condition(&field.origin_fields[0]) {
+                Some(condition) => quote! {
+                    if #condition {
+                        native.#name = None;
+                    } else {
+                        #parse
                     }
                 },
+                None => parse,
             }
         })
         .collect()
 }
 
-fn get_field_spec_methods(origin_fields: &[OriginField]) -> proc_macro2::TokenStream {
-    let mut cumulative_size = 0;
-    let size_methods = origin_fields.iter().map(|field| {
-        let field_name = &field.name;
-        let field_size = field.size;
-        let offset = cumulative_size;
-        let limit = offset + field_size;
-        cumulative_size = limit;
-        let method_name = quote::format_ident!("{}_spec", field_name);
-
+fn get_to_native_report_fields(
+    struct_name: &syn::Ident,
+    struct_attrs: &StructAttrs,
+    native_fields: &[NativeField],
+) -> Vec<proc_macro2::TokenStream> {
+    native_fields
+        .iter()
+        .filter(|field| !field.skip_native)
+        .map(|field| {
+            let name = &field.name;
+            if field.type_name == "bytes" {
+                return quote! { #name: self.#name() };
+            }
+            if field.type_name == "rest" {
+                return quote! { #name: self.#name().to_vec() };
+            }
+            if field.type_name == "flatten" {
+                return quote! {
+                    #name: {
+                        let (value, nested) = binary_mirror::ToNative::to_native_with_report(self.#name());
+                        errors.extend(nested);
+                        value
+                    }
+                };
+            }
+            // A combined datetime fails unless all of its parts are blank, and reports them all
+            let origin_names = field.origin_fields.iter().map(|f| &f.name).collect::<Vec<_>>();
+            let sentinel = get_none_condition(&field.origin_fields[0])
+                .map(|condition| quote! { && !#condition });
+            let failure = get_parse_failure_report(struct_name, struct_attrs, field);
+            quote! {
+                #name: {
+                    let value = self.#name();
+                    if value.is_none()
+                        && !(#(binary_mirror::is_blank(&self.#origin_names))&&*)
+                        #sentinel
+                    {
+                        #failure
+                        errors.push(binary_mirror::FieldError::new(
+                            stringify!(#name),
+                            [#(binary_mirror::to_bytes_repr(&self.#origin_names)),*].concat(),
+                        ));
+                    }
+                    value
+                }
+            }
+        })
+        .collect()
+}
+
+/// Raw field name and value expression for every fixed-size origin field
+fn get_from_native_fields(
+    native_field_map: &[NativeField2OriginFieldMap],
+) -> Vec<(syn::Ident, proc_macro2::TokenStream)> {
+    native_field_map
+        .iter()
+        .filter(|mapping| !is_rest(&mapping.origin_field) && !is_overlay_view(mapping))
+        .map(|mapping| {
+            let field_name = &mapping.origin_field.name;
+            let value = match &mapping.native_field {
+                Some(native_field) if native_field.skip_native => {
+                    let size = mapping.origin_field.size;
+                    let default_byte = mapping.origin_field.default_byte;
+                    let carry_raw = mapping.origin_field.attrs.as_ref().is_some_and(|attrs| attrs.carry_raw);
+                    if carry_raw {
+                        let carry = get_carry_name(&mapping.origin_field);
+                        quote! { native.#carry.unwrap_or([#default_byte; #size]) }
+                    } else {
+                        quote! { [#default_byte; #size] }
+                    }
+                }
+                Some(native_field) => {
+                    let native_name = &native_field.name;
+                    get_from_native_value(mapping, &quote!(native.#native_name))
+                }
+                None => get_from_native_value(mapping, &quote!(native)),
+            };
+            let none_value = mapping
+                .native_field
+                .as_ref()
+                .filter(|native_field| !native_field.skip_native)
+                .zip(mapping.origin_field.attrs.as_ref())
+                .and_then(|(native_field, attrs)| Some((&native_field.name, attrs.none_value.as_ref()?)));
+            match none_value {
+                // Write the sentinel back when the native value is None
+                Some((native_name, none_value)) => {
+                    let none_value = syn::LitByteStr::new(none_value, proc_macro2::Span::call_site());
+                    let value = quote! {
+                        match &native.#native_name {
+                            None => *#none_value,
+                            Some(_) => #value,
+                        }
+                    };
+                    (field_name.clone(), value)
+                }
+                None => (field_name.clone(), value),
+            }
+        })
+        .collect()
+}
+
+/// Expression encoding `source` (an `Option` of the native value, or the array for `bytes`)
+/// into the bytes of the mapped origin field
+fn get_from_native_value(
+    mapping: &NativeField2OriginFieldMap,
+    source: &proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    let size = mapping.origin_field.size;
+    let default_byte = mapping.origin_field.default_byte;
+
+    if let Some(native_field) = &mapping.native_field {
+        let attrs = mapping.origin_field.attrs.as_ref().unwrap();
+        // A datetime is written back in the zone of the raw bytes
+        let converted;
+        let source = match &native_field.tz {
+            Some((from, to)) => {
+                let convert = get_tz_convert(to, from);
+                converted = quote! { (#source).and_then(|dt| { #convert Some(dt) }) };
+                &converted
+            }
+            None => source,
+        };
+        if let Some(write_with) = &attrs.write_with {
+            let write_with = syn::parse_str::<syn::Path>(write_with)
+                .expect("Could not parse write_with");
+            return if attrs.type_name == "bytes" {
+                quote! {
+                    {
+                        let mut bytes = [#default_byte; #size];
+                        #write_with(&#source, &mut bytes);
+                        bytes
+                    }
+                }
+            } else {
+                quote! {
+                    {
+                        let mut bytes = [#default_byte; #size];
+                        if let Some(val) = &#source {
+                            #write_with(val, &mut bytes);
+                        }
+                        bytes
+                    }
+                }
+            };
+        }
+        match attrs.type_name.as_str() {
+            // | "hipstr" 
+            "str" | "compact_str" => quote! {
+                {
+                    let mut bytes = [#default_byte; #size];  // Use default_byte here
+                    if let Some(s) = &#source {
+                        let s = s.as_bytes();
+                        bytes[..s.len().min(#size)].copy_from_slice(&s[..s.len().min(#size)]);
+                    }
+                    bytes
+                }
+            },
+            "enum" => quote! {
+                match &#source {
+                    Some(enum_val) => enum_val.to_field_bytes::<#size>(#default_byte),
+                    None => [#default_byte; #size],
+                }
+            },
+            // The fraction of a second of a combined datetime, zero-padded to the field width
+            "subsec" => {
+                let scale = 10u32.pow(9 - size as u32);
+                quote! {
+                    {
+                        let mut bytes = [#default_byte; #size];
+                        if let Some(dt) = #source {
+                            let fraction = chrono::Timelike::nanosecond(&dt) % 1_000_000_000 / #scale;
+                            let mut s = binary_mirror::TextBuf::<#size>::new();
+                            let _ = ::core::fmt::Write::write_fmt(&mut s, format_args!("{:0width$}", fraction, width = #size));
+                            bytes.copy_from_slice(s.as_bytes());
+                        }
+                        bytes
+                    }
+                }
+            }
+            "datetime" => {
+                let format = attrs.format.as_deref().unwrap_or("%Y-%m-%d %H:%M:%S");
+                quote! {
+                    {
+                        let mut bytes = [#default_byte; #size];
+                        if let Some(dt) = #source {
+                            let mut s = binary_mirror::TextBuf::<#size>::new();
+                            let _ = dt.format(#format).write_to(&mut s);
+                            let b = s.as_bytes();
+                            bytes[..b.len()].copy_from_slice(b);
+                        }
+                        bytes
+                    }
+                }
+            }
+            "date" => {
+                let format = attrs.format.as_deref().unwrap_or("%Y-%m-%d");
+                quote! {
+                    {
+                        let mut bytes = [#default_byte; #size];
+                        if let Some(dt) = #source {
+                            let mut s = binary_mirror::TextBuf::<#size>::new();
+                            let _ = dt.format(#format).write_to(&mut s);
+                            let b = s.as_bytes();
+                            bytes[..b.len()].copy_from_slice(b);
+                        }
+                        bytes
+                    }
+                }
+            },
+            "time" => {
+                let format = attrs.format.as_deref().unwrap_or("%H%M%S");
+                quote! {
+                    {
+                        let mut bytes = [#default_byte; #size];
+                        if let Some(dt) = #source {
+                            let mut s = binary_mirror::TextBuf::<#size>::new();
+                            let _ = dt.format(#format).write_to(&mut s);
+                            let b = s.as_bytes();
+                            bytes[..b.len()].copy_from_slice(b);
+                        }
+                        bytes
+                    }
+                }
+            },
+            "i16" | "i32" | "i64" | "u16" | "u32" | "u64" | "f32" | "f64" | "decimal" => {
+                let text = get_number_text(attrs, size);
+                // Text wider than the field is cut, `try_to_raw()` reports it instead
+                let write = match attrs.fill {
+                    Some(fill) => quote! {
+                        binary_mirror::fill_field(s, #fill, &mut bytes);
+                    },
+                    None => quote! {
+                        let b = s.as_bytes();
+                        bytes[..b.len().min(#size)].copy_from_slice(&b[..b.len().min(#size)]);
+                    },
+                };
+                quote! {
+                    {
+                        let mut bytes = [#default_byte; #size];
+                        if let Some(val) = &#source {
+                            #text
+                            #write
+                        }
+                        bytes
+                    }
+                }
+            },
+            "bytes" => quote! {
+                #source
+            },
+            "flatten" if attrs.generic => {
+                let ty = get_flatten_ty(attrs);
+                let native_ty = get_flatten_native_ty(attrs);
+                quote! { <#ty as binary_mirror::FromNative<#native_ty>>::from_native(&#source) }
+            }
+            "flatten" => {
+                let ty = get_flatten_ty(attrs);
+                let native_ty = get_flatten_native_ty(attrs);
+                quote! {
+                    {
+                        let raw = <#ty as binary_mirror::FromNative<#native_ty>>::from_native(&#source);
+                        let mut bytes = [0u8; #size];
+                        bytes.copy_from_slice(binary_mirror::ToBytes::to_bytes(&raw));
+                        bytes
+                    }
+                }
+            }
+            _ => quote! {
+                {
+                    let mut bytes = [#default_byte; #size];
+                    if let Some(val) = &#source {
+                        let mut s = binary_mirror::TextBuf::<#size>::new();
+                        let _ = ::core::fmt::Write::write_fmt(&mut s, format_args!("{}", val));
+                        let b = s.as_bytes();
+                        bytes[..b.len()].copy_from_slice(b);
+                    }
+                    bytes
+                }
+            }
+        }
+    } else {
+        // Field without attributes, use default byte
+        quote! {
+            [#default_byte; #size]
+        }
+    }
+}
+
+/// Statements that format the numeric `val` into the text `s` written to a field of `size` bytes
+///
+/// Each step formats into its own `TextBuf` on the stack, with room for the `Display` of
+/// any float plus padding and separators, so writing a number doesn't allocate.
+fn get_number_text(attrs: &FieldAttrs, size: usize) -> proc_macro2::TokenStream {
+    let cap = 2 * size + 330;
+    let text = if let Some(precision) = attrs.precision {
+        let rounding = match attrs.rounding.as_deref() {
+            Some("truncate") => quote!(Truncate),
+            _ => quote!(HalfUp),
+        };
+        // a fill pads the field instead of zeros
+        let width = if attrs.fill.is_some() { 0 } else { size };
+        quote! {
+            let mut text = binary_mirror::TextBuf::<#cap>::new();
+            let _ = ::core::fmt::Write::write_fmt(&mut text, format_args!("{}", val));
+            let mut fixed = binary_mirror::TextBuf::<#cap>::new();
+            let _ = binary_mirror::write_fixed_point(
+                &mut fixed,
+                text.as_str(),
+                #precision,
+                binary_mirror::Rounding::#rounding,
+                #width,
+            );
+            let s = fixed.as_str();
+        }
+    } else {
+        let fmt = attrs.format.as_deref().unwrap_or("{}");
+        quote! {
+            let mut text = binary_mirror::TextBuf::<#cap>::new();
+            let _ = ::core::fmt::Write::write_fmt(&mut text, format_args!(#fmt, val));
+            let s = text.as_str();
+        }
+    };
+    let localize = attrs.numeric_locale.as_ref().map(|locale| {
+        let thousands = match locale.thousands.filter(|_| locale.group) {
+            Some(c) => quote!(Some(#c)),
+            None => quote!(None),
+        };
+        let decimal = locale.decimal;
+        quote! {
+            let mut localized = binary_mirror::TextBuf::<#cap>::new();
+            let _ = binary_mirror::write_localized(&mut localized, s, #thousands, #decimal);
+            let s = localized.as_str();
+        }
+    });
+    let place_sign = get_sign(attrs).map(|sign| {
+        quote! {
+            let mut signed = binary_mirror::TextBuf::<#cap>::new();
+            let _ = binary_mirror::write_sign(&mut signed, s, binary_mirror::Sign::#sign);
+            let s = signed.as_str();
+        }
+    });
+    quote! {
+        #text
+        #localize
+        #place_sign
+    }
+}
+
+/// Checks run by `try_to_raw()` before converting, failing on a numeric value whose text
+/// is wider than its field
+fn get_width_checks(native_field_map: &[NativeField2OriginFieldMap]) -> Vec<proc_macro2::TokenStream> {
+    native_field_map
+        .iter()
+        .filter_map(|mapping| {
+            let native_field = mapping.native_field.as_ref().filter(|field| !field.skip_native)?;
+            let attrs = mapping.origin_field.attrs.as_ref().filter(|attrs| attrs.write_with.is_none())?;
+            let name = &native_field.name;
+            let size = mapping.origin_field.size;
+            match attrs.type_name.as_str() {
+                "i16" | "i32" | "i64" | "u16" | "u32" | "u64" | "f32" | "f64" | "decimal" => {
+                    let text = get_number_text(attrs, size);
+                    let check = match attrs.fill {
+                        Some(fill) => quote! {
+                            binary_mirror::fill_number(stringify!(#name), s, #fill, &mut [0u8; #size])?;
+                        },
+                        None => quote! { binary_mirror::check_width(stringify!(#name), s, #size)?; },
+                    };
+                    Some(quote! {
+                        if let Some(val) = &self.#name {
+                            #text
+                            #check
+                        }
+                    })
+                }
+                "flatten" if !attrs.generic => Some(quote! { self.#name.try_to_raw()?; }),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// Parameter type and statements that format a native value into the origin fields under `target`
+fn get_field_writer(
+    field: &NativeField,
+    native_field_map: &[NativeField2OriginFieldMap],
+    target: &proc_macro2::TokenStream,
+) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
+    let name = &field.name;
+    let ty = &field.pure_ty;
+    let (param_ty, source) = match field.type_name.as_str() {
+        "str" | "compact_str" => (quote!(&str), quote!(Some(value))),
+        "bytes" | "flatten" => (quote!(#ty), quote!(value)),
+        _ => (quote!(#ty), quote!(Some(value))),
+    };
+    let assigns = native_field_map
+        .iter()
+        .filter(|mapping| {
+            mapping
+                .native_field
+                .as_ref()
+                .is_some_and(|native_field| native_field.name == *name)
+        })
+        .map(|mapping| {
+            let field_name = &mapping.origin_field.name;
+            let value = get_from_native_value(mapping, &quote!(value));
+            quote! { #target.#field_name = #value; }
+        });
+
+    (
+        param_ty,
+        quote! {
+            let value = #source;
+            #(#assigns)*
+        },
+    )
+}
+
+fn get_checksum_refresh(
+    checksum_fields: &[ChecksumField],
+    target: &proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    let refresh = checksum_fields.iter().map(|field| {
+        let field_name = &field.name;
+        let method_name = quote::format_ident!("compute_{}", field_name);
+        quote! { #target.#field_name = #target.#method_name(); }
+    });
+    quote! { #(#refresh)* }
+}
+
+fn get_setter_methods(
+    native_fields: &[NativeField],
+    native_field_map: &[NativeField2OriginFieldMap],
+    checksum_fields: &[ChecksumField],
+) -> Vec<proc_macro2::TokenStream> {
+    let checksum_refresh = get_checksum_refresh(checksum_fields, &quote!(self));
+    native_fields
+        .iter()
+        // The length of the trailing bytes is fixed once the record is viewed, and a generic
+        // field is assigned directly
+        .filter(|field| field.type_name != "rest" && !is_generic(&field.origin_fields[0]))
+        .map(|field| {
+            let method_name = quote::format_ident!("set_{}", field.name);
+            let (param_ty, write) = get_field_writer(field, native_field_map, &quote!(self));
+
+            quote! {
+                /// Format the value directly into the raw bytes, refreshing any checksum fields
+                pub fn #method_name(&mut self, value: #param_ty) {
+                    #write
+                    #checksum_refresh
+                }
+            }
+        })
+        .collect()
+}
+
+fn get_raw_builder(
+    name: &syn::Ident,
+    native_fields: &[NativeField],
+    native_field_map: &[NativeField2OriginFieldMap],
+    checksum_fields: &[ChecksumField],
+) -> proc_macro2::TokenStream {
+    let builder_name = quote::format_ident!("{}Builder", name);
+    // Start from the same bytes `Native::default().to_raw()` would produce
+    let initial_fields = native_field_map.iter().filter(|mapping| !is_overlay_view(mapping)).map(|mapping| {
+        let field_name = &mapping.origin_field.name;
+        let size = mapping.origin_field.size;
+        let attrs = mapping.origin_field.attrs.as_ref();
+        let default_byte = mapping.origin_field.default_byte;
+        let native_field = mapping.native_field.as_ref().filter(|field| !field.skip_native);
+        match (native_field, attrs) {
+            (Some(_), Some(attrs)) if attrs.type_name == "bytes" => quote! { #field_name: [0; #size] },
+            (Some(_), Some(attrs)) if attrs.type_name == "flatten" => {
+                let native_ty = get_flatten_native_ty(attrs);
+                let value = get_from_native_value(mapping, &quote!(<#native_ty as Default>::default()));
+                quote! { #field_name: #value }
+            }
+            (Some(_), Some(attrs)) if attrs.none_value.is_some() => {
+                let none_value = syn::LitByteStr::new(
+                    attrs.none_value.as_ref().unwrap(),
+                    proc_macro2::Span::call_site(),
+                );
+                quote! { #field_name: *#none_value }
+            }
+            _ => quote! { #field_name: [#default_byte; #size] },
+        }
+    });
+    let default_writes = native_fields
+        .iter()
+        .filter(|field| !field.skip_native && field.type_name != "bytes")
+        .filter_map(|field| {
+            let default = quote::format_ident!("{}", field.default_func.as_ref()?.as_str());
+            let (_, write) = get_field_writer(field, native_field_map, &quote!(raw));
+            let value = match field.type_name.as_str() {
+                "str" | "compact_str" => quote!(&#default()),
+                _ => quote!(#default()),
+            };
+            Some(quote! {
+                {
+                    let value = #value;
+                    #write
+                }
+            })
+        });
+    let methods = native_fields.iter().map(|field| {
+        let method_name = quote::format_ident!("with_{}", field.name);
+        let (param_ty, write) = get_field_writer(field, native_field_map, &quote!(self.raw));
+
+        quote! {
+            pub fn #method_name(mut self, value: #param_ty) -> Self {
+                #write
+                self
+            }
+        }
+    });
+    let checksum_refresh = get_checksum_refresh(checksum_fields, &quote!(raw));
+
+    quote! {
+        impl #builder_name {
+            pub fn new() -> Self {
+                #[allow(unused_mut)]
+                let mut raw = #name {
+                    #(#initial_fields,)*
+                };
+                #(#default_writes)*
+                Self { raw }
+            }
+
+            #(#methods)*
+
+            /// Finish the record, filling in any checksum fields
+            pub fn build(self) -> #name {
+                #[allow(unused_mut)]
+                let mut raw = self.raw;
+                #checksum_refresh
+                raw
+            }
+        }
+
+        impl Default for #builder_name {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        impl #name {
+            /// Start building the raw struct in place
+            pub fn builder() -> #builder_name {
+                #builder_name::new()
+            }
+        }
+    }
+}
+
+/// With `raw_default = true`, `Default` starting from the builder's record and `new` taking
+/// the `required` fields, both with their checksums filled in
+fn get_raw_default_impl(
+    name: &syn::Ident,
+    native_fields: &[NativeField],
+    native_field_map: &[NativeField2OriginFieldMap],
+    struct_attrs: &StructAttrs,
+) -> proc_macro2::TokenStream {
+    if !struct_attrs.raw_default {
+        return quote! {};
+    }
+    let builder_name = quote::format_ident!("{}Builder", name);
+    let required = native_fields
+        .iter()
+        .filter(|field| !field.skip_native)
+        .filter(|field| {
+            field.origin_fields.iter().any(|origin| origin.attrs.as_ref().is_some_and(|attrs| attrs.required))
+        })
+        .map(|field| {
+            let name = &field.name;
+            let (param_ty, write) = get_field_writer(field, native_field_map, &quote!(builder.raw));
+            (quote! { #name: #param_ty }, quote! { { let value = #name; #write } })
+        })
+        .collect::<Vec<_>>();
+    let params = required.iter().map(|(param, _)| param);
+    let writes = required.iter().map(|(_, write)| write);
+
+    quote! {
+        impl Default for #name {
+            fn default() -> Self {
+                #builder_name::new().build()
+            }
+        }
+
+        impl #name {
+            /// The default record with the `required` fields set, any checksum fields filled in
+            pub fn new(#(#params),*) -> Self {
+                #[allow(unused_mut)]
+                let mut builder = #builder_name::new();
+                #(#writes)*
+                builder.build()
+            }
+        }
+    }
+}
+
+fn get_native_methods(native_fields: &[NativeField]) -> Vec<proc_macro2::TokenStream> {
+    native_fields
+        .iter()
+        .filter(|field| !field.skip_native)
+        .map(|field| {
+            let name = &field.name;
+            let method_name = quote::format_ident!("with_{}", name);
+            let ty = &field.pure_ty;
+            let type_name = &field.type_name;
+
+            match type_name.as_str() {
+                "str" => quote! {
+                    pub fn #method_name(mut self, value: impl Into<String>) -> Self {
+                        self.#name = Some(value.into());
+                        self
+                    }
+                },
+                "compact_str" => quote! {
+                    pub fn #method_name(mut self, value: impl Into<compact_str::CompactString>) -> Self {
+                        self.#name = Some(value.into());
+                        self
+                    }
+                },
+                // "hipstr" => quote! {
+                //     pub fn #method_name<'borrow>(mut self, value: impl Into<hipstr::HipStr<'borrow>>) -> Self {
+                //         self.#name = value.into();
+                //         self
+                //     }
+                // },
+                "i16" | "i32" | "i64" | "u16" | "u32" | "u64" | "f32" | "f64" | "decimal"
+                | "datetime" | "date" | "time" | "enum" | "custom" => {
+                    quote! {
+                        pub fn #method_name(mut self, value: #ty) -> Self {
+                            self.#name = Some(value);
+                            self
+                        }
+                    }
+                }
+                _ => quote! {
+                    pub fn #method_name(mut self, value: #ty) -> Self {
+                        self.#name = value;
+                        self
+                    }
+                },
+            }
+        })
+        .collect()
+}
+
+fn get_field_spec_methods(origin_fields: &[OriginField]) -> proc_macro2::TokenStream {
+    let specs = origin_fields
+        .iter()
+        .zip(get_field_ranges(origin_fields))
+        .map(|(field, (offset, field_size))| {
+            let limit = offset + field_size;
+            let type_name = match &field.attrs {
+                Some(attrs) => {
+                    let type_name = &attrs.type_name;
+                    quote!(Some(#type_name))
+                }
+                None => quote!(None),
+            };
+
+            quote! {
+                binary_mirror::FieldSpec {
+                    offset: #offset,
+                    limit: #limit,
+                    size: #field_size,
+                    type_name: #type_name,
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+    let size_methods = origin_fields.iter().zip(&specs).map(|(field, spec)| {
+        let method_name = quote::format_ident!("{}_spec", field.name);
+
+        quote! {
+            pub fn #method_name() -> binary_mirror::FieldSpec {
+                #spec
+            }
+        }
+    });
+    let field_names = origin_fields.iter().map(|field| field.name.to_string());
+
+    quote! {
+        #(#size_methods)*
+
+        /// Hexdump of the record with offsets, hex, ASCII and the field each range belongs to
+        pub fn hexdump(&self) -> String {
+            binary_mirror::hexdump(binary_mirror::ToBytes::to_bytes(self), Self::field_specs())
+        }
+
+        /// Get the spec of every field in layout order, keyed by field name
+        pub fn field_specs() -> &'static [(&'static str, binary_mirror::FieldSpec)] {
+            const SPECS: &[(&str, binary_mirror::FieldSpec)] = &[
+                #((#field_names, #specs),)*
+            ];
+            SPECS
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ChecksumField {
+    name: syn::Ident,
+    size: usize,
+    algo: String,
+    start: usize,
+    end: usize,
+    format: String,
+    default_byte: u8,
+}
+
+fn get_checksum_fields(origin_fields: &[OriginField]) -> Vec<ChecksumField> {
+    let mut offset = 0;
+    let ranges = origin_fields
+        .iter()
+        .map(|field| {
+            let range = (field.name.to_string(), offset, offset + field.size);
+            offset += field.size;
+            range
+        })
+        .collect::<Vec<_>>();
+    let find_range = |name: &str| ranges.iter().find(|(field_name, _, _)| field_name == name);
+
+    // algo and over are checked in check_origin_fields
+    origin_fields
+        .iter()
+        .filter_map(|field| {
+            let attrs = field.attrs.as_ref()?;
+            if attrs.type_name != "checksum" {
+                return None;
+            }
+            let algo = attrs.algo.clone()?;
+            let (start, end) = match &attrs.over {
+                Some(over) => {
+                    let (first, last) = over.split_once("..")?;
+                    (find_range(first.trim())?.1, find_range(last.trim())?.2)
+                }
+                None => (0, find_range(&field.name.to_string())?.1),
+            };
+            Some(ChecksumField {
+                name: field.name.clone(),
+                size: field.size,
+                algo,
+                start,
+                end,
+                format: attrs
+                    .format
+                    .clone()
+                    .unwrap_or_else(|| format!("{{:0{}}}", field.size)),
+                default_byte: field.default_byte,
+            })
+        })
+        .collect()
+}
+
+fn get_checksum_methods(checksum_fields: &[ChecksumField]) -> Vec<proc_macro2::TokenStream> {
+    checksum_fields
+        .iter()
+        .map(|field| {
+            let method_name = quote::format_ident!("compute_{}", field.name);
+            let algo = quote::format_ident!("{}", field.algo);
+            let ChecksumField {
+                size,
+                start,
+                end,
+                format,
+                default_byte,
+                ..
+            } = field;
+
+            quote! {
+                /// Compute the checksum over the covered bytes, encoded as the field stores it
+                pub fn #method_name(&self) -> [u8; #size] {
+                    let bytes = binary_mirror::ToBytes::to_bytes(self);
+                    let value = binary_mirror::checksum::#algo(&bytes[#start..#end]);
+                    let mut s = binary_mirror::TextBuf::<{ #size + 32 }>::new();
+                    let _ = ::core::fmt::Write::write_fmt(&mut s, format_args!(#format, value));
+                    let b = s.as_bytes();
+                    let b = &b[b.len().saturating_sub(#size)..];
+                    let mut out = [#default_byte; #size];
+                    out[..b.len()].copy_from_slice(b);
+                    out
+                }
+            }
+        })
+        .collect()
+}
+
+/// A String expression with the value of a raw field in `record`, rendered as in Display
+///
+/// `on_error` is the expression used when the field doesn't parse, fields without a getter
+/// show their escaped bytes.
+fn get_field_value(
+    field: &OriginField,
+    native_field_map: &[NativeField2OriginFieldMap],
+    record: &proc_macro2::TokenStream,
+    on_error: &proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    let origin_field = &field.name;
+    let native_field = native_field_map
+        .iter()
+        .find(|mapping| mapping.origin_field.name == *origin_field)
+        .and_then(|mapping| mapping.native_field.as_ref());
+    let Some(native_field) = native_field else {
+        return quote! { binary_mirror::to_bytes_repr(&#record.#origin_field) };
+    };
+    let method_name = &native_field.name;
+    match native_field.type_name.as_str() {
+        "str" | "compact_str" | "i16" | "i32" | "i64" | "u16" | "u32" | "u64" | "f32" | "f64" | "decimal"
+        | "datetime" | "date" | "time" => quote! {
+            match #record.#method_name() {
+                Some(val) => val.to_string(),
+                None => #on_error,
+            }
+        },
+        "enum" | "custom" => quote! {
+            match #record.#method_name() {
+                Some(val) => format!("{:?}", val),
+                None => #on_error,
+            }
+        },
+        "flatten" => quote! { #record.#method_name().to_string() },
+        _ => quote! { binary_mirror::to_bytes_repr(&#record.#origin_field) },
+    }
+}
+
+/// `inspect_fields` and `inspect`, the Debug output annotated with the field specs
+fn get_inspect_methods(
+    name: &syn::Ident,
+    origin_fields: &[OriginField],
+    native_field_map: &[NativeField2OriginFieldMap],
+) -> proc_macro2::TokenStream {
+    let fields = origin_fields
+        .iter()
+        .zip(get_field_ranges(origin_fields))
+        .map(|(field, (offset, size))| {
+            let origin_field = &field.name;
+            let field_str = origin_field.to_string();
+            let limit = match is_rest(field) {
+                true => quote! { #offset + self.#origin_field.len() },
+                false => {
+                    let limit = offset + size;
+                    quote! { #limit }
+                }
+            };
+            let redacted = field.attrs.as_ref().is_some_and(|attrs| attrs.redact);
+            let (hex, value) = if redacted {
+                let mask = quote! { binary_mirror::redact::MASK.to_string() };
+                (mask.clone(), mask)
+            } else {
+                let on_error = quote! {
+                    format!("Error<bytes: \"{}\">", binary_mirror::to_bytes_repr(&self.#origin_field))
+                };
+                (
+                    quote! { binary_mirror::to_hex_repr(&self.#origin_field) },
+                    get_field_value(field, native_field_map, &quote!(self), &on_error),
+                )
+            };
+            quote! {
+                binary_mirror::FieldInspect {
+                    field: #field_str,
+                    offset: #offset,
+                    limit: #limit,
+                    hex: #hex,
+                    value: #value,
+                }
+            }
+        });
+
+    quote! {
+        /// Every field with its byte range, hex and decoded value, padding and overlays included
+        pub fn inspect_fields(&self) -> Vec<binary_mirror::FieldInspect> {
+            vec![#(#fields),*]
+        }
+
+        /// Multi-line Debug with the byte range of each field, `field @ [offset..limit]`
+        /// followed by its hex and decoded value, to track down misaligned layouts
+        pub fn inspect(&self) -> String {
+            binary_mirror::inspect(stringify!(#name), &self.inspect_fields())
+        }
+    }
+}
+
+fn get_diff_method(
+    origin_fields: &[OriginField],
+    native_field_map: &[NativeField2OriginFieldMap],
+) -> proc_macro2::TokenStream {
+    let mut offset = 0;
+    let checks = origin_fields.iter().map(|field| {
+        let origin_field = &field.name;
+        let field_str = origin_field.to_string();
+        let size = field.size;
+        let field_offset = offset;
+        offset += size;
+
+        let value = |record: proc_macro2::TokenStream| {
+            let bytes_repr = quote! { binary_mirror::to_bytes_repr(&#record.#origin_field) };
+            get_field_value(field, native_field_map, &record, &bytes_repr)
+        };
+        // Filler bytes carry no data worth reporting, an overlay is reported through its target
+        if is_pad(field) || overlay_target(field).is_some() {
+            return quote! {};
+        }
+        let (left, right) = if field.attrs.as_ref().is_some_and(|attrs| attrs.redact) {
+            let mask = quote! { binary_mirror::redact::MASK.to_string() };
+            (mask.clone(), mask)
+        } else {
+            (value(quote!(self)), value(quote!(other)))
+        };
+        quote! {
+            if self.#origin_field != other.#origin_field {
+                diffs.push(binary_mirror::FieldDiff {
+                    field: #field_str,
+                    offset: #field_offset,
+                    size: #size,
+                    left: #left,
+                    right: #right,
+                });
+            }
+        }
+    });
+
+    quote! {
+        /// Fields whose bytes differ between the two records, with the decoded values of both sides
+        pub fn diff(&self, other: &Self) -> Vec<binary_mirror::FieldDiff> {
+            let mut diffs = Vec::new();
+            #(#checks)*
+            diffs
+        }
+    }
+}
+
+fn get_field_bytes_methods(origin_fields: &[OriginField]) -> proc_macro2::TokenStream {
+    let field_strs = origin_fields.iter().map(|field| field.name.to_string()).collect::<Vec<_>>();
+    // An overlay reads and writes the bytes of its target
+    let field_names = origin_fields
+        .iter()
+        .map(|field| match overlay_target(field) {
+            Some(overlay) => quote::format_ident!("{}", overlay),
+            None => field.name.clone(),
+        })
+        .collect::<Vec<_>>();
+    let sizes = origin_fields.iter().zip(get_field_ranges(origin_fields)).map(|(field, (_, size))| {
+        if is_rest(field) {
+            let field_name = &field.name;
+            quote!(self.#field_name.len())
+        } else {
+            quote!(#size)
+        }
+    });
+
+    quote! {
+        /// Get the raw bytes of a field by its raw field name
+        pub fn field_bytes(&self, name: &str) -> Option<&[u8]> {
+            match name {
+                #(#field_strs => Some(&self.#field_names),)*
+                _ => None,
+            }
+        }
+
+        /// Overwrite the raw bytes of a field by its raw field name, bytes must match the field size
+        pub fn set_field_bytes(&mut self, name: &str, bytes: &[u8]) -> Result<(), binary_mirror::FieldError> {
+            match name {
+                #(#field_strs => {
+                    if bytes.len() != #sizes {
+                        return Err(binary_mirror::FieldError::size_mismatch(#field_strs, #sizes, bytes));
+                    }
+                    self.#field_names.copy_from_slice(bytes);
+                    Ok(())
+                })*
+                _ => Err(binary_mirror::FieldError::unknown_field(name)),
+            }
+        }
+    }
+}
+
+fn get_native_default_impl(
+    native_fields: &[NativeField],
+    native_name: &proc_macro2::Ident,
+    generics: &StructGenerics,
+    struct_attrs: &StructAttrs,
+) -> proc_macro2::TokenStream {
+    let default_fields = native_fields.iter().filter(|field| !field.skip_native).map(|field| {
+        let name = &field.name;
+        if let Some(default) = &field.default_func {
+            // let default_quote = quote! { #default };
+            let default_quote = quote::format_ident!("{}", default.as_str());
+            match field.type_name.as_str() {
+                // "hipstr" => quote! {
+                //     #name: Some(#default_quote())
+                // },
+                "str"| "compact_str" | "i16" | "i32" | "i64" | "u16" | "u32" | "u64" | "f32" | "f64" | "datetime"
+                | "date" | "time" | "enum" | "decimal" | "custom" => {
+                    quote! {
+                        #name: Some(#default_quote())
+                    }
+                }
+                _ => quote! {
+                    #name: Default::default()
+                },
+            }
+        } else {
+            quote! {
+                #name: Default::default()
+            }
+        }
+    });
+
+    let native_params = &generics.native_params;
+    // The native payloads need a default of their own
+    let params = &generics.params;
+    let native_default_generics = (!params.is_empty()).then(|| quote!(<#(#params: Default),*>));
+    let computed_defaults = get_computed_defaults(struct_attrs);
+    let carried_defaults = get_carried_raw(native_fields, |carry, _| quote! { #carry: None });
+    let preserved_default = get_preserved_default(struct_attrs);
+    quote! {
+        impl #native_default_generics Default for #native_name #native_params {
+            fn default() -> Self {
+                Self {
+                    #(#default_fields,)*
+                    #(#carried_defaults,)*
+                    #(#preserved_default,)*
+                    #(#computed_defaults,)*
+                }
+            }
+        }
+    }
+}
+
+fn get_native_to_raw_impl(
+    name: &syn::Ident,
+    native_name: &proc_macro2::Ident,
+    native_field_map: &[NativeField2OriginFieldMap],
+    has_rest: bool,
+) -> proc_macro2::TokenStream {
+    let width_checks = get_width_checks(native_field_map);
+    // Structs ending in a `rest` field are unsized and built on the heap
+    let raw = if has_rest { quote!(Box<#name>) } else { quote!(#name) };
+    let raw_ref = if has_rest { quote!(&*self.to_raw()) } else { quote!(&self.to_raw()) };
+    quote! {
+        impl #native_name {
+            pub fn to_raw(&self) -> #raw {
+                <#raw as binary_mirror::FromNative<#native_name>>::from_native(self)
+            }
+
+            /// `to_raw()` that fails instead of cutting a number wider than its field
+            pub fn try_to_raw(&self) -> Result<#raw, binary_mirror::FieldError> {
+                #(#width_checks)*
+                Ok(self.to_raw())
+            }
+
+            /// Convert to the raw struct and copy its bytes to the start of `buf`, returning
+            /// how many were written
+            pub fn write_raw_to(&self, buf: &mut [u8]) -> Result<usize, binary_mirror::BufferTooSmall> {
+                binary_mirror::ToBytes::write_to(#raw_ref, buf)
+            }
+        }
+    }
+}
+
+fn get_native_struct_code(
+    name: &syn::Ident,
+    native_fields: &[NativeField],
+    struct_attrs: &StructAttrs,
+) -> proc_macro2::TokenStream {
+    let native_name = quote::format_ident!("{}Native", name);
+    let fields_code = native_fields
+        .iter()
+        .filter(|field| !field.skip_native)
+        .map(|field| {
+            let name = &field.name;
+            let ty = &field.ty;
+            // Convert TokenStream to string and normalize whitespace
+            let ty_str = ty
+                .to_string()
+                .replace(" :: ", "::")
+                .replace(" < ", "<")
+                .replace(" > ", ">")
+                .replace(" >", ">");
+            let flatten_attr = if field.type_name == "flatten" { "    #[serde(flatten)]\n" } else { "" };
+            let repr_attr = get_bytes_repr_with(field)
+                .map_or(String::new(), |with| format!("    #[serde(with = \"{}\")]\n", with));
+            let doc = get_native_doc(field).map_or(String::new(), |doc| {
+                doc.lines()
+                    .map(|line| match line {
+                        "" => "    ///\n".to_string(),
+                        line => format!("    /// {}\n", line),
+                    })
+                    .collect()
+            });
+            let native_attrs = get_native_attrs(field)
+                .iter()
+                .map(|meta| format!("    #[{}]\n", meta.to_token_stream()))
+                .collect::<String>();
+            match get_native_serde(field) {
+                Some(args) => format!(
+                    "{}{}{}    #[serde({})]\n{}    pub {}: {},",
+                    doc, flatten_attr, repr_attr, args, native_attrs, name, ty_str
+                ),
+                None => format!("{}{}{}{}    pub {}: {},", doc, flatten_attr, repr_attr, native_attrs, name, ty_str),
+            }
+        })
+        .chain(get_carried_raw(native_fields, |carry, origin| {
+            format!("    #[doc(hidden)]\n    #[serde(skip)]\n    pub {}: Option<[u8; {}]>,", carry, origin.size)
+        }))
+        .chain(struct_attrs.preserve_unknown.then(|| {
+            "    #[doc(hidden)]\n    #[serde(skip)]\n    pub __raw: binary_mirror::PreservedBytes,".to_string()
+        }))
+        .chain(struct_attrs.computed.iter().map(|field| {
+            let ComputedField { name, ty, .. } = field;
+            let ty_str = quote!(#ty).to_string().replace(" :: ", "::").replace(" < ", "<").replace(" >", ">");
+            format!("    #[serde(default)]\n    pub {}: {},", name, ty_str)
+        }))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    quote! {
+        impl binary_mirror::NativeStructCode for #name {
+            fn native_struct_code() -> String {
+                format!(
+                    "pub struct {} {{\n{}\n}}",
+                    stringify!(#native_name),
+                    #fields_code
+                )
+            }
+        }
+    }
+}
+
+/// Serialized name of a native field, honouring a `rename` passed through `native_serde`
+fn get_serialized_name(field: &NativeField) -> String {
+    let renamed = get_native_serde(field).and_then(|args| {
+        let metas = syn::parse::Parser::parse2(
+            syn::punctuated::Punctuated::<syn::Meta, syn::Token![,]>::parse_terminated,
+            args.clone(),
+        )
+        .ok()?;
+        metas.into_iter().find_map(|meta| match meta {
+            syn::Meta::NameValue(syn::MetaNameValue {
+                path,
+                value: syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(lit), .. }),
+                ..
+            }) if path.is_ident("rename") => Some(lit.value()),
+            _ => None,
+        })
+    });
+    renamed.unwrap_or_else(|| field.name.to_string())
+}
+
+/// Smallest and largest value of an integer field, bounded by its type and by the digits
+/// that fit in its width once the sign is written
+fn get_integer_range(field: &OriginField) -> (i128, i128) {
+    let attrs = field.attrs.as_ref().unwrap();
+    let (type_min, type_max) = match attrs.type_name.as_str() {
+        "i16" => (i16::MIN as i128, i16::MAX as i128),
+        "i32" => (i32::MIN as i128, i32::MAX as i128),
+        "i64" => (i64::MIN as i128, i64::MAX as i128),
+        "u16" => (0, u16::MAX as i128),
+        "u32" => (0, u32::MAX as i128),
+        _ => (0, u64::MAX as i128),
+    };
+    // Grouping separators take up width too, such fields keep the range of their type
+    if attrs.numeric_locale.as_ref().is_some_and(|locale| locale.group) {
+        return (type_min, type_max);
+    }
+    let widest = |digits: usize| 10i128.checked_pow(digits as u32).map_or(i128::MAX, |n| n - 1);
+    let sign_len = if attrs.sign.as_deref() == Some("paren") { 2 } else { 1 };
+    let maximum = type_max.min(widest(field.size));
+    let minimum = type_min.max(-widest(field.size.saturating_sub(sign_len)));
+    (minimum, maximum)
+}
+
+fn get_native_json_schema(
+    native_name: &syn::Ident,
+    native_fields: &[NativeField],
+) -> proc_macro2::TokenStream {
+    let escape = |s: &str| s.replace('\\', "\\\\").replace('"', "\\\"");
+    let properties = native_fields
+        .iter()
+        .filter(|field| !field.skip_native)
+        .map(|field| {
+            let name = escape(&get_serialized_name(field));
+            let schema = match field.type_name.as_str() {
+                "flatten" => {
+                    let ty = get_flatten_ty(field.origin_fields[0].attrs.as_ref().unwrap());
+                    return quote! {
+                        let (nested_properties, nested_required) = #ty::__bm_json_schema_properties();
+                        properties.extend(nested_properties);
+                        required.extend(nested_required);
+                    };
+                }
+                "str" | "compact_str" | "decimal" | "datetime" | "time" | "enum" => {
+                    r#"{"type":["string","null"]}"#.to_string()
+                }
+                "date" => r#"{"type":["string","null"],"format":"date"}"#.to_string(),
+                "i16" | "i32" | "i64" | "u16" | "u32" | "u64" => {
+                    let (minimum, maximum) = get_integer_range(&field.origin_fields[0]);
+                    format!(
+                        r#"{{"type":["integer","null"],"minimum":{},"maximum":{}}}"#,
+                        minimum, maximum
+                    )
+                }
+                "f32" | "f64" => r#"{"type":["number","null"]}"#.to_string(),
+                "bytes" => {
+                    let size = field.origin_fields[0].size;
+                    let repr = field.origin_fields[0].attrs.as_ref().and_then(|attrs| attrs.bytes_repr.as_deref());
+                    let property = match repr {
+                        Some("hex") => format!(
+                            r#""{}":{{"type":"string","pattern":"^[0-9a-fA-F]{{{}}}$"}}"#,
+                            name,
+                            size * 2
+                        ),
+                        Some(_) => format!(
+                            r#""{}":{{"type":"string","contentEncoding":"base64","minLength":{len},"maxLength":{len}}}"#,
+                            name,
+                            len = (size + 2) / 3 * 4
+                        ),
+                        None => format!(
+                            r#""{}":{{"type":"array","items":{{"type":"integer","minimum":0,"maximum":255}},"minItems":{size},"maxItems":{size}}}"#,
+                            name
+                        ),
+                    };
+                    let required = format!("\"{}\"", name);
+                    return quote! {
+                        properties.push(#property.to_string());
+                        required.push(#required.to_string());
+                    };
+                }
+                "rest" => {
+                    let property = format!(
+                        r#""{}":{{"type":"array","items":{{"type":"integer","minimum":0,"maximum":255}}}}"#,
+                        name
+                    );
+                    let required = format!("\"{}\"", name);
+                    return quote! {
+                        properties.push(#property.to_string());
+                        required.push(#required.to_string());
+                    };
+                }
+                // Custom types serialize however their own Serialize impl decides
+                _ => "{}".to_string(),
+            };
+            let property = format!("\"{}\":{}", name, schema);
+            quote! { properties.push(#property.to_string()); }
+        })
+        .collect::<Vec<_>>();
+    let title = native_name.to_string();
+
+    quote! {
+        /// JSON Schema of the native struct as serialized by serde
+        pub fn native_json_schema() -> String {
+            let (properties, required) = Self::__bm_json_schema_properties();
+            format!(
+                r#"{{"$schema":"https://json-schema.org/draft/2020-12/schema","title":"{}","type":"object","properties":{{{}}},"required":[{}]}}"#,
+                #title,
+                properties.join(","),
+                required.join(",")
+            )
+        }
+
+        /// Schema properties and required names, shared with structs flattening this one
+        #[doc(hidden)]
+        pub fn __bm_json_schema_properties() -> (Vec<String>, Vec<String>) {
+            #[allow(unused_mut)]
+            let mut properties = Vec::new();
+            #[allow(unused_mut)]
+            let mut required = Vec::new();
+            #(#properties)*
+            (properties, required)
+        }
+    }
+}
+
+fn get_sql_methods(
+    native_fields: &[NativeField],
+) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
+    let fields = native_fields
+        .iter()
+        .filter(|field| !field.skip_native)
+        .collect::<Vec<_>>();
+    let columns = fields.iter().map(|field| {
+        let size = field.origin_fields[0].size;
+        let column_type = match field.type_name.as_str() {
+            "flatten" => {
+                let ty = get_flatten_ty(field.origin_fields[0].attrs.as_ref().unwrap());
+                return quote! { columns.extend(#ty::sql_columns()); };
+            }
+            "str" | "compact_str" => format!("VARCHAR({})", size),
+            "i16" => "SMALLINT".to_string(),
+            "i32" | "u16" => "INTEGER".to_string(),
+            "i64" | "u32" => "BIGINT".to_string(),
+            "u64" => "NUMERIC(20)".to_string(),
+            "f32" => "REAL".to_string(),
+            "f64" => "DOUBLE PRECISION".to_string(),
+            "decimal" => "NUMERIC".to_string(),
+            "datetime" => "TIMESTAMP".to_string(),
+            "date" => "DATE".to_string(),
+            "time" => "TIME".to_string(),
+            "bytes" | "rest" => "BYTEA".to_string(),
+            _ => "TEXT".to_string(),
+        };
+        let column = field.name.to_string();
+        quote! { columns.push((#column, #column_type)); }
+    });
+    let params = fields.iter().map(|field| {
+        let name = &field.name;
+        let value = match field.type_name.as_str() {
+            "flatten" => return quote! { params.extend(self.#name.sql_params()); },
+            "bytes" | "rest" => quote! { binary_mirror::SqlValue::Bytes(self.#name.to_vec()) },
+            "compact_str" | "decimal" | "datetime" | "date" | "time" => quote! {
+                binary_mirror::SqlValue::from(self.#name.as_ref().map(|v| v.to_string()))
+            },
+            "enum" | "custom" => quote! {
+                binary_mirror::SqlValue::from(self.#name.as_ref().map(|v| format!("{:?}", v)))
+            },
+            _ => quote! { binary_mirror::SqlValue::from(self.#name.clone()) },
+        };
+        quote! { params.push(#value); }
+    });
+
+    let raw_methods = quote! {
+        /// Column name and SQL type per native field, in the order of `sql_params`
+        pub fn sql_columns() -> Vec<(&'static str, &'static str)> {
+            #[allow(clippy::vec_init_then_push)]
+            let mut columns = Vec::new();
+            #(#columns)*
+            columns
+        }
+
+        /// `CREATE TABLE` statement with one column per native field
+        pub fn sql_create_table(table: &str) -> String {
+            let columns = Self::sql_columns()
+                .iter()
+                .map(|(name, column_type)| format!("    {} {}", name, column_type))
+                .collect::<Vec<_>>();
+            format!("CREATE TABLE {} (\n{}\n)", table, columns.join(",\n"))
+        }
+
+        /// `INSERT` statement with `$n` placeholders in the order of `sql_params`
+        pub fn sql_insert(table: &str) -> String {
+            let columns = Self::sql_columns();
+            let names = columns.iter().map(|(name, _)| *name).collect::<Vec<_>>();
+            let placeholders = (1..=columns.len()).map(|i| format!("${}", i)).collect::<Vec<_>>();
+            format!("INSERT INTO {} ({}) VALUES ({})", table, names.join(", "), placeholders.join(", "))
+        }
+    };
+    let native_methods = quote! {
+        /// Parameters to bind to the statement from `sql_insert`
+        pub fn sql_params(&self) -> Vec<binary_mirror::SqlValue> {
+            #[allow(clippy::vec_init_then_push)]
+            let mut params = Vec::new();
+            #(#params)*
+            params
+        }
+    };
+    (raw_methods, native_methods)
+}
+
+fn get_avro_schema(
+    native_name: &syn::Ident,
+    native_fields: &[NativeField],
+) -> proc_macro2::TokenStream {
+    let fields = native_fields
+        .iter()
+        .filter(|field| !field.skip_native)
+        .map(|field| {
+            let name = get_serialized_name(field);
+            // Types follow the serde output, so `apache_avro::to_value(&native)` matches the schema
+            let avro_type = match field.type_name.as_str() {
+                "flatten" => {
+                    let ty = get_flatten_ty(field.origin_fields[0].attrs.as_ref().unwrap());
+                    return quote! { fields.extend(#ty::__bm_avro_fields()); };
+                }
+                "i16" | "i32" | "u16" => "\"int\"",
+                // Avro has no unsigned long, a u64 above i64::MAX does not fit
+                "i64" | "u32" | "u64" => "\"long\"",
+                "f32" => "\"float\"",
+                "f64" => "\"double\"",
+                "bytes" if get_bytes_repr_with(field).is_some() => {
+                    let field = format!(r#"{{"name":"{}","type":"string"}}"#, name);
+                    return quote! { fields.push(#field.to_string()); };
+                }
+                "bytes" | "rest" => {
+                    let field = format!(
+                        r#"{{"name":"{}","type":{{"type":"array","items":"int"}}}}"#,
+                        name
+                    );
+                    return quote! { fields.push(#field.to_string()); };
+                }
+                _ => "\"string\"",
+            };
+            let field = format!(r#"{{"name":"{}","type":["null",{}],"default":null}}"#, name, avro_type);
+            quote! { fields.push(#field.to_string()); }
+        })
+        .collect::<Vec<_>>();
+    let record_name = native_name.to_string();
+
+    quote! {
+        /// Avro record schema of the native struct as serialized by serde
+        pub fn avro_schema() -> String {
+            format!(
+                r#"{{"type":"record","name":"{}","fields":[{}]}}"#,
+                #record_name,
+                Self::__bm_avro_fields().join(",")
+            )
+        }
+
+        /// Avro record fields, shared with structs flattening this one
+        #[doc(hidden)]
+        pub fn __bm_avro_fields() -> Vec<String> {
+            #[allow(unused_mut)]
+            let mut fields = Vec::new();
+            #(#fields)*
+            fields
+        }
+    }
+}
+
+fn get_proto_definition(
+    native_name: &syn::Ident,
+    native_fields: &[NativeField],
+) -> proc_macro2::TokenStream {
+    let fields = native_fields
+        .iter()
+        .filter(|field| !field.skip_native)
+        .map(|field| {
+            let proto_type = match field.type_name.as_str() {
+                "flatten" => {
+                    let ty = get_flatten_ty(field.origin_fields[0].attrs.as_ref().unwrap());
+                    return quote! { fields.extend(#ty::__bm_proto_fields()); };
+                }
+                "i16" | "i32" => "optional int32",
+                "i64" => "optional int64",
+                "u16" | "u32" => "optional uint32",
+                "u64" => "optional uint64",
+                "f32" => "optional float",
+                "f64" => "optional double",
+                "bytes" | "rest" => "bytes",
+                // Decimals, dates, times and enums travel in their text form
+                _ => "optional string",
+            };
+            let name = field.name.to_string();
+            quote! { fields.push((#proto_type, #name)); }
+        })
+        .collect::<Vec<_>>();
+    let message_name = native_name.to_string();
+
+    quote! {
+        /// `.proto` message definition matching the native struct
+        pub fn proto_definition() -> String {
+            let fields = Self::__bm_proto_fields()
+                .iter()
+                .enumerate()
+                .map(|(i, (proto_type, name))| format!("  {} {} = {};", proto_type, name, i + 1))
+                .collect::<Vec<_>>();
+            format!(
+                "syntax = \"proto3\";\n\nmessage {} {{\n{}\n}}\n",
+                #message_name,
+                fields.join("\n")
+            )
+        }
+
+        /// Field types and names in tag order, shared with structs flattening this one
+        #[doc(hidden)]
+        pub fn __bm_proto_fields() -> Vec<(&'static str, &'static str)> {
+            #[allow(unused_mut)]
+            let mut fields = Vec::new();
+            #(#fields)*
+            fields
+        }
+    }
+}
+
+fn get_layout_table(origin_fields: &[OriginField]) -> proc_macro2::TokenStream {
+    let rows = origin_fields
+        .iter()
+        .zip(get_field_ranges(origin_fields))
+        .map(|(field, (offset, size))| {
+            let attrs = field.attrs.as_ref();
+            let type_name = match attrs {
+                Some(attrs) => match attrs.enum_type.as_ref().or(attrs.flatten.as_ref()) {
+                    Some(inner) => format!("{}({})", attrs.type_name, inner),
+                    None => attrs.type_name.clone(),
+                },
+                None => String::new(),
+            };
+            let type_name = match overlay_target(field) {
+                Some(overlay) => format!("{} over {}", type_name, overlay),
+                None => type_name,
+            };
+            let format = attrs.and_then(|attrs| attrs.format.clone()).unwrap_or_default();
+            let default = match attrs.and_then(|attrs| attrs.default_func.as_ref()) {
+                Some(default_func) => format!("{}()", default_func),
+                None => {
+                    format!("{:?}", field.default_byte as char)
+                }
+            };
+            // The trailing `rest` field takes whatever follows the fixed fields
+            let size = if is_rest(field) { "*".to_string() } else { size.to_string() };
+            let doc = field.doc.as_deref().unwrap_or_default().replace('\n', " ");
+            let row = format!(
+                "| {} | {} | {} | {} | {} | {} | {} |",
+                field.name,
+                offset,
+                size,
+                type_name,
+                format.replace('|', "\\|"),
+                default,
+                doc.replace('|', "\\|")
+            );
+            row
+        })
+        .collect::<Vec<_>>();
+    let table = format!(
+        "| Field | Offset | Size | Type | Format | Default | Description |\n|---|---:|---:|---|---|---|---|\n{}\n",
+        rows.join("\n")
+    );
+
+    quote! {
+        /// Markdown table of the layout: name, offset, size, type, format, default and doc comment per field
+        pub fn layout_table() -> String {
+            #table.to_string()
+        }
+    }
+}
+
+/// `c_header()`, a packed C struct with the same field names and sizes, preceded by the
+/// structs it flattens and the enums its fields hold
+fn get_c_header(name: &syn::Ident, origin_fields: &[OriginField]) -> proc_macro2::TokenStream {
+    let mut dependencies = Vec::new();
+    let mut members = Vec::new();
+    for field in origin_fields {
+        let attrs = field.attrs.as_ref();
+        let type_name = attrs.map(|attrs| attrs.type_name.as_str()).unwrap_or_default();
+        let field_name = &field.name;
+        if let Some(overlay) = overlay_target(field) {
+            members.push(format!("    /* {}: {} over {} */", field_name, type_name, overlay));
+            continue;
+        }
+        if let Some(attrs) = attrs.filter(|attrs| attrs.type_name == "flatten") {
+            let ty = get_flatten_ty(attrs);
+            let c_name = ty.segments.last().expect("flatten path").ident.to_string();
+            dependencies.push(quote! { #ty::__bm_c_definitions(definitions); });
+            members.push(format!("    struct {} {};", c_name, field_name));
+            continue;
+        }
+        if let Some(enum_type) = attrs.and_then(|attrs| attrs.enum_type.as_ref()) {
+            let enum_ident = quote::format_ident!("{}", enum_type);
+            dependencies.push(quote! {
+                let variants = #enum_ident::VARIANTS
+                    .iter()
+                    .map(ToString::to_string)
+                    .zip(#enum_ident::byte_values().iter().copied())
+                    .collect::<Vec<_>>();
+                let definition = binary_mirror::c_enum_definition(stringify!(#enum_ident), &variants);
+                if !definitions.contains(&definition) {
+                    definitions.push(definition);
+                }
+            });
+        }
+        let comment = match attrs.and_then(|attrs| attrs.enum_type.as_ref().or(attrs.format.as_ref())) {
+            Some(detail) => format!(" /* {} {} */", type_name, detail.replace("*/", "* /")),
+            None if !type_name.is_empty() && type_name != "pad" => format!(" /* {} */", type_name),
+            None => String::new(),
+        };
+        // The trailing `rest` field is a flexible array member
+        let size = if is_rest(field) { String::new() } else { field.size.to_string() };
+        members.push(format!("    char {}[{}];{}", field_name, size, comment));
+    }
+    let definition = format!("struct {} {{\n{}\n}};\n", name, members.join("\n"));
+
+    quote! {
+        /// C declaration of the layout, a `#pragma pack(1)` struct with the same field names and
+        /// sizes, after the structs it flattens and the enums its fields hold
+        pub fn c_header() -> String {
+            let mut definitions = Vec::new();
+            Self::__bm_c_definitions(&mut definitions);
+            format!(
+                "#pragma pack(push, 1)\n\n{}\n#pragma pack(pop)\n",
+                definitions.join("\n")
+            )
+        }
+
+        /// The declarations `c_header` joins, each once, shared with structs flattening this one
+        #[doc(hidden)]
+        pub fn __bm_c_definitions(definitions: &mut Vec<String>) {
+            #(#dependencies)*
+            let definition = #definition.to_string();
+            if !definitions.contains(&definition) {
+                definitions.push(definition);
+            }
+        }
+    }
+}
+
+/// `python_struct_format()`, the layout as a format string for Python's `struct` module
+fn get_python_struct_format(origin_fields: &[OriginField]) -> proc_macro2::TokenStream {
+    let parts = origin_fields
+        .iter()
+        // Overlays take no bytes, and `struct` formats only have a fixed size
+        .filter(|field| overlay_target(field).is_none() && !is_rest(field))
+        .map(|field| {
+            let attrs = field.attrs.as_ref();
+            let size = field.size;
+            match attrs.map(|attrs| attrs.type_name.as_str()) {
+                Some("flatten") => {
+                    let ty = get_flatten_ty(attrs.unwrap());
+                    quote! { format.push_str(&#ty::python_struct_format()); }
+                }
+                Some("pad") => {
+                    let part = format!("{}x", size);
+                    quote! { format.push_str(#part); }
+                }
+                _ => {
+                    let part = format!("{}s", size);
+                    quote! { format.push_str(#part); }
+                }
+            }
+        });
+
+    quote! {
+        /// Format string for Python's `struct.unpack`, one `Ns` item per field with padding
+        /// skipped as `Nx`, flattened structs inlined and a `rest` field left out
+        pub fn python_struct_format() -> String {
+            #[allow(unused_mut)]
+            let mut format = String::new();
+            #(#parts)*
+            format
+        }
+    }
+}
+
+fn get_csv_methods(native_fields: &[NativeField]) -> proc_macro2::TokenStream {
+    let fields = native_fields
+        .iter()
+        .filter(|field| !field.skip_native)
+        .collect::<Vec<_>>();
+    // Flattened structs contribute their own columns
+    let columns = fields.iter().map(|field| match field.type_name.as_str() {
+        "flatten" => {
+            let native_ty = &field.pure_ty;
+            quote! { #native_ty::csv_header() }
+        }
+        _ => {
+            let column = get_serialized_name(field);
+            quote! { #column.to_string() }
+        }
+    });
+    let count = fields.len();
+    let cells = fields.iter().map(|field| {
+        let name = &field.name;
+        let value = match field.type_name.as_str() {
+            "flatten" => return quote! { self.#name.to_csv_row() },
+            "bytes" | "rest" => quote! { binary_mirror::to_bytes_repr(&self.#name) },
+            "enum" | "custom" => quote! {
+                self.#name.as_ref().map(|v| format!("{:?}", v)).unwrap_or_default()
+            },
+            _ => quote! {
+                self.#name.as_ref().map(|v| v.to_string()).unwrap_or_default()
+            },
+        };
+        quote! { binary_mirror::csv_field(&#value).into_owned() }
+    });
+
+    quote! {
+        /// CSV header line matching `to_csv_row`
+        pub fn csv_header() -> String {
+            let columns: [String; #count] = [#(#columns),*];
+            columns.join(",")
+        }
+
+        /// One CSV line, with empty cells for `None` values
+        pub fn to_csv_row(&self) -> String {
+            let cells: [String; #count] = [#(#cells),*];
+            cells.join(",")
+        }
+    }
+}
+
+/// `fix_fields` and `to_fix` over the fields with a `fix_tag`, the fields of nested structs
+/// following in place of their `flatten` field
+fn get_fix_methods(native_fields: &[NativeField]) -> proc_macro2::TokenStream {
+    let pushes = native_fields.iter().filter(|field| !field.skip_native).filter_map(|field| {
+        let name = &field.name;
+        let origin = &field.origin_fields[0];
+        let origin_field = &origin.name;
+        if field.type_name == "flatten" {
+            return Some(quote! { fields.extend(self.#name().fix_fields()); });
+        }
+        let tag = origin.attrs.as_ref()?.fix_tag?;
+        let raw_text = quote! {
+            String::from_utf8_lossy(binary_mirror::trim_ascii(&self.#origin_field)).into_owned()
+        };
+        // Enums and custom types go out as their wire code, which the FIX side usually shares
+        let value = match field.type_name.as_str() {
+            "datetime" => quote! { self.#name().map(|v| v.format("%Y%m%d-%H:%M:%S%.3f").to_string()) },
+            "date" => quote! { self.#name().map(|v| v.format("%Y%m%d").to_string()) },
+            "time" => quote! { self.#name().map(|v| v.format("%H:%M:%S%.3f").to_string()) },
+            "enum" | "custom" => quote! { self.#name().map(|_| #raw_text) },
+            "bytes" => quote! { Some(#raw_text) },
+            "rest" => quote! { Some(String::from_utf8_lossy(self.#name()).into_owned()) },
+            _ => quote! { self.#name().map(|v| v.to_string()) },
+        };
+        Some(quote! {
+            if let Some(value) = #value {
+                fields.push((#tag, value));
+            }
+        })
+    });
+
+    quote! {
+        /// The `fix_tag` fields holding a value, as FIX tags and value text in field order
+        pub fn fix_fields(&self) -> Vec<(u32, String)> {
+            #[allow(unused_mut)]
+            let mut fields = Vec::new();
+            #(#pushes)*
+            fields
+        }
+
+        /// The `fix_tag` fields as FIX `tag=value` pairs, each ended by SOH
+        pub fn to_fix(&self) -> String {
+            let mut out = String::new();
+            for (tag, value) in self.fix_fields() {
+                out.push_str(&format!("{}={}\x01", tag, value));
+            }
+            out
+        }
+    }
+}
+
+#[cfg(feature = "arrow")]
+fn get_arrow_impl(name: &syn::Ident, native_fields: &[NativeField]) -> proc_macro2::TokenStream {
+    let fields = native_fields
+        .iter()
+        .filter(|field| !field.skip_native)
+        .collect::<Vec<_>>();
+    let columns = fields.iter().map(|field| {
+        let column = field.name.to_string();
+        let data_type = match field.type_name.as_str() {
+            "flatten" => {
+                let ty = get_flatten_ty(field.origin_fields[0].attrs.as_ref().unwrap());
+                return quote! {
+                    fields.extend(
+                        <#ty as binary_mirror::arrow::ToArrow>::arrow_schema().fields().iter().cloned(),
+                    );
+                };
+            }
+            "i16" => quote!(Int16),
+            "i32" => quote!(Int32),
+            "i64" => quote!(Int64),
+            "u16" => quote!(UInt16),
+            "u32" => quote!(UInt32),
+            "u64" => quote!(UInt64),
+            "f32" => quote!(Float32),
+            "f64" => quote!(Float64),
+            "decimal" => quote! {
+                Decimal128(binary_mirror::arrow::DECIMAL_PRECISION, binary_mirror::arrow::DECIMAL_SCALE)
+            },
+            "datetime" => quote!(Timestamp(binary_mirror::arrow::arrow_schema::TimeUnit::Microsecond, None)),
+            "date" => quote!(Date32),
+            "time" => quote!(Time64(binary_mirror::arrow::arrow_schema::TimeUnit::Microsecond)),
+            "bytes" => {
+                let size = field.origin_fields[0].size as i32;
+                quote!(FixedSizeBinary(#size))
+            }
+            "rest" => quote!(Binary),
+            _ => quote!(Utf8),
+        };
+        quote! {
+            fields.push(std::sync::Arc::new(binary_mirror::arrow::arrow_schema::Field::new(
+                #column,
+                binary_mirror::arrow::arrow_schema::DataType::#data_type,
+                true,
+            )));
+        }
+    });
+    let builder_names = fields
+        .iter()
+        .map(|field| quote::format_ident!("{}_builder", field.name))
+        .collect::<Vec<_>>();
+    let builders = fields.iter().zip(&builder_names).map(|(field, builder)| {
+        let builder_type = match field.type_name.as_str() {
+            // Nested records are collected and converted by their own `ToArrow`
+            "flatten" => return quote! { let mut #builder = Vec::new(); },
+            "i16" => quote!(Int16Builder::new()),
+            "i32" => quote!(Int32Builder::new()),
+            "i64" => quote!(Int64Builder::new()),
+            "u16" => quote!(UInt16Builder::new()),
+            "u32" => quote!(UInt32Builder::new()),
+            "u64" => quote!(UInt64Builder::new()),
+            "f32" => quote!(Float32Builder::new()),
+            "f64" => quote!(Float64Builder::new()),
+            "decimal" => quote! {
+                Decimal128Builder::new().with_precision_and_scale(
+                    binary_mirror::arrow::DECIMAL_PRECISION,
+                    binary_mirror::arrow::DECIMAL_SCALE,
+                )?
+            },
+            "datetime" => quote!(TimestampMicrosecondBuilder::new()),
+            "date" => quote!(Date32Builder::new()),
+            "time" => quote!(Time64MicrosecondBuilder::new()),
+            "bytes" => {
+                let size = field.origin_fields[0].size as i32;
+                quote!(FixedSizeBinaryBuilder::new(#size))
+            }
+            "rest" => quote!(BinaryBuilder::new()),
+            _ => quote!(StringBuilder::new()),
+        };
         quote! {
-            pub fn #method_name() -> binary_mirror::FieldSpec {
-                binary_mirror::FieldSpec {
-                    offset: #offset,
-                    limit: #limit,
-                    size: #field_size,
+            let mut #builder = binary_mirror::arrow::arrow_array::builder::#builder_type;
+        }
+    });
+    let appends = fields.iter().zip(&builder_names).map(|(field, builder)| {
+        let getter = &field.name;
+        match field.type_name.as_str() {
+            "flatten" => quote! {
+                #builder.push(record.#getter());
+            },
+            "decimal" => quote! {
+                #builder.append_option(record.#getter().map(|mut d| {
+                    d.rescale(binary_mirror::arrow::DECIMAL_SCALE as u32);
+                    d.mantissa()
+                }));
+            },
+            "datetime" => quote! {
+                #builder.append_option(record.#getter().map(|dt| dt.and_utc().timestamp_micros()));
+            },
+            "date" => quote! {
+                #builder.append_option(
+                    record.#getter().map(binary_mirror::arrow::arrow_array::types::Date32Type::from_naive_date),
+                );
+            },
+            "time" => quote! {
+                #builder.append_option(record.#getter().map(|t| {
+                    chrono::Timelike::num_seconds_from_midnight(&t) as i64 * 1_000_000
+                        + chrono::Timelike::nanosecond(&t) as i64 / 1_000
+                }));
+            },
+            "bytes" => quote! {
+                #builder.append_value(record.#getter())?;
+            },
+            "rest" => quote! {
+                #builder.append_value(record.#getter());
+            },
+            "enum" | "custom" => quote! {
+                #builder.append_option(record.#getter().map(|v| format!("{:?}", v)));
+            },
+            _ => quote! {
+                #builder.append_option(record.#getter());
+            },
+        }
+    });
+
+    let finishes = fields.iter().zip(&builder_names).map(|(field, builder)| {
+        if field.type_name == "flatten" {
+            let ty = get_flatten_ty(field.origin_fields[0].attrs.as_ref().unwrap());
+            quote! {
+                columns.extend(
+                    <#ty as binary_mirror::arrow::ToArrow>::to_record_batch(#builder)?
+                        .columns()
+                        .iter()
+                        .cloned(),
+                );
+            }
+        } else {
+            quote! {
+                columns.push(std::sync::Arc::new(#builder.finish()) as binary_mirror::arrow::arrow_array::ArrayRef);
+            }
+        }
+    });
+
+    quote! {
+        impl binary_mirror::arrow::ToArrow for #name {
+            fn arrow_schema() -> binary_mirror::arrow::arrow_schema::Schema {
+                #[allow(clippy::vec_init_then_push)]
+                let mut fields: Vec<binary_mirror::arrow::arrow_schema::FieldRef> = Vec::new();
+                #(#columns)*
+                binary_mirror::arrow::arrow_schema::Schema::new(fields)
+            }
+
+            fn to_record_batch<'a, I>(
+                records: I,
+            ) -> Result<binary_mirror::arrow::arrow_array::RecordBatch, binary_mirror::arrow::arrow_schema::ArrowError>
+            where
+                I: IntoIterator<Item = &'a Self>,
+                Self: 'a,
+            {
+                use binary_mirror::arrow::arrow_array::builder::ArrayBuilder;
+                #(#builders)*
+                for record in records {
+                    #(#appends)*
+                }
+                #[allow(clippy::vec_init_then_push)]
+                let mut columns: Vec<binary_mirror::arrow::arrow_array::ArrayRef> = Vec::new();
+                #(#finishes)*
+                binary_mirror::arrow::arrow_array::RecordBatch::try_new(
+                    std::sync::Arc::new(<Self as binary_mirror::arrow::ToArrow>::arrow_schema()),
+                    columns,
+                )
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "arrow"))]
+fn get_arrow_impl(_name: &syn::Ident, _native_fields: &[NativeField]) -> proc_macro2::TokenStream {
+    quote! {}
+}
+
+#[cfg(feature = "arbitrary")]
+fn get_arbitrary_impl(
+    name: &syn::Ident,
+    native_name: &syn::Ident,
+    native_fields: &[NativeField],
+    struct_attrs: &StructAttrs,
+    has_rest: bool,
+) -> proc_macro2::TokenStream {
+    let fields = native_fields.iter().filter(|field| !field.skip_native).map(|field| {
+        let field_name = &field.name;
+        let value = get_arbitrary_value(field);
+        quote! { #field_name: #value }
+    });
+    // The raw struct goes through `from_native` so formats and checksums come out right
+    let raw_impl = (!has_rest).then(|| {
+        quote! {
+            impl<'a> binary_mirror::arbitrary::Arbitrary<'a> for #name {
+                fn arbitrary(
+                    u: &mut binary_mirror::arbitrary::Unstructured<'a>,
+                ) -> binary_mirror::arbitrary::Result<Self> {
+                    let native = <#native_name as binary_mirror::arbitrary::Arbitrary>::arbitrary(u)?;
+                    Ok(<Self as binary_mirror::FromNative<#native_name>>::from_native(&native))
+                }
+            }
+        }
+    });
+
+    let computed_defaults = get_computed_defaults(struct_attrs);
+    let computed_assigns = get_computed_assigns(struct_attrs, quote!(&native));
+    // what `to_native` carries over from the bytes `from_native` writes
+    let carried = get_carried_raw(native_fields, |carry, origin| {
+        let (default_byte, size) = (origin.default_byte, origin.size);
+        quote! { #carry: Some([#default_byte; #size]) }
+    });
+    let preserved_default = get_preserved_default(struct_attrs);
+
+    quote! {
+        impl<'a> binary_mirror::arbitrary::Arbitrary<'a> for #native_name {
+            fn arbitrary(
+                u: &mut binary_mirror::arbitrary::Unstructured<'a>,
+            ) -> binary_mirror::arbitrary::Result<Self> {
+                #[allow(unused_mut)]
+                let mut native = #native_name {
+                    #(#fields,)*
+                    #(#carried,)*
+                    #(#preserved_default,)*
+                    #(#computed_defaults,)*
+                };
+                #computed_assigns
+                Ok(native)
+            }
+        }
+
+        #raw_impl
+    }
+}
+
+/// Precision from a `format!` spec like `{:08.3}`
+#[cfg(feature = "arbitrary")]
+fn get_format_precision(format: Option<&str>) -> Option<u32> {
+    let spec = format?.split_once('.')?.1;
+    spec.chars()
+        .take_while(|c| c.is_ascii_digit())
+        .collect::<String>()
+        .parse()
+        .ok()
+}
+
+/// Width left for digits once written thousands separators are accounted for
+#[cfg(feature = "arbitrary")]
+fn get_grouped_digits(origin: &OriginField) -> usize {
+    let grouped = origin
+        .attrs
+        .as_ref()
+        .and_then(|attrs| attrs.numeric_locale.as_ref())
+        .is_some_and(|locale| locale.group);
+    if grouped {
+        origin.size - origin.size / 4
+    } else {
+        origin.size
+    }
+}
+
+/// Expression drawing a native value that fits the field's width and format
+#[cfg(feature = "arbitrary")]
+fn get_arbitrary_value(field: &NativeField) -> proc_macro2::TokenStream {
+    let origin = &field.origin_fields[0];
+    let size = origin.size;
+    let format = origin.attrs.as_ref().and_then(|attrs| attrs.format.as_deref());
+    let date = quote! {
+        chrono::NaiveDate::from_ymd_opt(
+            u.int_in_range(2000..=2099)?,
+            u.int_in_range(1..=12)?,
+            u.int_in_range(1..=28)?,
+        )
+    };
+    // Drawn before the closures that build the value, which can't use `?`
+    let hms = quote! {
+        let (hour, minute, second) = (u.int_in_range(0..=23)?, u.int_in_range(0..=59)?, u.int_in_range(0..=59)?);
+    };
+    let value = match field.type_name.as_str() {
+        // a blank str field reads back as an empty string rather than None
+        "str" | "compact_str" => {
+            let ty = &field.pure_ty;
+            return quote! {
+                Some(
+                    binary_mirror::arbitrary::bytes_from(u, binary_mirror::arbitrary::ALPHANUMERIC, 1, #size)?
+                        .into_iter()
+                        .map(char::from)
+                        .collect::<#ty>(),
+                )
+            };
+        }
+        "i16" | "i32" | "i64" | "u16" | "u32" | "u64" => {
+            let size = get_grouped_digits(origin);
+            let (type_min, type_max): (i128, i128) = match field.type_name.as_str() {
+                "i16" => (i16::MIN.into(), i16::MAX.into()),
+                "i32" => (i32::MIN.into(), i32::MAX.into()),
+                "i64" => (i64::MIN.into(), i64::MAX.into()),
+                "u16" => (0, u16::MAX.into()),
+                "u32" => (0, u32::MAX.into()),
+                _ => (0, u64::MAX.into()),
+            };
+            // as many digits as the field holds, less the sign or parentheses of a negative one
+            let sign_len = match origin.attrs.as_ref().and_then(|attrs| attrs.sign.as_deref()) {
+                Some("paren") => 2,
+                _ => 1,
+            };
+            let max = 10i128.saturating_pow(size.min(38) as u32).saturating_sub(1).min(type_max);
+            let min = (1 - 10i128.saturating_pow(size.saturating_sub(sign_len).min(38) as u32)).max(type_min);
+            let ty = quote::format_ident!("{}", field.type_name);
+            quote! { Some(u.int_in_range(#min..=#max)? as #ty) }
+        }
+        "f32" | "f64" | "decimal" => {
+            // digits on both sides of the point and the point itself fill the field
+            let size = get_grouped_digits(origin);
+            let precision = origin.attrs.as_ref().and_then(|attrs| attrs.precision);
+            let mut scale = precision.map(|p| p as u32).or(get_format_precision(format)).unwrap_or(2);
+            if size < scale as usize + 2 {
+                scale = 0;
+            }
+            let max_digits = if field.type_name == "f32" { 6 } else { 15 };
+            let digits = size.saturating_sub(if scale > 0 { 1 } else { 0 }).min(max_digits) as u32;
+            let max = 10i128.pow(digits) - 1;
+            let mantissa = quote! { u.int_in_range(0..=#max)? };
+            if field.type_name == "decimal" {
+                quote! { Some(rust_decimal::Decimal::new(#mantissa as i64, #scale)) }
+            } else {
+                let ty = quote::format_ident!("{}", field.type_name);
+                let divisor = 10i128.pow(scale);
+                quote! { Some(#mantissa as #ty / #divisor as #ty) }
+            }
+        }
+        "date" => date,
+        "time" => quote! {{
+            #hms
+            chrono::NaiveTime::from_hms_opt(hour, minute, second)
+        }},
+        "datetime" => match field.origin_fields.get(2) {
+            // as many sub-second digits as the fraction field holds
+            Some(subsec) => {
+                let max = 10u32.pow(subsec.size as u32) - 1;
+                let scale = 10u32.pow(9 - subsec.size as u32);
+                quote! {{
+                    let date = #date;
+                    #hms
+                    let nano = u.int_in_range(0..=#max)? * #scale;
+                    date.and_then(|date| date.and_hms_nano_opt(hour, minute, second, nano))
+                }}
+            }
+            None => quote! {{
+                let date = #date;
+                #hms
+                date.and_then(|date| date.and_hms_opt(hour, minute, second))
+            }},
+        },
+        "enum" => {
+            let enum_ty = &field.pure_ty;
+            quote! { #enum_ty::from_bytes(u.choose(#enum_ty::byte_values())?) }
+        }
+        "bytes" => return quote! { binary_mirror::arbitrary::Arbitrary::arbitrary(u)? },
+        "flatten" => {
+            let native_ty = &field.pure_ty;
+            return quote! { <#native_ty as binary_mirror::arbitrary::Arbitrary>::arbitrary(u)? };
+        }
+        "rest" => return quote! { binary_mirror::arbitrary::bytes_from(u, binary_mirror::arbitrary::ALPHANUMERIC, 0, 16)? },
+        _ => return quote! { Default::default() },
+    };
+    // an empty field is None, so leave some of them out
+    quote! {
+        if u.ratio(1u8, 8u8)? { None } else { #value }
+    }
+}
+
+#[cfg(not(feature = "arbitrary"))]
+fn get_arbitrary_impl(
+    _name: &syn::Ident,
+    _native_name: &syn::Ident,
+    _native_fields: &[NativeField],
+    _struct_attrs: &StructAttrs,
+    _has_rest: bool,
+) -> proc_macro2::TokenStream {
+    quote! {}
+}
+
+/// A validate(min/max) bound as a value of the field's native type, None when it does not fit it
+fn get_validate_bound(type_name: &str, value: &str) -> Option<proc_macro2::TokenStream> {
+    use proc_macro2::Literal;
+    let literal = match type_name {
+        "i16" => Literal::i16_unsuffixed(value.parse().ok()?),
+        "i32" => Literal::i32_unsuffixed(value.parse().ok()?),
+        "i64" => Literal::i64_unsuffixed(value.parse().ok()?),
+        "u16" => Literal::u16_unsuffixed(value.parse().ok()?),
+        "u32" => Literal::u32_unsuffixed(value.parse().ok()?),
+        "u64" => Literal::u64_unsuffixed(value.parse().ok()?),
+        "f32" => Literal::f32_unsuffixed(value.parse().ok().filter(|v: &f32| v.is_finite())?),
+        "f64" => Literal::f64_unsuffixed(value.parse().ok().filter(|v: &f64| v.is_finite())?),
+        "decimal" => {
+            let value = rust_decimal::Decimal::from_str_exact(value).ok()?;
+            let (mantissa, scale) = (value.mantissa(), value.scale());
+            return Some(quote! { rust_decimal::Decimal::from_i128_with_scale(#mantissa, #scale) });
+        }
+        _ => return None,
+    };
+    Some(literal.into_token_stream())
+}
+
+fn get_validate_impl(
+    generics: &StructGenerics,
+    origin_fields: &[OriginField],
+    native_fields: &[NativeField],
+    checksum_fields: &[ChecksumField],
+) -> proc_macro2::TokenStream {
+    let required_checks = origin_fields.iter().filter_map(|field| {
+        let attrs = field.attrs.as_ref()?;
+        if !attrs.required {
+            return None;
+        }
+        let field_name = &field.name;
+        let name = attrs.alias.as_ref().unwrap_or(&field_name.to_string()).clone();
+        Some(quote! {
+            if binary_mirror::is_blank(&self.#field_name) {
+                return Err(binary_mirror::ValidationError::new(
+                    #name,
+                    "required field is blank".to_string(),
+                    binary_mirror::to_bytes_repr(&self.#field_name),
+                ));
+            }
+        })
+    });
+    let checks = native_fields.iter().filter_map(|field| {
+        let validate = field.origin_fields[0].attrs.as_ref()?.validate.as_ref()?;
+        let name = &field.name;
+        let origin_field = &field.origin_fields[0].name;
+        let error = quote! {
+            |message: String| binary_mirror::ValidationError::new(
+                stringify!(#name),
+                message,
+                binary_mirror::to_bytes_repr(&self.#origin_field),
+            )
+        };
+
+        let ascii_check = validate.ascii.then(|| {
+            quote! {
+                if !self.#origin_field.is_ascii() {
+                    return Err(error("contains non-ascii bytes".to_string()));
+                }
+            }
+        });
+        let min_len_check = validate.min_len.map(|min_len| {
+            quote! {
+                let len = binary_mirror::trim_ascii(&self.#origin_field).len();
+                if len < #min_len {
+                    return Err(error(format!("length {} is less than min_len {}", len, #min_len)));
+                }
+            }
+        });
+        let max_len_check = validate.max_len.map(|max_len| {
+            quote! {
+                let len = binary_mirror::trim_ascii(&self.#origin_field).len();
+                if len > #max_len {
+                    return Err(error(format!("length {} is greater than max_len {}", len, #max_len)));
+                }
+            }
+        });
+        let regex_check = validate.regex.as_ref().map(|pattern| {
+            quote! {
+                static RE: binary_mirror::__private::__regex::OnceLock<binary_mirror::__private::__regex::Regex> =
+                    binary_mirror::__private::__regex::OnceLock::new();
+                // The pattern was already compiled by the derive
+                let re = RE.get_or_init(|| binary_mirror::__private::__regex::Regex::new(#pattern).unwrap());
+                match ::core::str::from_utf8(binary_mirror::trim_ascii(&self.#origin_field)) {
+                    Ok(s) if re.is_match(s) => {}
+                    _ => return Err(error(format!("does not match regex {}", #pattern))),
+                }
+            }
+        });
+        // The bounds are checked in check_origin_fields
+        let min_check = validate.min.as_ref().and_then(|min| {
+            let bound = get_validate_bound(&field.type_name, min)?;
+            Some(quote! {
+                if let Some(val) = self.#name() {
+                    if val < #bound {
+                        return Err(error(format!("value {} is less than min {}", val, #min)));
+                    }
+                }
+            })
+        });
+        let max_check = validate.max.as_ref().and_then(|max| {
+            let bound = get_validate_bound(&field.type_name, max)?;
+            Some(quote! {
+                if let Some(val) = self.#name() {
+                    if val > #bound {
+                        return Err(error(format!("value {} is greater than max {}", val, #max)));
+                    }
+                }
+            })
+        });
+
+        Some(quote! {
+            {
+                #[allow(unused_variables)]
+                let error = #error;
+                #ascii_check
+                #min_len_check
+                #max_len_check
+                #regex_check
+                #min_check
+                #max_check
+            }
+        })
+    });
+
+    let nested_checks = native_fields
+        .iter()
+        .filter(|field| field.type_name == "flatten")
+        .map(|field| {
+            let name = &field.name;
+            quote! { binary_mirror::Validate::validate(self.#name())?; }
+        });
+
+    let checksum_checks = checksum_fields.iter().map(|field| {
+        let field_name = &field.name;
+        let method_name = quote::format_ident!("compute_{}", field_name);
+        quote! {
+            let expected = self.#method_name();
+            if self.#field_name != expected {
+                return Err(binary_mirror::ValidationError::new(
+                    stringify!(#field_name),
+                    format!("checksum mismatch, expected \"{}\"", binary_mirror::to_bytes_repr(&expected)),
+                    binary_mirror::to_bytes_repr(&self.#field_name),
+                ));
+            }
+        }
+    });
+
+    let StructGenerics { impl_generics, where_clause, ty, .. } = generics;
+    quote! {
+        impl #impl_generics binary_mirror::Validate for #ty #where_clause {
+            fn validate(&self) -> Result<(), binary_mirror::ValidationError> {
+                #(#required_checks)*
+                #(#checks)*
+                #(#nested_checks)*
+                #(#checksum_checks)*
+                Ok(())
+            }
+        }
+    }
+}
+
+fn get_layout_assertions(
+    name: &syn::Ident,
+    origin_fields: &[OriginField],
+    struct_attrs: &StructAttrs,
+    has_rest: bool,
+) -> proc_macro2::TokenStream {
+    // Every field is a byte array, so the struct can be read from any address
+    // unless an explicit `repr(align)` raises its alignment
+    let align_message = format!("{} must have an alignment of 1 to be read from arbitrary bytes", name);
+    // `align_of` needs a sized type, an unsized struct only holds u8 and `repr(align)` is rejected.
+    // A record of 0 bytes would never advance `from_bytes_many` or `RecordIter`.
+    // Generic structs are checked once their parameters are known, the alignment in
+    // `from_bytes` and the size in `from_bytes_many` and `RecordIter`
+    let empty_message = format!("{} has no bytes, records of 0 bytes can't be split from a buffer", name);
+    let align_assertion = (!has_rest && !origin_fields.iter().any(is_generic)).then(|| {
+        quote! {
+            const _: () = assert!(::core::mem::align_of::<#name>() == 1, #align_message);
+            const _: () = assert!(::core::mem::size_of::<#name>() > 0, #empty_message);
+        }
+    });
+    let size_assertion = match struct_attrs.size {
+        Some(size) => {
+            let fields_size = origin_fields.iter().map(|field| field.size).sum::<usize>();
+            let message = format!(
+                "{} is declared as {} bytes but its fields sum to {} bytes",
+                name, size, fields_size
+            );
+            // With a `rest` field the declared size is the fixed prefix
+            let actual = if has_rest { quote!(#fields_size) } else { quote!(::core::mem::size_of::<#name>()) };
+            quote! {
+                const _: () = assert!(#actual == #size, #message);
+            }
+        }
+        None => quote! {},
+    };
+    let flatten_assertions = origin_fields.iter().filter(|field| !is_generic(field)).filter_map(|field| {
+        let attrs = field.attrs.as_ref()?;
+        attrs.flatten.as_ref()?;
+        let ty = get_flatten_ty(attrs);
+        let size = field.size;
+        let message = format!(
+            "flattened field `{}` is {} bytes but `{}` is not",
+            field.name,
+            size,
+            attrs.flatten.as_ref().unwrap()
+        );
+        Some(quote! {
+            const _: () = assert!(::core::mem::size_of::<#ty>() == #size, #message);
+        })
+    });
+    let enum_assertions = origin_fields.iter().filter_map(|field| {
+        let attrs = field.attrs.as_ref().filter(|attrs| attrs.type_name == "enum")?;
+        let ty = syn::parse_str::<syn::Type>(attrs.enum_type.as_ref()?).ok()?;
+        let size = field.size;
+        let message = format!(
+            "enum field `{}` is {} bytes, too short for the longest value of `{}`",
+            field.name,
+            size,
+            attrs.enum_type.as_ref().unwrap()
+        );
+        Some(quote! {
+            const _: () = assert!(<#ty>::MAX_LEN <= #size, #message);
+        })
+    });
+    let frame_assertion = (struct_attrs.frame.is_some() && !origin_fields.iter().any(is_generic)).then(|| {
+        quote! {
+            const _: binary_mirror::Frame = <#name as binary_mirror::Framing>::FRAME;
+        }
+    });
+    quote! {
+        #align_assertion
+        #size_assertion
+        #(#flatten_assertions)*
+        #(#enum_assertions)*
+        #frame_assertion
+    }
+}
+
+fn get_reflect_impl(
+    name: &syn::Ident,
+    native_name: &proc_macro2::Ident,
+    origin_fields: &[OriginField],
+) -> proc_macro2::TokenStream {
+    let size = origin_fields.iter().map(|field| field.size).sum::<usize>();
+    let fields = origin_fields.iter().zip(get_field_ranges(origin_fields)).map(|(field, (field_offset, size))| {
+        let field_name = field.name.to_string();
+        let (kind, alias, format, skip, skip_native) = match &field.attrs {
+            Some(attrs) => {
+                let kind = match attrs.type_name.as_str() {
+                    "str" => "Str",
+                    "compact_str" => "CompactStr",
+                    "bytes" => "Bytes",
+                    "i16" => "I16",
+                    "i32" => "I32",
+                    "i64" => "I64",
+                    "u16" => "U16",
+                    "u32" => "U32",
+                    "u64" => "U64",
+                    "f32" => "F32",
+                    "f64" => "F64",
+                    "decimal" => "Decimal",
+                    "datetime" => "DateTime",
+                    "date" => "Date",
+                    "time" => "Time",
+                    "enum" => "Enum",
+                    "custom" => "Custom",
+                    "checksum" => "Checksum",
+                    "flatten" => "Flatten",
+                    "rest" => "Rest",
+                    "pad" => "Pad",
+                    type_name => unreachable!("unchecked type {}", type_name),
+                };
+                let alias = match &attrs.alias {
+                    Some(alias) => quote!(Some(#alias)),
+                    None => quote!(None),
+                };
+                let format = match &attrs.format {
+                    Some(format) => quote!(Some(#format)),
+                    None => quote!(None),
+                };
+                (kind, alias, format, attrs.skip, attrs.skip_native)
+            }
+            None => ("Untyped", quote!(None), quote!(None), false, false),
+        };
+        let kind = quote::format_ident!("{}", kind);
+        let doc = match &field.doc {
+            Some(doc) => quote!(Some(#doc)),
+            None => quote!(None),
+        };
+        let nested = match field.attrs.as_ref().filter(|attrs| attrs.flatten.is_some()) {
+            Some(attrs) => {
+                let ty = get_flatten_ty(attrs);
+                quote!(Some(<#ty as binary_mirror::Reflect>::descriptor))
+            }
+            None => quote!(None),
+        };
+
+        quote! {
+            binary_mirror::FieldDescriptor {
+                name: #field_name,
+                alias: #alias,
+                offset: #field_offset,
+                size: #size,
+                kind: binary_mirror::FieldKind::#kind,
+                format: #format,
+                doc: #doc,
+                skip: #skip,
+                skip_native: #skip_native,
+                nested: #nested,
+            }
+        }
+    });
+
+    quote! {
+        impl binary_mirror::Reflect for #name {
+            fn descriptor() -> &'static binary_mirror::StructDescriptor {
+                const DESCRIPTOR: binary_mirror::StructDescriptor = binary_mirror::StructDescriptor {
+                    name: stringify!(#name),
+                    native_name: stringify!(#native_name),
+                    size: #size,
+                    fields: &[#(#fields,)*],
+                };
+                &DESCRIPTOR
+            }
+        }
+    }
+}
+
+/// Derives of the native and view structs, without `Debug` when some of their fields are
+/// redacted, which then get `get_redacted_debug_impl` instead
+fn get_native_derives(struct_attrs: &StructAttrs, redacted: bool) -> proc_macro2::TokenStream {
+    if struct_attrs.derives.is_empty() && redacted {
+        quote!(PartialEq, Serialize, Deserialize)
+    } else if struct_attrs.derives.is_empty() {
+        quote!(Debug, PartialEq, Serialize, Deserialize)
+    } else {
+        let native_derives = struct_attrs
+            .derives
+            .iter()
+            .filter(|derive| !(redacted && is_debug_derive(derive)))
+            .map(|derive| quote!(#derive))
+            .collect::<Vec<_>>();
+        quote!(#(#native_derives),*)
+    }
+}
+
+fn is_debug_derive(derive: &syn::Path) -> bool {
+    derive.segments.last().is_some_and(|segment| segment.ident == "Debug")
+}
+
+/// Whether the native or view struct derives `Debug` with redacted fields, so it needs a
+/// hand-written impl
+fn needs_redacted_debug(struct_attrs: &StructAttrs, fields: &[NativeField]) -> bool {
+    let derives_debug = struct_attrs.derives.is_empty() || struct_attrs.derives.iter().any(is_debug_derive);
+    derives_debug && fields.iter().any(is_redacted)
+}
+
+/// `VERSION`, `upgrade` from the previous layout and `decode_any` for `#[bm(version = N)]`
+fn get_version_methods(
+    name: &syn::Ident,
+    native_name: &syn::Ident,
+    struct_attrs: &StructAttrs,
+    origin_fields: &[OriginField],
+    native_fields: &[NativeField],
+) -> syn::Result<proc_macro2::TokenStream> {
+    let upgrade_attrs = origin_fields
+        .iter()
+        .filter_map(|field| field.attrs.as_ref())
+        .any(|attrs| attrs.since.is_some() || attrs.upgrade_with.is_some());
+    let Some(version) = struct_attrs.version else {
+        if struct_attrs.previous.is_some() || struct_attrs.version_field.is_some() {
+            return Err(syn::Error::new_spanned(name, "previous and version_field require version"));
+        }
+        if upgrade_attrs {
+            return Err(syn::Error::new_spanned(name, "since and upgrade_with require version and previous"));
+        }
+        return Ok(quote! {});
+    };
+    if upgrade_attrs && struct_attrs.previous.is_none() {
+        return Err(syn::Error::new_spanned(name, "since and upgrade_with require previous"));
+    }
+
+    let version_check = match &struct_attrs.version_field {
+        Some(version_field) => {
+            let Some(position) = origin_fields.iter().position(|field| field.name == *version_field) else {
+                return Err(syn::Error::new_spanned(
+                    version_field,
+                    format!("version_field `{}` not found", version_field),
+                ));
+            };
+            let (offset, _) = get_field_ranges(origin_fields)[position];
+            quote! {
+                if binary_mirror::parse_number::<u64>(binary_mirror::trim_ascii(&raw.#version_field))
+                    != Some(#version as u64)
+                {
+                    return Err(binary_mirror::DecodeError::unknown_tag(#offset, bytes));
+                }
+            }
+        }
+        None => quote! {},
+    };
+
+    let Some(previous) = &struct_attrs.previous else {
+        return Ok(quote! {
+            /// Layout version given with `#[bm(version = N)]`
+            pub const VERSION: u32 = #version;
+
+            /// Decode the oldest known layout, checking the version field if one is declared
+            pub fn decode_any(bytes: &[u8]) -> Result<#native_name, binary_mirror::DecodeError> {
+                let raw = <Self as binary_mirror::FromBytes>::from_bytes(bytes)?;
+                #version_check
+                Ok(binary_mirror::ToNative::to_native(raw))
+            }
+        });
+    };
+    let mut previous_native = previous.clone();
+    let last = previous_native.segments.last_mut().expect("previous path");
+    last.ident = quote::format_ident!("{}Native", last.ident);
+
+    let computed_assigns = get_computed_assigns(struct_attrs, quote!(&native));
+    let upgrade_fields = native_fields.iter().filter(|field| !field.skip_native).filter_map(|field| {
+        let name = &field.name;
+        let attrs = field.origin_fields[0].attrs.as_ref()?;
+        let is_version_field = struct_attrs.version_field.as_ref() == Some(&field.origin_fields[0].name);
+        if is_version_field && matches!(field.type_name.as_str(), "i16" | "i32" | "i64" | "u16" | "u32" | "u64") {
+            let ty = &field.pure_ty;
+            return Some(quote! { #name: Some(#version as #ty) });
+        }
+        match (&attrs.upgrade_with, attrs.since) {
+            (Some(upgrade_with), _) => {
+                let upgrade_with = syn::parse_str::<syn::Path>(upgrade_with).expect("Could not parse upgrade_with");
+                Some(quote! { #name: #upgrade_with(previous) })
+            }
+            // Added in this version, left to the default
+            (None, Some(since)) if since >= version => None,
+            (None, _) => Some(quote! { #name: previous.#name.clone() }),
+        }
+    });
+
+    Ok(quote! {
+        /// Layout version given with `#[bm(version = N)]`
+        pub const VERSION: u32 = #version;
+
+        /// Convert the previous layout's native struct, copying fields by name,
+        /// calling `upgrade_with`, defaulting fields added `since` this version
+        /// and setting a numeric version field to this version
+        #[allow(clippy::needless_update)]
+        pub fn upgrade(previous: &#previous_native) -> #native_name {
+            #[allow(unused_mut)]
+            let mut native = #native_name {
+                #(#upgrade_fields,)*
+                ..Default::default()
+            };
+            #computed_assigns
+            native
+        }
+
+        /// Decode this layout or any previous one, picked by length and the version field,
+        /// upgrading older layouts to this one
+        pub fn decode_any(bytes: &[u8]) -> Result<#native_name, binary_mirror::DecodeError> {
+            let current = || -> Result<#native_name, binary_mirror::DecodeError> {
+                let raw = <Self as binary_mirror::FromBytes>::from_bytes(bytes)?;
+                #version_check
+                Ok(binary_mirror::ToNative::to_native(raw))
+            };
+            current().or_else(|_| Ok(Self::upgrade(&#previous::decode_any(bytes)?)))
+        }
+    })
+}
+
+/// Serde impls on the raw struct that go through the native struct
+fn get_serde_raw_impl(
+    name: &syn::Ident,
+    native_name: &syn::Ident,
+    struct_attrs: &StructAttrs,
+) -> proc_macro2::TokenStream {
+    if !struct_attrs.serde_raw {
+        return quote! {};
+    }
+    quote! {
+        impl serde::Serialize for #name {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serde::Serialize::serialize(&binary_mirror::ToNative::to_native(self), serializer)
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for #name {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let native = <#native_name as serde::Deserialize>::deserialize(deserializer)?;
+                Ok(<#name as binary_mirror::FromNative<#native_name>>::from_native(&native))
+            }
+        }
+    }
+}
+
+/// Whether the native struct derives `Serialize`, which the serde view mirrors
+fn native_derives_serialize(struct_attrs: &StructAttrs) -> bool {
+    struct_attrs.derives.is_empty()
+        || struct_attrs
+            .derives
+            .iter()
+            .any(|derive| derive.segments.last().is_some_and(|segment| segment.ident == "Serialize"))
+}
+
+/// `SerdeView` for the raw struct, a borrowed struct with the fields and serde attributes of
+/// the native struct whose values are read from the raw bytes as it serializes
+///
+/// Strings are borrowed from the record. Computed fields are derived from the whole native
+/// struct, so a struct with computed fields serializes through `to_native`.
+fn get_serde_view(
+    name: &syn::Ident,
+    native_name: &syn::Ident,
+    native_fields: &[NativeField],
+    struct_attrs: &StructAttrs,
+) -> proc_macro2::TokenStream {
+    if !native_derives_serialize(struct_attrs) {
+        return quote! {};
+    }
+    if !struct_attrs.computed.is_empty() {
+        return quote! {
+            impl binary_mirror::SerdeView for #name {
+                type View<'a> = #native_name;
+
+                fn serde_view(&self) -> #native_name {
+                    binary_mirror::ToNative::to_native(self)
+                }
+            }
+        };
+    }
+    let view_name = quote::format_ident!("{}SerdeView", name);
+    let (fields, values): (Vec<_>, Vec<_>) = native_fields
+        .iter()
+        .filter(|field| !field.skip_native)
+        .map(|field| {
+            let field_name = &field.name;
+            let origin = &field.origin_fields[0];
+            let origin_field = &origin.name;
+            let attrs = origin.attrs.as_ref();
+            let native_serde = get_native_serde(field);
+            let serde_attr = native_serde.map(|args| quote! { #[serde(#args)] });
+            let repr_attr = get_bytes_repr_with(field).map(|with| quote! { #[serde(with = #with)] });
+            let with_warn = quote::format_ident!("{}_with_warn", field_name);
+            let native_value = quote! { self.#with_warn() };
+            // Strings are borrowed unless a `native_serde` adapter expects the native type
+            let borrow_str = matches!(field.type_name.as_str(), "str" | "compact_str")
+                && native_serde.is_none()
+                && attrs.is_some_and(|attrs| attrs.parse_with.is_none() && attrs.datetime_with.is_none());
+            let (ty, value) = match field.type_name.as_str() {
+                "flatten" => {
+                    let ty = get_flatten_ty(attrs.unwrap());
+                    (
+                        quote! { <#ty as binary_mirror::SerdeView>::View<'a> },
+                        quote! { binary_mirror::SerdeView::serde_view(self.#field_name()) },
+                    )
+                }
+                "rest" => (quote! { &'a [u8] }, quote! { self.#field_name() }),
+                _ if borrow_str => {
+                    let warn = get_parse_failure(name, struct_attrs, field);
+                    let parse = quote! {
+                        match ::core::str::from_utf8(binary_mirror::trim_ascii(&self.#origin_field)) {
+                            Ok(s) => Some(s),
+                            Err(_) => {
+                                #warn
+                                None
+                            }
+                        }
+                    };
+                    let value = match get_none_condition(origin) {
+                        Some(condition) => quote! { if #condition { None } else { #parse } },
+                        None => parse,
+                    };
+                    (quote! { Option<&'a str> }, value)
+                }
+                _ => (get_native_def_ty(field), native_value),
+            };
+            let flatten_attr = (field.type_name == "flatten").then(|| quote! { #[serde(flatten)] });
+            (
+                quote! {
+                    #flatten_attr
+                    #repr_attr
+                    #serde_attr
+                    #field_name: #ty
+                },
+                quote! { #field_name: #value },
+            )
+        })
+        .unzip();
+
+    let view_struct = quote! {
+        /// Serializes like the native struct, reading the raw record in place
+        #[doc(hidden)]
+        #[derive(serde::Serialize)]
+        pub struct #view_name<'a> {
+            #(#fields,)*
+            #[serde(skip)]
+            __raw: ::core::marker::PhantomData<&'a #name>,
+        }
+    };
+    quote! {
+        #view_struct
+
+        impl binary_mirror::SerdeView for #name {
+            type View<'a> = #view_name<'a>;
+
+            fn serde_view(&self) -> #view_name<'_> {
+                #view_name {
+                    #(#values,)*
+                    __raw: ::core::marker::PhantomData,
                 }
             }
         }
-    });
+    }
+}
 
+/// `to_json` and `write_json` with the `json` feature, serializing the serde view
+#[cfg(feature = "json")]
+fn get_json_methods(struct_attrs: &StructAttrs) -> proc_macro2::TokenStream {
+    if !native_derives_serialize(struct_attrs) {
+        return quote! {};
+    }
     quote! {
-        #(#size_methods)*
+        /// JSON of the native struct, serialized straight from the raw bytes
+        pub fn to_json(&self) -> String {
+            binary_mirror::json::serde_json::to_string(&binary_mirror::SerdeView::serde_view(self))
+                .expect("native fields serialize to JSON")
+        }
+
+        /// Write the JSON of the native struct to `writer`, serialized straight from the raw bytes
+        pub fn write_json<W: std::io::Write>(&self, writer: W) -> binary_mirror::json::serde_json::Result<()> {
+            binary_mirror::json::serde_json::to_writer(writer, &binary_mirror::SerdeView::serde_view(self))
+        }
     }
 }
 
-fn get_native_default_impl(
-    native_fields: &[NativeField],
-    native_name: &proc_macro2::Ident,
-) -> proc_macro2::TokenStream {
-    let default_fields = native_fields.iter().filter(|field| !field.skip_native).map(|field| {
-        let name = &field.name;
-        if let Some(default) = &field.default_func {
-            // let default_quote = quote! { #default };
-            let default_quote = quote::format_ident!("{}", default.as_str());
-            match field.type_name.as_str() {
-                // "hipstr" => quote! {
-                //     #name: Some(#default_quote())
-                // },
-                "str"| "compact_str" | "i16" | "i32" | "i64" | "u16" | "u32" | "u64" | "f32" | "f64" | "datetime"
-                | "date" | "time" | "enum" | "decimal" => {
+#[cfg(not(feature = "json"))]
+fn get_json_methods(_struct_attrs: &StructAttrs) -> proc_macro2::TokenStream {
+    quote! {}
+}
+
+/// Write every overlay holding a value over the bytes of its target, after the base fields
+fn get_overlay_writes(native_field_map: &[NativeField2OriginFieldMap]) -> proc_macro2::TokenStream {
+    let writes = native_field_map
+        .iter()
+        .filter(|mapping| is_overlay_view(mapping))
+        .filter_map(|mapping| {
+            let native_field = mapping.native_field.as_ref().filter(|field| !field.skip_native)?;
+            let native_name = &native_field.name;
+            let target = &mapping.origin_field.name;
+            let value = get_from_native_value(mapping, &quote!(native.#native_name));
+            Some(quote! {
+                if native.#native_name.is_some() {
+                    raw.#target = #value;
+                }
+            })
+        });
+    quote! { #(#writes)* }
+}
+
+/// With `preserve_unknown`, statements copying the original bytes of every field whose native
+/// value still matches them, then restoring the checksums once no field has changed
+///
+/// Nested and generic structs are written by their own `from_native`, they keep their bytes
+/// when they preserve them too.
+fn get_preserved_writes(
+    struct_attrs: &StructAttrs,
+    generics: &StructGenerics,
+    native_field_map: &[NativeField2OriginFieldMap],
+    checksum_fields: &[ChecksumField],
+) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
+    if !struct_attrs.preserve_unknown {
+        return (quote! {}, quote! {});
+    }
+    let name = &generics.ty;
+    let copies = native_field_map
+        .iter()
+        .filter(|mapping| !is_rest(&mapping.origin_field))
+        .map(|mapping| {
+            let field_name = &mapping.origin_field.name;
+            match &mapping.native_field {
+                Some(_) if is_generic(&mapping.origin_field) => {
                     quote! {
-                        #name: Some(#default_quote())
+                        if binary_mirror::ToBytes::to_bytes(&raw.#field_name)
+                            != binary_mirror::ToBytes::to_bytes(&original.#field_name)
+                        {
+                            unchanged = false;
+                        }
                     }
                 }
-                _ => quote! {
-                    #name: Default::default()
-                },
+                Some(native_field) if native_field.type_name == "flatten" => {
+                    quote! {
+                        if raw.#field_name != original.#field_name {
+                            unchanged = false;
+                        }
+                    }
+                }
+                Some(native_field) if !native_field.skip_native => {
+                    let native_name = &native_field.name;
+                    quote! {
+                        if original.#native_name() == native.#native_name {
+                            raw.#field_name = original.#field_name;
+                        } else {
+                            unchanged = false;
+                        }
+                    }
+                }
+                _ => quote! { raw.#field_name = original.#field_name; },
+            }
+        });
+    let restores = checksum_fields.iter().map(|field| {
+        let field_name = &field.name;
+        quote! { raw.#field_name = original.#field_name; }
+    });
+    let copies = quote! {
+        let original = native
+            .__raw
+            .as_bytes()
+            .and_then(|bytes| <#name as binary_mirror::FromBytes>::from_bytes(bytes).ok());
+        #[allow(unused_mut, unused_variables)]
+        let mut unchanged = original.is_some();
+        if let Some(original) = original {
+            #(#copies)*
+        }
+    };
+    let restores = quote! {
+        if let Some(original) = original.filter(|_| unchanged) {
+            #(#restores)*
+        }
+    };
+    (copies, restores)
+}
+
+/// `FromNative` for the raw struct, or for `Box<Raw>` when the struct ends in a `rest` field
+fn get_from_native_impl(
+    generics: &StructGenerics,
+    struct_attrs: &StructAttrs,
+    rest_field: Option<&OriginField>,
+    native_field_map: &[NativeField2OriginFieldMap],
+    from_native_fields: &[(syn::Ident, proc_macro2::TokenStream)],
+    checksum_fields: &[ChecksumField],
+) -> proc_macro2::TokenStream {
+    let StructGenerics { impl_generics, where_clause, ty: name, native_ty: native_name, .. } = generics;
+    let checksum_fill = get_checksum_refresh(checksum_fields, &quote!(raw));
+    let (preserved_copies, preserved_restores) =
+        get_preserved_writes(struct_attrs, generics, native_field_map, checksum_fields);
+    let overlay_writes = get_overlay_writes(native_field_map);
+    let field_names = from_native_fields.iter().map(|(field_name, _)| field_name);
+    let values = from_native_fields.iter().map(|(_, value)| value).collect::<Vec<_>>();
+    let Some(rest_field) = rest_field else {
+        return quote! {
+            impl #impl_generics binary_mirror::FromNative<#native_name> for #name #where_clause {
+                fn from_native(native: &#native_name) -> Self {
+                    #[allow(unused_mut)]
+                    let mut raw = Self {
+                        #(#field_names: #values,)*
+                    };
+                    #overlay_writes
+                    #preserved_copies
+                    #checksum_fill
+                    #preserved_restores
+                    raw
+                }
+            }
+        };
+    };
+    let rest = native_field_map
+        .iter()
+        .find(|mapping| mapping.origin_field.name == rest_field.name)
+        .and_then(|mapping| mapping.native_field.as_ref())
+        .filter(|native_field| !native_field.skip_native)
+        .map(|native_field| {
+            let rest_name = &native_field.name;
+            quote! { bytes.extend_from_slice(&native.#rest_name); }
+        });
+
+    quote! {
+        impl binary_mirror::FromNative<#native_name> for Box<#name> {
+            fn from_native(native: &#native_name) -> Self {
+                let mut bytes = Vec::new();
+                #(bytes.extend_from_slice(&#values);)*
+                #rest
+                let rest_len = bytes.len() - <#name as binary_mirror::FromBytes>::SIZE;
+                let bytes = Box::into_raw(bytes.into_boxed_slice());
+                // Safety:
+                // 1. The fixed fields were written in full, the remaining bytes form the rest field
+                // 2. The struct is #[repr(C)] and ends in a [u8] slice, so the pointer metadata
+                //    is the length of the trailing bytes and the layout matches the boxed slice
+                #[allow(unused_mut)]
+                let mut raw = unsafe {
+                    Box::from_raw(::core::ptr::slice_from_raw_parts_mut(
+                        bytes as *mut u8,
+                        rest_len,
+                    ) as *mut #name)
+                };
+                #overlay_writes
+                #preserved_copies
+                #checksum_fill
+                #preserved_restores
+                raw
             }
+        }
+    }
+}
+
+/// Copying, comparison, hashing and byte views over the raw bytes for `raw_derive(...)`, each
+/// trait bringing the ones it requires, so hashing agrees with `Borrow<[u8]>`
+fn get_raw_derive_impls(
+    struct_attrs: &StructAttrs,
+    generics: &StructGenerics,
+    origin_fields: &[OriginField],
+) -> proc_macro2::TokenStream {
+    let StructGenerics {
+        params,
+        impl_generics,
+        where_clause,
+        ty,
+        ..
+    } = generics;
+    let has = |names: &[&str]| struct_attrs.raw_derives.iter().any(|derive| names.iter().any(|name| derive == name));
+    let copy = has(&["Copy"]);
+    let clone = copy || has(&["Clone"]);
+    // Payload parameters must be copyable too, byte array fields always are
+    let copy_where = quote! { #where_clause #(, #params: ::core::marker::Copy)* };
+    let clone_where = quote! { #where_clause #(, #params: ::core::clone::Clone)* };
+    let clone = clone.then(|| {
+        let body = if copy {
+            quote! { *self }
         } else {
-            quote! {
-                #name: Default::default()
+            let names = origin_fields.iter().map(|field| &field.name);
+            quote! { Self { #(#names: ::core::clone::Clone::clone(&self.#names),)* } }
+        };
+        let where_clause = if copy { &copy_where } else { &clone_where };
+        quote! {
+            impl #impl_generics ::core::clone::Clone for #ty #where_clause {
+                fn clone(&self) -> Self {
+                    #body
+                }
             }
         }
     });
-
-    quote! {
-        impl Default for #native_name {
-            fn default() -> Self {
-                Self {
-                    #(#default_fields,)*
+    let copy = copy.then(|| quote! { impl #impl_generics ::core::marker::Copy for #ty #copy_where {} });
+    let ord = has(&["Ord"]);
+    let partial_ord = ord || has(&["PartialOrd"]);
+    let eq = ord || has(&["Eq"]);
+    let partial_eq = eq || partial_ord || has(&["PartialEq"]);
+    let hash = has(&["Hash"]);
+    let partial_eq = partial_eq.then(|| {
+        quote! {
+            impl #impl_generics PartialEq for #ty #where_clause {
+                fn eq(&self, other: &Self) -> bool {
+                    binary_mirror::ToBytes::to_bytes(self) == binary_mirror::ToBytes::to_bytes(other)
+                }
+            }
+        }
+    });
+    let eq = eq.then(|| quote! { impl #impl_generics Eq for #ty #where_clause {} });
+    let partial_ord = partial_ord.then(|| {
+        quote! {
+            impl #impl_generics PartialOrd for #ty #where_clause {
+                fn partial_cmp(&self, other: &Self) -> Option<::core::cmp::Ordering> {
+                    binary_mirror::ToBytes::to_bytes(self).partial_cmp(binary_mirror::ToBytes::to_bytes(other))
+                }
+            }
+        }
+    });
+    let ord = ord.then(|| {
+        quote! {
+            impl #impl_generics Ord for #ty #where_clause {
+                fn cmp(&self, other: &Self) -> ::core::cmp::Ordering {
+                    binary_mirror::ToBytes::to_bytes(self).cmp(binary_mirror::ToBytes::to_bytes(other))
+                }
+            }
+        }
+    });
+    let hash = hash.then(|| {
+        quote! {
+            impl #impl_generics ::core::hash::Hash for #ty #where_clause {
+                fn hash<H: ::core::hash::Hasher>(&self, state: &mut H) {
+                    ::core::hash::Hash::hash(binary_mirror::ToBytes::to_bytes(self), state)
                 }
             }
         }
+    });
+    let as_ref = has(&["AsRef"]).then(|| {
+        quote! {
+            impl #impl_generics AsRef<[u8]> for #ty #where_clause {
+                fn as_ref(&self) -> &[u8] {
+                    binary_mirror::ToBytes::to_bytes(self)
+                }
+            }
+        }
+    });
+    let borrow = has(&["Borrow"]).then(|| {
+        quote! {
+            impl #impl_generics ::core::borrow::Borrow<[u8]> for #ty #where_clause {
+                fn borrow(&self) -> &[u8] {
+                    binary_mirror::ToBytes::to_bytes(self)
+                }
+            }
+        }
+    });
+    quote! {
+        #clone
+        #copy
+        #partial_eq
+        #eq
+        #partial_ord
+        #ord
+        #hash
+        #as_ref
+        #borrow
     }
 }
 
-fn get_native_to_raw_impl(
-    name: &syn::Ident,
-    native_name: &proc_macro2::Ident,
-) -> proc_macro2::TokenStream {
+/// `FromBytes` methods, an unsized struct with a `rest` field accepts any length past the fixed prefix
+fn get_from_bytes_impl(has_rest: bool, is_generic: bool, cast: Option<CastBackend>) -> proc_macro2::TokenStream {
+    let align_check = is_generic.then(|| quote! { let () = Self::__BM_ALIGN; });
+    let (cast_one, cast_many, cast_owned) = get_from_bytes_casts(cast);
+    if has_rest {
+        return quote! {
+            fn from_bytes(bytes: &[u8]) -> Result<&Self, binary_mirror::BytesSizeError> {
+                if bytes.len() < Self::SIZE {
+                    return Err(binary_mirror::BytesSizeError::new(
+                        Self::SIZE,
+                        bytes.len(),
+                        binary_mirror::to_bytes_repr(bytes),
+                    ));
+                }
+                // Safety:
+                // 1. We've verified the fixed fields are all present
+                // 2. The struct is #[repr(C)] and ends in a [u8] slice, so the pointer metadata
+                //    is the length of the trailing bytes
+                // 3. Every field is a byte array, so any slice pointer is suitably aligned
+                Ok(unsafe {
+                    &*(::core::ptr::slice_from_raw_parts(bytes.as_ptr(), bytes.len() - Self::SIZE)
+                        as *const Self)
+                })
+            }
+        };
+    }
     quote! {
-        impl #native_name {
-            pub fn to_raw(&self) -> #name {
-                #name::from_native(self)
+        fn from_bytes(bytes: &[u8]) -> Result<&Self, binary_mirror::BytesSizeError> {
+            #align_check
+            let expected = Self::SIZE;
+            let actual = bytes.len();
+            if actual != expected {
+                return Err(binary_mirror::BytesSizeError::new(
+                    expected,
+                    actual,
+                    bytes.iter()
+                    .map(|&b| {
+                        match b {
+                            0x0A => "\\n".to_string(),
+                            0x0D => "\\r".to_string(),
+                            0x09 => "\\t".to_string(),
+                            0x20..=0x7E => (b as char).to_string(),
+                            _ => format!("\\x{:02x}", b),
+                        }
+                    })
+                    .collect::<Vec<String>>()
+                    .join("")
+                ));
+            }
+            #cast_one
+        }
+
+        fn from_bytes_many(bytes: &[u8]) -> Result<&[Self], binary_mirror::BytesSizeError> {
+            #align_check
+            let () = binary_mirror::__private::NonEmpty::<Self>::CHECK;
+            let remainder = bytes.len() % Self::SIZE;
+            if remainder != 0 {
+                return Err(binary_mirror::BytesSizeError::new(
+                    bytes.len() - remainder,
+                    bytes.len(),
+                    binary_mirror::to_bytes_repr(&bytes[bytes.len() - remainder..]),
+                ));
             }
+            #cast_many
+        }
+
+        fn from_bytes_owned(bytes: &[u8]) -> Result<Self, binary_mirror::BytesSizeError> {
+            let raw = Self::from_bytes(bytes)?;
+            #cast_owned
         }
     }
 }
 
-fn get_native_struct_code(
-    name: &syn::Ident,
-    native_fields: &[NativeField],
-) -> proc_macro2::TokenStream {
-    let native_name = quote::format_ident!("{}Native", name);
-    let fields_code = native_fields
-        .iter()
-        .filter(|field| !field.skip_native)
-        .map(|field| {
-            let name = &field.name;
-            let ty = &field.ty;
-            // Convert TokenStream to string and normalize whitespace
-            let ty_str = ty
-                .to_string()
-                .replace(" :: ", "::")
-                .replace(" < ", "<")
-                .replace(" > ", ">")
-                .replace(" >", ">");
-            format!("    pub {}: {},", name, ty_str)
-        })
-        .collect::<Vec<_>>()
-        .join("\n");
+/// The casts `from_bytes`, `from_bytes_many` and `from_bytes_owned` end with once the length
+/// is checked, through bytemuck or zerocopy when the struct opts in
+fn get_from_bytes_casts(
+    cast: Option<CastBackend>,
+) -> (proc_macro2::TokenStream, proc_macro2::TokenStream, proc_macro2::TokenStream) {
+    match cast {
+        Some(CastBackend::Bytemuck) => (
+            quote! { Ok(::bytemuck::from_bytes(bytes)) },
+            quote! { Ok(::bytemuck::cast_slice(bytes)) },
+            quote! { Ok(*raw) },
+        ),
+        Some(CastBackend::Zerocopy) => (
+            quote! {
+                Ok(<Self as ::zerocopy::FromBytes>::ref_from_bytes(bytes)
+                    .expect("the length is checked and the alignment is 1"))
+            },
+            quote! {
+                Ok(<[Self] as ::zerocopy::FromBytes>::ref_from_bytes(bytes)
+                    .expect("the length is checked and the alignment is 1"))
+            },
+            quote! {
+                Ok(<Self as ::zerocopy::FromBytes>::read_from_bytes(::zerocopy::IntoBytes::as_bytes(raw))
+                    .expect("the length is checked"))
+            },
+        ),
+        None => (
+            quote! {
+                // Safety:
+                // 1. We've verified the size matches
+                // 2. The struct is #[repr(C)]
+                // 3. Every field is a [u8; N] array or a generic `FromBytes` struct, and the alignment
+                //    is asserted to be 1 at compile time, so any slice pointer is suitably aligned
+                Ok(unsafe { &*(bytes.as_ptr() as *const Self) })
+            },
+            quote! {
+                // Safety:
+                // 1. The length is a whole number of records
+                // 2. The struct is #[repr(C)] and only holds byte arrays, so its alignment is 1
+                Ok(unsafe {
+                    ::core::slice::from_raw_parts(bytes.as_ptr() as *const Self, bytes.len() / Self::SIZE)
+                })
+            },
+            quote! {
+                // Safety: the struct only holds byte arrays, so a bitwise copy is a valid value
+                Ok(unsafe { ::core::ptr::read_unaligned(raw) })
+            },
+        ),
+    }
+}
 
-    quote! {
-        impl binary_mirror::NativeStructCode for #name {
-            fn native_struct_code() -> String {
-                format!(
-                    "pub struct {} {{\n{}\n}}",
-                    stringify!(#native_name),
-                    #fields_code
+/// `to_bytes` through bytemuck or zerocopy when the struct opts in, otherwise a slice over
+/// the struct's memory
+fn get_to_bytes_body(cast: Option<CastBackend>) -> proc_macro2::TokenStream {
+    match cast {
+        Some(CastBackend::Bytemuck) => quote! { ::bytemuck::bytes_of(self) },
+        Some(CastBackend::Zerocopy) => quote! { ::zerocopy::IntoBytes::as_bytes(self) },
+        None => quote! {
+            // Safety:
+            // 1. The struct is #[repr(C)]
+            // 2. We're reading the exact size of the struct, including any rest bytes
+            // 3. All fields are byte arrays or generic `FromBytes` structs made of them
+            // 4. The returned slice lifetime is tied to self
+            unsafe {
+                ::core::slice::from_raw_parts(
+                    (self as *const Self) as *const u8,
+                    ::core::mem::size_of_val(self)
                 )
             }
-        }
+        },
     }
 }
 
-fn get_native_derives(struct_attrs: &StructAttrs) -> proc_macro2::TokenStream {
-    if struct_attrs.derives.is_empty() {
-        quote!(Debug, PartialEq, Serialize, Deserialize)
-    } else {
-        let native_derives = struct_attrs
-            .derives
-            .iter()
-            .map(|derive| quote!(#derive))
-            .collect::<Vec<_>>();
-        quote!(#(#native_derives),*)
+/// `Zeroable` and `Pod` for `cast = "bytemuck"`, which also needs the struct to be `Copy`
+fn get_bytemuck_impls(name: &syn::Ident, cast: Option<CastBackend>) -> proc_macro2::TokenStream {
+    if cast != Some(CastBackend::Bytemuck) {
+        return quote! {};
+    }
+    quote! {
+        // Safety:
+        // 1. The struct is #[repr(C)] and only holds byte arrays, so it has no padding and
+        //    every bit pattern, all zeros included, is a valid value
+        // 2. Its alignment is asserted to be 1, and it holds no pointers or interior mutability
+        unsafe impl ::bytemuck::Zeroable for #name {}
+        unsafe impl ::bytemuck::Pod for #name {}
     }
 }
 
-fn impl_binary_mirror(input: &DeriveInput) -> TokenStream {
+fn impl_binary_mirror(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
     let name = &input.ident;
     let native_name = quote::format_ident!("{}Native", name);
-    let struct_attrs = get_struct_attrs(input);
+    check_repr_c(input)?;
+    let struct_attrs = get_struct_attrs(input)?;
+    check_generics(input, &struct_attrs)?;
+    let generics = get_struct_generics(input, &native_name);
+    let is_generic = !generics.params.is_empty();
+
+    let origin_fields = get_origin_fields(input, &struct_attrs)?;
+    check_generic_fields(&origin_fields, is_generic)?;
+    let rest_field = origin_fields.last().filter(|field| is_rest(field));
+    let has_rest = rest_field.is_some();
+    if has_rest && struct_attrs.serde_raw {
+        return Err(syn::Error::new_spanned(
+            name,
+            "serde_raw is not supported on structs with a rest field",
+        ));
+    }
+    let (native_fields, native_field_map) = get_native_fields_and_map(&origin_fields);
+    let debug_fields_token = get_debug_fields(&origin_fields);
+    let display_impl = get_display_impl(name, &generics, &native_fields, struct_attrs.display);
+    let methods = get_methods(name, &struct_attrs, &native_fields);
+    let native_fields_token = get_native_fields_token(&native_fields);
+    let to_native_fields_token = get_to_native_fields(&native_fields);
+    let from_native_fields = get_from_native_fields(&native_field_map);
+    let to_native_report_fields_token = get_to_native_report_fields(name, &struct_attrs, &native_fields);
+    let to_native_into_fields = get_to_native_into_fields(name, &struct_attrs, &native_fields);
+    let to_native_formats_fields = get_to_native_formats_fields(name, &struct_attrs, &native_fields);
+    let native_methods = get_native_methods(&native_fields);
+    if let Some(field) = struct_attrs
+        .computed
+        .iter()
+        .find(|computed| native_fields.iter().any(|field| field.name == computed.name))
+    {
+        return Err(syn::Error::new_spanned(
+            &field.name,
+            format!("computed field `{}` has the name of a native field", field.name),
+        ));
+    }
+    let computed_fields_token = get_computed_fields_token(&struct_attrs);
+    let (view_structs, view_methods) = get_views(&struct_attrs, &native_fields)?;
+    let computed_defaults = get_computed_defaults(&struct_attrs);
+    let computed_assigns = get_computed_assigns(&struct_attrs, quote!(&native));
+    let computed_into_assigns = get_computed_assigns(&struct_attrs, quote!(&*native));
+    let carried_fields_token = get_carried_raw(&native_fields, |carry, origin| {
+        let size = origin.size;
+        quote! {
+            #[doc(hidden)]
+            #[serde(skip)]
+            pub #carry: Option<[u8; #size]>
+        }
+    });
+    let carried_inits = get_carried_raw(&native_fields, |carry, origin| {
+        let name = &origin.name;
+        quote! { #carry: Some(self.#name) }
+    });
+    let carried_into_assigns = get_carried_raw(&native_fields, |carry, origin| {
+        let name = &origin.name;
+        quote! { native.#carry = Some(self.#name); }
+    });
+    let preserved_field_token = get_preserved_field_token(&struct_attrs);
+    let preserved_init = struct_attrs
+        .preserve_unknown
+        .then(|| quote! { __raw: binary_mirror::PreservedBytes::new(binary_mirror::ToBytes::to_bytes(self)) })
+        .into_iter()
+        .collect::<Vec<_>>();
+    let preserved_into_assign = struct_attrs.preserve_unknown.then(|| {
+        quote! { native.__raw.set(binary_mirror::ToBytes::to_bytes(self)); }
+    });
+    // These call into nested structs by name or need the offset of every field, which a
+    // generic struct only has once its parameters are known
+    let skip_generic = |tokens: proc_macro2::TokenStream| if is_generic { quote! {} } else { tokens };
+    let csv_methods = skip_generic(get_csv_methods(&native_fields));
+    let (sql_raw_methods, sql_native_methods) = get_sql_methods(&native_fields);
+    let (sql_raw_methods, sql_native_methods) = (skip_generic(sql_raw_methods), skip_generic(sql_native_methods));
+    let field_spec_methods = skip_generic(get_field_spec_methods(&origin_fields));
+    let field_bytes_methods = skip_generic(get_field_bytes_methods(&origin_fields));
+    let diff_method = skip_generic(get_diff_method(&origin_fields, &native_field_map));
+    let inspect_methods = skip_generic(get_inspect_methods(name, &origin_fields, &native_field_map));
+    let native_default_impl = get_native_default_impl(&native_fields, &native_name, &generics, &struct_attrs);
+    let native_to_raw_impl = skip_generic(get_native_to_raw_impl(name, &native_name, &native_field_map, has_rest));
+    let redacted_debug = needs_redacted_debug(&struct_attrs, &native_fields);
+    let native_derives = get_native_derives(&struct_attrs, redacted_debug);
+    let native_debug_impl = redacted_debug
+        .then(|| get_redacted_debug_impl(&native_name, Some(&generics), &native_fields, &struct_attrs.computed));
+    // A length field must hold the record size, the non-generic check runs with the layout assertions
+    let frame = match &struct_attrs.frame {
+        Some(frame) => {
+            let message = format!("the length field of the frame of {} is too short for its size", name);
+            quote! {
+                {
+                    let frame = #frame;
+                    assert!(frame.fits(<Self as binary_mirror::FromBytes>::SIZE), #message);
+                    frame
+                }
+            }
+        }
+        None => quote! { binary_mirror::Frame::Fixed },
+    };
+    let layout_assertions = get_layout_assertions(name, &origin_fields, &struct_attrs, has_rest);
+    let arrow_impl = skip_generic(get_arrow_impl(name, &native_fields));
+    let arbitrary_impl = skip_generic(get_arbitrary_impl(name, &native_name, &native_fields, &struct_attrs, has_rest));
+    let native_json_schema = skip_generic(get_native_json_schema(&native_name, &native_fields));
+    let avro_schema = skip_generic(get_avro_schema(&native_name, &native_fields));
+    let proto_definition = skip_generic(get_proto_definition(&native_name, &native_fields));
+    let layout_table = skip_generic(get_layout_table(&origin_fields));
+    let c_header = skip_generic(get_c_header(name, &origin_fields));
+    let python_struct_format = skip_generic(get_python_struct_format(&origin_fields));
+    let serde_raw_impl = get_serde_raw_impl(name, &native_name, &struct_attrs);
+    let serde_view = skip_generic(get_serde_view(name, &native_name, &native_fields, &struct_attrs));
+    let json_methods = skip_generic(get_json_methods(&struct_attrs));
+    let fix_methods = skip_generic(get_fix_methods(&native_fields));
+    let reflect_impl = skip_generic(get_reflect_impl(name, &native_name, &origin_fields));
+    let native_struct_code = skip_generic(get_native_struct_code(name, &native_fields, &struct_attrs));
+    let checksum_fields = get_checksum_fields(&origin_fields);
+    let checksum_methods = get_checksum_methods(&checksum_fields);
+    let validate_impl = get_validate_impl(&generics, &origin_fields, &native_fields, &checksum_fields);
+    let setter_methods = get_setter_methods(&native_fields, &native_field_map, &checksum_fields);
+    // The builder assembles the struct by value, which an unsized struct cannot be
+    let version_methods =
+        get_version_methods(name, &native_name, &struct_attrs, &origin_fields, &native_fields)?;
+    if struct_attrs.raw_default && (has_rest || is_generic) {
+        return Err(syn::Error::new_spanned(
+            name,
+            "raw_default is not supported on structs with a rest field or type parameters",
+        ));
+    }
+    let (raw_builder, raw_default_impl) = if has_rest || is_generic {
+        (quote! {}, quote! {})
+    } else {
+        (
+            get_raw_builder(name, &native_fields, &native_field_map, &checksum_fields),
+            get_raw_default_impl(name, &native_fields, &native_field_map, &struct_attrs),
+        )
+    };
+    let fixed_size = origin_fields.iter().map(|field| field.size).sum::<usize>();
+    let size_expr = if has_rest { quote!(#fixed_size) } else { quote!(::core::mem::size_of::<Self>()) };
+    // The array length has to be a literal, a generic struct's size is only known once its
+    // parameters are
+    let bytes_array_method = (!has_rest && !is_generic).then(|| {
+        quote! {
+            /// Copy the bytes into an array of the struct's size, for fixed stack buffers
+            pub fn to_bytes_array(&self) -> [u8; #fixed_size] {
+                let mut bytes = [0u8; #fixed_size];
+                bytes.copy_from_slice(binary_mirror::ToBytes::to_bytes(self));
+                bytes
+            }
+        }
+    });
+    let StructGenerics {
+        impl_generics,
+        where_clause,
+        ty,
+        native_ty,
+        native_params,
+        ..
+    } = &generics;
+    // A generic struct's alignment is only known once its parameters are, `from_bytes` checks it
+    let align_check = is_generic.then(|| {
+        let message = format!("the generic fields of {} must have an alignment of 1", name);
+        quote! {
+            #[doc(hidden)]
+            const __BM_ALIGN: () = assert!(::core::mem::align_of::<Self>() == 1, #message);
+        }
+    });
+    if struct_attrs.cast.is_some() && (has_rest || is_generic) {
+        return Err(syn::Error::new_spanned(
+            name,
+            "cast is not supported on structs with a rest field or type parameters",
+        ));
+    }
+    let from_bytes_impl = get_from_bytes_impl(has_rest, is_generic, struct_attrs.cast);
+    let to_bytes_body = get_to_bytes_body(struct_attrs.cast);
+    let bytemuck_impls = get_bytemuck_impls(name, struct_attrs.cast);
+    let copies = struct_attrs.raw_derives.iter().any(|derive| derive == "Clone" || derive == "Copy");
+    if copies && has_rest {
+        return Err(syn::Error::new_spanned(
+            name,
+            "raw_derive(Clone) and raw_derive(Copy) are not supported on structs with a rest field",
+        ));
+    }
+    let raw_derive_impls = get_raw_derive_impls(&struct_attrs, &generics, &origin_fields);
+    let from_native_impl = get_from_native_impl(
+        &generics,
+        &struct_attrs,
+        rest_field,
+        &native_field_map,
+        &from_native_fields,
+        &checksum_fields,
+    );
+
+    // Copying the bytes out needs a sized struct
+    let try_from_owned = (!has_rest).then(|| {
+        quote! {
+            impl #impl_generics TryFrom<&[u8]> for #ty #where_clause {
+                type Error = binary_mirror::BytesSizeError;
+
+                fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+                    <Self as binary_mirror::FromBytes>::from_bytes_owned(bytes)
+                }
+            }
+        }
+    });
+
+    // Type definitions stay at module level so they can be named, the impls go
+    // through `with_alloc_prelude`
+    let builder_struct = (!has_rest && !is_generic).then(|| {
+        let builder_name = quote::format_ident!("{}Builder", name);
+        quote! {
+            /// Assembles the raw struct field by field without going through the native struct
+            pub struct #builder_name {
+                raw: #name,
+            }
+        }
+    });
+    let native_struct = quote! {
+        #[derive(#native_derives)]
+        pub struct #native_name #native_params {
+            #(#native_fields_token,)*
+            #(#carried_fields_token,)*
+            #(#preserved_field_token,)*
+            #(#computed_fields_token,)*
+        }
+    };
 
-    let origin_fields = get_origin_fields(input);
-    let (native_fields, native_field_map) = get_native_fields_and_map(&origin_fields);
-    let debug_fields_token = get_debug_fields(&origin_fields);
-    let display_fields_token = get_display_fields(&native_fields);
-    let methods = get_methods(&native_fields);
-    let native_fields_token = get_native_fields_token(&native_fields);
-    let to_native_fields_token = get_to_native_fields(&native_fields);
-    let from_native_fields_token = get_from_native_fields(&native_field_map);
-    let native_methods = get_native_methods(&native_fields);
-    let field_spec_methods = get_field_spec_methods(&origin_fields);
-    let native_default_impl = get_native_default_impl(&native_fields, &native_name);
-    let native_to_raw_impl = get_native_to_raw_impl(name, &native_name);
-    let native_derives = get_native_derives(&struct_attrs);
-    let native_struct_code = get_native_struct_code(name, &native_fields);
+    let ref_generics = {
+        let mut ref_generics = input.generics.clone();
+        ref_generics.params.insert(0, syn::parse_quote!('a));
+        ref_generics
+    };
+    let (ref_impl_generics, _, _) = ref_generics.split_for_impl();
 
-    let gen = quote! {
-        impl #name {
+    let impls = quote! {
+        impl #impl_generics #ty #where_clause {
+            #align_check
             #(#methods)*
-            /// Get the size of the struct in bytes
+            #(#view_methods)*
+            #(#checksum_methods)*
+            #(#setter_methods)*
+            /// Get the size of the struct in bytes, the fixed prefix for structs with a rest field
             pub const fn size() -> usize {
-                std::mem::size_of::<Self>()
+                #size_expr
             }
+            #bytes_array_method
             #field_spec_methods
-        }
+            #field_bytes_methods
+            #diff_method
+            #inspect_methods
+            #native_json_schema
+            #avro_schema
+            #proto_definition
+            #layout_table
+            #c_header
+            #python_struct_format
+            #json_methods
+            #fix_methods
+            #sql_raw_methods
+            #version_methods
 
-        #[derive(#native_derives)]
-        pub struct #native_name {
-            #(#native_fields_token,)*
+            /// Validate the record and convert it to the native struct
+            pub fn try_to_native(&self) -> Result<#native_ty, binary_mirror::ValidationError> {
+                binary_mirror::Validate::validate(self)?;
+                Ok(binary_mirror::ToNative::to_native(self))
+            }
+
+            /// Convert to the native struct, parsing the date and time fields named in `overrides`
+            /// with the format given there instead of the declared one
+            pub fn to_native_with_formats(&self, overrides: &binary_mirror::FormatOverrides) -> #native_ty {
+                #[allow(unused_mut)]
+                let mut native = #native_name {
+                    #(#to_native_formats_fields,)*
+                    #(#carried_inits,)*
+                    #(#preserved_init,)*
+                    #(#computed_defaults,)*
+                };
+                #computed_assigns
+                native
+            }
+
+            /// Convert into an existing native struct, reusing the capacity of its strings
+            pub fn to_native_into(&self, native: &mut #native_ty) {
+                #(#to_native_into_fields)*
+                #(#carried_into_assigns)*
+                #preserved_into_assign
+                #computed_into_assigns
+            }
+
+            /// Convert to the native struct, collecting every non-blank field that failed to parse
+            pub fn to_native_with_report(&self) -> (#native_ty, Vec<binary_mirror::FieldError>) {
+                #[allow(unused_mut)]
+                let mut errors = Vec::new();
+                #[allow(unused_mut)]
+                let mut native = #native_name {
+                    #(#to_native_report_fields_token,)*
+                    #(#carried_inits,)*
+                    #(#preserved_init,)*
+                    #(#computed_defaults,)*
+                };
+                #computed_assigns
+                (native, errors)
+            }
         }
 
-        impl #native_name {
+        impl #native_params #native_name #native_params {
             #(#native_methods)*
+            #csv_methods
+            #sql_native_methods
         }
 
-        impl std::fmt::Debug for #name {
-            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        impl #impl_generics ::core::fmt::Debug for #ty #where_clause {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
                 f.debug_struct(stringify!(#name))
                     #(#debug_fields_token)*
                     .finish()
             }
         }
 
-        impl std::fmt::Display for #name {
-            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-                write!(f, "{} {{ ", stringify!(#name))?;
-                let mut first = true;
-                #(
-                    if first {
-                        first = false;
-                    } else {
-                        write!(f, ", ")?;
-                    }
-                    #display_fields_token
-                )*
-                write!(f, " }}")
-            }
-        }
+        #display_impl
 
         #native_default_impl
+        #native_debug_impl
         #native_to_raw_impl
+        #raw_builder
+        #raw_default_impl
         #native_struct_code
+        #validate_impl
+        #serde_raw_impl
+        #serde_view
+        #arrow_impl
+        #arbitrary_impl
+        #reflect_impl
+        #raw_derive_impls
+        #bytemuck_impls
 
-        impl binary_mirror::FromBytes for #name {
-            const SIZE: usize = std::mem::size_of::<Self>();
+        impl #impl_generics binary_mirror::Framing for #ty #where_clause {
+            const FRAME: binary_mirror::Frame = #frame;
+        }
 
-            fn from_bytes(bytes: &[u8]) -> Result<&Self, binary_mirror::BytesSizeError> {
-                let expected = Self::SIZE;
-                let actual = bytes.len();
-                if actual != expected {
-                    return Err(binary_mirror::BytesSizeError::new(
-                        expected,
-                        actual,
-                        bytes.iter()
-                        .map(|&b| {
-                            match b {
-                                0x0A => "\\n".to_string(),
-                                0x0D => "\\r".to_string(),
-                                0x09 => "\\t".to_string(),
-                                0x20..=0x7E => (b as char).to_string(),
-                                _ => format!("\\x{:02x}", b),
-                            }
-                        })
-                        .collect::<Vec<String>>()
-                        .join("")
-                    ));
-                }
-                // Safety:
-                // 1. We've verified the size matches
-                // 2. The struct is #[repr(C)]
-                // 3. The alignment is handled by the compiler
-                Ok(unsafe { &*(bytes.as_ptr() as *const Self) })
-            }
+        // Safety: every field is a byte array or a type parameter bounded on `RawRecord`, and
+        // the layout assertions keep the alignment at 1
+        unsafe impl #impl_generics binary_mirror::RawRecord for #ty #where_clause {}
 
+        impl #impl_generics binary_mirror::FromBytes for #ty #where_clause {
+            const SIZE: usize = #size_expr;
+
+            #from_bytes_impl
         }
 
-        impl binary_mirror::ToBytes for #name {
+        impl #impl_generics binary_mirror::ToBytes for #ty #where_clause {
             fn to_bytes(&self) -> &[u8] {
-                // Safety:
-                // 1. The struct is #[repr(C)]
-                // 2. We're reading the exact size of the struct
-                // 3. All fields are byte arrays
-                // 4. The returned slice lifetime is tied to self
-                unsafe {
-                    std::slice::from_raw_parts(
-                        (self as *const Self) as *const u8,
-                        Self::size()
-                    )
-                }
+                #to_bytes_body
             }
 
             fn to_bytes_owned(&self) -> Vec<u8> {
@@ -1106,97 +6379,753 @@ fn impl_binary_mirror(input: &DeriveInput) -> TokenStream {
             }
         }
 
-        impl binary_mirror::ToNative for #name {
-            type Native = #native_name;
+        impl #ref_impl_generics TryFrom<&'a [u8]> for &'a #ty #where_clause {
+            type Error = binary_mirror::BytesSizeError;
+
+            fn try_from(bytes: &'a [u8]) -> Result<Self, Self::Error> {
+                <#ty as binary_mirror::FromBytes>::from_bytes(bytes)
+            }
+        }
+
+        #try_from_owned
+
+        impl #impl_generics From<&#ty> for Vec<u8> #where_clause {
+            fn from(raw: &#ty) -> Self {
+                binary_mirror::ToBytes::to_bytes_owned(raw)
+            }
+        }
+
+        impl #impl_generics binary_mirror::ToNative for #ty #where_clause {
+            type Native = #native_ty;
 
             fn to_native(&self) -> Self::Native {
-                #native_name {
+                #[allow(unused_mut)]
+                let mut native = #native_name {
                     #(#to_native_fields_token,)*
-                }
+                    #(#carried_inits,)*
+                    #(#preserved_init,)*
+                    #(#computed_defaults,)*
+                };
+                #computed_assigns
+                native
             }
-        }
 
-        impl binary_mirror::FromNative<#native_name> for #name {
-            fn from_native(native: &#native_name) -> Self {
-                Self {
-                    #(#from_native_fields_token,)*
-                }
+            fn to_native_with_report(&self) -> (Self::Native, Vec<binary_mirror::FieldError>) {
+                self.to_native_with_report()
             }
         }
+
+        #from_native_impl
+    };
+
+    let impls = with_alloc_prelude(impls);
+    let gen = quote! {
+        #native_struct
+        #(#view_structs)*
+        #builder_struct
+        #layout_assertions
+        #impls
     };
 
-    gen.into()
+    Ok(gen)
+}
+
+/// The `impl` generics, raw and native struct types of a possibly generic struct
+fn get_struct_generics(input: &DeriveInput, native_name: &syn::Ident) -> StructGenerics {
+    let name = &input.ident;
+    let params = input.generics.type_params().map(|param| param.ident.clone()).collect::<Vec<_>>();
+    if params.is_empty() {
+        return StructGenerics {
+            params,
+            impl_generics: quote!(),
+            where_clause: quote!(),
+            ty: quote!(#name),
+            native_ty: quote!(#native_name),
+            native_params: quote!(),
+        };
+    }
+    // Every parameter is a nested raw struct, the native struct holds its native type
+    let mut generics = input.generics.clone();
+    let where_clause = generics.make_where_clause();
+    for param in &params {
+        where_clause.predicates.push(syn::parse_quote! {
+            #param: binary_mirror::RawRecord
+                + binary_mirror::FromBytes
+                + binary_mirror::ToBytes
+                + binary_mirror::ToNative
+                + binary_mirror::FromNative<<#param as binary_mirror::ToNative>::Native>
+                + binary_mirror::Validate
+                + ::core::fmt::Debug
+                + ::core::fmt::Display
+        });
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    StructGenerics {
+        impl_generics: quote!(#impl_generics),
+        where_clause: quote!(#where_clause),
+        ty: quote!(#name #ty_generics),
+        native_ty: quote!(#native_name<#(<#params as binary_mirror::ToNative>::Native),*>),
+        native_params: quote!(<#(#params),*>),
+        params,
+    }
+}
+
+/// Wrap generated impls in an anonymous const that brings `String`, `Vec`,
+/// `format!` and friends into scope, so they also compile in `no_std` crates
+fn with_alloc_prelude(impls: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    quote! {
+        const _: () = {
+            #[allow(unused_imports)]
+            use binary_mirror::__private::*;
+
+            #impls
+        };
+    }
+}
+
+#[derive(Debug, Default)]
+struct VariantAttrs {
+    value: Option<Vec<u8>>,
+    aliases: Vec<syn::LitByteStr>,
+    case_insensitive: Option<bool>,
+    other: bool,
+    label: Option<String>,
+    num: Option<u64>,
 }
 
-fn get_variant_value(attrs: &[syn::Attribute]) -> Option<Vec<u8>> {
+fn get_variant_attrs(attrs: &[syn::Attribute]) -> syn::Result<VariantAttrs> {
+    let mut variant_attrs = VariantAttrs::default();
     for attr in attrs {
         if attr.path().is_ident("bv") {
-            let mut byte_value = None;
-            let _ = attr.parse_nested_meta(|meta| {
+            attr.parse_nested_meta(|meta| {
                 if meta.path.is_ident("value") {
                     let lit = meta.value()?.parse::<syn::LitByteStr>()?;
-                    byte_value = Some(lit.value().to_vec());
+                    if lit.value().is_empty() {
+                        return Err(syn::Error::new(lit.span(), "bv value must not be empty"));
+                    }
+                    variant_attrs.value = Some(lit.value().to_vec());
+                } else if meta.path.is_ident("alias") {
+                    let lit = meta.value()?.parse::<syn::LitByteStr>()?;
+                    if lit.value().is_empty() {
+                        return Err(syn::Error::new(lit.span(), "bv alias must not be empty"));
+                    }
+                    variant_attrs.aliases.push(lit);
+                } else if meta.path.is_ident("case_insensitive") {
+                    let lit = meta.value()?.parse::<syn::LitBool>()?;
+                    variant_attrs.case_insensitive = Some(lit.value());
+                } else if meta.path.is_ident("other") {
+                    variant_attrs.other = true;
+                } else if meta.path.is_ident("num") {
+                    let lit = meta.value()?.parse::<syn::LitInt>()?;
+                    variant_attrs.num = Some(lit.base10_parse()?);
+                } else if meta.path.is_ident("label") {
+                    variant_attrs.label = Some(meta.value()?.parse::<LitStr>()?.value());
+                } else {
+                    return Err(meta.error(format!(
+                        "unknown bv attribute `{}`",
+                        meta.path.to_token_stream()
+                    )));
                 }
                 Ok(())
-            });
-            return byte_value;
+            })?;
+        }
+    }
+    Ok(variant_attrs)
+}
+
+/// `#[bv(...)]` on the enum itself, the defaults for every variant
+#[derive(Debug)]
+struct EnumAttrs {
+    case_insensitive: bool,
+    /// `trim` or `trim = b'0'`, the pad byte stripped before an exact match
+    trim: Option<u8>,
+    /// Derive `Display` and `FromStr` from the labels, `display = false` to write your own
+    display: bool,
+}
+
+impl Default for EnumAttrs {
+    fn default() -> Self {
+        Self {
+            case_insensitive: false,
+            trim: None,
+            display: true,
+        }
+    }
+}
+
+fn get_enum_attrs(attrs: &[syn::Attribute]) -> syn::Result<EnumAttrs> {
+    let mut enum_attrs = EnumAttrs::default();
+    for attr in attrs {
+        if attr.path().is_ident("bv") {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("case_insensitive") {
+                    let lit = meta.value()?.parse::<syn::LitBool>()?;
+                    enum_attrs.case_insensitive = lit.value();
+                } else if meta.path.is_ident("trim") {
+                    enum_attrs.trim = if meta.input.peek(syn::Token![=]) {
+                        Some(meta.value()?.parse::<syn::LitByte>()?.value())
+                    } else {
+                        Some(b' ')
+                    };
+                } else if meta.path.is_ident("display") {
+                    let lit = meta.value()?.parse::<syn::LitBool>()?;
+                    enum_attrs.display = lit.value();
+                } else {
+                    return Err(meta.error(format!(
+                        "unknown bv attribute `{}` on the enum",
+                        meta.path.to_token_stream()
+                    )));
+                }
+                Ok(())
+            })?;
         }
     }
-    None
+    Ok(enum_attrs)
+}
+
+/// A unit variant of a `BinaryEnum` and the encodings it accepts
+struct EnumVariant<'a> {
+    ident: &'a syn::Ident,
+    value: Vec<u8>,
+    aliases: Vec<syn::LitByteStr>,
+    case_insensitive: bool,
+    label: String,
+    num: Option<u64>,
 }
 
-fn impl_binary_enum(input: &DeriveInput) -> TokenStream {
+fn impl_binary_enum(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
     let name = &input.ident;
 
     let variants = match &input.data {
         Data::Enum(data) => &data.variants,
-        _ => panic!("BinaryEnum can only be derived for enums"),
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "BinaryEnum can only be derived for enums",
+            ))
+        }
     };
 
-    let match_arms_from = variants.iter().map(|variant| {
-        let variant_ident = &variant.ident;
-        let byte_value = get_variant_value(&variant.attrs).unwrap_or_else(|| {
-            let variant_str = variant_ident.to_string().to_uppercase();
-            vec![variant_str.chars().next().unwrap() as u8]
+    let enum_attrs = get_enum_attrs(&input.attrs)?;
+    // The `#[bv(other)]` variant and the type of the bytes it captures
+    let mut other_variant: Option<(&syn::Ident, &syn::Type)> = None;
+    let mut variant_values = Vec::new();
+    for variant in variants {
+        let variant_attrs = get_variant_attrs(&variant.attrs)?;
+        if variant_attrs.other {
+            let ty = match &variant.fields {
+                Fields::Unnamed(fields) if fields.unnamed.len() == 1 => &fields.unnamed[0].ty,
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        variant,
+                        "bv(other) variant must hold a single `[u8; N]` or `Vec<u8>`",
+                    ))
+                }
+            };
+            if other_variant.is_some() {
+                return Err(syn::Error::new_spanned(
+                    variant,
+                    "only one variant can be bv(other)",
+                ));
+            }
+            other_variant = Some((&variant.ident, ty));
+            continue;
+        }
+        if !matches!(variant.fields, Fields::Unit) {
+            return Err(syn::Error::new_spanned(
+                variant,
+                "BinaryEnum only supports unit variants",
+            ));
+        }
+        if variant_attrs.num.is_some() && variant_attrs.value.is_some() {
+            return Err(syn::Error::new_spanned(
+                variant,
+                "bv num and value cannot be combined",
+            ));
+        }
+        let value = match (variant_attrs.num, variant_attrs.value) {
+            (Some(num), _) => num.to_string().into_bytes(),
+            (None, Some(value)) => value,
+            (None, None) => {
+                let variant_str = variant.ident.to_string().to_uppercase();
+                vec![variant_str.chars().next().unwrap() as u8]
+            }
+        };
+        variant_values.push(EnumVariant {
+            ident: &variant.ident,
+            value,
+            aliases: variant_attrs.aliases,
+            case_insensitive: variant_attrs
+                .case_insensitive
+                .unwrap_or(enum_attrs.case_insensitive),
+            label: variant_attrs
+                .label
+                .unwrap_or_else(|| variant.ident.to_string()),
+            num: variant_attrs.num,
         });
-        let byte_len = byte_value.len();
+    }
 
-        quote! {
-            if bytes.len() >= #byte_len && &bytes[..#byte_len] == &[#(#byte_value),*] {
-                Some(Self::#variant_ident)
-            } else
+    // Every accepted encoding must map to exactly one variant
+    let mut seen: Vec<Vec<u8>> = variant_values.iter().map(|v| v.value.clone()).collect();
+    for variant in &variant_values {
+        for alias in &variant.aliases {
+            if seen.contains(&alias.value()) {
+                return Err(syn::Error::new(
+                    alias.span(),
+                    "bv alias duplicates another value or alias",
+                ));
+            }
+            seen.push(alias.value());
+        }
+    }
+
+    // `FromStr` must map every label back to one variant
+    for (i, variant) in variant_values.iter().enumerate() {
+        let duplicate = variant_values[..i].iter().any(|earlier| {
+            if variant.case_insensitive || earlier.case_insensitive {
+                earlier.label.eq_ignore_ascii_case(&variant.label)
+            } else {
+                earlier.label == variant.label
+            }
+        });
+        if duplicate {
+            return Err(syn::Error::new_spanned(
+                variant.ident,
+                format!("bv label \"{}\" duplicates the label of another variant", variant.label),
+            ));
         }
+    }
+
+    // With `trim` the pad is stripped and the value must match exactly,
+    // otherwise any input starting with the value matches
+    let (trim_input, input, len_cmp) = match enum_attrs.trim {
+        Some(pad) => (
+            quote! {
+                let mut end = bytes.len();
+                while end > 0 && bytes[end - 1] == #pad {
+                    end -= 1;
+                }
+                let trimmed = &bytes[..end];
+            },
+            quote! { trimmed },
+            quote! { == },
+        ),
+        None => (quote! {}, quote! { bytes }, quote! { >= }),
+    };
+
+    let match_arms_from = variant_values.iter().flat_map(|variant| {
+        let input = &input;
+        let len_cmp = &len_cmp;
+        let variant_ident = variant.ident;
+        let case_insensitive = variant.case_insensitive;
+        let num_arm = variant.num.map(|num| {
+            quote! {
+                if num == Some(#num) {
+                    Some(Self::#variant_ident)
+                } else
+            }
+        });
+        // A num variant matches by value, its aliases still match as bytes
+        let byte_values = match variant.num {
+            Some(_) => None,
+            None => Some(variant.value.clone()),
+        };
+        num_arm.into_iter().chain(
+            byte_values
+                .into_iter()
+                .chain(variant.aliases.iter().map(|alias| alias.value()))
+                .map(move |byte_value| {
+                let byte_len = byte_value.len();
+                let matches = if case_insensitive {
+                    quote! { #input[..#byte_len].eq_ignore_ascii_case(&[#(#byte_value),*]) }
+                } else {
+                    quote! { &#input[..#byte_len] == &[#(#byte_value),*] }
+                };
+
+                quote! {
+                    if #input.len() #len_cmp #byte_len && #matches {
+                        Some(Self::#variant_ident)
+                    } else
+                }
+            }),
+        )
     });
 
-    let match_arms_to = variants.iter().map(|variant| {
-        let variant_ident = &variant.ident;
-        let byte_value = get_variant_value(&variant.attrs).unwrap_or_else(|| {
-            let variant_str = variant_ident.to_string().to_uppercase();
-            vec![variant_str.chars().next().unwrap() as u8]
+    // Numeric codes compare the parsed field, so any zero padding matches
+    let has_num = variant_values.iter().any(|variant| variant.num.is_some());
+    let parse_num = if has_num {
+        quote! {
+            let num = ::core::str::from_utf8(bytes)
+                .ok()
+                .and_then(|s| s.trim().parse::<u64>().ok());
+        }
+    } else {
+        quote! {}
+    };
+    let write_field = if has_num {
+        let num_arms = variant_values.iter().filter_map(|variant| {
+            let variant_ident = variant.ident;
+            variant.num.map(|num| quote! { Self::#variant_ident => Some(#num), })
         });
+        quote! {
+            let num: Option<u64> = match self {
+                #(#num_arms)*
+                _ => None,
+            };
+            let s = match num {
+                Some(num) => format!("{:0width$}", num, width = field.len()),
+                None => String::new(),
+            };
+            let b = if num.is_some() { s.as_bytes() } else { self.as_bytes() };
+            let len = b.len().min(field.len());
+            field[..len].copy_from_slice(&b[..len]);
+        }
+    } else {
+        quote! {
+            let b = self.as_bytes();
+            let len = b.len().min(field.len());
+            field[..len].copy_from_slice(&b[..len]);
+        }
+    };
 
+    let match_arms_to = variant_values.iter().map(|variant| {
+        let variant_ident = variant.ident;
+        let byte_value = &variant.value;
         quote! {
             Self::#variant_ident => &[#(#byte_value),*],
         }
     });
 
+    let variant_idents = variant_values.iter().map(|variant| variant.ident);
+    let variant_count = variant_values.len();
+    let declared_max_len = variant_values
+        .iter()
+        .map(|variant| match variant.num {
+            Some(num) => num.to_string().len(),
+            None => variant.value.len(),
+        })
+        .max()
+        .unwrap_or(0);
+    // An array `other` variant writes its whole array, a boxed one is not bounded
+    let max_len = match other_variant {
+        Some((_, syn::Type::Array(array))) => {
+            let len = &array.len;
+            quote! {
+                if #len > #declared_max_len { #len } else { #declared_max_len }
+            }
+        }
+        _ => quote! { #declared_max_len },
+    };
+    let byte_values = variant_values.iter().map(|variant| {
+        let byte_value = &variant.value;
+        quote! { &[#(#byte_value),*] }
+    });
+
+    let display_arms = variant_values.iter().map(|variant| {
+        let variant_ident = variant.ident;
+        let label = &variant.label;
+        quote! {
+            Self::#variant_ident => f.write_str(#label),
+        }
+    });
+
+    let from_str_arms = variant_values.iter().map(|variant| {
+        let variant_ident = variant.ident;
+        let label = &variant.label;
+        let matches = if variant.case_insensitive {
+            quote! { s.eq_ignore_ascii_case(#label) }
+        } else {
+            quote! { s == #label }
+        };
+        quote! {
+            if #matches {
+                return Ok(Self::#variant_ident);
+            }
+        }
+    });
+
+    // Unknown values are kept by the other variant instead of yielding None
+    let (fallback, other_arm_to, as_bytes_ty) = match other_variant {
+        Some((other_ident, syn::Type::Array(array))) => {
+            let len = &array.len;
+            (
+                quote! { bytes.get(..#len).and_then(|b| b.try_into().ok()).map(Self::#other_ident) },
+                quote! { Self::#other_ident(b) => &b[..], },
+                quote! { &[u8] },
+            )
+        }
+        Some((other_ident, _)) => (
+            quote! { Some(Self::#other_ident(bytes.into())) },
+            quote! { Self::#other_ident(b) => b.as_ref(), },
+            quote! { &[u8] },
+        ),
+        None => (quote! { None }, quote! {}, quote! { &'static [u8] }),
+    };
+
+    // The other variant displays its raw bytes and parses back from them
+    let (other_arm_display, other_arm_from_str) = match other_variant {
+        Some((other_ident, syn::Type::Array(_))) => (
+            quote! { Self::#other_ident(b) => f.write_str(&String::from_utf8_lossy(b)), },
+            quote! {
+                s.as_bytes()
+                    .try_into()
+                    .map(Self::#other_ident)
+                    .map_err(|_| binary_mirror::ParseEnumError::new(stringify!(#name), s))
+            },
+        ),
+        Some((other_ident, _)) => (
+            quote! { Self::#other_ident(b) => f.write_str(&String::from_utf8_lossy(b.as_ref())), },
+            quote! { Ok(Self::#other_ident(s.as_bytes().into())) },
+        ),
+        None => (
+            quote! {},
+            quote! { Err(binary_mirror::ParseEnumError::new(stringify!(#name), s)) },
+        ),
+    };
+
+    // With only single-byte values, index a 256-entry table instead of testing each value
+    let single_byte = !has_num
+        && variant_values.len() < 256
+        && variant_values.iter().all(|variant| {
+            variant.value.len() == 1 && variant.aliases.iter().all(|alias| alias.value().len() == 1)
+        });
+    let from_bytes_body = if single_byte {
+        // 0 is no match, otherwise the position in variant_values plus one
+        let mut lookup = [0u8; 256];
+        for (i, variant) in variant_values.iter().enumerate() {
+            let values = std::iter::once(variant.value[0])
+                .chain(variant.aliases.iter().map(|alias| alias.value()[0]));
+            for b in values {
+                let mut keys = vec![b];
+                if variant.case_insensitive {
+                    keys.push(b.to_ascii_lowercase());
+                    keys.push(b.to_ascii_uppercase());
+                }
+                for key in keys {
+                    // The first declared variant wins, as in the if-else chain
+                    if lookup[key as usize] == 0 {
+                        lookup[key as usize] = i as u8 + 1;
+                    }
+                }
+            }
+        }
+        let lookup_arms = variant_values.iter().enumerate().map(|(i, variant)| {
+            let variant_ident = variant.ident;
+            let index = i as u8 + 1;
+            quote! { #index => Some(Self::#variant_ident), }
+        });
+        let len_check = match enum_attrs.trim {
+            Some(_) => quote! { if #input.len() == 1 },
+            None => quote! {},
+        };
+        quote! {
+            static LOOKUP: [u8; 256] = [#(#lookup),*];
+            #trim_input
+            match #input.first() {
+                Some(&b) #len_check => match LOOKUP[b as usize] {
+                    #(#lookup_arms)*
+                    _ => #fallback,
+                },
+                _ => #fallback,
+            }
+        }
+    } else {
+        quote! {
+            #trim_input
+            #parse_num
+            #(#match_arms_from)* {
+                #fallback
+            }
+        }
+    };
+
+    let display_impls = enum_attrs.display.then(|| {
+        quote! {
+            impl ::core::fmt::Display for #name {
+                fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                    match self {
+                        #(#display_arms)*
+                        #other_arm_display
+                    }
+                }
+            }
+
+            impl ::core::str::FromStr for #name {
+                type Err = binary_mirror::ParseEnumError;
+
+                fn from_str(s: &str) -> Result<Self, Self::Err> {
+                    #(#from_str_arms)*
+                    #other_arm_from_str
+                }
+            }
+        }
+    });
+
     let gen = quote! {
         impl #name {
+            /// Every unit variant in declaration order, the `other` variant excluded
+            pub const VARIANTS: &'static [Self] = &[#(Self::#variant_idents),*];
+
+            /// Length in bytes of the longest value `write_field` writes, checked at compile
+            /// time against the fields the enum is used in
+            pub const MAX_LEN: usize = #max_len;
+
+            pub fn variant_count() -> usize {
+                #variant_count
+            }
+
+            /// The canonical byte value of each of `VARIANTS`
+            pub fn byte_values() -> &'static [&'static [u8]] {
+                &[#(#byte_values),*]
+            }
+
             pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
-                #(#match_arms_from)* {
-                    None
-                }
+                #from_bytes_body
             }
 
-            pub fn as_bytes(&self) -> &'static [u8] {
+            pub fn as_bytes(&self) -> #as_bytes_ty {
                 match self {
                     #(#match_arms_to)*
+                    #other_arm_to
+                }
+            }
+
+            /// Write the value into a fixed-width field, num variants are
+            /// zero-padded to its width and other values fill it from the left
+            pub fn write_field(&self, field: &mut [u8]) {
+                #write_field
+            }
+
+            /// The value as a `N`-byte field, unused bytes set to `pad`
+            pub fn to_field_bytes<const N: usize>(&self, pad: u8) -> [u8; N] {
+                let mut bytes = [pad; N];
+                self.write_field(&mut bytes);
+                bytes
+            }
+        }
+
+        #display_impls
+    };
+
+    Ok(with_alloc_prelude(gen))
+}
+
+/// `tag_offset` and `tag` from `#[bmsg(...)]`
+fn get_message_attrs(attrs: &[syn::Attribute]) -> syn::Result<(Option<usize>, Option<Vec<u8>>)> {
+    let mut tag_offset = None;
+    let mut tag = None;
+    for attr in attrs {
+        if attr.path().is_ident("bmsg") {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("tag_offset") {
+                    let lit = meta.value()?.parse::<syn::LitInt>()?;
+                    tag_offset = Some(lit.base10_parse()?);
+                } else if meta.path.is_ident("tag") {
+                    let lit = meta.value()?.parse::<syn::LitByteStr>()?;
+                    if lit.value().is_empty() {
+                        return Err(syn::Error::new(lit.span(), "bmsg tag must not be empty"));
+                    }
+                    tag = Some(lit.value());
+                } else {
+                    return Err(meta.error(format!(
+                        "unknown bmsg attribute `{}`",
+                        meta.path.to_token_stream()
+                    )));
                 }
+                Ok(())
+            })?;
+        }
+    }
+    Ok((tag_offset, tag))
+}
+
+fn impl_binary_message(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let variants = match &input.data {
+        Data::Enum(data) => &data.variants,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "BinaryMessage can only be derived for enums",
+            ))
+        }
+    };
+    let (default_offset, enum_tag) = get_message_attrs(&input.attrs)?;
+    if enum_tag.is_some() {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "bmsg tag belongs on the variants, the enum only takes tag_offset",
+        ));
+    }
+    let default_offset = default_offset.unwrap_or(0);
+
+    let mut decode_arms = Vec::new();
+    let mut to_bytes_arms = Vec::new();
+    for variant in variants {
+        let variant_ident = &variant.ident;
+        let ty = match &variant.fields {
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 => &fields.unnamed[0].ty,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    variant,
+                    "BinaryMessage variants must wrap exactly one BinaryMirror struct",
+                ))
+            }
+        };
+        let (tag_offset, tag) = get_message_attrs(&variant.attrs)?;
+        let tag_offset = tag_offset.unwrap_or(default_offset);
+        let tag = tag.ok_or_else(|| {
+            syn::Error::new_spanned(variant, "BinaryMessage variants need #[bmsg(tag = b\"...\")]")
+        })?;
+        let tag_end = tag_offset + tag.len();
+        let tag = syn::LitByteStr::new(&tag, proc_macro2::Span::call_site());
+        // Variants holding `&'a T` borrow the input, owned variants copy it
+        let (inner, decode) = match ty {
+            syn::Type::Reference(reference) => {
+                let inner = &reference.elem;
+                (inner.as_ref(), quote! { <#inner as binary_mirror::FromBytes>::from_bytes(bytes)? })
             }
+            _ => (ty, quote! { <#ty as binary_mirror::FromBytes>::from_bytes_owned(bytes)? }),
+        };
+        decode_arms.push(quote! {
+            if bytes.get(#tag_offset..#tag_end) == Some(&#tag[..]) {
+                return Ok(Self::#variant_ident(#decode));
+            }
+        });
+        to_bytes_arms.push(quote! {
+            Self::#variant_ident(message) => <#inner as binary_mirror::ToBytes>::to_bytes(message),
+        });
+    }
+    let unknown_offset = default_offset;
+    // Borrowing variants tie the input to the enum's lifetime
+    let bytes_ty = match input.generics.lifetimes().next() {
+        Some(lifetime) => {
+            let lifetime = &lifetime.lifetime;
+            quote! { &#lifetime [u8] }
         }
+        None => quote! { &[u8] },
     };
 
-    gen.into()
+    Ok(quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// Decode a message, picking the variant by its tag bytes
+            /// Returns Err if no tag matches or the length doesn't match that variant
+            pub fn decode(bytes: #bytes_ty) -> Result<Self, binary_mirror::DecodeError> {
+                #(#decode_arms)*
+                Err(binary_mirror::DecodeError::unknown_tag(#unknown_offset, bytes))
+            }
+        }
+
+        impl #impl_generics binary_mirror::ToBytes for #name #ty_generics #where_clause {
+            fn to_bytes(&self) -> &[u8] {
+                match self {
+                    #(#to_bytes_arms)*
+                }
+            }
+        }
+    })
 }
 
 #[cfg(test)]
@@ -0,0 +1,104 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use binary_mirror::ToBytes;
+use binary_mirror_derive::{BinaryEnum, BinaryMirror};
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// The system allocator, counting every allocation so a test can assert there were none
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::SeqCst);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::SeqCst);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+#[derive(Debug, PartialEq, BinaryEnum, Serialize, Deserialize)]
+enum Side {
+    #[bv(value = b"B")]
+    Buy,
+    #[bv(value = b"S")]
+    Sell,
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct Order {
+    #[bm(type = "str")]
+    account: [u8; 10],
+    #[bm(type = "i32")]
+    qty: [u8; 6],
+    #[bm(type = "f64", precision = 2)]
+    price: [u8; 10],
+    #[bm(type = "decimal", precision = 2, fill = b'*', sign = "trailing")]
+    amount: [u8; 10],
+    #[bm(type = "i64", format = "{:08}", numeric_locale(thousands = ',', group))]
+    volume: [u8; 10],
+    #[bm(type = "enum", enum_type = "Side")]
+    side: [u8; 1],
+    #[bm(type = "date", format = "%Y%m%d")]
+    trade_date: [u8; 8],
+    #[bm(type = "time", format = "%H%M%S")]
+    trade_time: [u8; 6],
+    #[bm(type = "datetime", format = "%Y%m%d%H%M%S")]
+    created: [u8; 14],
+    #[bm(type = "checksum", algo = "crc16", format = "{:04X}")]
+    crc: [u8; 4],
+}
+
+#[test]
+fn test_raw_builder_does_not_allocate() {
+    let date = NaiveDate::from_ymd_opt(2024, 1, 2).unwrap();
+    let time = NaiveTime::from_hms_opt(9, 30, 15).unwrap();
+    let created = NaiveDateTime::new(date, time);
+    let amount = Decimal::new(-123456, 2);
+
+    let before = ALLOCATIONS.load(Ordering::SeqCst);
+    let raw = Order::builder()
+        .with_account("ACC1")
+        .with_qty(-42)
+        .with_price(2.675)
+        .with_amount(amount)
+        .with_volume(1234567)
+        .with_side(Side::Sell)
+        .with_trade_date(date)
+        .with_trade_time(time)
+        .with_created(created)
+        .build();
+    let allocations = ALLOCATIONS.load(Ordering::SeqCst) - before;
+
+    assert_eq!(allocations, 0);
+    assert_eq!(
+        &raw.to_bytes()[..75],
+        b"ACC1      -42   0000002.68**1234.56-1,234,567 S2024010209301520240102093015"
+    );
+    let native = OrderNative::default()
+        .with_account("ACC1")
+        .with_qty(-42)
+        .with_price(2.675)
+        .with_amount(amount)
+        .with_volume(1234567)
+        .with_side(Side::Sell)
+        .with_trade_date(date)
+        .with_trade_time(time)
+        .with_created(created);
+    assert_eq!(raw.to_bytes(), native.to_raw().to_bytes());
+}
@@ -0,0 +1,164 @@
+#![cfg(feature = "arbitrary")]
+
+use binary_mirror::arbitrary::{Arbitrary, Sample, Unstructured};
+use binary_mirror::{FromNative, ToBytes, ToNative};
+use binary_mirror_derive::{BinaryEnum, BinaryMirror};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, PartialEq, BinaryEnum, Serialize, Deserialize)]
+enum Side {
+    #[bv(value = b"B")]
+    Buy,
+    #[bv(value = b"S")]
+    Sell,
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct Header {
+    #[bm(type = "str")]
+    kind: [u8; 2],
+    #[bm(type = "u32")]
+    seq: [u8; 6],
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct Order {
+    #[bm(flatten = "Header")]
+    header: [u8; 8],
+    #[bm(type = "str")]
+    symbol: [u8; 6],
+    #[bm(type = "i32")]
+    qty: [u8; 5],
+    #[bm(type = "f64")]
+    price: [u8; 8],
+    #[bm(type = "decimal", format = "{:07.3}")]
+    fee: [u8; 7],
+    #[bm(type = "enum", enum_type = "Side")]
+    side: [u8; 1],
+    #[bm(type = "date", format = "%Y%m%d")]
+    date: [u8; 8],
+    #[bm(type = "time", format = "%H%M%S")]
+    time: [u8; 6],
+    #[bm(type = "bytes")]
+    flags: [u8; 2],
+    #[bm(type = "checksum", algo = "lrc")]
+    check: [u8; 1],
+}
+
+/// Deterministic pseudo-random bytes standing in for a fuzzer's input
+fn noise(seed: u64, len: usize) -> Vec<u8> {
+    let mut state = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+    (0..len)
+        .map(|_| {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (state >> 33) as u8
+        })
+        .collect()
+}
+
+/// What a fuzz target does with its input
+fn from_input<'a, T: Arbitrary<'a>>(data: &'a [u8]) -> T {
+    T::arbitrary(&mut Unstructured::new(data)).unwrap()
+}
+
+#[test]
+fn test_arbitrary_round_trip() {
+    for seed in 0..500 {
+        let data = noise(seed, 128);
+        let native = from_input::<OrderNative>(&data);
+        let raw = Order::from_native(&native);
+        assert_eq!(raw.to_native(), native, "seed {}", seed);
+        assert_eq!(raw.compute_check(), raw.check, "seed {}", seed);
+
+        let raw = from_input::<Order>(&data);
+        assert_eq!(Order::from_native(&raw.to_native()).to_bytes(), raw.to_bytes());
+    }
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct Tick {
+    #[bm(type = "date", format = "%Y%m%d", datetime_with = "time", subsec_with = "msec", alias = "ts")]
+    date: [u8; 8],
+    #[bm(type = "time", format = "%H%M%S")]
+    time: [u8; 6],
+    msec: [u8; 3],
+}
+
+#[test]
+fn test_arbitrary_subsec_datetime() {
+    for seed in 0..200 {
+        let native = from_input::<TickNative>(&noise(seed, 32));
+        assert_eq!(Tick::from_native(&native).to_native(), native, "seed {}", seed);
+    }
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct Ledger {
+    #[bm(type = "i64", sign = "paren", numeric_locale(thousands = ',', group))]
+    qty: [u8; 9],
+    #[bm(type = "decimal", precision = 2, numeric_locale(decimal = ',', thousands = '.', group))]
+    amount: [u8; 13],
+    #[bm(type = "i32", skip_native = true, carry_raw = true)]
+    routing: [u8; 3],
+}
+
+#[test]
+fn test_arbitrary_signed_grouped_numbers() {
+    for seed in 0..200 {
+        let native = from_input::<LedgerNative>(&noise(seed, 32));
+        assert_eq!(Ledger::from_native(&native).to_native(), native, "seed {}", seed);
+    }
+}
+
+#[test]
+fn test_arbitrary_respects_width() {
+    for seed in 0..200 {
+        let native = from_input::<OrderNative>(&noise(seed, 128));
+        if let Some(qty) = native.qty {
+            assert!((-9999..=99999).contains(&qty));
+        }
+        if let Some(symbol) = &native.symbol {
+            assert!((1..=6).contains(&symbol.len()));
+            assert!(symbol.bytes().all(|b| b.is_ascii_alphanumeric()));
+        }
+        if let Some(fee) = native.fee {
+            assert_eq!(fee.scale(), 3);
+        }
+    }
+}
+
+#[test]
+fn test_arbitrary_exhausted_input() {
+    // Fuzzers start from an empty input, every optional field then comes out empty
+    let native = from_input::<OrderNative>(&[]);
+    assert_eq!(Order::from_native(&native).to_native(), native);
+    assert_eq!(native.side, None);
+    assert_eq!(Side::VARIANTS.len(), 2);
+}
+
+#[test]
+fn test_sample() {
+    let sample = OrderNative::sample();
+    assert_eq!(sample, OrderNative::sample());
+    assert_ne!(sample, OrderNative::sample_seeded(1));
+
+    let raw = Order::sample();
+    assert_eq!(Order::from_native(&raw.to_native()).to_bytes(), raw.to_bytes());
+}
+
+#[cfg(feature = "fake")]
+#[test]
+fn test_fake() {
+    use binary_mirror::rand::rngs::mock::StepRng;
+
+    let mut rng = StepRng::new(0x0123_4567_89ab_cdef, 0x9e37_79b9_7f4a_7c15);
+    let orders = (0..20).map(|_| OrderNative::fake(&mut rng)).collect::<Vec<_>>();
+    assert!(orders.windows(2).any(|pair| pair[0] != pair[1]));
+    for native in &orders {
+        assert_eq!(&Order::from_native(native).to_native(), native);
+    }
+}
@@ -0,0 +1,128 @@
+#![cfg(feature = "arrow")]
+
+use binary_mirror::arrow::arrow_array::cast::AsArray;
+use binary_mirror::arrow::arrow_array::Array;
+use binary_mirror::arrow::arrow_array::types::{
+    Date32Type, Decimal128Type, Int32Type, Time64MicrosecondType, TimestampMicrosecondType,
+};
+use binary_mirror::arrow::arrow_schema::{DataType, TimeUnit};
+use binary_mirror::arrow::ToArrow;
+use binary_mirror::FromBytes;
+use binary_mirror_derive::{BinaryEnum, BinaryMirror};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, PartialEq, BinaryEnum, Serialize, Deserialize)]
+enum OrderSide {
+    #[bv(value = b"B")]
+    Buy,
+    #[bv(value = b"S")]
+    Sell,
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct Tick {
+    #[bm(type = "str")]
+    symbol: [u8; 6],
+    #[bm(type = "i32")]
+    qty: [u8; 4],
+    #[bm(type = "decimal")]
+    price: [u8; 8],
+    #[bm(type = "datetime", format = "%Y%m%d%H%M%S")]
+    ts: [u8; 14],
+    #[bm(type = "date", format = "%Y%m%d")]
+    trade_date: [u8; 8],
+    #[bm(type = "time", format = "%H%M%S")]
+    trade_time: [u8; 6],
+    #[bm(type = "enum", enum_type = "OrderSide")]
+    side: [u8; 1],
+    #[bm(type = "bytes")]
+    flags: [u8; 2],
+}
+
+#[test]
+fn test_arrow_schema() {
+    let schema = Tick::arrow_schema();
+    let types = schema
+        .fields()
+        .iter()
+        .map(|field| (field.name().as_str(), field.data_type().clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        types,
+        [
+            ("symbol", DataType::Utf8),
+            ("qty", DataType::Int32),
+            ("price", DataType::Decimal128(38, 10)),
+            ("ts", DataType::Timestamp(TimeUnit::Microsecond, None)),
+            ("trade_date", DataType::Date32),
+            ("trade_time", DataType::Time64(TimeUnit::Microsecond)),
+            ("side", DataType::Utf8),
+            ("flags", DataType::FixedSizeBinary(2)),
+        ]
+    );
+}
+
+#[test]
+fn test_to_record_batch() {
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(b"2330  10  585.5   2024010209000020240102090000BXY");
+    bytes.extend_from_slice(b"2317  abc 12.25   2024010209000120240102      SZZ");
+    let records = Tick::from_bytes_many(&bytes).unwrap();
+    let batch = Tick::to_record_batch(records).unwrap();
+    assert_eq!(batch.num_rows(), 2);
+
+    let symbol = batch.column(0).as_string::<i32>();
+    assert_eq!(symbol.value(1), "2317");
+    let qty = batch.column(1).as_primitive::<Int32Type>();
+    assert_eq!(qty.value(0), 10);
+    assert!(qty.is_null(1));
+    let price = batch.column(2).as_primitive::<Decimal128Type>();
+    assert_eq!(price.value(0), 5_855_000_000_000);
+    let ts = batch.column(3).as_primitive::<TimestampMicrosecondType>();
+    assert_eq!(ts.value(0), 1_704_186_000_000_000);
+    let trade_date = batch.column(4).as_primitive::<Date32Type>();
+    assert_eq!(trade_date.value(0), 19_724);
+    let trade_time = batch.column(5).as_primitive::<Time64MicrosecondType>();
+    assert_eq!(trade_time.value(0), 9 * 3_600_000_000);
+    assert!(trade_time.is_null(1));
+    let side = batch.column(6).as_string::<i32>();
+    assert_eq!(side.value(1), "Sell");
+    let flags = batch.column(7).as_fixed_size_binary();
+    assert_eq!(flags.value(0), b"XY");
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct TickHeader {
+    #[bm(type = "str")]
+    venue: [u8; 3],
+    #[bm(type = "i32")]
+    seq: [u8; 4],
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct TickWithHeader {
+    #[bm(flatten = "TickHeader")]
+    header: [u8; 7],
+    #[bm(type = "i32")]
+    qty: [u8; 4],
+}
+
+#[test]
+fn test_flatten_record_batch() {
+    let schema = TickWithHeader::arrow_schema();
+    let names = schema.fields().iter().map(|field| field.name().as_str()).collect::<Vec<_>>();
+    assert_eq!(names, ["venue", "seq", "qty"]);
+
+    let records = [
+        TickWithHeader::from_bytes(b"XNS1   10  ").unwrap(),
+        TickWithHeader::from_bytes(b"XNS2   20  ").unwrap(),
+    ];
+    let batch = TickWithHeader::to_record_batch(records).unwrap();
+    assert_eq!(batch.num_columns(), 3);
+    assert_eq!(batch.column(0).as_string::<i32>().value(1), "XNS");
+    assert_eq!(batch.column(1).as_primitive::<Int32Type>().value(1), 2);
+    assert_eq!(batch.column(2).as_primitive::<Int32Type>().value(0), 10);
+}
@@ -1,5 +1,7 @@
-use binary_mirror::{FromBytes, FromNative, NativeStructCode, ToBytes, ToNative};
-use binary_mirror_derive::{BinaryEnum, BinaryMirror};
+use binary_mirror::{
+    FieldKind, FromBytes, FromNative, NativeStructCode, Reflect, ToBytes, ToNative, Validate,
+};
+use binary_mirror_derive::{BinaryEnum, BinaryMessage, BinaryMirror};
 use chrono::{NaiveDate, NaiveDateTime, NaiveTime, Timelike};
 use compact_str::ToCompactString;
 use rust_decimal::prelude::*;
@@ -27,6 +29,86 @@ enum OrderType {
     Limit,
 }
 
+#[derive(Debug, PartialEq, BinaryEnum)]
+#[bv(case_insensitive = true)]
+enum TimeInForce {
+    #[bv(value = b"DAY")]
+    Day,
+    #[bv(value = b"IOC")]
+    Ioc,
+    #[bv(value = b"F", case_insensitive = false)]
+    Fok,
+}
+
+#[derive(Debug, PartialEq, BinaryEnum)]
+enum TradeSide {
+    #[bv(value = b"B", alias = b"1", alias = b"BUY", label = "buy")]
+    Buy,
+    #[bv(value = b"S", alias = b"2")]
+    Sell,
+}
+
+#[derive(Debug, Clone, PartialEq, BinaryEnum, Serialize, Deserialize)]
+enum Venue {
+    #[bv(value = b"NY")]
+    Nyse,
+    #[bv(value = b"NQ")]
+    Nasdaq,
+    #[bv(other)]
+    Other([u8; 2]),
+}
+
+#[derive(Debug, PartialEq, BinaryEnum)]
+enum Condition {
+    #[bv(value = b"R")]
+    Regular,
+    #[bv(other)]
+    Other(Vec<u8>),
+}
+
+#[derive(Debug, PartialEq, BinaryEnum, Serialize, Deserialize)]
+#[bv(trim)]
+enum LimitKind {
+    #[bv(value = b"L")]
+    Limit,
+    #[bv(value = b"LOC")]
+    LimitOnClose,
+}
+
+#[derive(Debug, PartialEq, BinaryEnum)]
+#[bv(trim = b'0')]
+enum Session {
+    #[bv(value = b"1")]
+    Morning,
+    #[bv(value = b"2")]
+    Afternoon,
+}
+
+#[derive(Debug, PartialEq, BinaryEnum, Serialize, Deserialize)]
+enum Status {
+    #[bv(num = 0)]
+    Ok,
+    #[bv(num = 12)]
+    Rejected,
+    #[bv(value = b"X")]
+    Cancelled,
+}
+
+#[derive(Debug, PartialEq, BinaryEnum)]
+#[bv(case_insensitive = true)]
+enum ExecType {
+    #[bv(value = b"0")]
+    New,
+    #[bv(value = b"F", alias = b"2")]
+    Trade,
+    #[bv(value = b"4", case_insensitive = false)]
+    Canceled,
+    #[bv(value = b"8")]
+    Rejected,
+    #[bv(other)]
+    Other([u8; 1]),
+}
+
 #[repr(C)]
 #[derive(BinaryMirror)]
 struct TestStruct {
@@ -77,6 +159,7 @@ fn test_with_datetime() {
     );
 }
 
+#[repr(C)]
 #[derive(BinaryMirror)]
 struct WithDefaultByte {
     #[bm(type = "str", default_byte = b'0')]
@@ -118,6 +201,104 @@ fn test_default_bytes() {
     assert_eq!(&binary.zero, b"abc00"); // Padded with '0' chars
 }
 
+#[repr(C)]
+#[derive(BinaryMirror)]
+#[bm(default_byte = b'0')]
+struct WithStructDefaultByte {
+    #[bm(type = "i32", format = "{:05}")]
+    qty: [u8; 5],
+    #[bm(type = "f64")]
+    price: [u8; 6],
+    #[bm(type = "str", default_byte = b' ')]
+    name: [u8; 4],
+    #[bm(pad)]
+    _reserved: [u8; 2],
+}
+
+#[test]
+fn test_struct_default_byte() {
+    let raw = WithStructDefaultByte::from_native(&WithStructDefaultByteNative::default());
+    assert_eq!(raw.to_bytes(), b"00000000000    00");
+
+    let native = WithStructDefaultByteNative::default().with_qty(12).with_name("AB");
+    assert_eq!(WithStructDefaultByte::from_native(&native).to_bytes(), b"00012000000AB  00");
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+#[bm(computed(name = "notional", type = "Option<f64>"))]
+#[bm(computed(name = "large", type = "bool", with = "is_large"))]
+struct PricedTrade {
+    #[bm(type = "f64")]
+    price: [u8; 6],
+    #[bm(type = "i32")]
+    qty: [u8; 4],
+}
+
+fn notional(native: &PricedTradeNative) -> Option<f64> {
+    Some(native.price? * native.qty? as f64)
+}
+
+fn is_large(native: &PricedTradeNative) -> bool {
+    native.notional.is_some_and(|notional| notional >= 1000.0)
+}
+
+#[test]
+fn test_computed_fields() {
+    let raw = PricedTrade::from_bytes(b"12.50 100 ").unwrap();
+    let native = raw.to_native();
+    assert_eq!(native.notional, Some(1250.0));
+    assert!(native.large);
+    assert_eq!(raw.to_native_with_report().0, native);
+
+    let mut into = PricedTradeNative::default();
+    assert_eq!(into.notional, None);
+    raw.to_native_into(&mut into);
+    assert_eq!(into, native);
+
+    // from_native ignores them
+    let edited = PricedTradeNative { notional: Some(1.0), large: false, ..native };
+    assert_eq!(PricedTrade::from_native(&edited).to_bytes(), b"12.5  100 ");
+
+    let json = serde_json::to_string(&PricedTrade::from_bytes(b"1.0   2   ").unwrap().to_native()).unwrap();
+    assert_eq!(json, r#"{"price":1.0,"qty":2,"notional":2.0,"large":false}"#);
+    let parsed: PricedTradeNative = serde_json::from_str(r#"{"price":1.0,"qty":2}"#).unwrap();
+    assert_eq!(parsed.notional, None);
+    assert!(PricedTrade::native_struct_code().contains("pub notional: Option<f64>,"));
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+#[bm(view(name = "OrderKey", fields(company, exchange, ordqty)))]
+struct ViewedOrder {
+    #[bm(type = "str")]
+    company: [u8; 4],
+    #[bm(type = "str", alias = "exchange")]
+    exh: [u8; 4],
+    #[bm(type = "f64")]
+    price: [u8; 6],
+    #[bm(type = "i32")]
+    ordqty: [u8; 4],
+}
+
+#[test]
+fn test_partial_view() {
+    let raw = ViewedOrder::from_bytes(b"TSMCTWSE612.0 100 ").unwrap();
+    let key = raw.to_order_key();
+    assert_eq!(
+        key,
+        OrderKey {
+            company: Some("TSMC".to_string()),
+            exchange: Some("TWSE".to_string()),
+            ordqty: Some(100),
+        }
+    );
+    assert_eq!(
+        serde_json::to_string(&key).unwrap(),
+        r#"{"company":"TSMC","exchange":"TWSE","ordqty":100}"#
+    );
+}
+
 #[test]
 fn test_struct_derivation() {
     let test = TestStruct {
@@ -236,6 +417,322 @@ fn test_binary_enum() {
     assert_eq!(Direction::from_bytes(b"X"), None);
 }
 
+#[test]
+fn test_binary_enum_case_insensitive() {
+    assert_eq!(TimeInForce::from_bytes(b"day"), Some(TimeInForce::Day));
+    assert_eq!(TimeInForce::from_bytes(b"IoC"), Some(TimeInForce::Ioc));
+    assert_eq!(TimeInForce::from_bytes(b"F"), Some(TimeInForce::Fok));
+    // Per-variant setting overrides the enum default
+    assert_eq!(TimeInForce::from_bytes(b"f"), None);
+    // as_bytes always emits the declared value
+    assert_eq!(TimeInForce::Day.as_bytes(), b"DAY");
+    // Case-sensitive enums stay strict
+    assert_eq!(OrderSide::from_bytes(b"b"), None);
+}
+
+#[test]
+fn test_binary_enum_aliases() {
+    assert_eq!(TradeSide::from_bytes(b"B"), Some(TradeSide::Buy));
+    assert_eq!(TradeSide::from_bytes(b"1"), Some(TradeSide::Buy));
+    assert_eq!(TradeSide::from_bytes(b"BUY"), Some(TradeSide::Buy));
+    assert_eq!(TradeSide::from_bytes(b"2"), Some(TradeSide::Sell));
+    assert_eq!(TradeSide::from_bytes(b"3"), None);
+    // as_bytes emits the canonical value
+    assert_eq!(TradeSide::from_bytes(b"1").unwrap().as_bytes(), b"B");
+    assert_eq!(TradeSide::from_bytes(b"2").unwrap().as_bytes(), b"S");
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct WithVenue {
+    #[bm(type = "enum", enum_type = "Venue")]
+    venue: [u8; 2],
+}
+
+#[test]
+fn test_binary_enum_other() {
+    assert_eq!(Venue::from_bytes(b"NY"), Some(Venue::Nyse));
+    assert_eq!(Venue::from_bytes(b"ZZ"), Some(Venue::Other(*b"ZZ")));
+    assert_eq!(Venue::from_bytes(b"Z"), None);
+    assert_eq!(Venue::Other(*b"ZZ").as_bytes(), b"ZZ");
+
+    assert_eq!(Condition::from_bytes(b"R"), Some(Condition::Regular));
+    assert_eq!(
+        Condition::from_bytes(b"@4"),
+        Some(Condition::Other(b"@4".to_vec()))
+    );
+    assert_eq!(Condition::Other(b"@4".to_vec()).as_bytes(), b"@4");
+
+    // Unknown codes survive a native round-trip
+    let raw = WithVenue::from_bytes(b"XQ").unwrap();
+    assert_eq!(raw.venue(), Some(Venue::Other(*b"XQ")));
+    let binary = WithVenue::from_native(&raw.to_native());
+    assert_eq!(&binary.venue, b"XQ");
+}
+
+#[test]
+fn test_binary_enum_display_from_str() {
+    // Variant name by default, label when given
+    assert_eq!(OrderSide::Buy.to_string(), "Buy");
+    assert_eq!(TradeSide::Buy.to_string(), "buy");
+    assert_eq!(TradeSide::Sell.to_string(), "Sell");
+
+    assert_eq!("buy".parse::<TradeSide>().unwrap(), TradeSide::Buy);
+    assert_eq!("Sell".parse::<TradeSide>().unwrap(), TradeSide::Sell);
+    let err = "Buy".parse::<TradeSide>().unwrap_err();
+    assert_eq!(err.to_string(), "unknown TradeSide value \"Buy\"");
+
+    // The other variant shows and parses its raw bytes
+    assert_eq!(Venue::Other(*b"XQ").to_string(), "XQ");
+    assert_eq!("XQ".parse::<Venue>().unwrap(), Venue::Other(*b"XQ"));
+    assert!("XQZ".parse::<Venue>().is_err());
+    assert_eq!(
+        "@4".parse::<Condition>().unwrap(),
+        Condition::Other(b"@4".to_vec())
+    );
+}
+
+#[derive(Debug, PartialEq, BinaryEnum)]
+#[bv(display = false)]
+enum Liquidity {
+    #[bv(value = b"A")]
+    Added,
+    #[bv(value = b"R")]
+    Removed,
+}
+
+impl std::fmt::Display for Liquidity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Liquidity::Added => "maker",
+            Liquidity::Removed => "taker",
+        })
+    }
+}
+
+#[test]
+fn test_binary_enum_from_str_case() {
+    assert_eq!("day".parse::<TimeInForce>().unwrap(), TimeInForce::Day);
+    assert_eq!("IOC".parse::<TimeInForce>().unwrap(), TimeInForce::Ioc);
+    // Fok opts out of the enum-level setting
+    assert_eq!("Fok".parse::<TimeInForce>().unwrap(), TimeInForce::Fok);
+    assert!("fok".parse::<TimeInForce>().is_err());
+
+    assert_eq!(Liquidity::Added.to_string(), "maker");
+    assert_eq!(Liquidity::from_bytes(b"R"), Some(Liquidity::Removed));
+}
+
+#[test]
+fn test_binary_enum_variants() {
+    assert_eq!(OrderType::VARIANTS, &[OrderType::Market, OrderType::Limit]);
+    assert_eq!(OrderType::variant_count(), 2);
+    assert_eq!(OrderType::byte_values(), &[b"MKT" as &[u8], b"LMT"]);
+
+    // Aliases aren't listed and the other variant isn't a fixed code
+    assert_eq!(TradeSide::byte_values(), &[b"B" as &[u8], b"S"]);
+    assert_eq!(Venue::variant_count(), 2);
+    for (variant, bytes) in Venue::VARIANTS.iter().zip(Venue::byte_values()) {
+        assert_eq!(variant.as_bytes(), *bytes);
+    }
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct WithLimitKind {
+    #[bm(type = "enum", enum_type = "LimitKind")]
+    kind: [u8; 3],
+}
+
+#[test]
+fn test_binary_enum_trim() {
+    assert_eq!(LimitKind::from_bytes(b"L  "), Some(LimitKind::Limit));
+    assert_eq!(LimitKind::from_bytes(b"L"), Some(LimitKind::Limit));
+    assert_eq!(LimitKind::from_bytes(b"LOC"), Some(LimitKind::LimitOnClose));
+    // No prefix hits once trimmed
+    assert_eq!(LimitKind::from_bytes(b"LO "), None);
+    assert_eq!(LimitKind::from_bytes(b"L X"), None);
+
+    assert_eq!(Session::from_bytes(b"100"), Some(Session::Morning));
+    assert_eq!(Session::from_bytes(b"12"), None);
+
+    let raw = WithLimitKind::from_bytes(b"L  ").unwrap();
+    assert_eq!(raw.kind(), Some(LimitKind::Limit));
+    let binary = WithLimitKind::from_native(&raw.to_native());
+    assert_eq!(&binary.kind, b"L  ");
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct WithStatus {
+    #[bm(type = "enum", enum_type = "Status")]
+    status: [u8; 3],
+}
+
+#[test]
+fn test_binary_enum_num() {
+    assert_eq!(Status::from_bytes(b"012"), Some(Status::Rejected));
+    assert_eq!(Status::from_bytes(b"12"), Some(Status::Rejected));
+    assert_eq!(Status::from_bytes(b"000"), Some(Status::Ok));
+    assert_eq!(Status::from_bytes(b"X  "), Some(Status::Cancelled));
+    assert_eq!(Status::from_bytes(b"013"), None);
+    assert_eq!(Status::Rejected.as_bytes(), b"12");
+
+    let mut field = [b' '; 3];
+    Status::Rejected.write_field(&mut field);
+    assert_eq!(&field, b"012");
+    assert_eq!(&Status::Rejected.to_field_bytes::<4>(b' '), b"0012");
+
+    let raw = WithStatus::from_bytes(b"012").unwrap();
+    assert_eq!(raw.status(), Some(Status::Rejected));
+    let binary = WithStatus::from_native(&raw.to_native());
+    assert_eq!(&binary.status, b"012");
+    let native = WithStatusNative::default().with_status(Status::Cancelled);
+    let binary = WithStatus::from_native(&native);
+    assert_eq!(&binary.status, b"X  ");
+}
+
+#[test]
+fn test_binary_enum_single_byte_lookup() {
+    assert_eq!(ExecType::from_bytes(b"0"), Some(ExecType::New));
+    assert_eq!(ExecType::from_bytes(b"f"), Some(ExecType::Trade));
+    assert_eq!(ExecType::from_bytes(b"2"), Some(ExecType::Trade));
+    assert_eq!(ExecType::from_bytes(b"4"), Some(ExecType::Canceled));
+    // Same prefix semantics as the if-else chain
+    assert_eq!(ExecType::from_bytes(b"8  "), Some(ExecType::Rejected));
+    assert_eq!(ExecType::from_bytes(b"Z"), Some(ExecType::Other(*b"Z")));
+    assert_eq!(ExecType::from_bytes(b""), None);
+    for b in 0..=255u8 {
+        let expected = ExecType::VARIANTS
+            .iter()
+            .find(|variant| variant.as_bytes() == [b])
+            .map(|variant| variant.to_string());
+        if let Some(expected) = expected {
+            assert_eq!(ExecType::from_bytes(&[b]).unwrap().to_string(), expected);
+        }
+    }
+
+    assert_eq!(Session::from_bytes(b"200"), Some(Session::Afternoon));
+    assert_eq!(Session::from_bytes(b"21"), None);
+    assert_eq!(Session::from_bytes(b""), None);
+}
+
+#[test]
+fn test_binary_enum_to_field_bytes() {
+    assert_eq!(&OrderType::Limit.to_field_bytes::<5>(b' '), b"LMT  ");
+    assert_eq!(&OrderSide::Sell.to_field_bytes::<3>(b'0'), b"S00");
+    // Truncated when the field is narrower than the value
+    assert_eq!(&OrderType::Market.to_field_bytes::<2>(b' '), b"MK");
+    assert_eq!(&Venue::Other(*b"XQ").to_field_bytes::<3>(b' '), b"XQ ");
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+#[bm(log = "log")]
+struct WithLogCrate {
+    #[bm(type = "i32")]
+    qty: [u8; 4],
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+#[bm(log = "none")]
+struct WithoutLogging {
+    #[bm(type = "i32")]
+    qty: [u8; 4],
+}
+
+struct CaptureLogger;
+
+static CAPTURED: std::sync::Mutex<Vec<String>> = std::sync::Mutex::new(Vec::new());
+
+impl log::Log for CaptureLogger {
+    fn enabled(&self, _: &log::Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &log::Record) {
+        CAPTURED
+            .lock()
+            .unwrap()
+            .push(format!("{} {}", record.level(), record.args()));
+    }
+
+    fn flush(&self) {}
+}
+
+#[test]
+fn test_log_backend() {
+    log::set_logger(&CaptureLogger).unwrap();
+    log::set_max_level(log::LevelFilter::Warn);
+
+    let raw = WithLogCrate::from_bytes(b"12x ").unwrap();
+    assert_eq!(raw.qty_with_warn(), None);
+    let raw = WithoutLogging::from_bytes(b"12x ").unwrap();
+    assert_eq!(raw.qty_with_warn(), None);
+
+    let captured = CAPTURED.lock().unwrap();
+    assert_eq!(captured.len(), 1);
+    assert!(captured[0].starts_with("WARN Failed to parse qty in WithLogCrate"));
+}
+
+static FAILURES: std::sync::Mutex<Vec<(&str, &str, Vec<u8>)>> =
+    std::sync::Mutex::new(Vec::new());
+
+fn record_failure(struct_name: &'static str, field: &'static str, bytes: &[u8]) {
+    FAILURES
+        .lock()
+        .unwrap()
+        .push((struct_name, field, bytes.to_vec()));
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+#[bm(log = "none", on_error = "record_failure")]
+struct WithOnError {
+    #[bm(type = "i32")]
+    qty: [u8; 4],
+    #[bm(type = "f64")]
+    price: [u8; 6],
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+#[bm(log = "none")]
+struct WithGlobalHook {
+    #[bm(type = "i32")]
+    qty: [u8; 4],
+}
+
+#[test]
+fn test_parse_failure_hook() {
+    let raw = WithOnError::from_bytes(b"12x 1.5   ").unwrap();
+    assert_eq!(raw.qty_with_warn(), None);
+    let native = raw.to_native();
+    assert_eq!(native.price, Some(1.5));
+    let (_, errors) = raw.to_native_with_report();
+    assert_eq!(errors.len(), 1);
+
+    binary_mirror::set_parse_failure_hook(record_failure);
+    let raw = WithGlobalHook::from_bytes(b"x   ").unwrap();
+    assert_eq!(raw.qty_with_warn(), None);
+    binary_mirror::clear_parse_failure_hook();
+
+    let failures = FAILURES.lock().unwrap();
+    let failures: Vec<_> = failures
+        .iter()
+        .filter(|(name, _, _)| name.starts_with("With"))
+        .collect();
+    assert_eq!(
+        failures,
+        [
+            &("WithOnError", "qty", b"12x ".to_vec()),
+            &("WithOnError", "qty", b"12x ".to_vec()),
+            &("WithOnError", "qty", b"12x ".to_vec()),
+            &("WithGlobalHook", "qty", b"x   ".to_vec()),
+        ]
+    );
+}
+
 #[test]
 fn test_struct_from_bytes() {
     let bytes = b"Hello     123 no_type000000123.4500000000123.4CME       20240101123456B";
@@ -392,6 +889,190 @@ fn test_struct_to_bytes_owned() {
     );
 }
 
+#[test]
+fn test_struct_write_to() {
+    let bytes = b"Hello     123 no_type000000123.4500000000123.4CME       20240101123456B";
+    let test = TestStruct::from_bytes(bytes).unwrap();
+    let mut buf = [0u8; 80];
+    assert_eq!(test.write_to(&mut buf).unwrap(), bytes.len());
+    assert_eq!(&buf[..bytes.len()], bytes);
+    let err = test.write_to(&mut buf[..10]).unwrap_err();
+    assert_eq!((err.needed(), err.available()), (71, 10));
+    assert_eq!(err.to_string(), "buffer too small: needed 71 bytes but only 10 are available");
+
+    let mut out = Vec::new();
+    test.to_bytes_into(&mut out).unwrap();
+    test.to_bytes_into(&mut out).unwrap();
+    assert_eq!(out, [&bytes[..], &bytes[..]].concat());
+
+    let array: [u8; 71] = test.to_bytes_array();
+    assert_eq!(&array, bytes);
+    let records = [test.to_bytes_array(), array];
+    assert_eq!(TestStruct::from_bytes_many(&records.concat()).unwrap().len(), 2);
+
+    let mut buf = [0u8; 71];
+    assert_eq!(test.to_native().write_raw_to(&mut buf).unwrap(), 71);
+    assert_eq!(TestStruct::from_bytes(&buf).unwrap().name(), test.name());
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+#[bm(raw_derive(Hash, Ord, AsRef, Borrow))]
+struct RawKey {
+    #[bm(type = "str")]
+    symbol: [u8; 4],
+    #[bm(type = "i32")]
+    qty: [u8; 3],
+}
+
+#[test]
+fn test_raw_derive() {
+    use std::collections::{BTreeSet, HashMap, HashSet};
+
+    let records = b"MSFT100AAPL200MSFT100AAPL 50";
+    let keys = RawKey::from_bytes_many(records).unwrap();
+    assert!(keys[0] == keys[2]);
+    assert!(keys[1] < keys[0]);
+    assert_eq!(keys.iter().collect::<HashSet<_>>().len(), 3);
+    let sorted = keys.iter().collect::<BTreeSet<_>>();
+    assert_eq!(sorted.first().unwrap().to_bytes(), b"AAPL 50");
+
+    // Hashing the bytes agrees with Borrow<[u8]>, so maps look up by raw bytes
+    let mut qty = HashMap::new();
+    qty.insert(RawKey::from_bytes_owned(b"AAPL200").unwrap(), 200);
+    assert_eq!(qty.get(&b"AAPL200"[..]), Some(&200));
+
+    fn byte_len(bytes: impl AsRef<[u8]>) -> usize {
+        bytes.as_ref().len()
+    }
+    assert_eq!(byte_len(&keys[0]), 7);
+    let borrowed: &[u8] = std::borrow::Borrow::borrow(&keys[1]);
+    assert_eq!(borrowed, b"AAPL200");
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+#[bm(raw_derive(Copy, PartialEq))]
+struct RawCopyKey {
+    #[bm(type = "str")]
+    symbol: [u8; 4],
+    #[bm(type = "i32")]
+    qty: [u8; 3],
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+#[bm(raw_derive(Clone))]
+struct ClonedEnvelope<P> {
+    #[bm(type = "str")]
+    venue: [u8; 4],
+    body: P,
+}
+
+#[test]
+fn test_raw_derive_copy() {
+    let keys = RawCopyKey::from_bytes_many(b"MSFT100AAPL200").unwrap();
+    let mut copied = keys[0];
+    copied.qty = *b"150";
+    assert_eq!(keys[0].qty(), Some(100));
+    assert_eq!(copied.qty(), Some(150));
+    let copies: Vec<RawCopyKey> = keys.to_vec();
+    assert!(copies[1] == keys[1]);
+
+    let raw = ClonedEnvelope::<RawCopyKey>::from_bytes(b"XNASAAPL200").unwrap();
+    let mut cloned = raw.clone();
+    cloned.venue = *b"XLON";
+    assert_eq!(cloned.to_bytes(), b"XLONAAPL200");
+    assert_eq!(raw.to_bytes(), b"XNASAAPL200");
+}
+
+#[test]
+fn test_c_header() {
+    assert_eq!(
+        TestStruct::c_header(),
+        r#"#pragma pack(push, 1)
+
+enum OrderSide {
+    ORDER_SIDE_BUY = 'B',
+    ORDER_SIDE_SELL = 'S',
+};
+
+struct TestStruct {
+    char name[10]; /* str */
+    char value[4]; /* i32 */
+    char no_type[7];
+    char decimal[20]; /* decimal */
+    char f32[5]; /* f32 */
+    char exh[10]; /* str */
+    char date[8]; /* date %Y%m%d */
+    char time[6]; /* time %H%M%S */
+    char side[1]; /* enum OrderSide */
+};
+
+#pragma pack(pop)
+"#
+    );
+    let header = WithHeader::c_header();
+    assert!(header.contains("struct MsgHeader {\n    char msg_type[2]; /* str */\n    char seq[4]; /* u32 */\n};"));
+    assert!(header.contains("    struct MsgHeader header;\n"));
+    assert!(WithMemo::c_header().contains("    char memo[]; /* rest */\n"));
+    assert_eq!(
+        binary_mirror::c_enum_definition("LimitKind", &[("Good Till\"Cancel".to_string(), &b"GTC"[..])]),
+        "#define LIMIT_KIND_GOOD_TILL_CANCEL \"GTC\"\n"
+    );
+}
+
+#[test]
+fn test_python_struct_format() {
+    assert_eq!(TestStruct::python_struct_format(), "10s4s7s20s5s10s8s6s1s");
+    assert_eq!(WithHeader::python_struct_format(), "2s4s4s3s");
+    assert_eq!(WithMemo::python_struct_format(), "4s3s");
+    assert_eq!(WithPadding::python_struct_format(), "4s3x3s2x");
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, BinaryMirror)]
+#[bm(cast = "bytemuck")]
+struct PodQuote {
+    #[bm(type = "str")]
+    symbol: [u8; 4],
+    #[bm(type = "i32")]
+    qty: [u8; 3],
+}
+
+#[repr(C)]
+#[derive(
+    BinaryMirror,
+    zerocopy::FromBytes,
+    zerocopy::IntoBytes,
+    zerocopy::KnownLayout,
+    zerocopy::Immutable,
+)]
+#[bm(cast = "zerocopy")]
+struct ZerocopyQuote {
+    #[bm(type = "str")]
+    symbol: [u8; 4],
+    #[bm(type = "i32")]
+    qty: [u8; 3],
+}
+
+#[test]
+fn test_cast_backends() {
+    let pod: &PodQuote = bytemuck::from_bytes(b"AAPL100");
+    assert_eq!(pod.qty(), Some(100));
+    assert_eq!(bytemuck::bytes_of(&PodQuote::from_bytes_owned(b"MSFT 50").unwrap()), b"MSFT 50");
+    assert_eq!(PodQuote::from_bytes_many(b"AAPL100MSFT 50").unwrap()[1].symbol(), Some("MSFT".to_string()));
+    assert!(PodQuote::from_bytes(b"AAPL10").is_err());
+    let zeroed: PodQuote = bytemuck::Zeroable::zeroed();
+    assert_eq!(zeroed.to_bytes(), [0; 7]);
+
+    let quote = ZerocopyQuote::from_bytes(b"AAPL100").unwrap();
+    assert_eq!(zerocopy::IntoBytes::as_bytes(quote), b"AAPL100");
+    assert_eq!(ZerocopyQuote::from_bytes_owned(b"MSFT 50").unwrap().qty(), Some(50));
+    assert_eq!(ZerocopyQuote::from_bytes_many(b"AAPL100MSFT 50").unwrap().len(), 2);
+    assert!(ZerocopyQuote::from_bytes_many(b"AAPL100MSFT").is_err());
+}
+
 #[test]
 fn test_binary_enum_roundtrip() {
     // Test custom byte values
@@ -444,6 +1125,35 @@ fn test_ignore_warn() {
     assert_eq!(native.value, None);
 }
 
+#[repr(C)]
+#[derive(BinaryMirror)]
+#[bm(on_error = "record_failure")]
+struct IgnoreWarnOnError {
+    #[bm(type = "i32", ignore_warn = true)]
+    qty: [u8; 4],
+    #[bm(type = "str", ignore_warn = true)]
+    name: [u8; 4],
+}
+
+#[test]
+fn test_ignore_warn_still_calls_hook() {
+    let raw = IgnoreWarnOnError::from_bytes(b"12x \xffAB ").unwrap();
+    let native = raw.to_native();
+    assert_eq!(native.qty, None);
+    assert_eq!(native.name, None);
+    let mut into = IgnoreWarnOnErrorNative::default();
+    raw.to_native_into(&mut into);
+    assert_eq!(into.qty, None);
+
+    let failures = FAILURES.lock().unwrap();
+    let failures: Vec<_> = failures
+        .iter()
+        .filter(|(name, _, _)| *name == "IgnoreWarnOnError")
+        .map(|(_, field, _)| *field)
+        .collect();
+    assert_eq!(failures, ["qty", "name", "qty", "name"]);
+}
+
 #[test]
 fn test_native_builder() {
     let native = TestStructNative::default()
@@ -576,6 +1286,42 @@ fn test_bytes_serde() {
     assert_eq!(parsed.padded, [0xFF, 0xFE, b'0', b'0', b'0']);
 }
 
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct WithBytesRepr {
+    #[bm(type = "bytes", bytes_repr = "hex")]
+    key: [u8; 4],
+    #[bm(type = "bytes", bytes_repr = "base64")]
+    token: [u8; 5],
+}
+
+#[test]
+fn test_bytes_repr() {
+    let native = WithBytesRepr::from_bytes(b"\x0a\x1b\xc2\xffhello").unwrap().to_native();
+    let json = serde_json::to_string(&native).unwrap();
+    assert_eq!(json, r#"{"key":"0a1bc2ff","token":"aGVsbG8="}"#);
+
+    let parsed: WithBytesReprNative = serde_json::from_str(r#"{"key":"0A1BC2FF","token":"aGVsbG8="}"#).unwrap();
+    assert_eq!(parsed, native);
+
+    for json in [
+        r#"{"key":"0a1bc2","token":"aGVsbG8="}"#,
+        r#"{"key":"0a1bc2zz","token":"aGVsbG8="}"#,
+        r#"{"key":"0a1bc2ff","token":"aGVsbG8"}"#,
+        r#"{"key":"0a1bc2ff","token":"aGVs*G8="}"#,
+        r#"{"key":[10,27,194,255],"token":"aGVsbG8="}"#,
+    ] {
+        assert!(serde_json::from_str::<WithBytesReprNative>(json).is_err(), "{}", json);
+    }
+
+    assert!(WithBytesRepr::native_struct_code()
+        .contains("    #[serde(with = \"binary_mirror::bytes_repr::hex\")]\n    pub key: [u8; 4usize],"));
+    let schema: serde_json::Value = serde_json::from_str(&WithBytesRepr::native_json_schema()).unwrap();
+    assert_eq!(schema["properties"]["key"]["pattern"], "^[0-9a-fA-F]{8}$");
+    assert_eq!(schema["properties"]["token"]["maxLength"], 8);
+    assert!(WithBytesRepr::avro_schema().contains(r#"{"name":"key","type":"string"}"#));
+}
+
 #[test]
 fn test_field_specs() {
     let name_spec = TestStruct::name_spec();
@@ -611,369 +1357,2933 @@ fn test_field_specs() {
 }
 
 #[test]
-fn test_multi_byte_enum() {
-    // Test multi-byte values
-    assert_eq!(OrderType::from_bytes(b"MKT"), Some(OrderType::Market));
-    assert_eq!(OrderType::from_bytes(b"LMT"), Some(OrderType::Limit));
-    assert_eq!(OrderType::from_bytes(b"XXX"), None);
+fn test_all_field_specs() {
+    let specs = TestStruct::field_specs();
+    assert_eq!(
+        specs.iter().map(|(name, _)| *name).collect::<Vec<_>>(),
+        vec!["name", "value", "no_type", "decimal", "f32", "exh", "date", "time", "side"]
+    );
+    assert_eq!(specs[1].1, TestStruct::value_spec());
+    assert_eq!(specs[1].1.type_name, Some("i32"));
+    assert_eq!(specs[2].1.type_name, None);
+    assert_eq!(specs.last().unwrap().1.limit, TestStruct::SIZE);
+}
 
-    // Test roundtrip
-    let market = OrderType::Market;
-    assert_eq!(market.as_bytes(), b"MKT");
+#[test]
+fn test_field_bytes_by_name() {
+    let mut test = TestStruct::from_bytes(
+        b"Hello     123 no_type000000123.4500000000123.4CME       20240101123456B",
+    )
+    .unwrap()
+    .to_native()
+    .to_raw();
+    assert_eq!(test.field_bytes("name"), Some(&b"Hello     "[..]));
+    assert_eq!(test.field_bytes("exchange"), None);
+
+    test.set_field_bytes("value", b"456 ").unwrap();
+    assert_eq!(test.value(), Some(456));
+
+    let err = test.set_field_bytes("value", b"4567 ").unwrap_err();
     assert_eq!(
-        OrderType::from_bytes(market.as_bytes()),
-        Some(OrderType::Market)
+        err.kind(),
+        &binary_mirror::FieldErrorKind::SizeMismatch {
+            expected: 4,
+            actual: 5
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "field value expects 4 bytes but got 5 bytes, content: \"4567 \""
     );
+    let err = test.set_field_bytes("missing", b"").unwrap_err();
+    assert_eq!(
+        err.kind(),
+        &binary_mirror::FieldErrorKind::UnknownField {
+            name: "missing".to_string()
+        }
+    );
+    assert_eq!(err.to_string(), "unknown field missing");
+}
 
-    let limit = OrderType::Limit;
-    assert_eq!(limit.as_bytes(), b"LMT");
+#[test]
+fn test_setters() {
+    let mut test = TestStruct::from_bytes(
+        b"Hello     123 no_type000000123.4500000000123.4CME       20240101123456B",
+    )
+    .unwrap()
+    .to_native()
+    .to_raw();
+    test.set_name("World");
+    test.set_value(42);
+    test.set_side(OrderSide::Sell);
+    assert_eq!(&test.name, b"World     ");
+    assert_eq!(test.value(), Some(42));
+    assert_eq!(test.side(), Some(OrderSide::Sell));
+    assert_eq!(test.exchange(), Some("CME".to_string()));
+
+    let mut raw = WithChecksumNative::default()
+        .with_account("7992739871")
+        .with_amount(1234)
+        .to_raw();
+    raw.set_amount(99);
     assert_eq!(
-        OrderType::from_bytes(limit.as_bytes()),
-        Some(OrderType::Limit)
+        raw.to_bytes(),
+        WithChecksumNative::default()
+            .with_account("7992739871")
+            .with_amount(99)
+            .to_raw()
+            .to_bytes()
     );
+    assert!(raw.validate().is_ok());
+}
 
-    // Test in struct
-    #[repr(C)]
-    #[derive(BinaryMirror)]
-    struct Order {
-        #[bm(type = "enum", enum_type = "OrderType")]
-        order_type: [u8; 3],
+#[test]
+fn test_raw_builder() {
+    let raw = WithChecksum::builder()
+        .with_account("7992739871")
+        .with_amount(1234)
+        .build();
+    assert_eq!(
+        raw.to_bytes(),
+        WithChecksumNative::default()
+            .with_account("7992739871")
+            .with_amount(1234)
+            .to_raw()
+            .to_bytes()
+    );
+    assert!(raw.validate().is_ok());
+
+    let raw = WithSentinel::builder().with_account("ACC1").build();
+    assert_eq!(raw.to_bytes(), b"9999999900000000ACC1  ");
+
+    let raw = WithDefaults::builder().with_value(7).build();
+    assert_eq!(raw.name(), Some("UNKNOWN".to_string()));
+    assert_eq!(raw.value(), Some(7));
+    assert_eq!(raw.order_type(), Some(OrderType::Limit));
+    assert_eq!(raw.decimal(), Some(decimal_default()));
+}
+
+#[test]
+fn test_to_native_into() {
+    let first = TestStruct::from_bytes(
+        b"Hello     123 no_type000000123.4500000000123.4CME       20240101123456B",
+    )
+    .unwrap();
+    let second = TestStruct::from_bytes(
+        b"Hi        456 no_type000000001.0000000000001.5TWSE      20240102010203S",
+    )
+    .unwrap();
+    let mut native = first.to_native();
+    let name_ptr = native.name.as_ref().unwrap().as_ptr();
+    second.to_native_into(&mut native);
+    assert_eq!(native, second.to_native());
+    assert_eq!(native.name.as_ref().unwrap().as_ptr(), name_ptr);
+
+    let raw = WithSentinel::from_bytes(b"9999999900000000      ").unwrap();
+    let mut native = WithSentinelNative::default().with_account("ACC1");
+    raw.to_native_into(&mut native);
+    assert_eq!(native, raw.to_native());
+}
+
+#[test]
+fn test_numeric_parsing_from_bytes() {
+    let mut raw = TestStruct::builder().build();
+    raw.set_field_bytes("value", b" -12").unwrap();
+    assert_eq!(raw.value(), Some(-12));
+    raw.set_field_bytes("value", b"+7  ").unwrap();
+    assert_eq!(raw.value(), Some(7));
+    raw.set_field_bytes("value", b"1\xff2 ").unwrap();
+    assert_eq!(raw.value(), None);
+    raw.set_field_bytes("f32", b"-1.5 ").unwrap();
+    assert_eq!(raw.f32(), Some(-1.5));
+    raw.set_field_bytes("f32", b"1.2.3").unwrap();
+    assert_eq!(raw.f32(), None);
+    assert_eq!(binary_mirror::parse_number::<u16>(b"70000"), None);
+    assert_eq!(binary_mirror::parse_number::<i64>(b""), None);
+}
+
+#[test]
+fn test_trim_helpers_match_std() {
+    let cases: [&[u8]; 8] = [
+        b"",
+        b"        ",
+        b"                 ",
+        b"  abc  ",
+        b"abc                    ",
+        b"                   abc",
+        b"\t\r\n a b \n         ",
+        b"        \x00        ",
+    ];
+    for case in cases {
+        assert_eq!(binary_mirror::trim_ascii(case), case.trim_ascii());
+        assert_eq!(binary_mirror::trim_ascii_start(case), case.trim_ascii_start());
+        assert_eq!(binary_mirror::trim_ascii_end(case), case.trim_ascii_end());
+        assert_eq!(binary_mirror::is_blank(case), case.trim_ascii().is_empty());
     }
+}
 
-    let order = Order {
-        order_type: *b"MKT",
-    };
+#[test]
+fn test_from_bytes_many() {
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(b"Hello     123 no_type000000123.4500000000123.4CME       20240101123456B");
+    bytes.extend_from_slice(b"Hi        456 no_type000000001.0000000000001.5TWSE      20240102010203S");
+    let records = TestStruct::from_bytes_many(&bytes).unwrap();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0].name(), Some("Hello".to_string()));
+    assert_eq!(records[1].value(), Some(456));
+    assert_eq!(records[1].side(), Some(OrderSide::Sell));
+    assert!(TestStruct::from_bytes_many(&[]).unwrap().is_empty());
 
-    assert_eq!(order.order_type(), Some(OrderType::Market));
-    let native = order.to_native();
-    assert_eq!(native.order_type, Some(OrderType::Market));
+    bytes.extend_from_slice(b"Hey");
+    let err = TestStruct::from_bytes_many(&bytes).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "bytes size mismatch: expected {} bytes but got {} bytes, content: \"Hey\"",
+            TestStruct::SIZE * 2,
+            TestStruct::SIZE * 2 + 3
+        )
+    );
 }
 
-fn default_i32() -> i32 {
-    42
+#[test]
+fn test_iter_records() {
+    let bytes = b"2330    10S2317     5B23";
+    let mut records = WithSerdeRaw::iter_records(bytes);
+    assert_eq!(records.len(), 3);
+    assert_eq!(records.next().unwrap().unwrap().qty(), Some(10));
+    assert_eq!(records.next().unwrap().unwrap().qty(), Some(5));
+    assert_eq!(records.remaining(), b"23");
+    let err = records.next().unwrap().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "bytes size mismatch: expected 11 bytes but got 2 bytes, content: \"23\""
+    );
+    assert!(records.next().is_none());
+
+    let symbols = WithSerdeRaw::iter_records(&bytes[..22])
+        .map(|record| record.map(|record| record.symbol().unwrap()))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(symbols, ["2330", "2317"]);
 }
 
-fn default_str() -> String {
-    "UNKNOWN".to_string()
+#[test]
+fn test_mmap_records() {
+    let dir = std::env::temp_dir();
+    let path = dir.join(format!("binary_mirror_mmap_{}.bin", std::process::id()));
+    std::fs::write(&path, b"2330    10S2317     5B").unwrap();
+    let records = unsafe { binary_mirror::MmapRecords::<WithSerdeRaw>::open(&path) }.unwrap();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[1].symbol(), Some("2317".to_string()));
+    let qtys = records.iter().map(|record| record.qty().unwrap()).collect::<Vec<_>>();
+    assert_eq!(qtys, [10, 5]);
+    drop(records);
+
+    std::fs::write(&path, b"2330    10S23").unwrap();
+    let err = unsafe { binary_mirror::MmapRecords::<WithSerdeRaw>::open(&path) }.unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    std::fs::remove_file(&path).unwrap();
 }
 
-fn now() -> chrono::NaiveDateTime {
-    chrono::Local::now().naive_utc().with_nanosecond(0).unwrap()
+/// AsyncRead handing out at most 3 bytes per read, to split records across reads
+struct Trickle<'a>(&'a [u8]);
+
+impl tokio::io::AsyncRead for Trickle<'_> {
+    fn poll_read(
+        mut self: std::pin::Pin<&mut Self>,
+        _cx: &mut std::task::Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        let n = self.0.len().min(3).min(buf.remaining());
+        buf.put_slice(&self.0[..n]);
+        self.0 = &self.0[n..];
+        std::task::Poll::Ready(Ok(()))
+    }
 }
 
-fn today() -> chrono::NaiveDate {
-    chrono::Local::now().date_naive()
+#[tokio::test]
+async fn test_read_records() {
+    use binary_mirror::stream::{read_records, Stream};
+
+    let mut stream = read_records::<WithSerdeRaw, _>(Trickle(b"2330    10S2317     5B23"));
+    let mut items = Vec::new();
+    while let Some(item) = std::future::poll_fn(|cx| std::pin::Pin::new(&mut stream).poll_next(cx)).await {
+        items.push(item);
+    }
+    assert_eq!(items.len(), 3);
+    assert_eq!(items[0].as_ref().unwrap().qty(), Some(10));
+    assert_eq!(items[1].as_ref().unwrap().symbol(), Some("2317".to_string()));
+    let err = items[2].as_ref().unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+    assert_eq!(err.to_string(), "stream ended after 2 of 11 bytes, content: \"23\"");
 }
 
-fn now_time() -> chrono::NaiveTime {
-    chrono::Local::now().time().with_nanosecond(0).unwrap()
+#[test]
+fn test_decode_par() {
+    let mut bytes = Vec::new();
+    for _ in 0..100 {
+        bytes.extend_from_slice(b"Hello     123 no_type000000123.4500000000123.4CME       20240101123456B");
+        bytes.extend_from_slice(b"Hi        456 no_type000000001.0000000000001.5TWSE      20240102010203S");
+    }
+    let natives = binary_mirror::decode_par::<TestStruct>(&bytes).unwrap();
+    let expected = TestStruct::from_bytes_many(&bytes)
+        .unwrap()
+        .iter()
+        .map(|record| record.to_native())
+        .collect::<Vec<_>>();
+    assert_eq!(natives.len(), 200);
+    assert_eq!(natives, expected);
+    assert!(binary_mirror::decode_par::<TestStruct>(&bytes[1..]).is_err());
 }
 
-fn order_type_default() -> OrderType {
-    OrderType::Limit
+#[test]
+fn test_from_bytes_owned() {
+    let raw = {
+        let bytes =
+            b"Hello     123 no_type000000123.4500000000123.4CME       20240101123456B".to_vec();
+        TestStruct::from_bytes_owned(&bytes).unwrap()
+    };
+    assert_eq!(raw.name(), Some("Hello".to_string()));
+    assert_eq!(raw.side(), Some(OrderSide::Buy));
+    assert!(TestStruct::from_bytes_owned(b"too short").is_err());
 }
 
-fn decimal_default() -> Decimal {
-    Decimal::from_str("123.45").unwrap()
+#[test]
+fn test_std_conversions() {
+    let bytes: &[u8] = b"Hello     123 no_type000000123.4500000000123.4CME       20240101123456B";
+    let borrowed: &TestStruct = bytes.try_into().unwrap();
+    assert_eq!(borrowed.value(), Some(123));
+    let owned = TestStruct::try_from(bytes).unwrap();
+    assert_eq!(owned.name(), Some("Hello".to_string()));
+    assert!(TestStruct::try_from(&bytes[1..]).is_err());
+
+    let encoded: Vec<u8> = (&owned).into();
+    assert_eq!(encoded, bytes);
 }
 
 #[repr(C)]
 #[derive(BinaryMirror)]
-struct WithDefaults {
-    #[bm(type = "str", default_func = "default_str")]
-    name: [u8; 10],
-    #[bm(type = "i32", default_func = "default_i32")]
-    value: [u8; 4],
-    #[bm(type = "datetime", default_func = "now", format = "%Y%m%d%H%M%S")]
-    timestamp: [u8; 14],
-    #[bm(type = "date", default_func = "today", format = "%Y%m%d")]
-    today: [u8; 8],
-    #[bm(type = "time", default_func = "now_time", format = "%H%M%S")]
-    time: [u8; 6],
-    #[bm(
-        type = "date",
-        default_func = "now",
-        format = "%Y-%m-%d",
-        datetime_with = "tt",
-        alias = "concate_datetime"
-    )]
-    date: [u8; 10],
-    #[bm(type = "time", format = "%H:%M:%S")]
-    tt: [u8; 8],
+#[bm(serde_raw = true)]
+struct WithSerdeRaw {
+    #[bm(type = "str")]
+    symbol: [u8; 6],
+    #[bm(type = "i32")]
+    qty: [u8; 4],
+    #[bm(type = "enum", enum_type = "OrderSide")]
+    side: [u8; 1],
+}
+
+#[test]
+fn test_serde_raw_serialize() {
+    let raw = WithSerdeRaw::from_bytes(b"2330    10S").unwrap();
+    assert_eq!(
+        serde_json::to_string(raw).unwrap(),
+        r#"{"symbol":"2330","qty":10,"side":"Sell"}"#
+    );
+}
+
+#[test]
+fn test_serde_raw_deserialize() {
+    let raw: WithSerdeRaw =
+        serde_json::from_str(r#"{"symbol":"2330","qty":10,"side":"Sell"}"#).unwrap();
+    assert_eq!(raw.to_bytes(), b"2330  10  S");
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct WithNativeSerde {
+    #[bm(type = "str", native_serde(rename = "sym"))]
+    symbol: [u8; 6],
     #[bm(
-        type = "enum",
-        enum_type = "OrderType",
-        default_func = "order_type_default"
+        type = "i32",
+        native_serde(rename = "orderQty", skip_serializing_if = "Option::is_none")
     )]
-    order_type: [u8; 3],
-    #[bm(type = "decimal", default_func = "decimal_default")]
-    decimal: [u8; 10],
+    qty: [u8; 4],
 }
 
 #[test]
-fn test_defaults() {
-    let native = WithDefaultsNative::default();
-    println!("{:?}", native);
-    let binary = WithDefaults::from_native(&native);
-    println!("{:?}", binary);
-    assert_eq!(binary.name(), Some("UNKNOWN".to_string()));
-    assert_eq!(binary.value(), Some(42));
-    assert_eq!(binary.timestamp(), Some(now()));
-    assert_eq!(binary.today(), Some(today()));
-    assert_eq!(binary.time(), Some(now_time()));
-    assert_eq!(binary.concate_datetime(), Some(now()));
-    assert_eq!(binary.order_type(), Some(OrderType::Limit));
-    assert_eq!(binary.decimal(), Some(Decimal::from_str("123.45").unwrap()));
-    // Test overriding defaults
-    let native = WithDefaultsNative::default()
-        .with_name("TEST")
-        .with_value(100)
-        .with_decimal(Decimal::from_str("999.99").unwrap())
-        .with_order_type(OrderType::Market);
+fn test_native_serde_pass_through() {
+    let raw = WithNativeSerde::from_bytes(b"2330    10").unwrap();
+    assert_eq!(
+        serde_json::to_string(&raw.to_native()).unwrap(),
+        r#"{"sym":"2330","orderQty":10}"#
+    );
+    let raw = WithNativeSerde::from_bytes(b"2330  abcd").unwrap();
+    assert_eq!(
+        serde_json::to_string(&raw.to_native()).unwrap(),
+        r#"{"sym":"2330"}"#
+    );
+    assert_eq!(
+        WithNativeSerde::native_struct_code(),
+        r#"pub struct WithNativeSerdeNative {
+    #[serde(rename = "sym")]
+    pub symbol: Option<String>,
+    #[serde(rename = "orderQty", skip_serializing_if = "Option::is_none")]
+    pub qty: Option<i32>,
+}"#
+    );
+}
 
-    let binary = WithDefaults::from_native(&native);
-    assert_eq!(binary.name(), Some("TEST".to_string()));
-    assert_eq!(binary.value(), Some(100));
-    assert_eq!(binary.decimal(), Some(Decimal::from_str("999.99").unwrap()));
-    assert_eq!(binary.order_type(), Some(OrderType::Market));
+#[test]
+fn test_native_json_schema() {
+    let schema: serde_json::Value =
+        serde_json::from_str(&WithNativeSerde::native_json_schema()).unwrap();
+    assert_eq!(
+        schema,
+        serde_json::json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "title": "WithNativeSerdeNative",
+            "type": "object",
+            "properties": {
+                "sym": {"type": ["string", "null"]},
+                "orderQty": {"type": ["integer", "null"], "minimum": -999, "maximum": 9999},
+            },
+            "required": [],
+        })
+    );
+
+    let schema: serde_json::Value =
+        serde_json::from_str(&TestStruct::native_json_schema()).unwrap();
+    let properties = schema["properties"].as_object().unwrap();
+    assert_eq!(
+        properties.keys().collect::<Vec<_>>(),
+        ["datetime", "decimal", "exchange", "f32", "name", "side", "value"]
+    );
+    assert_eq!(properties["f32"]["type"], serde_json::json!(["number", "null"]));
+}
+
+#[test]
+fn test_native_json_schema_integer_range() {
+    let schema: serde_json::Value =
+        serde_json::from_str(&Settlement::native_json_schema()).unwrap();
+    // Parentheses take two bytes of the six
+    assert_eq!(schema["properties"]["qty"]["minimum"], -9999);
+    assert_eq!(schema["properties"]["qty"]["maximum"], 999999);
+
+    let schema: serde_json::Value = serde_json::from_str(&AvroCounters::native_json_schema()).unwrap();
+    // A 20-digit field holds every u64
+    assert_eq!(schema["properties"]["volume"]["minimum"], 0);
+    assert_eq!(schema["properties"]["volume"]["maximum"], u64::MAX);
+    assert_eq!(schema["properties"]["trades"]["maximum"], u32::MAX);
+}
+
+#[test]
+fn test_csv_row() {
+    let native = TestStruct::from_bytes(
+        b"Hello     123 no_type000000123.4500000000123.4CME       20240101123456B",
+    )
+    .unwrap()
+    .to_native();
+    assert_eq!(
+        TestStructNative::csv_header(),
+        "name,value,decimal,f32,exchange,datetime,side"
+    );
+    assert_eq!(
+        native.to_csv_row(),
+        "Hello,123,123.45,123.4,CME,2024-01-01 12:34:56,Buy"
+    );
+
+    let native = WithNativeSerdeNative::default().with_symbol("A,\"B\"");
+    assert_eq!(WithNativeSerdeNative::csv_header(), "sym,orderQty");
+    assert_eq!(native.to_csv_row(), "\"A,\"\"B\"\"\",");
+}
+
+#[test]
+fn test_sql_statements() {
+    assert_eq!(
+        TestStruct::sql_create_table("trades"),
+        "CREATE TABLE trades (
+    name VARCHAR(10),
+    value INTEGER,
+    decimal NUMERIC,
+    f32 REAL,
+    exchange VARCHAR(10),
+    datetime TIMESTAMP,
+    side TEXT
+)"
+    );
+    assert_eq!(
+        TestStruct::sql_insert("trades"),
+        "INSERT INTO trades (name, value, decimal, f32, exchange, datetime, side) \
+         VALUES ($1, $2, $3, $4, $5, $6, $7)"
+    );
+    let native = TestStruct::from_bytes(
+        b"Hello     abc no_type000000123.4500000000123.4CME       20240101123456B",
+    )
+    .unwrap()
+    .to_native();
+    use binary_mirror::SqlValue;
+    assert_eq!(
+        native.sql_params(),
+        [
+            SqlValue::Text("Hello".to_string()),
+            SqlValue::Null,
+            SqlValue::Text("123.45".to_string()),
+            SqlValue::Float(123.4f32 as f64),
+            SqlValue::Text("CME".to_string()),
+            SqlValue::Text("2024-01-01 12:34:56".to_string()),
+            SqlValue::Text("Buy".to_string()),
+        ]
+    );
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct AvroCounters {
+    #[bm(type = "u32")]
+    trades: [u8; 10],
+    #[bm(type = "u64")]
+    volume: [u8; 20],
+}
+
+#[test]
+fn test_avro_u64() {
+    let schema: serde_json::Value = serde_json::from_str(&AvroCounters::avro_schema()).unwrap();
+    assert_eq!(schema["fields"][0]["type"], serde_json::json!(["null", "long"]));
+    assert_eq!(schema["fields"][1]["type"], serde_json::json!(["null", "long"]));
+
+    let raw = AvroCounters::from_bytes(b"        12            12345678").unwrap();
+    let json = serde_json::to_value(raw.to_native()).unwrap();
+    assert_eq!(json["volume"], serde_json::json!(12345678));
+}
+
+#[test]
+fn test_avro_schema() {
+    let schema: serde_json::Value =
+        serde_json::from_str(&WithNativeSerde::avro_schema()).unwrap();
+    assert_eq!(
+        schema,
+        serde_json::json!({
+            "type": "record",
+            "name": "WithNativeSerdeNative",
+            "fields": [
+                {"name": "sym", "type": ["null", "string"], "default": null},
+                {"name": "orderQty", "type": ["null", "int"], "default": null},
+            ],
+        })
+    );
+    let schema: serde_json::Value = serde_json::from_str(&TestStruct::avro_schema()).unwrap();
+    assert_eq!(schema["fields"][3]["type"], serde_json::json!(["null", "float"]));
+    assert_eq!(schema["fields"][5]["type"], serde_json::json!(["null", "string"]));
+}
+
+#[test]
+fn test_proto_definition() {
+    assert_eq!(
+        TestStruct::proto_definition(),
+        r#"syntax = "proto3";
+
+message TestStructNative {
+  optional string name = 1;
+  optional int32 value = 2;
+  optional string decimal = 3;
+  optional float f32 = 4;
+  optional string exchange = 5;
+  optional string datetime = 6;
+  optional string side = 7;
+}
+"#
+    );
+}
+
+#[test]
+fn test_hexdump() {
+    let raw = WithSerdeRaw::from_bytes(b"2330    10S").unwrap();
+    assert_eq!(
+        raw.hexdump(),
+        "00000000  32 33 33 30 20 20                                |2330            |  symbol
+00000006  20 20 31 30                                      |  10            |  qty
+0000000a  53                                               |S               |  side
+"
+    );
+
+    let raw = WithDefaultsNative::default().to_raw();
+    let dump = raw.hexdump();
+    assert!(dump.contains("\n0000000e  "));
+    assert!(dump.ends_with("|  decimal\n"));
+}
+
+#[test]
+fn test_diff() {
+    let upstream = WithSerdeRaw::from_bytes(b"2330    10S").unwrap();
+    let recovered = WithSerdeRaw::from_bytes(b"2330    1xB").unwrap();
+    assert!(upstream.diff(upstream).is_empty());
+
+    let diffs = upstream.diff(recovered);
+    assert_eq!(
+        diffs,
+        [
+            binary_mirror::FieldDiff {
+                field: "qty",
+                offset: 6,
+                size: 4,
+                left: "10".to_string(),
+                right: "  1x".to_string(),
+            },
+            binary_mirror::FieldDiff {
+                field: "side",
+                offset: 10,
+                size: 1,
+                left: "Sell".to_string(),
+                right: "Buy".to_string(),
+            },
+        ]
+    );
+    assert_eq!(diffs[0].to_string(), "qty: 10 !=   1x");
+}
+
+#[test]
+fn test_fixed_len_codec() {
+    use binary_mirror::codec::bytes::BytesMut;
+    use binary_mirror::codec::{Decoder, Encoder, FixedLenCodec};
+
+    let mut codec = FixedLenCodec::<WithSerdeRaw>::new();
+    let mut buf = BytesMut::from(&b"2330    10S2317"[..]);
+    let first = codec.decode(&mut buf).unwrap().unwrap();
+    assert_eq!(first.qty(), Some(10));
+    assert!(codec.decode(&mut buf).unwrap().is_none());
+    assert_eq!(&buf[..], b"2317");
+
+    buf.extend_from_slice(b"     5B");
+    let second = codec.decode(&mut buf).unwrap().unwrap();
+    assert_eq!(second.symbol(), Some("2317".to_string()));
+    assert!(buf.is_empty());
+
+    let mut out = BytesMut::new();
+    codec.encode(&first, &mut out).unwrap();
+    codec.encode(second, &mut out).unwrap();
+    assert_eq!(&out[..], b"2330    10S2317     5B");
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+#[bm(frame = "crlf")]
+struct CrlfQuote {
+    #[bm(type = "str")]
+    symbol: [u8; 4],
+    #[bm(type = "i32")]
+    qty: [u8; 3],
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+#[bm(frame = "ascii_len(4)")]
+struct LenQuote {
+    #[bm(type = "str")]
+    symbol: [u8; 4],
+    #[bm(type = "i32")]
+    qty: [u8; 3],
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+#[bm(frame = "bcd_len(2)")]
+struct BcdQuote {
+    #[bm(type = "str")]
+    symbol: [u8; 4],
+    #[bm(type = "i32")]
+    qty: [u8; 3],
+}
+
+#[test]
+fn test_frame_fits() {
+    use binary_mirror::{Frame, LengthEncoding};
+
+    let ascii = Frame::LengthPrefixed { width: 2, encoding: LengthEncoding::Ascii };
+    assert!(ascii.fits(99));
+    assert!(!ascii.fits(100));
+    let bcd = Frame::LengthPrefixed { width: 1, encoding: LengthEncoding::Bcd };
+    assert!(bcd.fits(99));
+    assert!(!bcd.fits(100));
+    assert!(Frame::Fixed.fits(usize::MAX));
+}
+
+#[test]
+fn test_frames() {
+    use binary_mirror::codec::bytes::BytesMut;
+    use binary_mirror::codec::{Decoder, Encoder, FixedLenCodec};
+    use binary_mirror::{Frame, Framing};
+
+    assert_eq!(WithSerdeRaw::FRAME, Frame::Fixed);
+    assert_eq!(CrlfQuote::FRAME.frame_len(CrlfQuote::SIZE), 9);
+
+    let mut codec = FixedLenCodec::<CrlfQuote>::new();
+    let mut buf = BytesMut::from(&b"2330 10\r\n2317  5\r"[..]);
+    assert_eq!(codec.decode(&mut buf).unwrap().unwrap().qty(), Some(10));
+    assert!(codec.decode(&mut buf).unwrap().is_none());
+    buf.extend_from_slice(b"\n");
+    let quote = codec.decode(&mut buf).unwrap().unwrap();
+    assert_eq!(quote.symbol(), Some("2317".to_string()));
+    let mut out = BytesMut::new();
+    codec.encode(&quote, &mut out).unwrap();
+    assert_eq!(&out[..], b"2317  5\r\n");
+
+    let mut buf = BytesMut::from(&b"2330 10\n\n"[..]);
+    let err = codec.decode(&mut buf).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "invalid frame: expected terminator \"\\r\\n\", content: \"2330 10\\n\\n\""
+    );
+
+    let mut codec = FixedLenCodec::<LenQuote>::new();
+    let mut out = BytesMut::new();
+    codec.encode(LenQuote::from_bytes(b"2330 10").unwrap(), &mut out).unwrap();
+    assert_eq!(&out[..], b"00072330 10");
+    assert_eq!(codec.decode(&mut out).unwrap().unwrap().qty(), Some(10));
+    let mut buf = BytesMut::from(&b"00082330 10"[..]);
+    assert!(codec.decode(&mut buf).is_err());
+
+    let mut codec = FixedLenCodec::<BcdQuote>::new();
+    let mut out = BytesMut::new();
+    codec.encode(BcdQuote::from_bytes(b"2330 10").unwrap(), &mut out).unwrap();
+    assert_eq!(&out[..], b"\x00\x072330 10");
+    assert_eq!(codec.decode(&mut out).unwrap().unwrap().symbol(), Some("2330".to_string()));
+
+    let mut framed = Vec::new();
+    Frame::Wrapped { start: b"\x01", end: b"\x03" }.write(b"2330 10", &mut framed);
+    assert_eq!(framed, b"\x012330 10\x03");
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct NewOrderMsg {
+    #[bm(type = "str")]
+    msg_type: [u8; 1],
+    #[bm(type = "str")]
+    symbol: [u8; 4],
+    #[bm(type = "i32")]
+    qty: [u8; 3],
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct CancelMsg {
+    #[bm(type = "str")]
+    msg_type: [u8; 1],
+    #[bm(type = "i64")]
+    order_id: [u8; 6],
+}
+
+#[derive(Debug, BinaryMessage)]
+#[bmsg(tag_offset = 0)]
+enum Message {
+    #[bmsg(tag = b"D")]
+    NewOrder(NewOrderMsg),
+    #[bmsg(tag = b"F")]
+    Cancel(CancelMsg),
+}
+
+#[derive(BinaryMessage)]
+enum MessageRef<'a> {
+    #[bmsg(tag = b"D")]
+    NewOrder(&'a NewOrderMsg),
+    #[bmsg(tag = b"F")]
+    Cancel(&'a CancelMsg),
+}
+
+#[test]
+fn test_binary_message() {
+    match Message::decode(b"D2330 10").unwrap() {
+        Message::NewOrder(order) => assert_eq!(order.qty(), Some(10)),
+        Message::Cancel(_) => panic!("expected a new order"),
+    }
+    let cancel = Message::decode(b"F000042").unwrap();
+    assert!(matches!(&cancel, Message::Cancel(cancel) if cancel.order_id() == Some(42)));
+    assert_eq!(cancel.to_bytes(), b"F000042");
+
+    let bytes = b"D2317  5";
+    match MessageRef::decode(bytes).unwrap() {
+        MessageRef::NewOrder(order) => assert!(std::ptr::eq(order.to_bytes(), &bytes[..])),
+        MessageRef::Cancel(_) => panic!("expected a new order"),
+    }
+
+    let err = Message::decode(b"X000042").unwrap_err();
+    assert_eq!(err.to_string(), "unknown message tag at offset 0, content: \"X000042\"");
+    let err = Message::decode(b"F00042").unwrap_err();
+    assert!(matches!(err, binary_mirror::DecodeError::Size(_)));
+    assert!(Message::decode(b"").is_err());
+}
+
+#[test]
+fn test_layout_table() {
+    assert_eq!(
+        WithDefaults::layout_table().lines().take(4).collect::<Vec<_>>(),
+        [
+            "| Field | Offset | Size | Type | Format | Default | Description |",
+            "|---|---:|---:|---|---|---|---|",
+            "| name | 0 | 10 | str |  | default_str() |  |",
+            "| value | 10 | 4 | i32 |  | default_i32() |  |",
+        ]
+    );
+    assert!(WithDefaults::layout_table()
+        .contains("| order_type | 60 | 3 | enum(OrderType) |  | order_type_default() |"));
+    assert_eq!(
+        TestStruct::layout_table().lines().nth(4).unwrap(),
+        "| no_type | 14 | 7 |  |  | ' ' |  |"
+    );
+    assert!(TestStruct::layout_table().contains("| date | 56 | 8 | date | %Y%m%d | ' ' |"));
+}
+
+#[test]
+fn test_reflect() {
+    let descriptor = TestStruct::descriptor();
+    assert_eq!(descriptor.name, "TestStruct");
+    assert_eq!(descriptor.native_name, "TestStructNative");
+    assert_eq!(descriptor.size, TestStruct::SIZE);
+    assert_eq!(descriptor.fields.len(), 9);
+
+    let exh = TestStruct::field("exh").unwrap();
+    assert_eq!(exh.alias, Some("exchange"));
+    assert_eq!(exh.kind, FieldKind::Str);
+    assert_eq!((exh.offset, exh.size), (46, 10));
+
+    let date = TestStruct::field("date").unwrap();
+    assert_eq!(date.kind, FieldKind::Date);
+    assert_eq!(date.format, Some("%Y%m%d"));
+    assert!(date.skip);
+
+    assert_eq!(TestStruct::field("no_type").unwrap().kind, FieldKind::Untyped);
+    assert_eq!(FieldKind::from_type_name("decimal"), Some(FieldKind::Decimal));
+    assert_eq!(FieldKind::Decimal.type_name(), Some("decimal"));
+    for (field, (name, spec)) in TestStruct::fields().iter().zip(TestStruct::field_specs()) {
+        assert_eq!(field.name, *name);
+        assert_eq!(field.offset, spec.offset);
+        assert_eq!(field.kind.type_name(), spec.type_name);
+    }
+}
+
+#[test]
+fn test_multi_byte_enum() {
+    // Test multi-byte values
+    assert_eq!(OrderType::from_bytes(b"MKT"), Some(OrderType::Market));
+    assert_eq!(OrderType::from_bytes(b"LMT"), Some(OrderType::Limit));
+    assert_eq!(OrderType::from_bytes(b"XXX"), None);
+
+    // Test roundtrip
+    let market = OrderType::Market;
+    assert_eq!(market.as_bytes(), b"MKT");
+    assert_eq!(
+        OrderType::from_bytes(market.as_bytes()),
+        Some(OrderType::Market)
+    );
+
+    let limit = OrderType::Limit;
+    assert_eq!(limit.as_bytes(), b"LMT");
+    assert_eq!(
+        OrderType::from_bytes(limit.as_bytes()),
+        Some(OrderType::Limit)
+    );
+
+    // Test in struct
+    #[repr(C)]
+    #[derive(BinaryMirror)]
+    struct Order {
+        #[bm(type = "enum", enum_type = "OrderType")]
+        order_type: [u8; 3],
+    }
+
+    let order = Order {
+        order_type: *b"MKT",
+    };
+
+    assert_eq!(order.order_type(), Some(OrderType::Market));
+    let native = order.to_native();
+    assert_eq!(native.order_type, Some(OrderType::Market));
+}
+
+fn default_i32() -> i32 {
+    42
+}
+
+fn default_str() -> String {
+    "UNKNOWN".to_string()
+}
+
+fn now() -> chrono::NaiveDateTime {
+    chrono::Local::now().naive_utc().with_nanosecond(0).unwrap()
+}
+
+fn today() -> chrono::NaiveDate {
+    chrono::Local::now().date_naive()
+}
+
+fn now_time() -> chrono::NaiveTime {
+    chrono::Local::now().time().with_nanosecond(0).unwrap()
+}
+
+fn order_type_default() -> OrderType {
+    OrderType::Limit
+}
+
+fn decimal_default() -> Decimal {
+    Decimal::from_str("123.45").unwrap()
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct WithDefaults {
+    #[bm(type = "str", default_func = "default_str")]
+    name: [u8; 10],
+    #[bm(type = "i32", default_func = "default_i32")]
+    value: [u8; 4],
+    #[bm(type = "datetime", default_func = "now", format = "%Y%m%d%H%M%S")]
+    timestamp: [u8; 14],
+    #[bm(type = "date", default_func = "today", format = "%Y%m%d")]
+    today: [u8; 8],
+    #[bm(type = "time", default_func = "now_time", format = "%H%M%S")]
+    time: [u8; 6],
+    #[bm(
+        type = "date",
+        default_func = "now",
+        format = "%Y-%m-%d",
+        datetime_with = "tt",
+        alias = "concate_datetime"
+    )]
+    date: [u8; 10],
+    #[bm(type = "time", format = "%H:%M:%S")]
+    tt: [u8; 8],
+    #[bm(
+        type = "enum",
+        enum_type = "OrderType",
+        default_func = "order_type_default"
+    )]
+    order_type: [u8; 3],
+    #[bm(type = "decimal", default_func = "decimal_default")]
+    decimal: [u8; 10],
+}
+
+#[test]
+fn test_defaults() {
+    let native = WithDefaultsNative::default();
+    println!("{:?}", native);
+    let binary = WithDefaults::from_native(&native);
+    println!("{:?}", binary);
+    assert_eq!(binary.name(), Some("UNKNOWN".to_string()));
+    assert_eq!(binary.value(), Some(42));
+    assert_eq!(binary.timestamp(), Some(now()));
+    assert_eq!(binary.today(), Some(today()));
+    assert_eq!(binary.time(), Some(now_time()));
+    assert_eq!(binary.concate_datetime(), Some(now()));
+    assert_eq!(binary.order_type(), Some(OrderType::Limit));
+    assert_eq!(binary.decimal(), Some(Decimal::from_str("123.45").unwrap()));
+    // Test overriding defaults
+    let native = WithDefaultsNative::default()
+        .with_name("TEST")
+        .with_value(100)
+        .with_decimal(Decimal::from_str("999.99").unwrap())
+        .with_order_type(OrderType::Market);
+
+    let binary = WithDefaults::from_native(&native);
+    assert_eq!(binary.name(), Some("TEST".to_string()));
+    assert_eq!(binary.value(), Some(100));
+    assert_eq!(binary.decimal(), Some(Decimal::from_str("999.99").unwrap()));
+    assert_eq!(binary.order_type(), Some(OrderType::Market));
+}
+
+#[test]
+fn test_native_to_raw() {
+    // Create native struct with builder pattern
+    let native = TestStructNative::default()
+        .with_name("AAPL")
+        .with_value(123)
+        .with_decimal(Decimal::from_str("123.45").unwrap())
+        .with_f32(123.4)
+        .with_exchange("NYSE")
+        .with_datetime(NaiveDateTime::new(
+            NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            NaiveTime::from_hms_opt(12, 34, 56).unwrap(),
+        ))
+        .with_side(OrderSide::Buy);
+
+    // Convert directly to raw binary struct
+    let raw = native.to_raw();
+
+    // Verify values
+    assert_eq!(raw.name(), Some("AAPL".to_string()));
+    assert_eq!(raw.value(), Some(123));
+    assert_eq!(raw.decimal(), Some(Decimal::from_str("123.45").unwrap()));
+    assert_eq!(raw.f32(), Some(123.4));
+    assert_eq!(raw.exchange(), Some("NYSE".to_string()));
+    assert_eq!(
+        raw.datetime(),
+        Some(NaiveDateTime::new(
+            NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            NaiveTime::from_hms_opt(12, 34, 56).unwrap()
+        ))
+    );
+    assert_eq!(raw.side(), Some(OrderSide::Buy));
+
+    // Test roundtrip
+    let native2 = raw.to_native();
+    let raw2 = native2.to_raw();
+    assert_eq!(raw.to_bytes(), raw2.to_bytes());
+}
+
+#[test]
+fn test_native_struct_code() {
+    let code = TestStruct::native_struct_code();
+    assert_eq!(
+        code,
+        r#"pub struct TestStructNative {
+    pub name: Option<String>,
+    pub value: Option<i32>,
+    pub decimal: Option<rust_decimal::Decimal>,
+    pub f32: Option<f32>,
+    pub exchange: Option<String>,
+    pub datetime: Option<chrono::NaiveDateTime>,
+    pub side: Option<OrderSide>,
+}"#
+    );
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+#[bm(derive(Debug, Clone))]
+struct CustomDerives {
+    #[bm(type = "str")]
+    name: [u8; 10],
+}
+
+#[test]
+fn test_custom_native_derives() {
+    let code = CustomDerives::native_struct_code();
+    assert_eq!(
+        code,
+        r#"pub struct CustomDerivesNative {
+    pub name: Option<String>,
+}"#
+    );
+
+    // Test that the actual struct has the same derives
+    let native = CustomDerivesNative::default();
+    let _cloned = native.clone(); // Should compile because we have Clone
+    let _debug = format!("{:?}", native); // Should compile because we have Debug
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct WithSkippedFields {
+    #[bm(type = "str")]
+    name: [u8; 10],
+    #[bm(type = "i32", skip = true, default_byte = b'\x33')]
+    skipped_value: [u8; 4],
+    #[bm(type = "str")]
+    description: [u8; 20],
+}
+
+#[test]
+fn test_skipped_fields() {
+    let native = WithSkippedFieldsNative::default()
+        .with_name("TEST")
+        .with_description("Description");
+
+    let raw = native.to_raw();
+    let bytes = raw.to_bytes();
+    let parsed = WithSkippedFields::from_bytes(bytes).unwrap();
+
+    // Verify values
+    assert_eq!(raw.name(), Some("TEST".to_string()));
+    assert_eq!(raw.description(), Some("Description".to_string()));
+    assert_eq!(parsed.name(), Some("TEST".to_string()));
+    assert_eq!(parsed.description(), Some("Description".to_string()));
+
+    // Verify native struct code doesn't include skipped field
+    let code = WithSkippedFields::native_struct_code();
+    assert_eq!(
+        code,
+        r#"pub struct WithSkippedFieldsNative {
+    pub name: Option<String>,
+    pub description: Option<String>,
+}"#
+    );
+
+    // Test roundtrip
+    let native2 = raw.to_native();
+    let raw2 = native2.to_raw();
+    assert_eq!(raw.to_bytes(), raw2.to_bytes());
+    assert_eq!(WithSkippedFields::size(), 34);
+    assert_eq!(WithSkippedFields::SIZE, 34);
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+#[bm(size = 12)]
+struct DeclaredQuote {
+    #[bm(type = "str")]
+    symbol: [u8; 8],
+    #[bm(type = "i32")]
+    qty: [u8; 4],
+}
+
+#[test]
+fn test_declared_size() {
+    assert_eq!(DeclaredQuote::SIZE, 12);
+    let raw = DeclaredQuote::from_bytes(b"AAPL     100").unwrap();
+    assert_eq!(raw.qty(), Some(100));
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct WithSkipNativeStruct {
+    #[bm(type = "str")]
+    name: [u8; 10],
+    #[bm(type = "i32", skip_native = true)]
+    raw_value: [u8; 4],
+    #[bm(type = "str")]
+    description: [u8; 5],
+}
+
+#[test]
+fn test_skip_native() {
+    let raw = WithSkipNativeStruct {
+        name: *b"TEST      ",
+        raw_value: *b"1234",
+        description: *b"Descr",
+    };
+
+    // Field should be accessible in raw struct
+    assert_eq!(raw.raw_value(), Some(1234));
+
+    // Convert to native
+    let native = raw.to_native();
+
+    // Field should not appear in native struct
+    let code = WithSkipNativeStruct::native_struct_code();
+    assert_eq!(
+        code,
+        r#"pub struct WithSkipNativeStructNative {
+    pub name: Option<String>,
+    pub description: Option<String>,
+}"#
+    );
+
+    // Roundtrip should preserve the raw value
+    let raw2 = native.to_raw();
+    assert_eq!(raw2.raw_value(), None);
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct WithCarriedRaw {
+    #[bm(type = "str")]
+    name: [u8; 4],
+    #[bm(type = "i32", skip_native = true, carry_raw = true)]
+    routing: [u8; 4],
+}
+
+#[test]
+fn test_skip_native_carry_raw() {
+    let raw = WithCarriedRaw::from_bytes(b"TEST1234").unwrap();
+    assert_eq!(raw.routing(), Some(1234));
+
+    // The bytes survive the round trip without a native value
+    let native = raw.to_native();
+    assert_eq!(native.to_raw().to_bytes(), b"TEST1234");
+    let mut into = WithCarriedRawNative::default();
+    raw.to_native_into(&mut into);
+    assert_eq!(into, native);
+
+    // A native struct built from scratch, or from JSON, writes the default bytes
+    let json = serde_json::to_string(&native).unwrap();
+    assert_eq!(json, r#"{"name":"TEST"}"#);
+    let parsed: WithCarriedRawNative = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed.to_raw().to_bytes(), b"TEST    ");
+    assert_eq!(WithCarriedRawNative::default().with_name("AB").to_raw().to_bytes(), b"AB      ");
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+#[bm(preserve_unknown = true)]
+struct PreservedQuote {
+    #[bm(type = "str")]
+    symbol: [u8; 6],
+    #[bm(type = "i32")]
+    qty: [u8; 6],
+    #[bm(type = "f64")]
+    price: [u8; 8],
+    #[bm(pad)]
+    _filler: [u8; 2],
+    #[bm(type = "checksum", algo = "lrc")]
+    lrc: [u8; 3],
+}
+
+#[test]
+fn test_preserve_unknown() {
+    // Zero-padded quantity, a price that fails to parse, stray filler bytes and a stale checksum
+    let bytes = b"AAPL  00010012.5X   ##999";
+    let raw = PreservedQuote::from_bytes(bytes).unwrap();
+    let native = raw.to_native();
+    assert_eq!(native.qty, Some(100));
+    assert_eq!(native.price, None);
+    assert_eq!(native.to_raw().to_bytes(), bytes);
+
+    // A changed field is written from its value, the others keep their bytes
+    let changed = raw.to_native().with_qty(7);
+    let written = changed.to_raw();
+    assert_eq!(&written.to_bytes()[..22], b"AAPL  7     12.5X   ##");
+    assert_eq!(written.lrc, written.compute_lrc());
+
+    // The carried bytes are left out of serialization but not of comparisons
+    let json = serde_json::to_string(&native).unwrap();
+    let parsed: PreservedQuoteNative = serde_json::from_str(&json).unwrap();
+    assert_ne!(parsed, native);
+    let mut cleared = raw.to_native();
+    cleared.__raw.clear();
+    assert_eq!(parsed, cleared);
+    assert_eq!(parsed.to_raw().to_bytes()[..22], *b"AAPL  100             ");
+    let mut into = PreservedQuoteNative::default();
+    raw.to_native_into(&mut into);
+    assert_eq!(into.to_raw().to_bytes(), bytes);
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct WithStringTypes {
+    #[bm(type = "str")]
+    regular: [u8; 10],
+    #[bm(type = "compact_str")]
+    compact: [u8; 10],
+    // #[bm(type = "hipstr")]
+    // hip: [u8; 10],
+}
+
+#[test]
+fn test_string_types() {
+    let native = WithStringTypesNative::default()
+        .with_regular("test")
+        .with_compact("test");
+    // .with_hip(HipStr::from("test"));
+
+    let raw = native.to_raw();
+    assert_eq!(raw.regular(), Some("test".to_string()));
+    assert_eq!(raw.compact(), Some("test".to_compact_string()));
+    // assert_eq!(raw.hip(), "test");
+
+    // Test roundtrip
+    // let native2 = raw.to_native();
+    // assert_eq!(native2.hip, HipStr::from("test"));
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct ReversedDateTime {
+    #[bm(
+        type = "time",
+        format = "%H:%M:%S",
+        datetime_with = "date",
+        alias = "datetime"
+    )]
+    time: [u8; 8],
+    #[bm(type = "date", format = "%Y-%m-%d")]
+    date: [u8; 10],
+    #[bm(type = "date", format = "%Y-%m-%d", datetime_with = "tt", alias = "ddtt")]
+    dd: [u8; 10],
+    #[bm(type = "time", format = "%H:%M:%S")]
+    tt: [u8; 8],
+}
+
+#[test]
+fn test_reversed_datetime() {
+    let raw = ReversedDateTime {
+        time: *b"12:34:56",
+        date: *b"2024-01-01",
+        dd: *b"2024-01-02",
+        tt: *b"12:34:56",
+    };
+
+    // Test parsing
+    assert_eq!(
+        raw.datetime(),
+        Some(NaiveDateTime::new(
+            NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            NaiveTime::from_hms_opt(12, 34, 56).unwrap()
+        ))
+    );
+    assert_eq!(raw.ddtt(), Some(NaiveDateTime::new(
+        NaiveDate::from_ymd_opt(2024, 1, 2).unwrap(),
+        NaiveTime::from_hms_opt(12, 34, 56).unwrap()
+    )));
+
+    // Test roundtrip
+    let native = raw.to_native();
+    let raw2 = native.to_raw();
+    assert_eq!(raw.datetime(), raw2.datetime());
+    assert_eq!(raw.to_bytes(), raw2.to_bytes());
+}
+
+#[repr(C, packed)]
+#[derive(BinaryMirror)]
+struct PackedQuote {
+    #[bm(type = "str")]
+    symbol: [u8; 4],
+    #[bm(type = "i32")]
+    qty: [u8; 3],
+}
+
+#[test]
+fn test_repr_c_packed() {
+    let raw = PackedQuote::from_bytes(b"AAPL100").unwrap();
+    assert_eq!(raw.qty(), Some(100));
+    assert_eq!(PackedQuote::from_native(&raw.to_native()).to_bytes(), b"AAPL100");
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct WithValidation {
+    #[bm(type = "i32", validate(min = 0, max = 9999))]
+    qty: [u8; 4],
+    #[bm(type = "f32", validate(min = -100, max = 100.5))]
+    ratio: [u8; 6],
+    #[bm(type = "decimal", validate(min = 0.01))]
+    price: [u8; 8],
+    #[bm(type = "str", validate(regex = "^[A-Z]+$", min_len = 2, max_len = 4))]
+    symbol: [u8; 6],
+    #[bm(type = "str", validate(ascii))]
+    note: [u8; 4],
+}
+
+#[test]
+fn test_validate() {
+    let valid = WithValidation {
+        qty: *b"0100",
+        ratio: *b"-99.5 ",
+        price: *b"12.5    ",
+        symbol: *b"AAPL  ",
+        note: *b"ok  ",
+    };
+    assert!(valid.validate().is_ok());
+    assert_eq!(valid.try_to_native().unwrap().symbol, Some("AAPL".to_string()));
+
+    let blank_price = WithValidation {
+        price: *b"        ",
+        ..valid
+    };
+    assert!(blank_price.validate().is_ok());
+
+    let cases: [(WithValidation, &str, &str); 6] = [
+        (WithValidation { qty: *b"-001", ..valid }, "qty", "value -1 is less than min 0"),
+        (WithValidation { ratio: *b"100.75", ..valid }, "ratio", "value 100.75 is greater than max 100.5"),
+        (WithValidation { price: *b"0       ", ..valid }, "price", "value 0 is less than min 0.01"),
+        (WithValidation { symbol: *b"aapl  ", ..valid }, "symbol", "does not match regex ^[A-Z]+$"),
+        (WithValidation { symbol: *b"ABCDE ", ..valid }, "symbol", "length 5 is greater than max_len 4"),
+        (WithValidation { note: *b"\xffok ", ..valid }, "note", "contains non-ascii bytes"),
+    ];
+    for (record, field, message) in cases {
+        let err = record.validate().unwrap_err();
+        assert_eq!(err.field(), field);
+        assert!(err.to_string().contains(message), "{}", err);
+        assert!(record.try_to_native().is_err());
+    }
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct WithChecksum {
+    #[bm(type = "str")]
+    account: [u8; 10],
+    #[bm(type = "i32")]
+    amount: [u8; 6],
+    #[bm(type = "checksum", algo = "mod10", over = "account..account")]
+    check_digit: [u8; 1],
+    #[bm(type = "checksum", algo = "crc16", format = "{:04X}")]
+    crc: [u8; 4],
+    #[bm(type = "checksum", algo = "lrc", over = "account..amount")]
+    lrc: [u8; 3],
+}
+
+#[test]
+fn test_checksum_algorithms() {
+    use binary_mirror::checksum;
+    assert_eq!(checksum::lrc(b"\x01\x02\x04"), 0x07);
+    assert_eq!(checksum::crc16(b"123456789"), 0xBB3D);
+    assert_eq!(checksum::mod10(b"7992739871"), 3);
+}
+
+#[test]
+fn test_checksum() {
+    let native = WithChecksumNative::default()
+        .with_account("7992739871")
+        .with_amount(1234);
+    let raw = native.to_raw();
+    assert_eq!(&raw.check_digit, b"3");
+    assert_eq!(raw.crc, raw.compute_crc());
+    assert_eq!(
+        raw.lrc,
+        format!("{:03}", binary_mirror::checksum::lrc(b"79927398711234  ")).as_bytes()
+    );
+    assert!(raw.validate().is_ok());
+
+    // Native struct does not contain the checksum fields
+    assert_eq!(
+        WithChecksum::native_struct_code(),
+        r#"pub struct WithChecksumNative {
+    pub account: Option<String>,
+    pub amount: Option<i32>,
+}"#
+    );
+
+    let mut bytes = raw.to_bytes_owned();
+    bytes[0] = b'8';
+    let tampered = WithChecksum::from_bytes(&bytes).unwrap();
+    let err = tampered.validate().unwrap_err();
+    assert_eq!(err.field(), "check_digit");
+    assert!(tampered.try_to_native().is_err());
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Price {
+    ticks: i64,
+}
+
+impl std::fmt::Display for Price {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}T", self.ticks)
+    }
+}
+
+fn parse_price(bytes: &[u8]) -> Option<Price> {
+    let s = std::str::from_utf8(binary_mirror::trim_ascii(bytes)).ok()?;
+    let ticks = s.strip_suffix('T')?.parse().ok()?;
+    Some(Price { ticks })
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct WithCustom {
+    #[bm(type = "custom", parse_with = "parse_price", native_type = "Price")]
+    price: [u8; 6],
+}
+
+#[test]
+fn test_custom_parse_with() {
+    let raw = WithCustom { price: *b"125T  " };
+    assert_eq!(raw.price(), Some(Price { ticks: 125 }));
+    assert_eq!(format!("{}", raw), "WithCustom { price: Price { ticks: 125 } }");
+
+    let invalid = WithCustom { price: *b"125   " };
+    assert_eq!(invalid.price(), None);
+
+    let native = raw.to_native();
+    assert_eq!(native.price, Some(Price { ticks: 125 }));
+    assert_eq!(native.to_raw().to_bytes(), raw.to_bytes());
+    assert_eq!(
+        WithCustom::native_struct_code(),
+        r#"pub struct WithCustomNative {
+    pub price: Option<Price>,
+}"#
+    );
+}
+
+fn write_price(price: &Price, bytes: &mut [u8]) {
+    let s = format!("{}T", price.ticks);
+    bytes[..s.len()].copy_from_slice(s.as_bytes());
+}
+
+/// Signed amount with the sign in the last byte, e.g. `00125-`
+fn parse_signed_amount(bytes: &[u8]) -> Option<i64> {
+    let (digits, sign) = bytes.split_at(bytes.len() - 1);
+    let value = std::str::from_utf8(digits).ok()?.trim().parse::<i64>().ok()?;
+    match sign {
+        b"-" => Some(-value),
+        b" " | b"+" => Some(value),
+        _ => None,
+    }
+}
+
+fn write_signed_amount(value: &i64, bytes: &mut [u8]) {
+    let width = bytes.len() - 1;
+    let s = format!("{:>width$}", value.abs(), width = width);
+    bytes[..width].copy_from_slice(s.as_bytes());
+    bytes[width] = if *value < 0 { b'-' } else { b' ' };
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct WithWriteWith {
+    #[bm(
+        type = "custom",
+        parse_with = "parse_price",
+        write_with = "write_price",
+        native_type = "Price"
+    )]
+    price: [u8; 6],
+    #[bm(
+        type = "custom",
+        parse_with = "parse_signed_amount",
+        write_with = "write_signed_amount",
+        native_type = "i64"
+    )]
+    amount: [u8; 6],
+}
+
+#[test]
+fn test_write_with() {
+    let native = WithWriteWithNative::default()
+        .with_price(Price { ticks: 42 })
+        .with_amount(-125);
+    let raw = native.to_raw();
+    assert_eq!(&raw.price, b"42T   ");
+    assert_eq!(&raw.amount, b"  125-");
+    assert_eq!(raw.price(), Some(Price { ticks: 42 }));
+    assert_eq!(raw.amount(), Some(-125));
+
+    let raw = WithWriteWithNative::default().to_raw();
+    assert_eq!(&raw.price, b"      ");
+    assert_eq!(&raw.amount, b"      ");
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct WithRequired {
+    #[bm(type = "str", required = true)]
+    account: [u8; 8],
+    #[bm(type = "i32")]
+    qty: [u8; 4],
+}
+
+#[test]
+fn test_required() {
+    let raw = WithRequired::from_bytes(b"ACC1    0010").unwrap();
+    assert!(raw.validate().is_ok());
+
+    let blank = WithRequired::from_bytes(b"  \t     0010").unwrap();
+    let err = blank.validate().unwrap_err();
+    assert_eq!(err.field(), "account");
+    assert!(err.to_string().contains("required field is blank"));
+    assert!(blank.try_to_native().is_err());
+
+    // Not required fields may be blank
+    let blank_qty = WithRequired::from_bytes(b"ACC1        ").unwrap();
+    assert!(blank_qty.validate().is_ok());
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+#[bm(raw_default = true)]
+struct RawDefaultOrder {
+    #[bm(type = "str", required = true)]
+    account: [u8; 6],
+    #[bm(type = "decimal", none_value = b"9999")]
+    price: [u8; 4],
+    #[bm(type = "str", default_byte = b'0')]
+    branch: [u8; 3],
+    #[bm(type = "checksum", algo = "lrc", over = "account..branch")]
+    lrc: [u8; 3],
+}
+
+#[test]
+fn test_raw_default_and_new() {
+    // The builder's record: none_value sentinels, default bytes and a valid checksum
+    let blank = RawDefaultOrder::default();
+    assert_eq!(&blank.price, b"9999");
+    assert_eq!(&blank.branch, b"000");
+    assert_eq!(blank.lrc, blank.compute_lrc());
+    assert_eq!(blank.to_native().price, None);
+
+    let raw = RawDefaultOrder::new("ACC1");
+    assert_eq!(&raw.account, b"ACC1  ");
+    assert_eq!(raw.lrc, raw.compute_lrc());
+    assert!(raw.validate().is_ok());
+    assert_eq!(RawDefaultOrder::builder().with_account("ACC1").build().to_bytes(), raw.to_bytes());
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct WithSentinel {
+    #[bm(type = "decimal", none_value = b"99999999")]
+    limit_price: [u8; 8],
+    #[bm(type = "date", format = "%Y%m%d", none_value = b"00000000")]
+    expiry: [u8; 8],
+    #[bm(type = "str", none_if_blank = true)]
+    account: [u8; 6],
+}
+
+#[test]
+fn test_sentinel_none() {
+    let raw = WithSentinel::from_bytes(b"9999999900000000      ").unwrap();
+    assert_eq!(raw.limit_price(), None);
+    assert_eq!(raw.expiry(), None);
+    assert_eq!(raw.account(), None);
+
+    let native = raw.to_native();
+    assert_eq!(native.limit_price, None);
+    assert_eq!(native.expiry, None);
+    assert_eq!(native.account, None);
+    // None is written back as the sentinel
+    assert_eq!(native.to_raw().to_bytes(), raw.to_bytes());
+
+    let native = WithSentinelNative::default()
+        .with_limit_price(Decimal::from_str("12.5").unwrap())
+        .with_expiry(NaiveDate::from_ymd_opt(2024, 6, 30).unwrap())
+        .with_account("ACC1");
+    let raw = native.to_raw();
+    assert_eq!(raw.to_bytes(), b"12.5    20240630ACC1  ");
+    assert_eq!(raw.limit_price(), Some(Decimal::from_str("12.5").unwrap()));
+    assert_eq!(raw.account(), Some("ACC1".to_string()));
+}
+
+#[test]
+fn test_to_native_with_report() {
+    let test = TestStruct {
+        name: *b"Test      ",
+        value: *b"abc\0",
+        no_type: *b"no_type",
+        decimal: *b"                    ",
+        f32: *b"123.x",
+        exh: *b"CME       ",
+        date: *b"xxxxxxxx",
+        time: *b"xxxxxx",
+        side: *b" ",
+    };
+    let (native, errors) = test.to_native_with_report();
+    assert_eq!(native.name, Some("Test".to_string()));
+    assert_eq!(native.value, None);
+    // Blank fields are not reported
+    assert_eq!(
+        errors.iter().map(|e| e.field()).collect::<Vec<_>>(),
+        vec!["value", "f32", "datetime"]
+    );
+    assert_eq!(
+        errors[0].to_string(),
+        "failed to parse value, content: \"abc\\x00\""
+    );
+
+    // A combined datetime is reported unless all of its parts are blank, with all their bytes
+    let half_blank = TestStruct {
+        date: *b"20240101",
+        time: *b"      ",
+        ..test
+    };
+    let (native, errors) = half_blank.to_native_with_report();
+    assert_eq!(native.datetime, None);
+    assert_eq!(
+        errors[2].to_string(),
+        "failed to parse datetime, content: \"20240101      \""
+    );
+    let all_blank = TestStruct {
+        date: *b"        ",
+        time: *b"      ",
+        ..test
+    };
+    assert_eq!(all_blank.to_native_with_report().1.len(), 2);
+
+    let raw = WithSentinel::from_bytes(b"9999999900000000      ").unwrap();
+    let (_, errors) = raw.to_native_with_report();
+    assert!(errors.is_empty());
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct MsgHeader {
+    #[bm(type = "str")]
+    msg_type: [u8; 2],
+    #[bm(type = "u32", validate(min = 1))]
+    seq: [u8; 4],
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct WithHeader {
+    #[bm(flatten = "MsgHeader")]
+    header: [u8; 6],
+    #[bm(type = "str")]
+    symbol: [u8; 4],
+    #[bm(type = "i32")]
+    qty: [u8; 3],
+}
+
+#[test]
+fn test_flatten() {
+    let raw = WithHeader::from_bytes(b"NO42  AAPL100").unwrap();
+    assert_eq!(raw.header().msg_type(), Some("NO".to_string()));
+    assert_eq!(raw.header().seq(), Some(42));
+
+    let native = raw.to_native();
+    assert_eq!(native.header.seq, Some(42));
+    assert_eq!(native.symbol, Some("AAPL".to_string()));
+    assert_eq!(WithHeader::from_native(&native).to_bytes(), b"NO42  AAPL100");
+
+    let json = serde_json::to_string(&native).unwrap();
+    assert_eq!(json, r#"{"msg_type":"NO","seq":42,"symbol":"AAPL","qty":100}"#);
+    let parsed: WithHeaderNative = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed, native);
+
+    assert_eq!(WithHeaderNative::csv_header(), "msg_type,seq,symbol,qty");
+    assert_eq!(native.to_csv_row(), "NO,42,AAPL,100");
+    assert_eq!(
+        WithHeader::sql_insert("orders"),
+        "INSERT INTO orders (msg_type, seq, symbol, qty) VALUES ($1, $2, $3, $4)"
+    );
+    assert!(WithHeader::layout_table().contains("| header | 0 | 6 | flatten(MsgHeader) |  | ' ' |"));
+    assert!(WithHeader::proto_definition().contains("optional uint32 seq = 2;"));
+
+    let invalid = WithHeader::from_bytes(b"NO0   AAPL100").unwrap();
+    let error = invalid.validate().unwrap_err();
+    assert_eq!(error.field(), "seq");
+}
+
+#[test]
+fn test_try_to_raw() {
+    let raw = WithHeader::from_bytes(b"NO42  AAPL100").unwrap();
+    let mut native = raw.to_native();
+    assert_eq!(native.try_to_raw().unwrap().to_bytes(), b"NO42  AAPL100");
+
+    native.qty = Some(1000);
+    // to_raw cuts the text to the field width
+    assert_eq!(native.to_raw().to_bytes(), b"NO42  AAPL100");
+    let error = native.try_to_raw().unwrap_err();
+    assert_eq!(error.field(), "qty");
+    assert_eq!(
+        error.to_string(),
+        "field qty expects 3 bytes but got 4 bytes, content: \"1000\""
+    );
+
+    native.qty = Some(100);
+    native.header.seq = Some(123456);
+    assert_eq!(native.try_to_raw().unwrap_err().field(), "seq");
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct TickTime {
+    #[bm(type = "str")]
+    symbol: [u8; 4],
+    #[bm(type = "date", format = "%Y%m%d", datetime_with = "time", subsec_with = "msec", alias = "ts")]
+    date: [u8; 8],
+    #[bm(type = "time", format = "%H%M%S")]
+    time: [u8; 6],
+    msec: [u8; 3],
+    #[bm(type = "time", format = "%H:%M:%S", datetime_with = "micro_date", subsec_with = "micros")]
+    micro_time: [u8; 8],
+    micros: [u8; 6],
+    #[bm(type = "date", format = "%Y-%m-%d")]
+    micro_date: [u8; 10],
+}
+
+#[test]
+fn test_subsec_datetime() {
+    let raw = TickTime::from_bytes(b"AAPL2024010109301504209:30:150000422024-01-01").unwrap();
+    let ts = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap().and_hms_milli_opt(9, 30, 15, 42).unwrap();
+    assert_eq!(raw.ts(), Some(ts));
+    let micro = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap().and_hms_micro_opt(9, 30, 15, 42).unwrap();
+    assert_eq!(raw.micro_time(), Some(micro));
+
+    // A blank fraction is a whole second, a non-numeric one fails
+    let whole = TickTime::from_bytes(b"AAPL20240101093015   09:30:150000422024-01-01").unwrap();
+    assert_eq!(whole.ts(), NaiveDate::from_ymd_opt(2024, 1, 1).unwrap().and_hms_opt(9, 30, 15));
+    let bad = TickTime::from_bytes(b"AAPL2024010109301504x09:30:150000422024-01-01").unwrap();
+    assert_eq!(bad.ts(), None);
+
+    // The digits are left-aligned, trailing blanks are zeros
+    let short = TickTime::from_bytes(b"AAPL202401010930155  09:30:150000422024-01-01").unwrap();
+    assert_eq!(short.ts(), NaiveDate::from_ymd_opt(2024, 1, 1).unwrap().and_hms_milli_opt(9, 30, 15, 500));
+    let right = TickTime::from_bytes(b"AAPL20240101093015  509:30:150000422024-01-01").unwrap();
+    assert_eq!(right.ts(), None);
+
+    let native = raw.to_native();
+    assert_eq!(native.ts, Some(ts));
+    assert_eq!(TickTime::from_native(&native).to_bytes(), raw.to_bytes());
+
+    let mut raw = TickTime::from_native(&native);
+    raw.set_ts(ts + chrono::Duration::milliseconds(958));
+    assert_eq!(&raw.to_bytes()[4..21], b"20240101093016000");
+    raw.set_micro_time(micro + chrono::Duration::microseconds(7));
+    assert_eq!(&raw.micros, b"000049");
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct FractionalTimes {
+    #[bm(type = "time", format = "%H:%M:%S%.3f")]
+    dotted: [u8; 12],
+    #[bm(type = "time", format = "%H%M%S%f")]
+    millis: [u8; 9],
+    #[bm(type = "time", format = "%H%M%S%f")]
+    micros: [u8; 12],
+    #[bm(type = "datetime", format = "%Y%m%d%H%M%S%.f")]
+    stamp: [u8; 18],
+}
+
+#[test]
+fn test_fractional_seconds() {
+    let raw = FractionalTimes::from_bytes(b"09:30:15.04209301504209301500004220240101093015.042").unwrap();
+    let time = |nano| NaiveTime::from_hms_nano_opt(9, 30, 15, nano);
+    assert_eq!(raw.dotted(), time(42_000_000));
+    // A bare %f takes the digits left in the field rather than nanoseconds
+    assert_eq!(raw.millis(), time(42_000_000));
+    assert_eq!(raw.micros(), time(42_000));
+    assert_eq!(
+        raw.stamp(),
+        NaiveDate::from_ymd_opt(2024, 1, 1).unwrap().and_time(time(42_000_000).unwrap()).into()
+    );
+    assert_eq!(FractionalTimes::from_native(&raw.to_native()).to_bytes(), raw.to_bytes());
+
+    // Extra precision is truncated to the field width, missing precision is zero-padded
+    let precise = time(42_123_456);
+    let native = FractionalTimesNative {
+        dotted: precise,
+        millis: precise,
+        micros: time(42_000_000),
+        stamp: precise.map(|t| NaiveDate::from_ymd_opt(2024, 1, 1).unwrap().and_time(t)),
+    };
+    assert_eq!(
+        FractionalTimes::from_native(&native).to_bytes(),
+        b"09:30:15.04209301504209301504200020240101093015.042"
+    );
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct FixedPrices {
+    #[bm(type = "f64", precision = 2)]
+    price: [u8; 8],
+    #[bm(type = "decimal", precision = 3, rounding = "truncate")]
+    fee: [u8; 7],
+    #[bm(type = "f32", precision = 0)]
+    lots: [u8; 4],
+}
+
+#[test]
+fn test_fixed_precision() {
+    let write = |price, fee: &str, lots| {
+        FixedPrices::from_native(&FixedPricesNative {
+            price: Some(price),
+            fee: Decimal::from_str(fee).ok(),
+            lots: Some(lots),
+        })
+        .to_bytes()
+        .to_vec()
+    };
+    // 2.675 is stored just below itself, a format string would round it down
+    assert_eq!(write(2.675, "1.2349", 12.5), b"00002.68001.2340013");
+    assert_eq!(write(-9.999, "-0.0004", 0.4), b"-0010.00000.0000000");
+    assert_eq!(write(1234.5, "12", 999.5), b"01234.50012.0001000");
+
+    let raw = FixedPrices::from_bytes(b"00002.68001.2340013").unwrap();
+    assert_eq!(raw.price(), Some(2.68));
+    assert_eq!(raw.fee(), Decimal::from_str("1.234").ok());
+    assert_eq!(FixedPrices::from_native(&raw.to_native()).to_bytes(), raw.to_bytes());
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct Settlement {
+    #[bm(type = "decimal", precision = 2, sign = "trailing")]
+    amount: [u8; 10],
+    #[bm(type = "i32", sign = "paren")]
+    qty: [u8; 6],
+    #[bm(type = "f64", sign = "trailing")]
+    fee: [u8; 6],
+    #[bm(type = "i64", format = "{:06}", sign = "paren")]
+    lots: [u8; 6],
+}
+
+#[test]
+fn test_sign_conventions() {
+    let raw = Settlement::from_bytes(b"000123.45- (250)1.5-  (0042)").unwrap();
+    assert_eq!(raw.amount(), Decimal::from_str("-123.45").ok());
+    assert_eq!(raw.qty(), Some(-250));
+    assert_eq!(raw.fee(), Some(-1.5));
+    assert_eq!(raw.lots(), Some(-42));
+    assert_eq!(
+        Settlement::from_native(&raw.to_native()).to_bytes(),
+        b"000123.45-(250) 1.5-  (0042)"
+    );
+
+    let native = SettlementNative {
+        amount: Decimal::from_str("5").ok(),
+        qty: Some(7),
+        fee: Some(2.25),
+        lots: Some(42),
+    };
+    assert_eq!(Settlement::from_native(&native).to_bytes(), b"0000005.007     2.25  000042");
+
+    // A leading sign still reads, an unbalanced parenthesis does not
+    let raw = Settlement::from_bytes(b"-12.5     -12   +2.5  (42   ").unwrap();
+    assert_eq!(raw.amount(), Decimal::from_str("-12.5").ok());
+    assert_eq!(raw.qty(), Some(-12));
+    assert_eq!(raw.fee(), Some(2.5));
+    assert_eq!(raw.lots(), None);
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct LocalePrices {
+    #[bm(type = "f64", numeric_locale(decimal = ','))]
+    price: [u8; 8],
+    #[bm(type = "decimal", precision = 2, numeric_locale(thousands = ',', group))]
+    amount: [u8; 12],
+    #[bm(type = "i32", numeric_locale(decimal = ',', thousands = '.'))]
+    qty: [u8; 7],
+    #[bm(type = "decimal", precision = 2, sign = "trailing", numeric_locale(decimal = ',', thousands = '.', group))]
+    total: [u8; 12],
+}
+
+#[test]
+fn test_numeric_locale() {
+    let raw = LocalePrices::from_bytes(b"1234,56 1,234,567.891.234  001.234,50- ").unwrap();
+    assert_eq!(raw.price(), Some(1234.56));
+    assert_eq!(raw.amount(), Decimal::from_str("1234567.89").ok());
+    assert_eq!(raw.qty(), Some(1234));
+    assert_eq!(raw.total(), Decimal::from_str("-1234.5").ok());
+    // Separators are only written with group, padding zeros are not grouped
+    assert_eq!(
+        LocalePrices::from_native(&raw.to_native()).to_bytes(),
+        b"1234,56 1,234,567.891234   0001.234,50-"
+    );
+
+    let native = LocalePricesNative {
+        price: Some(0.5),
+        amount: Decimal::from_str("12.5").ok(),
+        qty: Some(-7),
+        total: Decimal::from_str("999").ok(),
+    };
+    assert_eq!(LocalePrices::from_native(&native).to_bytes(), b"0,5     000000012.50-7     000000999,00");
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct Cheque {
+    #[bm(type = "decimal", precision = 2, fill = b'*')]
+    amount: [u8; 10],
+    #[bm(type = "u32", fill = b'0')]
+    count: [u8; 6],
+    #[bm(type = "i32", fill = b'0')]
+    net: [u8; 6],
+    #[bm(type = "i64", sign = "paren", fill = b'*')]
+    refund: [u8; 8],
+}
+
+#[test]
+fn test_fill() {
+    let bytes = b"****123.45000042-00012***(500)";
+    let raw = Cheque::from_bytes(bytes).unwrap();
+    assert_eq!(raw.amount(), Decimal::from_str("123.45").ok());
+    assert_eq!(raw.count(), Some(42));
+    assert_eq!(raw.net(), Some(-12));
+    assert_eq!(raw.refund(), Some(-500));
+    assert_eq!(Cheque::from_native(&raw.to_native()).to_bytes(), bytes);
+
+    let raw = Cheque::from_bytes(b"**********000000     0********").unwrap();
+    assert_eq!(raw.amount(), None);
+    assert_eq!(raw.count(), Some(0));
+    assert_eq!(raw.net(), Some(0));
+    assert_eq!(raw.refund(), None);
+    let native = ChequeNative { amount: None, count: Some(0), net: Some(7), refund: Some(1) };
+    assert_eq!(Cheque::from_native(&native).to_bytes(), b"          000000000007*******1");
+
+    let native = ChequeNative { amount: None, count: None, net: None, refund: Some(-1234567) };
+    assert_eq!(native.to_raw().refund, *b"(1234567");
+    let error = native.try_to_raw().unwrap_err();
+    assert_eq!(error.field(), "refund");
+    assert_eq!(error.to_string(), "field refund expects 8 bytes but got 9 bytes, content: \"(1234567)\"");
+
+    let mut bytes = [0u8; 4];
+    assert!(binary_mirror::fill_number("amount", "12345", b'*', &mut bytes).is_err());
+    assert_eq!(&bytes, b"1234");
+    assert!(binary_mirror::fill_number("amount", "12", b'*', &mut bytes).is_ok());
+    assert_eq!(&bytes, b"**12");
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct ZonedStamps {
+    #[bm(type = "datetime", format = "%Y%m%d%H%M%S", assume_tz = "Asia/Taipei")]
+    traded: [u8; 14],
+    #[bm(type = "date", format = "%Y%m%d", datetime_with = "time", assume_tz = "America/New_York", to = "+08:00", alias = "settled")]
+    date: [u8; 8],
+    #[bm(type = "time", format = "%H%M%S")]
+    time: [u8; 6],
+    #[bm(type = "datetime", format = "%Y%m%d%H%M%S", assume_tz = "utc", to = "Asia/Taipei")]
+    shown: [u8; 14],
+}
+
+#[test]
+fn test_assumed_timezone() {
+    let raw = ZonedStamps::from_bytes(b"202401010900152024070109300020240101010000").unwrap();
+    let at = |y, m, d, h, min, s| NaiveDate::from_ymd_opt(y, m, d).unwrap().and_hms_opt(h, min, s);
+    // Taipei is 8 hours ahead of UTC, New York 4 hours behind it in the summer
+    assert_eq!(raw.traded(), at(2024, 1, 1, 1, 0, 15));
+    assert_eq!(raw.settled(), at(2024, 7, 1, 21, 30, 0));
+    assert_eq!(raw.shown(), at(2024, 1, 1, 9, 0, 0));
+    assert_eq!(ZonedStamps::from_native(&raw.to_native()).to_bytes(), raw.to_bytes());
+
+    // Across the date line and in New York winter time
+    let native = ZonedStampsNative {
+        traded: at(2023, 12, 31, 20, 0, 0),
+        settled: at(2024, 1, 2, 0, 0, 0),
+        shown: at(2024, 1, 1, 7, 59, 59),
+    };
+    assert_eq!(
+        ZonedStamps::from_native(&native).to_bytes(),
+        b"202401010400002024010111000020231231235959"
+    );
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct QuotePayload {
+    #[bm(type = "f64")]
+    bid: [u8; 5],
+    #[bm(type = "f64")]
+    ask: [u8; 5],
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct Envelope<P> {
+    #[bm(type = "str")]
+    venue: [u8; 4],
+    body: P,
+}
+
+#[test]
+fn test_generic_struct() {
+    assert_eq!(Envelope::<MsgHeader>::SIZE, 10);
+    assert_eq!(Envelope::<QuotePayload>::SIZE, 14);
+
+    let raw = Envelope::<MsgHeader>::from_bytes(b"XNASNO42  ").unwrap();
+    assert_eq!(raw.venue(), Some("XNAS".to_string()));
+    assert_eq!(raw.body().seq(), Some(42));
+    assert!(format!("{:?}", raw).contains("body: MsgHeader {"));
+
+    let raw = Envelope::<QuotePayload>::from_bytes(b"XNAS1.25 1.26 ").unwrap();
+    assert_eq!(raw.body().ask(), Some(1.26));
+    let native: EnvelopeNative<QuotePayloadNative> = raw.to_native();
+    assert_eq!(native.body.bid, Some(1.25));
+    let json = serde_json::to_string(&native).unwrap();
+    assert_eq!(json, r#"{"venue":"XNAS","bid":1.25,"ask":1.26}"#);
+    assert_eq!(Envelope::<QuotePayload>::from_native(&native).to_bytes(), b"XNAS1.25 1.26 ");
+
+    let default = EnvelopeNative::<QuotePayloadNative>::default().with_venue("XLON");
+    assert_eq!(Envelope::<QuotePayload>::from_native(&default).to_bytes(), b"XLON          ");
+
+    let invalid = Envelope::<MsgHeader>::from_bytes(b"XNASNOx   ").unwrap();
+    let (_, errors) = invalid.to_native_with_report();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].field(), "seq");
+    let invalid = Envelope::<MsgHeader>::from_bytes(b"XNASNO0   ").unwrap();
+    assert_eq!(invalid.validate().unwrap_err().field(), "seq");
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+#[bm(raw_derive(AsRef))]
+struct WithMemo {
+    #[bm(type = "str")]
+    code: [u8; 4],
+    #[bm(type = "i32")]
+    qty: [u8; 3],
+    #[bm(type = "rest")]
+    memo: [u8],
+}
+
+#[test]
+fn test_rest_field() {
+    assert_eq!(WithMemo::SIZE, 7);
+    let raw = WithMemo::from_bytes(b"AAPL100free text memo").unwrap();
+    assert_eq!(raw.code(), Some("AAPL".to_string()));
+    assert_eq!(raw.qty(), Some(100));
+    assert_eq!(raw.memo(), b"free text memo");
+    assert_eq!(raw.to_bytes(), b"AAPL100free text memo");
+    assert_eq!(raw.as_ref(), b"AAPL100free text memo");
+
+    let empty = WithMemo::from_bytes(b"AAPL100").unwrap();
+    assert_eq!(empty.memo(), b"");
+    let err = WithMemo::from_bytes(b"AAPL10").unwrap_err();
+    assert_eq!(err.to_string(), "bytes size mismatch: expected 7 bytes but got 6 bytes, content: \"AAPL10\"");
+
+    let native = raw.to_native();
+    assert_eq!(native.memo, b"free text memo".to_vec());
+    assert_eq!(native.to_raw().to_bytes(), b"AAPL100free text memo");
+    let mut buf = [b'#'; 24];
+    assert_eq!(native.write_raw_to(&mut buf).unwrap(), 21);
+    assert_eq!(&buf, b"AAPL100free text memo###");
+    let shorter = native.with_memo(b"short".to_vec());
+    let rebuilt = <Box<WithMemo>>::from_native(&shorter);
+    assert_eq!(rebuilt.to_bytes(), b"AAPL100short");
+
+    let mut native_into = WithMemoNative::default();
+    empty.to_native_into(&mut native_into);
+    assert!(native_into.memo.is_empty());
+    assert_eq!(native_into.qty, Some(100));
+
+    assert_eq!(raw.field_bytes("memo"), Some(&b"free text memo"[..]));
+    assert_eq!(raw.diff(empty).len(), 1);
+    assert!(WithMemo::layout_table().contains("| memo | 7 | * | rest |"));
+    assert_eq!(WithMemo::field("memo").unwrap().kind, FieldKind::Rest);
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct WithPadding {
+    #[bm(type = "str")]
+    code: [u8; 4],
+    #[bm(pad)]
+    _reserved: [u8; 3],
+    #[bm(type = "i32")]
+    qty: [u8; 3],
+    #[bm(pad, default_byte = b'0')]
+    filler: [u8; 2],
+}
+
+#[test]
+fn test_padding() {
+    let raw = WithPadding::from_bytes(b"AAPLxyz100ab").unwrap();
+    assert_eq!(
+        format!("{:?}", raw),
+        "WithPadding { code: hex: [0x41, 0x41, 0x50, 0x4c], bytes: \"AAPL\", qty: hex: [0x31, 0x30, 0x30], bytes: \"100\" }"
+    );
+    let native = raw.to_native();
+    assert_eq!(WithPadding::from_native(&native).to_bytes(), b"AAPL   10000");
+
+    let other = WithPadding::from_bytes(b"AAPL   100  ").unwrap();
+    assert!(raw.diff(other).is_empty());
+    assert_eq!(WithPadding::field("_reserved").unwrap().kind, FieldKind::Pad);
+    assert_eq!(WithPadding::field("filler").unwrap().kind, FieldKind::Pad);
+    assert!(WithPadding::layout_table().contains("| filler | 10 | 2 | pad |  | '0' |"));
+
+    // A leading underscore alone doesn't make a field padding
+    let raw = Underscored::from_bytes(b"AAPLxy").unwrap();
+    assert_eq!(Underscored::field("_spare").unwrap().kind, FieldKind::Untyped);
+    assert_eq!(raw.inspect_fields()[1].value, "xy");
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct Underscored {
+    #[bm(type = "str")]
+    code: [u8; 4],
+    _spare: [u8; 2],
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct WithOverlay {
+    #[bm(type = "str")]
+    status: [u8; 1],
+    #[bm(type = "str")]
+    error_text: [u8; 8],
+    #[bm(type = "decimal", overlay = "error_text", when(status = b"P"))]
+    price: [u8; 0],
+}
+
+#[test]
+fn test_overlay() {
+    assert_eq!(WithOverlay::SIZE, 9);
+    let filled = WithOverlay::from_bytes(b"P12.5    ").unwrap();
+    assert_eq!(filled.price(), Some(Decimal::from_str("12.5").unwrap()));
+    let rejected = WithOverlay::from_bytes(b"EBAD SYM ").unwrap();
+    assert_eq!(rejected.price(), None);
+    assert!(rejected.to_native_with_report().1.is_empty());
+    let unpriced = WithOverlay::from_bytes(b"P12.5x   ").unwrap();
+    assert_eq!(unpriced.to_native_with_report().1[0].field(), "price");
+    assert_eq!(rejected.error_text(), Some("BAD SYM".to_string()));
+    assert!(!format!("{:?}", filled).contains("price"));
+
+    let native = WithOverlayNative::default()
+        .with_status("P")
+        .with_price(Decimal::from_str("1.25").unwrap());
+    assert_eq!(WithOverlay::from_native(&native).to_bytes(), b"P1.25    ");
+    let native = WithOverlayNative::default().with_status("E").with_error_text("NO FUNDS");
+    assert_eq!(WithOverlay::from_native(&native).to_bytes(), b"ENO FUNDS");
+
+    let mut raw = WithOverlay::from_native(&native);
+    raw.set_price(Decimal::from_str("7").unwrap());
+    assert_eq!(raw.error_text(), Some("7".to_string()));
+    assert_eq!(raw.field_bytes("price"), Some(&b"7       "[..]));
+
+    let price = WithOverlay::field("price").unwrap();
+    assert_eq!((price.offset, price.size), (1, 8));
+    assert!(WithOverlay::layout_table().contains("| price | 1 | 8 | decimal over error_text |"));
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+#[bm(version = 1, version_field = "ver")]
+struct OrderV1 {
+    #[bm(type = "u32")]
+    ver: [u8; 1],
+    #[bm(type = "str")]
+    symbol: [u8; 4],
+    #[bm(type = "i32")]
+    qty: [u8; 3],
+}
+
+fn widen_qty(v1: &OrderV1Native) -> Option<i64> {
+    v1.qty.map(i64::from)
+}
+
+fn default_venue() -> String {
+    "XNYS".to_string()
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+#[bm(version = 2, previous = "OrderV1", version_field = "ver")]
+struct OrderV2 {
+    #[bm(type = "u32")]
+    ver: [u8; 1],
+    #[bm(type = "str")]
+    symbol: [u8; 4],
+    #[bm(type = "i64", upgrade_with = "widen_qty")]
+    qty: [u8; 6],
+    #[bm(type = "str", since = 2, default_func = "default_venue")]
+    venue: [u8; 4],
+}
+
+#[test]
+fn test_versioned_layouts() {
+    assert_eq!((OrderV1::VERSION, OrderV2::VERSION), (1, 2));
+
+    let v1 = OrderV1::from_bytes(b"1AAPL100").unwrap().to_native();
+    let upgraded = OrderV2::upgrade(&v1);
+    assert_eq!(upgraded.ver, Some(2));
+    assert_eq!(upgraded.symbol, Some("AAPL".to_string()));
+    assert_eq!(upgraded.qty, Some(100));
+    assert_eq!(upgraded.venue, Some("XNYS".to_string()));
+
+    assert_eq!(OrderV2::decode_any(b"1AAPL100").unwrap(), upgraded);
+    let current = OrderV2::decode_any(b"2MSFT000200XLON").unwrap();
+    assert_eq!(current.qty, Some(200));
+    assert_eq!(current.venue, Some("XLON".to_string()));
+
+    let err = OrderV2::decode_any(b"9AAPL100").unwrap_err();
+    assert!(matches!(err, binary_mirror::DecodeError::UnknownTag { offset: 0, .. }));
+    let err = OrderV2::decode_any(b"1AAPL10").unwrap_err();
+    assert!(matches!(err, binary_mirror::DecodeError::Size(_)));
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct GatewayHeader {
+    #[bm(type = "str", fix_tag = 49)]
+    sender: [u8; 4],
+    #[bm(type = "u32")]
+    seq: [u8; 4],
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct GatewayOrder {
+    #[bm(flatten = "GatewayHeader")]
+    header: [u8; 8],
+    #[bm(type = "str", fix_tag = 55)]
+    symbol: [u8; 6],
+    #[bm(type = "enum", enum_type = "OrderSide", fix_tag = 54)]
+    side: [u8; 1],
+    #[bm(type = "i32", fix_tag = 38)]
+    qty: [u8; 4],
+    #[bm(type = "decimal", fix_tag = 44)]
+    price: [u8; 8],
+    #[bm(type = "datetime", format = "%Y%m%d%H%M%S", fix_tag = 60)]
+    ts: [u8; 14],
+    #[bm(type = "str")]
+    note: [u8; 4],
+}
+
+#[test]
+fn test_fix_tags() {
+    let order = GatewayOrder::from_bytes(b"GW01   72330  B100 585.5   20240102090000memo").unwrap();
+    assert_eq!(
+        order.fix_fields(),
+        [
+            (49, "GW01".to_string()),
+            (55, "2330".to_string()),
+            (54, "B".to_string()),
+            (38, "100".to_string()),
+            (44, "585.5".to_string()),
+            (60, "20240102-09:00:00.000".to_string()),
+        ]
+    );
+    assert_eq!(
+        order.to_fix(),
+        "49=GW01\x0155=2330\x0154=B\x0138=100\x0144=585.5\x0160=20240102-09:00:00.000\x01"
+    );
+
+    // Fields without a value are left out
+    let order = GatewayOrder::from_bytes(b"GW01   72330  X    585.5                 memo").unwrap();
+    assert_eq!(order.to_fix(), "49=GW01\x0155=2330\x0144=585.5\x01");
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+#[bm(frame = "lf")]
+struct SettlementHeader {
+    #[bm(type = "str")]
+    kind: [u8; 1],
+    #[bm(type = "date", format = "%Y%m%d")]
+    date: [u8; 8],
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+#[bm(frame = "lf")]
+struct SettlementDetail {
+    #[bm(type = "str")]
+    kind: [u8; 1],
+    #[bm(type = "str")]
+    account: [u8; 4],
+    #[bm(type = "i64")]
+    amount: [u8; 6],
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+#[bm(frame = "lf")]
+struct SettlementTrailer {
+    #[bm(type = "str")]
+    kind: [u8; 1],
+    #[bm(type = "u32")]
+    count: [u8; 3],
+}
+
+#[test]
+fn test_flat_file_reader() {
+    use binary_mirror::{FlatFileError, FlatFileReader, FlatRecord, RecordKind, RecordTypes};
+
+    const TYPES: RecordTypes = RecordTypes { offset: 0, header: b"H", detail: b"D", trailer: b"T" };
+    let read = |bytes: &'static [u8]| {
+        FlatFileReader::<SettlementHeader, SettlementDetail, SettlementTrailer>::new(bytes, TYPES)
+            .trailer_count(|trailer| trailer.count().map(|count| count as usize))
+    };
+
+    let file = b"H20240102\nDA001   100\nDA002  -250\nT002\nH20240103\nT000\n";
+    let records = read(file).collect::<Result<Vec<_>, _>>().unwrap();
+    let kinds = records.iter().map(FlatRecord::kind).collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        [
+            RecordKind::Header,
+            RecordKind::Detail,
+            RecordKind::Detail,
+            RecordKind::Trailer,
+            RecordKind::Header,
+            RecordKind::Trailer,
+        ]
+    );
+    match &records[2] {
+        FlatRecord::Detail(detail) => assert_eq!(detail.amount(), Some(-250)),
+        other => panic!("expected a detail, got {:?}", other.kind()),
+    }
+
+    let errors = |bytes| read(bytes).filter_map(Result::err).map(|err| err.to_string()).collect::<Vec<_>>();
+    assert_eq!(
+        errors(b"H20240102\nDA001   100\nT002\n"),
+        ["trailer at offset 22 counts 2 detail records but 1 were read"]
+    );
+    assert_eq!(
+        errors(b"DA001   100\nH20240102\nH20240103\nT000\n"),
+        ["unexpected detail record at offset 0", "unexpected header record at offset 22"]
+    );
+    assert_eq!(errors(b"H20240102\nDA001   100\n"), ["file ends at offset 22 without a trailer"]);
+    assert_eq!(
+        errors(b"H20240102\nXA001   100\nT001\n"),
+        ["unknown record type at offset 10, content: \"XA001   100\\n\""]
+    );
+    let mut truncated = read(b"H20240102\nDA001   1");
+    assert!(truncated.next().unwrap().is_ok());
+    assert!(matches!(truncated.next(), Some(Err(FlatFileError::Frame { offset: 10, .. }))));
+    assert!(truncated.next().is_none());
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+#[bm(frame = "lf")]
+struct TapeRecord {
+    #[bm(type = "str")]
+    kind: [u8; 1],
+    #[bm(type = "i32", validate(min = 0, max = 9999))]
+    qty: [u8; 4],
+}
+
+#[test]
+fn test_resync_records() {
+    use binary_mirror::{CorruptReason, ResyncRecords};
+
+    let tape = b"R0010\nR-001\nR0030\nxxR0040\nR00";
+    let items = ResyncRecords::<TapeRecord>::new(tape)
+        .map(|item| item.map(|record| record.qty().unwrap()))
+        .collect::<Vec<_>>();
+    assert_eq!(items.len(), 6);
+    assert_eq!(items[0].as_ref().unwrap(), &10);
+    let skipped = items[1].as_ref().unwrap_err();
+    assert_eq!((skipped.offset, skipped.len), (6, 6));
+    assert!(matches!(skipped.reason, CorruptReason::Validation(_)));
+    assert_eq!(items[2].as_ref().unwrap(), &30);
+    let skipped = items[3].as_ref().unwrap_err();
+    assert_eq!((skipped.offset, skipped.len), (18, 2));
+    assert!(matches!(skipped.reason, CorruptReason::Frame(_)));
+    assert_eq!(items[4].as_ref().unwrap(), &40);
+    let skipped = items[5].as_ref().unwrap_err();
+    assert_eq!((skipped.offset, skipped.len), (26, 3));
+
+    let shifted = b"R0010\n0R0020\n";
+    let qtys = |iter: ResyncRecords<'_, TapeRecord>| {
+        iter.filter_map(Result::ok).map(|record| record.qty().unwrap()).collect::<Vec<_>>()
+    };
+    assert_eq!(qtys(ResyncRecords::new(shifted)), [10, 20]);
+    let skipped = ResyncRecords::<TapeRecord>::new(b"9999\nR0010\n").next().unwrap().unwrap_err();
+    assert_eq!(
+        skipped.to_string(),
+        "skipped 5 bytes at offset 0: invalid frame: expected terminator \"\\n\", content: \"9999\\nR\""
+    );
+    // Without a boundary, any bytes that frame and validate pass for a record
+    assert_eq!(qtys(ResyncRecords::new(b"R0010\n99990\nR0020\n")), [10, 9990, 20]);
+    let bounded = ResyncRecords::<TapeRecord>::new(b"R0010\n99990\nR0020\n").boundary(|frame| frame[0] == b'R');
+    assert_eq!(qtys(bounded), [10, 20]);
+}
+
+#[test]
+fn test_accumulator() {
+    use binary_mirror::Accumulator;
+
+    let mut acc = Accumulator::<TapeRecord>::new();
+    let qtys = |records: binary_mirror::Records<'_, TapeRecord>| {
+        records.map(|record| record.unwrap().qty().unwrap()).collect::<Vec<_>>()
+    };
+    assert_eq!(qtys(acc.push(b"R00")), Vec::<i32>::new());
+    assert_eq!(acc.pending(), b"R00");
+    assert_eq!(qtys(acc.push(b"10\nR0020\nR0")), [10, 20]);
+    assert_eq!(qtys(acc.push(b"030")), Vec::<i32>::new());
+    assert_eq!(qtys(acc.push(b"\n")), [30]);
+    assert!(acc.pending().is_empty());
+
+    // Records left in the iterator come with the next push
+    let mut records = acc.push(b"R0040\nR0050\n");
+    assert_eq!(records.len(), 2);
+    assert_eq!(records.next().unwrap().unwrap().qty(), Some(40));
+    assert_eq!(qtys(acc.push(b"R0060\n")), [50, 60]);
+
+    // A broken frame is skipped, its length known from the frame
+    let mut records = acc.push(b"R0070|R0080\n");
+    assert_eq!(
+        records.next().unwrap().unwrap_err().to_string(),
+        "invalid frame: expected terminator \"\\n\", content: \"R0070|\""
+    );
+    assert_eq!(records.next().unwrap().unwrap().qty(), Some(80));
+
+    acc.push(b"R00");
+    acc.clear();
+    assert!(acc.pending().is_empty());
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct VenueDates {
+    #[bm(type = "date", format = "%Y%m%d")]
+    trade_date: [u8; 8],
+    #[bm(type = "time", format = "%H%M%S")]
+    trade_time: [u8; 6],
+    #[bm(type = "datetime", format = "%Y%m%d%H%M%S", none_if_blank = true)]
+    settled: [u8; 14],
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct VenueFill {
+    #[bm(flatten = "VenueDates")]
+    dates: [u8; 28],
+    #[bm(type = "date", format = "%Y%m%d")]
+    value_date: [u8; 8],
+}
+
+#[test]
+fn test_runtime_formats() {
+    use binary_mirror::FormatOverrides;
+
+    let dates = VenueDates::from_bytes(b"02012024090000              ").unwrap();
+    assert_eq!(dates.trade_date(), None);
+    assert_eq!(
+        dates.trade_date_with_format("%d%m%Y"),
+        chrono::NaiveDate::from_ymd_opt(2024, 1, 2)
+    );
+    assert_eq!(dates.trade_time_with_format("%S%M%H"), chrono::NaiveTime::from_hms_opt(0, 0, 9));
+    assert_eq!(dates.settled_with_format("%d%m%Y%H%M%S"), None);
+
+    let overrides = FormatOverrides::new()
+        .with("trade_date", "%Y%m%d")
+        .with("trade_date", "%d%m%Y")
+        .with("value_date", "%d%m%Y");
+    assert_eq!(overrides.get("trade_date"), Some("%d%m%Y"));
+    assert_eq!(overrides.get("trade_time"), None);
+
+    let fill = VenueFill::from_bytes(b"02012024090000              03012024").unwrap();
+    let native = fill.to_native_with_formats(&overrides);
+    assert_eq!(native.dates.trade_date, chrono::NaiveDate::from_ymd_opt(2024, 1, 2));
+    assert_eq!(native.dates.trade_time, chrono::NaiveTime::from_hms_opt(9, 0, 0));
+    assert_eq!(native.dates.settled, None);
+    assert_eq!(native.value_date, chrono::NaiveDate::from_ymd_opt(2024, 1, 3));
+    assert_eq!(fill.to_native().value_date, None);
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct LayoutQuote {
+    #[bm(type = "str", alias = "sym")]
+    symbol: [u8; 6],
+    #[bm(type = "i16")]
+    lots: [u8; 6],
+    #[bm(type = "decimal")]
+    price: [u8; 8],
+    #[bm(type = "datetime", format = "%Y%m%d%H%M%S")]
+    ts: [u8; 14],
+    #[bm(type = "bytes")]
+    flags: [u8; 2],
+    #[bm(pad)]
+    _filler: [u8; 2],
 }
 
 #[test]
-fn test_native_to_raw() {
-    // Create native struct with builder pattern
-    let native = TestStructNative::default()
-        .with_name("AAPL")
-        .with_value(123)
-        .with_decimal(Decimal::from_str("123.45").unwrap())
-        .with_f32(123.4)
-        .with_exchange("NYSE")
-        .with_datetime(NaiveDateTime::new(
-            NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
-            NaiveTime::from_hms_opt(12, 34, 56).unwrap(),
-        ))
-        .with_side(OrderSide::Buy);
+fn test_dynamic_mirror() {
+    use binary_mirror::DynamicMirror;
 
-    // Convert directly to raw binary struct
-    let raw = native.to_raw();
+    let records: [&[u8]; 2] = [
+        b"2330    10  585.50  20240102090000\x00\x01  ",
+        b"2317  99999 abc     2024010209    \xff\x10  ",
+    ];
+    let mirror = DynamicMirror::from_descriptor(LayoutQuote::descriptor()).unwrap();
+    assert_eq!(mirror.size(), std::mem::size_of::<LayoutQuote>());
+    for bytes in records {
+        let quote = LayoutQuote::from_bytes(bytes).unwrap();
+        assert_eq!(
+            mirror.decode_value(bytes).unwrap(),
+            serde_json::to_value(quote.to_native()).unwrap()
+        );
+    }
+    assert_eq!(mirror.decode_many(&records.concat()).unwrap().len(), 2);
+    assert!(mirror.decode(&records[0][1..]).is_err());
 
-    // Verify values
-    assert_eq!(raw.name(), Some("AAPL".to_string()));
-    assert_eq!(raw.value(), Some(123));
-    assert_eq!(raw.decimal(), Some(Decimal::from_str("123.45").unwrap()));
-    assert_eq!(raw.f32(), Some(123.4));
-    assert_eq!(raw.exchange(), Some("NYSE".to_string()));
+    let csv = "name,offset,size,type,format\n\
+               sym,0,6,str\n\
+               price,14,8,decimal\n\
+               trade_date,,8,date,%Y%m%d\n\
+               side,,1,enum\n";
+    let mirror = DynamicMirror::from_csv(csv).unwrap();
+    assert_eq!(mirror.size(), 31);
+    assert_eq!(mirror.fields()[2].offset, 22);
     assert_eq!(
-        raw.datetime(),
-        Some(NaiveDateTime::new(
-            NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
-            NaiveTime::from_hms_opt(12, 34, 56).unwrap()
-        ))
+        serde_json::Value::Object(mirror.decode(b"2330  ........585.50  20240102B").unwrap()),
+        serde_json::json!({
+            "sym": "2330",
+            "price": "585.5",
+            "trade_date": "2024-01-02",
+            "side": "B",
+        })
     );
-    assert_eq!(raw.side(), Some(OrderSide::Buy));
 
-    // Test roundtrip
-    let native2 = raw.to_native();
-    let raw2 = native2.to_raw();
-    assert_eq!(raw.to_bytes(), raw2.to_bytes());
+    let built = DynamicMirror::new()
+        .field("sym", FieldKind::Str, 6)
+        .unwrap()
+        .field_at("price", 14, 8, FieldKind::Decimal, None)
+        .unwrap()
+        .field("trade_date", FieldKind::Date, 8)
+        .unwrap()
+        .field("side", FieldKind::Enum, 1)
+        .unwrap();
+    assert_eq!(built.fields()[2].format, None);
+    assert_eq!(built.size(), mirror.size());
+
+    let err = DynamicMirror::from_csv("sym,0,6,str\nqty,6,four,i32\n").unwrap_err();
+    assert_eq!(err.to_string(), "invalid layout at line 2: size of field `qty` is not a number: \"four\"");
+    let err = DynamicMirror::from_csv("sym,0,6,str\nsym,6,4,money\n").unwrap_err();
+    assert_eq!(err.to_string(), "invalid layout at line 2: unknown type \"money\" of field `sym`");
+    let err = DynamicMirror::new().field("sym", FieldKind::Str, 6).unwrap().field("sym", FieldKind::Str, 6);
+    assert_eq!(err.unwrap_err().to_string(), "invalid layout: field `sym` is defined twice");
 }
 
 #[test]
-fn test_native_struct_code() {
-    let code = TestStruct::native_struct_code();
+fn test_schema() {
+    use binary_mirror::{Schema, SchemaMismatch};
+
+    let schema = LayoutQuote::schema();
+    assert_eq!(schema.name, "LayoutQuote");
+    assert_eq!(schema.size, 38);
+    assert_eq!(schema.field("ts").unwrap().format.as_deref(), Some("%Y%m%d%H%M%S"));
+    assert_eq!(Schema::from_json(&schema.to_json()).unwrap(), schema);
+    assert_eq!(Schema::from_yaml(&schema.to_yaml()).unwrap(), schema);
+    assert!(LayoutQuote::check_schema(&schema).is_ok());
+
+    let published = Schema::from_yaml(
+        "name: VenueQuote
+size: 40
+fields:
+- { name: symbol, offset: 0, size: 6, type: str }
+- { name: lots, offset: 6, size: 6, type: i32 }
+- { name: price, offset: 12, size: 10, type: decimal }
+- { name: ts, offset: 22, size: 14, type: datetime, format: '%Y%m%d%H%M%S' }
+- { name: _filler, offset: 36, size: 2, type: pad }
+- { name: venue, offset: 38, size: 2, type: str }
+",
+    )
+    .unwrap();
+    let err = LayoutQuote::check_schema(&published).unwrap_err();
     assert_eq!(
-        code,
-        r#"pub struct TestStructNative {
-    pub name: Option<String>,
-    pub value: Option<i32>,
-    pub decimal: Option<rust_decimal::Decimal>,
-    pub f32: Option<f32>,
-    pub exchange: Option<String>,
-    pub datetime: Option<chrono::NaiveDateTime>,
-    pub side: Option<OrderSide>,
-}"#
+        err.mismatches(),
+        [
+            SchemaMismatch::Size {
+                expected: 38,
+                actual: 40
+            },
+            SchemaMismatch::Type {
+                field: "lots".to_string(),
+                expected: Some("i16".to_string()),
+                actual: Some("i32".to_string())
+            },
+            SchemaMismatch::FieldSize {
+                field: "price".to_string(),
+                expected: 8,
+                actual: 10
+            },
+            SchemaMismatch::Offset {
+                field: "ts".to_string(),
+                expected: 20,
+                actual: 22
+            },
+            SchemaMismatch::MissingField {
+                field: "flags".to_string()
+            },
+            SchemaMismatch::UnknownField {
+                field: "venue".to_string()
+            },
+        ]
+    );
+    assert_eq!(
+        err.to_string(),
+        "schema of LayoutQuote doesn't match: size is 40 instead of 38; field `lots` has type i32 instead of i16; \
+         field `price` is 10 bytes instead of 8; field `ts` is at offset 22 instead of 20; field `flags` is missing; \
+         field `venue` is unknown"
+    );
+}
+
+#[test]
+fn test_schema_flatten() {
+    use binary_mirror::SchemaMismatch;
+
+    assert!(WithHeader::field("header").unwrap().nested.is_some());
+    let schema = WithHeader::schema();
+    let fields = schema.fields.iter().map(|field| (field.name.as_str(), field.offset)).collect::<Vec<_>>();
+    assert_eq!(fields, [("header.msg_type", 0), ("header.seq", 2), ("symbol", 6), ("qty", 10)]);
+    assert_eq!(schema.field("header.seq").unwrap().type_name.as_deref(), Some("u32"));
+
+    let mut published = schema.clone();
+    published.fields[1].type_name = Some("i32".to_string());
+    assert_eq!(
+        WithHeader::check_schema(&published).unwrap_err().mismatches(),
+        [SchemaMismatch::Type {
+            field: "header.seq".to_string(),
+            expected: Some("u32".to_string()),
+            actual: Some("i32".to_string())
+        }]
+    );
+    assert_eq!(
+        binary_mirror::compat::diff(&schema, &published).to_string(),
+        "WithHeader (13 bytes) -> WithHeader (13 bytes)\n  retyped `header.seq` from u32 to i32"
     );
 }
 
 #[repr(C)]
 #[derive(BinaryMirror)]
-#[bm(derive(Debug, Clone))]
-struct CustomDerives {
+struct AllocationV1 {
     #[bm(type = "str")]
-    name: [u8; 10],
+    account: [u8; 8],
+    #[bm(type = "i32")]
+    qty: [u8; 6],
+    #[bm(type = "date", format = "%Y%m%d")]
+    trade_date: [u8; 8],
 }
 
-#[test]
-fn test_custom_native_derives() {
-    let code = CustomDerives::native_struct_code();
-    assert_eq!(
-        code,
-        r#"pub struct CustomDerivesNative {
-    pub name: Option<String>,
-}"#
-    );
-
-    // Test that the actual struct has the same derives
-    let native = CustomDerivesNative::default();
-    let _cloned = native.clone(); // Should compile because we have Clone
-    let _debug = format!("{:?}", native); // Should compile because we have Debug
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct AllocationV2 {
+    #[bm(type = "str")]
+    account: [u8; 8],
+    #[bm(type = "i64")]
+    qty: [u8; 8],
+    #[bm(type = "date", format = "%d%m%Y")]
+    trade_date: [u8; 8],
+    #[bm(type = "str")]
+    giveup: [u8; 4],
 }
 
 #[repr(C)]
 #[derive(BinaryMirror)]
-struct WithSkippedFields {
+struct AllocationV3 {
     #[bm(type = "str")]
-    name: [u8; 10],
-    #[bm(type = "i32", skip = true, default_byte = b'\x33')]
-    skipped_value: [u8; 4],
+    account: [u8; 8],
+    #[bm(type = "i32")]
+    qty: [u8; 6],
+    #[bm(type = "date", format = "%Y%m%d")]
+    trade_date: [u8; 8],
     #[bm(type = "str")]
-    description: [u8; 20],
+    giveup: [u8; 4],
 }
 
 #[test]
-fn test_skipped_fields() {
-    let native = WithSkippedFieldsNative::default()
-        .with_name("TEST")
-        .with_description("Description");
-
-    let raw = native.to_raw();
-    let bytes = raw.to_bytes();
-    let parsed = WithSkippedFields::from_bytes(&bytes).unwrap();
-
-    // Verify values
-    assert_eq!(raw.name(), Some("TEST".to_string()));
-    assert_eq!(raw.description(), Some("Description".to_string()));
-    assert_eq!(parsed.name(), Some("TEST".to_string()));
-    assert_eq!(parsed.description(), Some("Description".to_string()));
+fn test_compat_check() {
+    use binary_mirror::compat::{self, FieldChange};
 
-    // Verify native struct code doesn't include skipped field
-    let code = WithSkippedFields::native_struct_code();
+    let report = compat::check::<AllocationV1, AllocationV2>();
+    assert!(!report.is_identical());
+    assert!(!report.is_append_only());
     assert_eq!(
-        code,
-        r#"pub struct WithSkippedFieldsNative {
-    pub name: Option<String>,
-    pub description: Option<String>,
-}"#
+        report.changes,
+        [
+            FieldChange::Resized {
+                field: "qty".to_string(),
+                from: 6,
+                to: 8
+            },
+            FieldChange::Retyped {
+                field: "qty".to_string(),
+                from: Some("i32".to_string()),
+                to: Some("i64".to_string())
+            },
+            FieldChange::Moved {
+                field: "trade_date".to_string(),
+                from: 14,
+                to: 16
+            },
+            FieldChange::Reformatted {
+                field: "trade_date".to_string(),
+                from: Some("%Y%m%d".to_string()),
+                to: Some("%d%m%Y".to_string())
+            },
+            FieldChange::Added {
+                field: "giveup".to_string(),
+                offset: 24,
+                size: 4
+            },
+        ]
+    );
+    assert_eq!(
+        report.to_string(),
+        "AllocationV1 (22 bytes) -> AllocationV2 (28 bytes)\n  \
+         resized `qty` from 6 to 8 bytes\n  \
+         retyped `qty` from i32 to i64\n  \
+         moved `trade_date` from offset 14 to 16\n  \
+         reformatted `trade_date` from %Y%m%d to %d%m%Y\n  \
+         added `giveup` at offset 24, 4 bytes"
     );
 
-    // Test roundtrip
-    let native2 = raw.to_native();
-    let raw2 = native2.to_raw();
-    assert_eq!(raw.to_bytes(), raw2.to_bytes());
-    assert_eq!(WithSkippedFields::size(), 34);
-    assert_eq!(WithSkippedFields::SIZE, 34);
+    let report = compat::check::<AllocationV1, AllocationV3>();
+    assert!(report.is_append_only());
+    assert_eq!(report.added().map(FieldChange::field).collect::<Vec<_>>(), ["giveup"]);
+
+    let report = compat::check::<AllocationV3, AllocationV1>();
+    assert!(!report.is_append_only());
+    assert_eq!(report.removed().count(), 1);
+    assert!(compat::check::<AllocationV1, AllocationV1>().is_identical());
 }
 
 #[repr(C)]
 #[derive(BinaryMirror)]
-struct WithSkipNativeStruct {
+struct DocumentedFill {
+    /// Exchange symbol, left aligned
     #[bm(type = "str")]
-    name: [u8; 10],
-    #[bm(type = "i32", skip_native = true)]
-    raw_value: [u8; 4],
+    symbol: [u8; 6],
+    /// Filled quantity in lots
+    ///
+    /// Negative for a bust | cancel
+    #[bm(type = "i32")]
+    qty: [u8; 6],
     #[bm(type = "str")]
-    description: [u8; 5],
+    venue: [u8; 4],
 }
 
 #[test]
-fn test_skip_native() {
-    let raw = WithSkipNativeStruct {
-        name: *b"TEST      ",
-        raw_value: *b"1234",
-        description: *b"Descr",
-    };
+fn test_field_docs() {
+    assert_eq!(DocumentedFill::field("symbol").unwrap().doc, Some("Exchange symbol, left aligned"));
+    assert_eq!(
+        DocumentedFill::field("qty").unwrap().doc,
+        Some("Filled quantity in lots\n\nNegative for a bust | cancel")
+    );
+    assert_eq!(DocumentedFill::field("venue").unwrap().doc, None);
 
-    // Field should be accessible in raw struct
-    assert_eq!(raw.raw_value(), Some(1234));
+    let table = DocumentedFill::layout_table();
+    assert!(table.contains("| symbol | 0 | 6 | str |  | ' ' | Exchange symbol, left aligned |"));
+    assert!(table.contains("| qty | 6 | 6 | i32 |  | ' ' | Filled quantity in lots  Negative for a bust \\| cancel |"));
+    assert!(table.contains("| venue | 12 | 4 | str |  | ' ' |  |"));
 
-    // Convert to native
-    let native = raw.to_native();
+    assert_eq!(
+        DocumentedFill::native_struct_code(),
+        "pub struct DocumentedFillNative {\n    \
+         /// Exchange symbol, left aligned\n    \
+         pub symbol: Option<String>,\n    \
+         /// Filled quantity in lots\n    \
+         ///\n    \
+         /// Negative for a bust | cancel\n    \
+         pub qty: Option<i32>,\n    \
+         pub venue: Option<String>,\n}"
+    );
+}
 
-    // Field should not appear in native struct
-    let code = WithSkipNativeStruct::native_struct_code();
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct WithNativeAttrs {
+    #[bm(type = "str", native_attrs(doc(alias = "ticker"), serde(rename = "sym")))]
+    symbol: [u8; 6],
+    #[bm(
+        type = "i32",
+        native_serde(rename = "qty"),
+        native_attrs(serde(default), allow(clippy::option_option))
+    )]
+    quantity: [u8; 4],
+}
+
+#[test]
+fn test_native_attrs_pass_through() {
+    let raw = WithNativeAttrs::from_bytes(b"2330    10").unwrap();
+    assert_eq!(serde_json::to_string(&raw.to_native()).unwrap(), r#"{"sym":"2330","qty":10}"#);
+    let native: WithNativeAttrsNative = serde_json::from_str(r#"{"sym":"2317"}"#).unwrap();
+    assert_eq!(native.quantity, None);
     assert_eq!(
-        code,
-        r#"pub struct WithSkipNativeStructNative {
-    pub name: Option<String>,
-    pub description: Option<String>,
+        WithNativeAttrs::native_struct_code(),
+        r#"pub struct WithNativeAttrsNative {
+    #[serde(rename = "sym")]
+    #[doc(alias = "ticker")]
+    pub symbol: Option<String>,
+    #[serde(rename = "qty", default)]
+    #[allow(clippy::option_option)]
+    pub quantity: Option<i32>,
 }"#
     );
+}
 
-    // Roundtrip should preserve the raw value
-    let raw2 = native.to_raw();
-    assert_eq!(raw2.raw_value(), None);
+#[repr(C)]
+#[derive(BinaryMirror)]
+#[bm(display_format = "multiline")]
+struct MultilineFill {
+    #[bm(type = "str")]
+    symbol: [u8; 6],
+    #[bm(type = "bytes")]
+    flags: [u8; 2],
+    #[bm(type = "i32")]
+    qty: [u8; 4],
 }
 
 #[repr(C)]
 #[derive(BinaryMirror)]
-struct WithStringTypes {
+#[bm(display_format = "keyvalue")]
+struct KeyValueFill {
     #[bm(type = "str")]
-    regular: [u8; 10],
-    #[bm(type = "compact_str")]
-    compact: [u8; 10],
-    // #[bm(type = "hipstr")]
-    // hip: [u8; 10],
+    symbol: [u8; 6],
+    #[bm(type = "bytes")]
+    flags: [u8; 2],
+    #[bm(type = "i32")]
+    qty: [u8; 4],
+    #[bm(type = "enum", enum_type = "OrderSide")]
+    side: [u8; 1],
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct CompactFill {
+    #[bm(type = "bytes")]
+    flags: [u8; 2],
+    #[bm(type = "str")]
+    symbol: [u8; 6],
+    #[bm(type = "i32")]
+    qty: [u8; 4],
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+#[bm(display = false)]
+struct CustomDisplayFill {
+    #[bm(type = "str")]
+    symbol: [u8; 6],
+    #[bm(type = "i32")]
+    qty: [u8; 4],
+}
+
+impl std::fmt::Display for CustomDisplayFill {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} x{}", self.symbol().unwrap_or_default(), self.qty().unwrap_or_default())
+    }
 }
 
 #[test]
-fn test_string_types() {
-    let native = WithStringTypesNative::default()
-        .with_regular("test")
-        .with_compact("test");
-    // .with_hip(HipStr::from("test"));
+fn test_display_styles() {
+    // Bytes fields are left out along with their separator
+    let fill = MultilineFill::from_bytes(b"2330  XY  10").unwrap();
+    assert_eq!(fill.to_string(), "MultilineFill {\n    symbol: 2330,\n    qty: 10\n}");
 
-    let raw = native.to_raw();
-    assert_eq!(raw.regular(), Some("test".to_string()));
-    assert_eq!(raw.compact(), Some("test".to_compact_string()));
-    // assert_eq!(raw.hip(), "test");
+    let fill = KeyValueFill::from_bytes(b"2330  XYabcdS").unwrap();
+    assert_eq!(fill.to_string(), "symbol=2330 qty=Error<bytes: \"abcd\"> side=Sell");
 
-    // Test roundtrip
-    // let native2 = raw.to_native();
-    // assert_eq!(native2.hip, HipStr::from("test"));
+    let fill = CompactFill::from_bytes(b"XY2330    10").unwrap();
+    assert_eq!(fill.to_string(), "CompactFill { symbol: 2330, qty: 10 }");
+
+    let fill = CustomDisplayFill::from_bytes(b"2330    10").unwrap();
+    assert_eq!(fill.to_string(), "2330 x10");
 }
 
 #[repr(C)]
 #[derive(BinaryMirror)]
-struct ReversedDateTime {
-    #[bm(
-        type = "time",
-        format = "%H:%M:%S",
-        datetime_with = "date",
-        alias = "datetime"
-    )]
-    time: [u8; 8],
-    #[bm(type = "date", format = "%Y-%m-%d")]
-    date: [u8; 10],
-    #[bm(type = "date", format = "%Y-%m-%d", datetime_with = "tt", alias = "ddtt")]
-    dd: [u8; 10],
-    #[bm(type = "time", format = "%H:%M:%S")]
-    tt: [u8; 8],
+#[bm(display_format = "keyvalue", view(name = "AccountView", fields(account, qty)))]
+struct RedactedFill {
+    #[bm(type = "str", redact = true)]
+    account: [u8; 7],
+    #[bm(type = "str", redact_serde = true)]
+    client_id: [u8; 4],
+    #[bm(type = "i32")]
+    qty: [u8; 4],
 }
 
 #[test]
-fn test_reversed_datetime() {
-    let raw = ReversedDateTime {
-        time: *b"12:34:56",
-        date: *b"2024-01-01",
-        dd: *b"2024-01-02",
-        tt: *b"12:34:56",
-    };
+fn test_redact() {
+    let fill = RedactedFill::from_bytes(b"9801234C001  10").unwrap();
+    assert_eq!(fill.account(), Some("9801234".to_string()));
+    assert_eq!(
+        format!("{:?}", fill),
+        "RedactedFill { account: ***, client_id: ***, qty: hex: [0x20, 0x20, 0x31, 0x30], bytes: \"  10\" }"
+    );
+    assert_eq!(fill.to_string(), "account=*** client_id=*** qty=10");
 
-    // Test parsing
+    let native = fill.to_native();
+    assert_eq!(native.account, Some("9801234".to_string()));
     assert_eq!(
-        raw.datetime(),
-        Some(NaiveDateTime::new(
-            NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
-            NaiveTime::from_hms_opt(12, 34, 56).unwrap()
-        ))
+        format!("{:?}", native),
+        "RedactedFillNative { account: ***, client_id: ***, qty: Some(10) }"
     );
-    assert_eq!(raw.ddtt(), Some(NaiveDateTime::new(
-        NaiveDate::from_ymd_opt(2024, 1, 2).unwrap(),
-        NaiveTime::from_hms_opt(12, 34, 56).unwrap()
-    )));
+    assert_eq!(format!("{:?}", fill.to_account_view()), "AccountView { account: ***, qty: Some(10) }");
 
-    // Test roundtrip
-    let native = raw.to_native();
-    let raw2 = native.to_raw();
-    assert_eq!(raw.datetime(), raw2.datetime());
-    assert_eq!(raw.to_bytes(), raw2.to_bytes());
+    // Only redact_serde masks serialization, which then can't round trip the value
+    let json = serde_json::to_string(&native).unwrap();
+    assert_eq!(json, r#"{"account":"9801234","client_id":"***","qty":10}"#);
+
+    let other = RedactedFill::from_bytes(b"9805678C001  20").unwrap();
+    let diffs = fill.diff(other);
+    assert_eq!((diffs[0].field, diffs[0].left.as_str(), diffs[0].right.as_str()), ("account", "***", "***"));
+    assert_eq!((diffs[1].left.as_str(), diffs[1].right.as_str()), ("10", "20"));
+}
+
+#[test]
+fn test_inspect() {
+    let fill = RedactedFill::from_bytes(b"9801234C001  1x").unwrap();
+    assert_eq!(
+        fill.inspect(),
+        r#"RedactedFill {
+    account   @ [0..7]    hex: [***]  value: ***
+    client_id @ [7..11]   hex: [***]  value: ***
+    qty       @ [11..15]  hex: [0x20, 0x20, 0x31, 0x78]  value: Error<bytes: "  1x">
+}"#
+    );
+
+    let raw = WithSerdeRaw::from_bytes(b"2330    10S").unwrap();
+    let fields = raw.inspect_fields();
+    assert_eq!(fields.len(), 3);
+    assert_eq!(
+        fields[2],
+        binary_mirror::FieldInspect {
+            field: "side",
+            offset: 10,
+            limit: 11,
+            hex: "0x53".to_string(),
+            value: "Sell".to_string(),
+        }
+    );
+    assert_eq!(fields[1].to_string(), "qty @ [6..10] hex: [0x20, 0x20, 0x31, 0x30] value: 10");
 }
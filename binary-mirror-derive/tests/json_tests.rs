@@ -0,0 +1,103 @@
+#![cfg(feature = "json")]
+
+use binary_mirror::{FromBytes, SerdeView, ToNative};
+use binary_mirror_derive::{BinaryEnum, BinaryMirror};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, PartialEq, BinaryEnum, Serialize, Deserialize)]
+enum OrderSide {
+    #[bv(value = b"B")]
+    Buy,
+    #[bv(value = b"S")]
+    Sell,
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct Venue {
+    #[bm(type = "str")]
+    exchange: [u8; 4],
+    #[bm(type = "bytes", bytes_repr = "hex")]
+    session: [u8; 2],
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct Fill {
+    #[bm(type = "str", native_serde(rename = "sym"))]
+    symbol: [u8; 6],
+    #[bm(type = "i32", native_serde(skip_serializing_if = "Option::is_none"))]
+    qty: [u8; 4],
+    #[bm(type = "decimal")]
+    price: [u8; 8],
+    #[bm(type = "datetime", format = "%Y%m%d%H%M%S")]
+    ts: [u8; 14],
+    #[bm(type = "enum", enum_type = "OrderSide")]
+    side: [u8; 1],
+    #[bm(type = "str", none_value = b"----")]
+    account: [u8; 4],
+    #[bm(flatten = "Venue")]
+    venue: [u8; 6],
+}
+
+#[test]
+fn test_to_json_matches_native() {
+    let records: [&[u8]; 3] = [
+        b"2330  10  585.5   20240102090000BACC1XTAI\x00\x01",
+        b"2317  abc 12.25   20240102090001S----XTAI\xff\x10",
+        b"\xff\xfe        bad     20240102      X    \xff\xff\xff\xff\x00\x00",
+    ];
+    for bytes in records {
+        let fill = Fill::from_bytes(bytes).unwrap();
+        assert_eq!(fill.to_json(), serde_json::to_string(&fill.to_native()).unwrap());
+        assert_eq!(
+            serde_json::to_value(fill.serde_view()).unwrap(),
+            serde_json::to_value(fill.to_native()).unwrap()
+        );
+    }
+
+    let fill = Fill::from_bytes(b"2317  abc 12.25   20240102090001S----XTAI\xff\x10").unwrap();
+    assert_eq!(
+        fill.to_json(),
+        r#"{"sym":"2317","price":"12.25","ts":"2024-01-02T09:00:01","side":"Sell","account":null,"exchange":"XTAI","session":"ff10"}"#
+    );
+
+    let mut out = Vec::new();
+    fill.write_json(&mut out).unwrap();
+    assert_eq!(out, fill.to_json().into_bytes());
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+#[bm(computed(name = "notional", type = "Option<f64>"))]
+struct PricedTrade {
+    #[bm(type = "f64")]
+    price: [u8; 6],
+    #[bm(type = "i32")]
+    qty: [u8; 4],
+}
+
+fn notional(native: &PricedTradeNative) -> Option<f64> {
+    Some(native.price? * native.qty? as f64)
+}
+
+#[test]
+fn test_to_json_computed() {
+    let trade = PricedTrade::from_bytes(b"12.50 100 ").unwrap();
+    assert_eq!(trade.to_json(), r#"{"price":12.5,"qty":100,"notional":1250.0}"#);
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct Memo {
+    #[bm(type = "str")]
+    tag: [u8; 3],
+    #[bm(type = "rest")]
+    memo: [u8],
+}
+
+#[test]
+fn test_to_json_rest() {
+    let memo = Memo::from_bytes(b"ABCxy").unwrap();
+    assert_eq!(memo.to_json(), r#"{"tag":"ABC","memo":[120,121]}"#);
+}
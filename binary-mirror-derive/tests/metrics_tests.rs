@@ -0,0 +1,60 @@
+#![cfg(feature = "metrics")]
+
+use binary_mirror::{FromBytes, ToNative};
+use binary_mirror_derive::BinaryMirror;
+use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+use serde::{Deserialize, Serialize};
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+#[bm(log = "none")]
+struct Fill {
+    #[bm(type = "i32")]
+    qty: [u8; 4],
+    #[bm(type = "f64")]
+    price: [u8; 6],
+}
+
+#[test]
+fn test_parse_failure_counter() {
+    let recorder = DebuggingRecorder::new();
+    let snapshotter = recorder.snapshotter();
+    metrics::with_local_recorder(&recorder, || {
+        let raw = Fill::from_bytes(b"12x 1.5   ").unwrap();
+        let _ = raw.to_native();
+        let _ = raw.qty_with_warn();
+        let raw = Fill::from_bytes(b"12  abc   ").unwrap();
+        let (_, errors) = raw.to_native_with_report();
+        assert_eq!(errors.len(), 1);
+    });
+
+    let mut counts = snapshotter
+        .snapshot()
+        .into_vec()
+        .into_iter()
+        .map(|(key, _, _, value)| {
+            let key = key.key();
+            let labels: Vec<_> = key
+                .labels()
+                .map(|label| format!("{}={}", label.key(), label.value()))
+                .collect();
+            (key.name().to_string(), labels, value)
+        })
+        .collect::<Vec<_>>();
+    counts.sort_by(|a, b| a.1.cmp(&b.1));
+    assert_eq!(
+        counts,
+        [
+            (
+                "binary_mirror_parse_failures".to_string(),
+                vec!["struct=Fill".to_string(), "field=price".to_string()],
+                DebugValue::Counter(1)
+            ),
+            (
+                "binary_mirror_parse_failures".to_string(),
+                vec!["struct=Fill".to_string(), "field=qty".to_string()],
+                DebugValue::Counter(2)
+            ),
+        ]
+    );
+}
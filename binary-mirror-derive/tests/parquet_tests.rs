@@ -0,0 +1,67 @@
+#![cfg(feature = "parquet")]
+
+use binary_mirror::arrow::arrow_array::cast::AsArray;
+use binary_mirror::arrow::arrow_array::types::Int32Type;
+use binary_mirror::parquet::{write_parquet, write_parquet_with};
+use binary_mirror::FromBytes;
+use binary_mirror_derive::BinaryMirror;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use serde::{Deserialize, Serialize};
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct Quote {
+    #[bm(type = "str")]
+    symbol: [u8; 6],
+    #[bm(type = "i32")]
+    qty: [u8; 4],
+}
+
+fn read_back(path: &std::path::Path) -> Vec<(String, i32)> {
+    let reader = ParquetRecordBatchReaderBuilder::try_new(std::fs::File::open(path).unwrap())
+        .unwrap()
+        .build()
+        .unwrap();
+    reader
+        .flat_map(|batch| {
+            let batch = batch.unwrap();
+            let symbol = batch.column(0).as_string::<i32>().clone();
+            let qty = batch.column(1).as_primitive::<Int32Type>().clone();
+            (0..batch.num_rows())
+                .map(|i| (symbol.value(i).to_string(), qty.value(i)))
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+#[test]
+fn test_write_parquet() {
+    let bytes = b"2330  10  2317  20  2454  30  ";
+    let records = Quote::from_bytes_many(bytes).unwrap();
+    let path = std::env::temp_dir().join(format!("binary_mirror_quotes_{}.parquet", std::process::id()));
+
+    write_parquet::<Quote, _, _>(std::fs::File::create(&path).unwrap(), records).unwrap();
+    assert_eq!(
+        read_back(&path),
+        [("2330".to_string(), 10), ("2317".to_string(), 20), ("2454".to_string(), 30)]
+    );
+
+    // Owned records built from natives, split over several batches
+    let natives = [
+        QuoteNative::default().with_symbol("1101").with_qty(1),
+        QuoteNative::default().with_symbol("1102").with_qty(2),
+        QuoteNative::default().with_symbol("1103").with_qty(3),
+    ];
+    write_parquet_with::<Quote, _, _>(
+        std::fs::File::create(&path).unwrap(),
+        natives.iter().map(QuoteNative::to_raw),
+        2,
+        None,
+    )
+    .unwrap();
+    assert_eq!(
+        read_back(&path),
+        [("1101".to_string(), 1), ("1102".to_string(), 2), ("1103".to_string(), 3)]
+    );
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,16 @@
+use binary_mirror_derive::BinaryMirror;
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct AssumeTz {
+    #[bm(type = "datetime", assume_tz = "Taipei")]
+    traded: [u8; 14],
+    #[bm(type = "datetime", assume_tz = "+8")]
+    settled: [u8; 14],
+    #[bm(type = "time", assume_tz = "utc")]
+    time: [u8; 6],
+    #[bm(type = "datetime", to = "utc")]
+    shown: [u8; 14],
+}
+
+fn main() {}
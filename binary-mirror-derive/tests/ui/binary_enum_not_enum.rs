@@ -0,0 +1,8 @@
+use binary_mirror_derive::BinaryEnum;
+
+#[derive(BinaryEnum)]
+struct NotEnum {
+    value: u8,
+}
+
+fn main() {}
@@ -0,0 +1,17 @@
+use binary_mirror_derive::BinaryMirror;
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct UnknownRepr {
+    #[bm(type = "bytes", bytes_repr = "hexa")]
+    key: [u8; 4],
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct ReprOnStr {
+    #[bm(type = "str", bytes_repr = "base64")]
+    symbol: [u8; 4],
+}
+
+fn main() {}
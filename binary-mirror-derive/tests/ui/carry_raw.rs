@@ -0,0 +1,10 @@
+use binary_mirror_derive::BinaryMirror;
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct CarryRaw {
+    #[bm(type = "i32", carry_raw = true)]
+    qty: [u8; 4],
+}
+
+fn main() {}
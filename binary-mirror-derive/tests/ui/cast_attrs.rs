@@ -0,0 +1,21 @@
+use binary_mirror_derive::BinaryMirror;
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+#[bm(cast = "bytemuk")]
+struct UnknownCast {
+    #[bm(type = "str")]
+    symbol: [u8; 4],
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+#[bm(cast = "bytemuck")]
+struct CastWithRest {
+    #[bm(type = "str")]
+    symbol: [u8; 4],
+    #[bm(type = "rest")]
+    memo: [u8],
+}
+
+fn main() {}
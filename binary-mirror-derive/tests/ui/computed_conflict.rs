@@ -0,0 +1,11 @@
+use binary_mirror_derive::BinaryMirror;
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+#[bm(computed(name = "price", type = "f64"))]
+struct ComputedConflict {
+    #[bm(type = "f64")]
+    price: [u8; 6],
+}
+
+fn main() {}
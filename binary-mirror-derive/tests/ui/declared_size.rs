@@ -0,0 +1,14 @@
+use serde::{Deserialize, Serialize};
+use binary_mirror_derive::BinaryMirror;
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+#[bm(size = 16)]
+struct DeclaredSize {
+    #[bm(type = "str")]
+    name: [u8; 10],
+    #[bm(type = "i32")]
+    value: [u8; 4],
+}
+
+fn main() {}
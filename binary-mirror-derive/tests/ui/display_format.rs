@@ -0,0 +1,19 @@
+use binary_mirror_derive::BinaryMirror;
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+#[bm(display_format = "multi_line")]
+struct UnknownFormat {
+    #[bm(type = "str")]
+    symbol: [u8; 4],
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+#[bm(display = false, display_format = "keyvalue")]
+struct HiddenFormat {
+    #[bm(type = "str")]
+    symbol: [u8; 4],
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use binary_mirror_derive::BinaryMirror;
+use serde::{Deserialize, Serialize};
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct Empty {
+    #[bm(type = "bytes")]
+    nothing: [u8; 0],
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use binary_mirror_derive::BinaryEnum;
+
+#[derive(BinaryEnum)]
+enum Side {
+    #[bv(value = b"B", alias = b"1")]
+    Buy,
+    #[bv(value = b"S", alias = b"B")]
+    Sell,
+}
+
+fn main() {}
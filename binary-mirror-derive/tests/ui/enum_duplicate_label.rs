@@ -0,0 +1,20 @@
+use binary_mirror_derive::BinaryEnum;
+
+#[derive(BinaryEnum)]
+enum Side {
+    #[bv(value = b"B", label = "buy")]
+    Buy,
+    #[bv(value = b"S", label = "buy")]
+    Sell,
+}
+
+#[derive(BinaryEnum)]
+#[bv(case_insensitive = true)]
+enum Flag {
+    #[bv(value = b"Y")]
+    Yes,
+    #[bv(value = b"y", label = "YES")]
+    AlsoYes,
+}
+
+fn main() {}
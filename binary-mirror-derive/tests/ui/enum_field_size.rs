@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+use binary_mirror_derive::{BinaryEnum, BinaryMirror};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, BinaryEnum, Serialize, Deserialize)]
+enum Venue {
+    #[bv(value = b"N")]
+    Nyse,
+    #[bv(value = b"NSDQ")]
+    Nasdaq,
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct Order {
+    #[bm(type = "enum", enum_type = "Venue")]
+    venue: [u8; 2],
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use binary_mirror_derive::BinaryEnum;
+
+#[derive(BinaryEnum)]
+enum Side {
+    #[bv(value = b"B")]
+    Buy,
+    #[bv(other)]
+    Other,
+}
+
+fn main() {}
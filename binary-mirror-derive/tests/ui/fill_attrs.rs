@@ -0,0 +1,14 @@
+use binary_mirror_derive::BinaryMirror;
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct FillAttrs {
+    #[bm(type = "str", fill = b'*')]
+    name: [u8; 6],
+    #[bm(type = "i32", fill = b'-')]
+    qty: [u8; 6],
+    #[bm(type = "f64", fill = b'.')]
+    price: [u8; 8],
+}
+
+fn main() {}
@@ -0,0 +1,19 @@
+use binary_mirror_derive::BinaryMirror;
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct DuplicateTag {
+    #[bm(type = "str", fix_tag = 55)]
+    symbol: [u8; 4],
+    #[bm(type = "str", fix_tag = 55)]
+    alias: [u8; 4],
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct TagOnSkipped {
+    #[bm(type = "str", fix_tag = 58, skip = true)]
+    text: [u8; 4],
+}
+
+fn main() {}
@@ -0,0 +1,18 @@
+use binary_mirror_derive::BinaryMirror;
+use serde::{Deserialize, Serialize};
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct Header {
+    #[bm(type = "str")]
+    msg_type: [u8; 2],
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct Message {
+    #[bm(flatten = "Header")]
+    header: [u8; 3],
+}
+
+fn main() {}
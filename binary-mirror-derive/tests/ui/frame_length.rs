@@ -0,0 +1,14 @@
+use serde::{Deserialize, Serialize};
+use binary_mirror_derive::BinaryMirror;
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+#[bm(frame = "ascii_len(1)")]
+struct Quote {
+    #[bm(type = "str")]
+    symbol: [u8; 8],
+    #[bm(type = "i32")]
+    qty: [u8; 4],
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use binary_mirror_derive::BinaryMirror;
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct Tagged<P> {
+    #[bm(type = "str", fix_tag = 55)]
+    symbol: [u8; 6],
+    body: P,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use binary_mirror_derive::BinaryMirror;
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct GenericLifetime<'a> {
+    #[bm(type = "str")]
+    code: [u8; 4],
+    marker: std::marker::PhantomData<&'a ()>,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use binary_mirror_derive::BinaryMirror;
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct GenericNotLast<P> {
+    body: P,
+    #[bm(type = "str")]
+    code: [u8; 4],
+}
+
+fn main() {}
@@ -0,0 +1,16 @@
+use binary_mirror_derive::{BinaryMessage, BinaryMirror};
+use serde::{Deserialize, Serialize};
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct Heartbeat {
+    #[bm(type = "str")]
+    msg_type: [u8; 1],
+}
+
+#[derive(BinaryMessage)]
+enum Message {
+    Heartbeat(Heartbeat),
+}
+
+fn main() {}
@@ -0,0 +1,20 @@
+use binary_mirror_derive::BinaryMirror;
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct MissingOptions {
+    #[bm(type = "enum")]
+    side: [u8; 1],
+    #[bm(type = "date", datetime_with = "tiem")]
+    date: [u8; 8],
+    #[bm(type = "str", none_value = b"999")]
+    name: [u8; 4],
+    #[bm(type = "checksum", algo = "crc32")]
+    crc: [u8; 4],
+    #[bm(type = "u16", validate(min = -1))]
+    qty: [u8; 4],
+    #[bm(type = "i32", validate(max = 1.5))]
+    price: [u8; 6],
+}
+
+fn main() {}
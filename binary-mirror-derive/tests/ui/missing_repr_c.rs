@@ -0,0 +1,9 @@
+use binary_mirror_derive::BinaryMirror;
+
+#[derive(BinaryMirror)]
+struct MissingReprC {
+    #[bm(type = "str")]
+    name: [u8; 10],
+}
+
+fn main() {}
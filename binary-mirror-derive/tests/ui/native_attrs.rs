@@ -0,0 +1,12 @@
+use binary_mirror_derive::BinaryMirror;
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct Padded {
+    #[bm(type = "str")]
+    symbol: [u8; 6],
+    #[bm(pad, native_attrs(doc(alias = "gap")))]
+    _gap: [u8; 2],
+}
+
+fn main() {}
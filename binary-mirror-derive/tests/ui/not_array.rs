@@ -0,0 +1,10 @@
+use binary_mirror_derive::BinaryMirror;
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct NotArray {
+    #[bm(type = "str")]
+    name: String,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use binary_mirror_derive::BinaryMirror;
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct NotU8Array {
+    #[bm(type = "str")]
+    name: [u16; 4],
+}
+
+fn main() {}
@@ -0,0 +1,16 @@
+use binary_mirror_derive::BinaryMirror;
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct NumericLocale {
+    #[bm(type = "f64", numeric_locale(decimal = ',', thousands = ','))]
+    price: [u8; 8],
+    #[bm(type = "f64", numeric_locale(decimal = '-'))]
+    bid: [u8; 8],
+    #[bm(type = "f64", numeric_locale(group))]
+    ask: [u8; 8],
+    #[bm(type = "date", numeric_locale(decimal = ','))]
+    date: [u8; 8],
+}
+
+fn main() {}
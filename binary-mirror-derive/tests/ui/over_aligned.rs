@@ -0,0 +1,10 @@
+use binary_mirror_derive::BinaryMirror;
+
+#[repr(C, align(8))]
+#[derive(BinaryMirror)]
+struct OverAligned {
+    #[bm(type = "str")]
+    name: [u8; 10],
+}
+
+fn main() {}
@@ -0,0 +1,14 @@
+use binary_mirror_derive::BinaryMirror;
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct OverlaySize {
+    #[bm(type = "str")]
+    text: [u8; 8],
+    #[bm(type = "decimal", overlay = "text")]
+    price: [u8; 8],
+    #[bm(type = "i32", overlay = "missing")]
+    qty: [u8; 0],
+}
+
+fn main() {}
@@ -0,0 +1,16 @@
+use binary_mirror_derive::BinaryMirror;
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct Reply {
+    #[bm(type = "str")]
+    status: [u8; 1],
+    #[bm(type = "str")]
+    error_text: [u8; 8],
+    #[bm(type = "decimal", overlay = "error_text", when(status = b"OK"))]
+    price: [u8; 0],
+    #[bm(type = "i32", when(status = b"P"))]
+    qty: [u8; 3],
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use binary_mirror_derive::BinaryMirror;
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct PadValue {
+    #[bm(type = "str")]
+    code: [u8; 4],
+    #[bm(pad = 12)]
+    filler: [u8; 12],
+}
+
+fn main() {}
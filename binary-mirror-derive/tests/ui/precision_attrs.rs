@@ -0,0 +1,18 @@
+use binary_mirror_derive::BinaryMirror;
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct PrecisionAttrs {
+    #[bm(type = "i32", precision = 2)]
+    qty: [u8; 6],
+    #[bm(type = "f64", format = "{:08.2}", precision = 2)]
+    price: [u8; 8],
+    #[bm(type = "decimal", precision = 4)]
+    fee: [u8; 5],
+    #[bm(type = "f64", precision = 2, rounding = "half_even")]
+    bid: [u8; 8],
+    #[bm(type = "f64", rounding = "truncate")]
+    ask: [u8; 8],
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+use binary_mirror_derive::BinaryMirror;
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+#[bm(raw_default = true)]
+struct Memo {
+    #[bm(type = "str")]
+    code: [u8; 4],
+    #[bm(type = "rest")]
+    memo: [u8],
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use binary_mirror_derive::BinaryMirror;
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+#[bm(raw_derive(Hsah))]
+struct UnknownRawDerive {
+    #[bm(type = "str")]
+    symbol: [u8; 4],
+}
+
+fn main() {}
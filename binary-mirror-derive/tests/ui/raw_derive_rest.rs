@@ -0,0 +1,13 @@
+use binary_mirror_derive::BinaryMirror;
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+#[bm(raw_derive(Clone))]
+struct ClonedMemo {
+    #[bm(type = "str")]
+    symbol: [u8; 4],
+    #[bm(type = "rest")]
+    memo: [u8],
+}
+
+fn main() {}
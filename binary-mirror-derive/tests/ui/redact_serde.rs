@@ -0,0 +1,10 @@
+use binary_mirror_derive::BinaryMirror;
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct Card {
+    #[bm(type = "bytes", bytes_repr = "hex", redact_serde = true)]
+    pan: [u8; 8],
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use binary_mirror_derive::BinaryMirror;
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct RestNotLast {
+    #[bm(type = "rest")]
+    memo: [u8],
+    #[bm(type = "str")]
+    code: [u8; 4],
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use binary_mirror_derive::BinaryMirror;
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct SignAttrs {
+    #[bm(type = "str", sign = "trailing")]
+    name: [u8; 6],
+    #[bm(type = "i32", sign = "suffix")]
+    qty: [u8; 6],
+}
+
+fn main() {}
@@ -0,0 +1,14 @@
+use binary_mirror_derive::BinaryMirror;
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct SubsecAttrs {
+    #[bm(type = "date", datetime_with = "time", subsec_with = "msec")]
+    date: [u8; 8],
+    #[bm(type = "time", subsec_with = "date")]
+    time: [u8; 6],
+    #[bm(type = "u32")]
+    msec: [u8; 3],
+}
+
+fn main() {}
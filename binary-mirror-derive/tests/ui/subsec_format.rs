@@ -0,0 +1,12 @@
+use binary_mirror_derive::BinaryMirror;
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct SubsecFormat {
+    #[bm(type = "time", format = "%H%M%S%f")]
+    odd_digits: [u8; 10],
+    #[bm(type = "time", format = "%H:%M:%S%.3f")]
+    cut: [u8; 10],
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use binary_mirror_derive::BinaryMirror;
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+#[bm(frame = "length(4)")]
+struct UnknownFrame {
+    #[bm(type = "str")]
+    name: [u8; 10],
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use binary_mirror_derive::BinaryMirror;
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+#[bm(log = "println")]
+struct Trade {
+    #[bm(type = "i32")]
+    qty: [u8; 4],
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use binary_mirror_derive::BinaryMirror;
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct UnknownType {
+    #[bm(type = "strr")]
+    name: [u8; 10],
+    #[bm(type = "i32", alais = "qty")]
+    value: [u8; 4],
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use binary_mirror_derive::BinaryMirror;
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+struct ValidateRegex {
+    #[bm(type = "str", validate(regex = "^[A-Z+$"))]
+    symbol: [u8; 6],
+}
+
+fn main() {}
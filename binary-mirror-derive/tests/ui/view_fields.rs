@@ -0,0 +1,19 @@
+use binary_mirror_derive::BinaryMirror;
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+#[bm(view(name = "PriceKey", fields(price, qty)))]
+struct UnknownViewField {
+    #[bm(type = "f64")]
+    price: [u8; 6],
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+#[bm(view(name = "PriceKey", fields(price, price)))]
+struct RepeatedViewField {
+    #[bm(type = "f64")]
+    price: [u8; 6],
+}
+
+fn main() {}
@@ -0,0 +1,69 @@
+//! Derived structs covering most field types, compiled in a `no_std` crate.
+
+#![no_std]
+extern crate alloc;
+
+use binary_mirror_derive::{BinaryEnum, BinaryMirror};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, PartialEq, BinaryEnum, Serialize, Deserialize)]
+pub enum Side {
+    #[bv(value = b"B")]
+    Buy,
+    #[bv(value = b"S")]
+    Sell,
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+#[bm(view(name = "TradeKey", fields(symbol, side)))]
+pub struct Trade {
+    #[bm(type = "str")]
+    pub symbol: [u8; 6],
+    #[bm(type = "i64")]
+    pub qty: [u8; 6],
+    #[bm(type = "decimal")]
+    pub price: [u8; 8],
+    #[bm(type = "date", format = "%Y%m%d")]
+    pub date: [u8; 8],
+    #[bm(type = "enum", enum_type = "Side")]
+    pub side: [u8; 1],
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+pub struct Header {
+    #[bm(type = "str")]
+    pub kind: [u8; 2],
+    #[bm(type = "u32")]
+    pub seq: [u8; 4],
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+#[bm(preserve_unknown = true)]
+pub struct Quote {
+    #[bm(flatten = "Header")]
+    pub header: [u8; 6],
+    #[bm(type = "compact_str")]
+    pub venue: [u8; 4],
+    #[bm(type = "f64", validate(min = 0))]
+    pub px: [u8; 8],
+    #[bm(type = "time", format = "%H%M%S")]
+    pub time: [u8; 6],
+    #[bm(type = "bytes")]
+    pub raw: [u8; 3],
+    #[bm(type = "i32", skip_native = true, carry_raw = true)]
+    pub routing: [u8; 2],
+    #[bm(type = "checksum", algo = "lrc")]
+    pub check: [u8; 1],
+}
+
+#[repr(C)]
+#[derive(BinaryMirror)]
+pub struct Memo {
+    #[bm(type = "i32")]
+    pub id: [u8; 4],
+    #[bm(type = "rest")]
+    pub memo: [u8],
+}
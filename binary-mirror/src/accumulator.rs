@@ -0,0 +1,92 @@
+//! Push-based decoding of records arriving in chunks of any size.
+
+use core::iter::FusedIterator;
+use core::marker::PhantomData;
+
+use alloc::vec::Vec;
+
+use crate::{FrameError, Framing, FromBytes};
+
+/// Buffers chunks, such as the reads of a socket, until they hold whole records
+///
+/// Each `push` appends a chunk and returns the records completed by it, viewed in place in
+/// the buffer. Bytes of a record still being received stay buffered for the next `push`.
+/// Records are framed by `T::FRAME`, a frame with wrong framing bytes is yielded as Err
+/// and skipped.
+#[derive(Debug, Clone)]
+pub struct Accumulator<T> {
+    buf: Vec<u8>,
+    /// Start of the bytes not yet yielded
+    start: usize,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T: FromBytes + Framing> Default for Accumulator<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: FromBytes + Framing> Accumulator<T> {
+    pub fn new() -> Self {
+        Self {
+            buf: Vec::new(),
+            start: 0,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Append `chunk` and iterate over the records it completes
+    ///
+    /// Records the iterator isn't advanced past are yielded by the next `push`.
+    pub fn push(&mut self, chunk: &[u8]) -> Records<'_, T> {
+        self.buf.drain(..self.start);
+        self.start = 0;
+        self.buf.extend_from_slice(chunk);
+        Records {
+            buf: &self.buf,
+            start: &mut self.start,
+            _marker: PhantomData,
+        }
+    }
+
+    /// The buffered bytes of records not yet complete or not yet yielded
+    pub fn pending(&self) -> &[u8] {
+        &self.buf[self.start..]
+    }
+
+    /// Drop the buffered bytes, e.g. after the connection is reset
+    pub fn clear(&mut self) {
+        self.buf.clear();
+        self.start = 0;
+    }
+}
+
+/// The complete records in an [`Accumulator`], see [`Accumulator::push`]
+pub struct Records<'a, T> {
+    buf: &'a [u8],
+    start: &'a mut usize,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<'a, T: FromBytes + Framing + 'a> Iterator for Records<'a, T> {
+    type Item = Result<&'a T, FrameError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let frame_len = T::FRAME.frame_len(T::SIZE);
+        let frame = self.buf.get(*self.start..*self.start + frame_len)?;
+        *self.start += frame_len;
+        Some(T::FRAME.strip(frame, T::SIZE).map(|record| {
+            T::from_bytes(record).expect("a stripped frame holds SIZE bytes")
+        }))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = (self.buf.len() - *self.start) / T::FRAME.frame_len(T::SIZE);
+        (len, Some(len))
+    }
+}
+
+impl<'a, T: FromBytes + Framing + 'a> ExactSizeIterator for Records<'a, T> {}
+
+impl<'a, T: FromBytes + Framing + 'a> FusedIterator for Records<'a, T> {}
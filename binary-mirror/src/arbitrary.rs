@@ -0,0 +1,62 @@
+//! Random records for fuzzing and property tests, enabled with the `arbitrary` feature
+//!
+//! Enable the `arbitrary` feature of `binary-mirror-derive` to have [`arbitrary::Arbitrary`]
+//! implemented for the raw and native structs, so cargo-fuzz, libfuzzer-sys and proptest's
+//! `arbitrary` interop can drive them. Values always fit their field's width and format.
+//! [`Sample`] builds fixtures through the same impls from a seed or, with the `fake`
+//! feature, a `rand::Rng`.
+//!
+//! [`arbitrary::Arbitrary`]: https://docs.rs/arbitrary
+
+use alloc::vec::Vec;
+
+pub use ::arbitrary::{Arbitrary, Error, Result, Unstructured};
+
+/// Characters used for generated `str` fields
+pub const ALPHANUMERIC: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+
+/// Bytes handed to `Arbitrary` by [`Sample`], enough for any record to draw every field
+const SAMPLE_BYTES: usize = 1 << 16;
+
+/// Between `min` and `max` bytes, each drawn from `alphabet`
+pub fn bytes_from(u: &mut Unstructured<'_>, alphabet: &[u8], min: usize, max: usize) -> Result<Vec<u8>> {
+    let len = u.int_in_range(min..=max)?;
+    (0..len).map(|_| u.choose(alphabet).copied()).collect()
+}
+
+/// Fixtures from the `Arbitrary` impl of a type, without a fuzzer's input
+pub trait Sample: for<'a> Arbitrary<'a> {
+    /// A plausible value that is the same on every call
+    fn sample() -> Self {
+        Self::sample_seeded(0)
+    }
+
+    /// A plausible value, the same for the same seed
+    fn sample_seeded(seed: u64) -> Self {
+        // splitmix64, a deterministic stream of well mixed bytes
+        let mut state = seed;
+        let mut data = Vec::with_capacity(SAMPLE_BYTES);
+        while data.len() < SAMPLE_BYTES {
+            state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+            let mut z = state;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+            data.extend_from_slice(&(z ^ (z >> 31)).to_le_bytes());
+        }
+        from_data(&data)
+    }
+
+    /// A plausible value drawn from a random number generator
+    #[cfg(feature = "fake")]
+    fn fake<R: rand::Rng + ?Sized>(rng: &mut R) -> Self {
+        let mut data = alloc::vec![0u8; SAMPLE_BYTES];
+        rng.fill_bytes(&mut data);
+        from_data(&data)
+    }
+}
+
+impl<T: for<'a> Arbitrary<'a>> Sample for T {}
+
+fn from_data<T: for<'a> Arbitrary<'a>>(data: &[u8]) -> T {
+    T::arbitrary(&mut Unstructured::new(data)).expect("a sample buffer has enough bytes for any record")
+}
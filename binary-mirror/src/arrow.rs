@@ -0,0 +1,26 @@
+//! Arrow conversion for derived structs, enabled with the `arrow` feature
+//!
+//! Enable the `arrow` feature of `binary-mirror-derive` to have `ToArrow` generated.
+
+pub use arrow_array;
+pub use arrow_schema;
+
+use arrow_array::RecordBatch;
+use arrow_schema::{ArrowError, Schema};
+
+/// Scale of the `Decimal128` columns generated for `decimal` fields
+pub const DECIMAL_SCALE: i8 = 10;
+
+/// Precision of the `Decimal128` columns generated for `decimal` fields
+pub const DECIMAL_PRECISION: u8 = 38;
+
+pub trait ToArrow {
+    /// Arrow schema with one nullable column per native field
+    fn arrow_schema() -> Schema;
+
+    /// Collect raw records into a single `RecordBatch`
+    fn to_record_batch<'a, I>(records: I) -> Result<RecordBatch, ArrowError>
+    where
+        I: IntoIterator<Item = &'a Self>,
+        Self: 'a;
+}
@@ -0,0 +1,43 @@
+/// Eight spaces packed into a word, for skipping space padding a word at a time
+const SPACES: u64 = u64::from_ne_bytes([b' '; 8]);
+
+/// Trim leading ASCII whitespace, skipping space padding eight bytes at a time
+pub fn trim_ascii_start(bytes: &[u8]) -> &[u8] {
+    let mut start = 0;
+    while let Some(chunk) = bytes.get(start..start + 8) {
+        if u64::from_ne_bytes(chunk.try_into().unwrap()) != SPACES {
+            break;
+        }
+        start += 8;
+    }
+    while start < bytes.len() && bytes[start].is_ascii_whitespace() {
+        start += 1;
+    }
+    &bytes[start..]
+}
+
+/// Trim trailing ASCII whitespace, skipping space padding eight bytes at a time
+pub fn trim_ascii_end(bytes: &[u8]) -> &[u8] {
+    let mut end = bytes.len();
+    while end >= 8 {
+        let chunk = &bytes[end - 8..end];
+        if u64::from_ne_bytes(chunk.try_into().unwrap()) != SPACES {
+            break;
+        }
+        end -= 8;
+    }
+    while end > 0 && bytes[end - 1].is_ascii_whitespace() {
+        end -= 1;
+    }
+    &bytes[..end]
+}
+
+/// Trim leading and trailing ASCII whitespace, same result as `<[u8]>::trim_ascii`
+pub fn trim_ascii(bytes: &[u8]) -> &[u8] {
+    trim_ascii_start(trim_ascii_end(bytes))
+}
+
+/// Whether the bytes are empty or only ASCII whitespace
+pub fn is_blank(bytes: &[u8]) -> bool {
+    trim_ascii_end(bytes).is_empty()
+}
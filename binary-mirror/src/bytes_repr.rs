@@ -0,0 +1,145 @@
+//! Serde adapters behind `#[bm(bytes_repr = "...")]`, serializing a `bytes` field of the
+//! native struct as a compact string instead of an array of integers.
+
+use core::fmt;
+use serde::de::{Error, Unexpected, Visitor};
+use serde::{Deserializer, Serializer};
+
+/// Lowercase hex, two characters per byte, `"0a1b"`
+///
+/// Decoding accepts either case.
+pub mod hex {
+    use super::*;
+
+    const DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+    struct Hex<'a>(&'a [u8]);
+
+    impl fmt::Display for Hex<'_> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            for b in self.0 {
+                let pair = [DIGITS[usize::from(b >> 4)], DIGITS[usize::from(b & 0xf)]];
+                // Both bytes are ASCII hex digits
+                f.write_str(core::str::from_utf8(&pair).unwrap())?;
+            }
+            Ok(())
+        }
+    }
+
+    fn digit(c: u8) -> Option<u8> {
+        match c {
+            b'0'..=b'9' => Some(c - b'0'),
+            b'a'..=b'f' => Some(c - b'a' + 10),
+            b'A'..=b'F' => Some(c - b'A' + 10),
+            _ => None,
+        }
+    }
+
+    pub fn serialize<S: Serializer, const N: usize>(bytes: &[u8; N], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&Hex(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>, const N: usize>(deserializer: D) -> Result<[u8; N], D::Error> {
+        struct HexVisitor<const N: usize>;
+
+        impl<const N: usize> Visitor<'_> for HexVisitor<N> {
+            type Value = [u8; N];
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "a hex string of {} bytes", N)
+            }
+
+            fn visit_str<E: Error>(self, v: &str) -> Result<[u8; N], E> {
+                if v.len() != N * 2 {
+                    return Err(E::invalid_length(v.len(), &self));
+                }
+                let mut out = [0; N];
+                for (byte, pair) in out.iter_mut().zip(v.as_bytes().chunks_exact(2)) {
+                    match (digit(pair[0]), digit(pair[1])) {
+                        (Some(high), Some(low)) => *byte = high << 4 | low,
+                        _ => return Err(E::invalid_value(Unexpected::Str(v), &self)),
+                    }
+                }
+                Ok(out)
+            }
+        }
+
+        deserializer.deserialize_str(HexVisitor::<N>)
+    }
+}
+
+/// Standard base64 with padding, `"Chs="`
+pub mod base64 {
+    use super::*;
+
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    struct Base64<'a>(&'a [u8]);
+
+    impl fmt::Display for Base64<'_> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            for chunk in self.0.chunks(3) {
+                let group = chunk.iter().enumerate().fold(0u32, |acc, (i, b)| acc | u32::from(*b) << (16 - 8 * i));
+                let mut quad = [b'='; 4];
+                for (i, c) in quad.iter_mut().take(chunk.len() + 1).enumerate() {
+                    *c = ALPHABET[(group >> (18 - 6 * i)) as usize & 0x3f];
+                }
+                // Every byte is from the alphabet or padding
+                f.write_str(core::str::from_utf8(&quad).unwrap())?;
+            }
+            Ok(())
+        }
+    }
+
+    fn sextet(c: u8) -> Option<u32> {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        };
+        Some(u32::from(value))
+    }
+
+    pub fn serialize<S: Serializer, const N: usize>(bytes: &[u8; N], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&Base64(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>, const N: usize>(deserializer: D) -> Result<[u8; N], D::Error> {
+        struct Base64Visitor<const N: usize>;
+
+        impl<const N: usize> Visitor<'_> for Base64Visitor<N> {
+            type Value = [u8; N];
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "a base64 string of {} bytes", N)
+            }
+
+            fn visit_str<E: Error>(self, v: &str) -> Result<[u8; N], E> {
+                if v.len() != (N + 2) / 3 * 4 {
+                    return Err(E::invalid_length(v.len(), &self));
+                }
+                let mut out = [0; N];
+                for (chunk, quad) in out.chunks_mut(3).zip(v.as_bytes().chunks_exact(4)) {
+                    let (data, padding) = quad.split_at(chunk.len() + 1);
+                    let mut group = 0u32;
+                    for (i, c) in data.iter().enumerate() {
+                        let value = sextet(*c).ok_or_else(|| E::invalid_value(Unexpected::Str(v), &self))?;
+                        group |= value << (18 - 6 * i);
+                    }
+                    if padding.iter().any(|c| *c != b'=') {
+                        return Err(E::invalid_value(Unexpected::Str(v), &self));
+                    }
+                    for (i, byte) in chunk.iter_mut().enumerate() {
+                        *byte = (group >> (16 - 8 * i)) as u8;
+                    }
+                }
+                Ok(out)
+            }
+        }
+
+        deserializer.deserialize_str(Base64Visitor::<N>)
+    }
+}
@@ -0,0 +1,58 @@
+//! C declarations behind the `c_header()` generated by `#[derive(BinaryMirror)]`.
+
+use alloc::format;
+use alloc::string::String;
+
+/// Upper snake case for C constants, `OrderSide` becomes `ORDER_SIDE`
+pub fn c_constant_name(name: &str) -> String {
+    let mut out = String::with_capacity(name.len() + 4);
+    let mut prev_lower = false;
+    for c in name.chars() {
+        if c.is_ascii_uppercase() && prev_lower {
+            out.push('_');
+        }
+        prev_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+        out.push(if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' });
+    }
+    out
+}
+
+/// C declaration of a `BinaryEnum` from its labels and byte values
+///
+/// Single-byte values become an `enum` of character constants, which compare directly
+/// against the `char` field. Longer values become `#define`d string constants.
+pub fn c_enum_definition(name: &str, variants: &[(String, &[u8])]) -> String {
+    let prefix = c_constant_name(name);
+    if variants.iter().all(|(_, value)| value.len() == 1) {
+        let constants = variants
+            .iter()
+            .map(|(label, value)| {
+                format!("    {}_{} = '{}',\n", prefix, c_constant_name(label), c_escape(value, b'\''))
+            })
+            .collect::<String>();
+        return format!("enum {} {{\n{}}};\n", name, constants);
+    }
+    variants
+        .iter()
+        .map(|(label, value)| {
+            format!("#define {}_{} \"{}\"\n", prefix, c_constant_name(label), c_escape(value, b'"'))
+        })
+        .collect()
+}
+
+/// Bytes as the inside of a C character or string literal quoted with `quote`
+fn c_escape(bytes: &[u8], quote: u8) -> String {
+    let mut out = String::with_capacity(bytes.len());
+    for &b in bytes {
+        match b {
+            b'\\' => out.push_str("\\\\"),
+            b if b == quote => {
+                out.push('\\');
+                out.push(b as char);
+            }
+            0x20..=0x7E => out.push(b as char),
+            _ => out.push_str(&format!("\\x{:02x}", b)),
+        }
+    }
+    out
+}
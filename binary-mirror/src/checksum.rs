@@ -0,0 +1,38 @@
+//! Checksum algorithms used by `#[bm(type = "checksum", algo = "...")]` fields.
+
+/// Longitudinal redundancy check: XOR of all bytes
+pub fn lrc(bytes: &[u8]) -> u8 {
+    bytes.iter().fold(0, |acc, &b| acc ^ b)
+}
+
+/// CRC-16/ARC (poly 0x8005 reflected, init 0x0000)
+pub fn crc16(bytes: &[u8]) -> u16 {
+    bytes.iter().fold(0u16, |mut crc, &b| {
+        crc ^= b as u16;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xA001 } else { crc >> 1 };
+        }
+        crc
+    })
+}
+
+/// Luhn (mod 10) check digit over the ASCII digits, other bytes are ignored
+pub fn mod10(bytes: &[u8]) -> u8 {
+    let sum: u32 = bytes
+        .iter()
+        .rev()
+        .filter(|b| b.is_ascii_digit())
+        .enumerate()
+        .map(|(i, &b)| {
+            let d = (b - b'0') as u32;
+            if i % 2 == 0 {
+                let d = d * 2;
+                if d > 9 { d - 9 } else { d }
+            } else {
+                d
+            }
+        })
+        .sum();
+    ((10 - sum % 10) % 10) as u8
+}
+
@@ -0,0 +1,73 @@
+//! `tokio_util::codec` support for reading and writing fixed-length records with `Framed`.
+
+use std::borrow::Borrow;
+use std::io;
+use std::marker::PhantomData;
+
+pub use bytes;
+use bytes::BytesMut;
+pub use tokio_util::codec::{Decoder, Encoder};
+
+use crate::{FromBytes, Framing, ToBytes};
+
+/// Splits a byte stream into frames of `T::SIZE` bytes plus the framing of `T::FRAME` and
+/// decodes each into an owned `T`, encodes any `T` (or `&T`) by writing its framed raw bytes
+pub struct FixedLenCodec<T> {
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> FixedLenCodec<T> {
+    pub fn new() -> Self {
+        Self { _marker: PhantomData }
+    }
+}
+
+impl<T> Default for FixedLenCodec<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for FixedLenCodec<T> {
+    fn clone(&self) -> Self {
+        Self::new()
+    }
+}
+
+impl<T> std::fmt::Debug for FixedLenCodec<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FixedLenCodec").finish()
+    }
+}
+
+impl<T: FromBytes + Framing> Decoder for FixedLenCodec<T> {
+    type Item = T;
+    type Error = io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<T>, io::Error> {
+        let frame_len = T::FRAME.frame_len(T::SIZE);
+        if src.len() < frame_len {
+            src.reserve(frame_len - src.len());
+            return Ok(None);
+        }
+        let frame = src.split_to(frame_len);
+        let record = T::FRAME
+            .strip(&frame, T::SIZE)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        T::from_bytes_owned(record)
+            .map(Some)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+impl<T: ToBytes + Framing, I: Borrow<T>> Encoder<I> for FixedLenCodec<T> {
+    type Error = io::Error;
+
+    fn encode(&mut self, item: I, dst: &mut BytesMut) -> Result<(), io::Error> {
+        let bytes = item.borrow().to_bytes();
+        T::FRAME.write_prefix(bytes.len(), dst);
+        dst.extend_from_slice(bytes);
+        dst.extend_from_slice(T::FRAME.suffix());
+        Ok(())
+    }
+}
@@ -0,0 +1,177 @@
+//! Layout changes between two derived types, e.g. two versions of the same message.
+
+use core::fmt;
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::schema::or_none;
+use crate::{Reflect, Schema};
+
+/// How one field differs from the old layout to the new one, fields are matched by name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldChange {
+    Added { field: String, offset: usize, size: usize },
+    Removed { field: String, offset: usize, size: usize },
+    Moved { field: String, from: usize, to: usize },
+    Resized { field: String, from: usize, to: usize },
+    Retyped {
+        field: String,
+        from: Option<String>,
+        to: Option<String>,
+    },
+    Reformatted {
+        field: String,
+        from: Option<String>,
+        to: Option<String>,
+    },
+}
+
+impl FieldChange {
+    pub fn field(&self) -> &str {
+        match self {
+            Self::Added { field, .. }
+            | Self::Removed { field, .. }
+            | Self::Moved { field, .. }
+            | Self::Resized { field, .. }
+            | Self::Retyped { field, .. }
+            | Self::Reformatted { field, .. } => field,
+        }
+    }
+}
+
+impl fmt::Display for FieldChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Added { field, offset, size } => {
+                write!(f, "added `{}` at offset {}, {} bytes", field, offset, size)
+            }
+            Self::Removed { field, offset, size } => {
+                write!(f, "removed `{}` at offset {}, {} bytes", field, offset, size)
+            }
+            Self::Moved { field, from, to } => write!(f, "moved `{}` from offset {} to {}", field, from, to),
+            Self::Resized { field, from, to } => write!(f, "resized `{}` from {} to {} bytes", field, from, to),
+            Self::Retyped { field, from, to } => {
+                write!(f, "retyped `{}` from {} to {}", field, or_none(from), or_none(to))
+            }
+            Self::Reformatted { field, from, to } => {
+                write!(f, "reformatted `{}` from {} to {}", field, or_none(from), or_none(to))
+            }
+        }
+    }
+}
+
+/// The differences between two layouts, see [`check`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompatReport {
+    pub from: String,
+    pub to: String,
+    pub from_size: usize,
+    pub to_size: usize,
+    /// Changes of the old fields in layout order, then the added fields
+    pub changes: Vec<FieldChange>,
+}
+
+impl CompatReport {
+    /// True if both layouts are the same apart from the struct names
+    pub fn is_identical(&self) -> bool {
+        self.from_size == self.to_size && self.changes.is_empty()
+    }
+
+    /// True if the new layout only appends fields after the old one, so a reader of the old
+    /// layout can still decode the prefix of a new record
+    pub fn is_append_only(&self) -> bool {
+        self.changes
+            .iter()
+            .all(|change| matches!(change, FieldChange::Added { offset, .. } if *offset >= self.from_size))
+    }
+
+    pub fn added(&self) -> impl Iterator<Item = &FieldChange> {
+        self.changes.iter().filter(|change| matches!(change, FieldChange::Added { .. }))
+    }
+
+    pub fn removed(&self) -> impl Iterator<Item = &FieldChange> {
+        self.changes.iter().filter(|change| matches!(change, FieldChange::Removed { .. }))
+    }
+}
+
+impl fmt::Display for CompatReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({} bytes) -> {} ({} bytes)", self.from, self.from_size, self.to, self.to_size)?;
+        if self.changes.is_empty() {
+            return f.write_str(": no field changes");
+        }
+        for change in &self.changes {
+            write!(f, "\n  {}", change)?;
+        }
+        Ok(())
+    }
+}
+
+/// Compare the layout of `A`, the old version, with the layout of `B`, the new one
+pub fn check<A: Reflect, B: Reflect>() -> CompatReport {
+    diff(&A::schema(), &B::schema())
+}
+
+/// Compare two schemas, e.g. a published one with the layout of a derived type
+///
+/// [`Schema::check`] reports the same differences as mismatches.
+pub fn diff(from: &Schema, to: &Schema) -> CompatReport {
+    let mut changes = Vec::new();
+    for old in &from.fields {
+        let field = old.name.clone();
+        let Some(new) = to.field(&old.name) else {
+            changes.push(FieldChange::Removed {
+                field,
+                offset: old.offset,
+                size: old.size,
+            });
+            continue;
+        };
+        if old.offset != new.offset {
+            changes.push(FieldChange::Moved {
+                field: field.clone(),
+                from: old.offset,
+                to: new.offset,
+            });
+        }
+        if old.size != new.size {
+            changes.push(FieldChange::Resized {
+                field: field.clone(),
+                from: old.size,
+                to: new.size,
+            });
+        }
+        if old.type_name != new.type_name {
+            changes.push(FieldChange::Retyped {
+                field: field.clone(),
+                from: old.type_name.clone(),
+                to: new.type_name.clone(),
+            });
+        }
+        if old.format != new.format {
+            changes.push(FieldChange::Reformatted {
+                field,
+                from: old.format.clone(),
+                to: new.format.clone(),
+            });
+        }
+    }
+    changes.extend(
+        to.fields
+            .iter()
+            .filter(|new| from.field(&new.name).is_none())
+            .map(|new| FieldChange::Added {
+                field: new.name.clone(),
+                offset: new.offset,
+                size: new.size,
+            }),
+    );
+    CompatReport {
+        from: from.name.clone(),
+        to: to.name.clone(),
+        from_size: from.size,
+        to_size: to.size,
+        changes,
+    }
+}
@@ -0,0 +1,249 @@
+//! Layouts defined at runtime, enabled with the `dynamic` feature
+//!
+//! `DynamicMirror` decodes records whose layout is only known once the program runs, such
+//! as one loaded from a spreadsheet, into JSON objects shaped like the serde output of a
+//! derived native struct.
+
+use std::fmt;
+
+use lexical_core::FromLexical;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::{to_bytes_repr, trim_ascii, BytesSizeError, FieldKind, StructDescriptor};
+
+/// One field of a [`DynamicMirror`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DynamicField {
+    pub name: String,
+    pub offset: usize,
+    pub size: usize,
+    pub kind: FieldKind,
+    /// The chrono format of `date`, `time` and `datetime` fields, the derive default if None
+    pub format: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LayoutError {
+    /// Line of the layout text, 1-based, 0 for layouts built in code
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for LayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            0 => write!(f, "invalid layout: {}", self.message),
+            line => write!(f, "invalid layout at line {}: {}", line, self.message),
+        }
+    }
+}
+
+impl std::error::Error for LayoutError {}
+
+/// A record layout built or loaded at runtime
+///
+/// Fields decode like the derived getters of the same `type`: strings are trimmed, numbers
+/// and dates that don't parse are null, decimals, dates and times are strings. Enums and
+/// custom types have no Rust type to parse into and are kept as trimmed strings. Checksum,
+/// pad and untyped fields are left out, as in a native struct.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DynamicMirror {
+    fields: Vec<DynamicField>,
+}
+
+impl DynamicMirror {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a field right after the end of the layout so far
+    pub fn field(self, name: &str, kind: FieldKind, size: usize) -> Result<Self, LayoutError> {
+        let offset = self.size();
+        self.field_at(name, offset, size, kind, None)
+    }
+
+    /// Append a field at `offset`, with a chrono `format` for dates and times
+    pub fn field_at(
+        mut self,
+        name: &str,
+        offset: usize,
+        size: usize,
+        kind: FieldKind,
+        format: Option<&str>,
+    ) -> Result<Self, LayoutError> {
+        self.push(DynamicField {
+            name: name.to_string(),
+            offset,
+            size,
+            kind,
+            format: format.map(str::to_string),
+        })?;
+        Ok(self)
+    }
+
+    /// Append a field, Err for a `flatten` field, whose nested layout is unknown, or a name
+    /// already in the layout
+    pub fn push(&mut self, field: DynamicField) -> Result<(), LayoutError> {
+        let error = |message: String| Err(LayoutError { line: 0, message });
+        if field.kind == FieldKind::Flatten {
+            return error(format!("field `{}` is flatten, list the nested fields instead", field.name));
+        }
+        if self.fields.iter().any(|other| other.name == field.name) {
+            return error(format!("field `{}` is defined twice", field.name));
+        }
+        self.fields.push(field);
+        Ok(())
+    }
+
+    /// The layout of a derived struct, to decode it without its Rust type
+    ///
+    /// Fields are named by their alias, `skip_native` fields are treated as padding.
+    /// Attributes outside of the descriptor, such as `none_value` or `sign`, don't apply.
+    /// Err if it flattens a nested struct.
+    pub fn from_descriptor(descriptor: &StructDescriptor) -> Result<Self, LayoutError> {
+        let mut mirror = Self::new();
+        for field in descriptor.fields {
+            mirror.push(DynamicField {
+                name: field.alias.unwrap_or(field.name).to_string(),
+                offset: field.offset,
+                size: field.size,
+                kind: if field.skip_native { FieldKind::Pad } else { field.kind },
+                format: field.format.map(str::to_string),
+            })?;
+        }
+        Ok(mirror)
+    }
+
+    /// Load a layout from CSV lines of `name,offset,size,type[,format]`, as exported from a
+    /// spreadsheet
+    ///
+    /// A first line starting with `name` is taken as the header. An empty offset places the
+    /// field right after the previous one. `type` is a `#[bm(type = ...)]` name or `pad`.
+    pub fn from_csv(text: &str) -> Result<Self, LayoutError> {
+        let mut mirror = Self::new();
+        for (i, line) in text.lines().enumerate() {
+            let line_no = i + 1;
+            let line = line.trim();
+            if line.is_empty() || (i == 0 && line.starts_with("name")) {
+                continue;
+            }
+            let error = |message: String| LayoutError { line: line_no, message };
+            let cells = line.split(',').map(str::trim).collect::<Vec<_>>();
+            let [name, offset, size, type_name, rest @ ..] = cells.as_slice() else {
+                return Err(error(format!("expected name,offset,size,type[,format], got \"{}\"", line)));
+            };
+            let parse = |what: &str, cell: &str| {
+                cell.parse::<usize>()
+                    .map_err(|_| error(format!("{} of field `{}` is not a number: \"{}\"", what, name, cell)))
+            };
+            let offset = match *offset {
+                "" => mirror.size(),
+                offset => parse("offset", offset)?,
+            };
+            let size = parse("size", size)?;
+            let kind = FieldKind::from_type_name(type_name)
+                .ok_or_else(|| error(format!("unknown type \"{}\" of field `{}`", type_name, name)))?;
+            let format = rest.first().filter(|format| !format.is_empty()).map(|format| format.to_string());
+            mirror
+                .push(DynamicField {
+                    name: name.to_string(),
+                    offset,
+                    size,
+                    kind,
+                    format,
+                })
+                .map_err(|err| error(err.message))?;
+        }
+        Ok(mirror)
+    }
+
+    pub fn fields(&self) -> &[DynamicField] {
+        &self.fields
+    }
+
+    /// Record size, up to the end of the last field
+    pub fn size(&self) -> usize {
+        self.fields.iter().map(|field| field.offset + field.size).max().unwrap_or(0)
+    }
+
+    /// Decode a record into a JSON object, one entry per field
+    ///
+    /// Err if `bytes` is not exactly `size()` bytes long, or shorter than that for a layout
+    /// ending in a `rest` field, which takes the bytes after `size()`.
+    pub fn decode(&self, bytes: &[u8]) -> Result<Map<String, Value>, BytesSizeError> {
+        let has_rest = self.fields.iter().any(|field| field.kind == FieldKind::Rest);
+        if bytes.len() < self.size() || (!has_rest && bytes.len() > self.size()) {
+            return Err(BytesSizeError::new(self.size(), bytes.len(), to_bytes_repr(bytes)));
+        }
+        Ok(self
+            .fields
+            .iter()
+            .filter(|field| !matches!(field.kind, FieldKind::Checksum | FieldKind::Pad | FieldKind::Untyped))
+            .map(|field| {
+                let bytes = match field.kind {
+                    FieldKind::Rest => &bytes[field.offset..],
+                    _ => &bytes[field.offset..field.offset + field.size],
+                };
+                (field.name.clone(), decode_field(field, bytes))
+            })
+            .collect())
+    }
+
+    /// Decode a record into a JSON object value
+    pub fn decode_value(&self, bytes: &[u8]) -> Result<Value, BytesSizeError> {
+        self.decode(bytes).map(Value::Object)
+    }
+
+    /// Decode consecutive records of `size()` bytes
+    ///
+    /// Err if the length of `bytes` is not a multiple of `size()`. A `rest` field is empty
+    /// in each record.
+    pub fn decode_many(&self, bytes: &[u8]) -> Result<Vec<Map<String, Value>>, BytesSizeError> {
+        let size = self.size();
+        let records = bytes.chunks_exact(size.max(1));
+        if size == 0 || !records.remainder().is_empty() {
+            return Err(BytesSizeError::new(size, bytes.len(), to_bytes_repr(bytes)));
+        }
+        records.map(|record| self.decode(record)).collect()
+    }
+}
+
+fn decode_field(field: &DynamicField, bytes: &[u8]) -> Value {
+    let trimmed = trim_ascii(bytes);
+    let text = || std::str::from_utf8(trimmed).ok();
+    let format = |default: &'static str| field.format.as_deref().unwrap_or(default);
+    let value = match field.kind {
+        FieldKind::Str | FieldKind::CompactStr | FieldKind::Enum | FieldKind::Custom => {
+            text().map(|s| Value::String(s.to_string()))
+        }
+        FieldKind::I16 => number::<i16>(trimmed),
+        FieldKind::I32 => number::<i32>(trimmed),
+        FieldKind::I64 => number::<i64>(trimmed),
+        FieldKind::U16 => number::<u16>(trimmed),
+        FieldKind::U32 => number::<u32>(trimmed),
+        FieldKind::U64 => number::<u64>(trimmed),
+        FieldKind::F32 => number::<f32>(trimmed),
+        FieldKind::F64 => number::<f64>(trimmed),
+        FieldKind::Decimal => text()
+            .and_then(|s| s.parse::<rust_decimal::Decimal>().ok())
+            .map(|d| Value::String(d.normalize().to_string())),
+        FieldKind::DateTime => text()
+            .and_then(|s| chrono::NaiveDateTime::parse_from_str(s, format("%Y%m%d%H%M%S")).ok())
+            .and_then(|dt| serde_json::to_value(dt).ok()),
+        FieldKind::Date => text()
+            .and_then(|s| chrono::NaiveDate::parse_from_str(s, format("%Y%m%d")).ok())
+            .and_then(|d| serde_json::to_value(d).ok()),
+        FieldKind::Time => text()
+            .and_then(|s| chrono::NaiveTime::parse_from_str(s, format("%H%M%S")).ok())
+            .and_then(|t| serde_json::to_value(t).ok()),
+        FieldKind::Bytes | FieldKind::Rest => Some(Value::from(bytes.to_vec())),
+        FieldKind::Checksum | FieldKind::Pad | FieldKind::Untyped | FieldKind::Flatten => None,
+    };
+    value.unwrap_or(Value::Null)
+}
+
+/// Parse as `T` like the derived getter, so out of range values are null too
+fn number<T: FromLexical + Serialize>(digits: &[u8]) -> Option<Value> {
+    crate::parse_number::<T>(digits).and_then(|v| serde_json::to_value(v).ok())
+}
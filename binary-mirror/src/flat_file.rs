@@ -0,0 +1,272 @@
+//! Flat files of header, detail and trailer records, the layout of most settlement files.
+
+use core::fmt;
+use core::marker::PhantomData;
+
+use alloc::string::String;
+
+use crate::{to_bytes_repr, BytesSizeError, FrameError, Framing, FromBytes};
+
+/// The three kinds of record in a flat file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordKind {
+    Header,
+    Detail,
+    Trailer,
+}
+
+impl fmt::Display for RecordKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Header => "header",
+            Self::Detail => "detail",
+            Self::Trailer => "trailer",
+        })
+    }
+}
+
+/// The record-type field telling the kinds apart, the bytes each kind holds at `offset`
+///
+/// `offset` counts from the start of the record, after any framing prefix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecordTypes {
+    pub offset: usize,
+    pub header: &'static [u8],
+    pub detail: &'static [u8],
+    pub trailer: &'static [u8],
+}
+
+/// One record of a flat file, viewed in place
+#[derive(Debug)]
+pub enum FlatRecord<'a, H, D, T> {
+    Header(&'a H),
+    Detail(&'a D),
+    Trailer(&'a T),
+}
+
+impl<H, D, T> FlatRecord<'_, H, D, T> {
+    pub fn kind(&self) -> RecordKind {
+        match self {
+            Self::Header(_) => RecordKind::Header,
+            Self::Detail(_) => RecordKind::Detail,
+            Self::Trailer(_) => RecordKind::Trailer,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum FlatFileError {
+    /// The record-type field matches none of the kinds, reading stops here
+    UnknownRecordType { offset: usize, bytes: String },
+    /// The framing around a record is wrong or the file ends inside it, reading stops here
+    Frame { offset: usize, error: FrameError },
+    /// The record bytes don't fit the struct, reading stops here
+    Size(BytesSizeError),
+    /// A header inside an open group, or a detail or trailer outside of one
+    Unexpected { offset: usize, kind: RecordKind },
+    /// The trailer's record count, None when it doesn't parse, differs from the details read
+    CountMismatch {
+        offset: usize,
+        expected: Option<usize>,
+        actual: usize,
+    },
+    /// The file ends after a header without its trailer
+    MissingTrailer { offset: usize },
+}
+
+impl fmt::Display for FlatFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownRecordType { offset, bytes } => {
+                write!(f, "unknown record type at offset {}, content: \"{}\"", offset, bytes)
+            }
+            Self::Frame { offset, error } => write!(f, "at offset {}: {}", offset, error),
+            Self::Size(err) => err.fmt(f),
+            Self::Unexpected { offset, kind } => write!(f, "unexpected {} record at offset {}", kind, offset),
+            Self::CountMismatch {
+                offset,
+                expected: Some(expected),
+                actual,
+            } => write!(
+                f,
+                "trailer at offset {} counts {} detail records but {} were read",
+                offset, expected, actual
+            ),
+            Self::CountMismatch { offset, actual, .. } => write!(
+                f,
+                "trailer at offset {} has no valid record count, {} detail records were read",
+                offset, actual
+            ),
+            Self::MissingTrailer { offset } => write!(f, "file ends at offset {} without a trailer", offset),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FlatFileError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Frame { error, .. } => Some(error),
+            Self::Size(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<BytesSizeError> for FlatFileError {
+    fn from(err: BytesSizeError) -> Self {
+        Self::Size(err)
+    }
+}
+
+/// Iterator over the records of a flat file of header, detail and trailer records
+///
+/// Each record is framed by the `FRAME` of its own type, so headers, details and trailers
+/// may differ in size. Every header opens a group that its trailer closes, so a file can
+/// hold several batches. With [`trailer_count`](Self::trailer_count) each trailer is checked
+/// against the number of details in its group.
+pub struct FlatFileReader<'a, H, D, T> {
+    bytes: &'a [u8],
+    offset: usize,
+    types: RecordTypes,
+    trailer_count: Option<fn(&T) -> Option<usize>>,
+    /// Details read since the header of the open group, None outside of a group
+    details: Option<usize>,
+    done: bool,
+    _marker: PhantomData<(&'a H, &'a D)>,
+}
+
+impl<'a, H, D, T> FlatFileReader<'a, H, D, T>
+where
+    H: FromBytes + Framing + 'a,
+    D: FromBytes + Framing + 'a,
+    T: FromBytes + Framing + 'a,
+{
+    pub fn new(bytes: &'a [u8], types: RecordTypes) -> Self {
+        Self {
+            bytes,
+            offset: 0,
+            types,
+            trailer_count: None,
+            details: None,
+            done: false,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Check each trailer against the number of detail records in its group
+    pub fn trailer_count(mut self, count: fn(&T) -> Option<usize>) -> Self {
+        self.trailer_count = Some(count);
+        self
+    }
+
+    /// The bytes not yet consumed
+    pub fn remaining(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    fn kind(&self) -> Option<RecordKind> {
+        let RecordTypes {
+            offset,
+            header,
+            detail,
+            trailer,
+        } = self.types;
+        let holds = |prefix_len: usize, tag: &[u8]| {
+            let start = prefix_len + offset;
+            self.bytes.get(start..start + tag.len()) == Some(tag)
+        };
+        if holds(H::FRAME.prefix_len(), header) {
+            Some(RecordKind::Header)
+        } else if holds(D::FRAME.prefix_len(), detail) {
+            Some(RecordKind::Detail)
+        } else if holds(T::FRAME.prefix_len(), trailer) {
+            Some(RecordKind::Trailer)
+        } else {
+            None
+        }
+    }
+
+    /// Split off the next frame of a `R` record and view the record in it
+    fn take<R: FromBytes + Framing + 'a>(&mut self) -> Result<&'a R, FlatFileError> {
+        let len = R::FRAME.frame_len(R::SIZE).min(self.bytes.len());
+        let (frame, rest) = self.bytes.split_at(len);
+        let record = R::FRAME.strip(frame, R::SIZE).map_err(|error| FlatFileError::Frame {
+            offset: self.offset,
+            error,
+        })?;
+        self.bytes = rest;
+        self.offset += len;
+        Ok(R::from_bytes(record)?)
+    }
+
+    fn read(&mut self, kind: RecordKind) -> Result<FlatRecord<'a, H, D, T>, FlatFileError> {
+        let offset = self.offset;
+        let (record, misplaced) = match kind {
+            RecordKind::Header => (FlatRecord::Header(self.take::<H>()?), self.details.is_some()),
+            RecordKind::Detail => (FlatRecord::Detail(self.take::<D>()?), self.details.is_none()),
+            RecordKind::Trailer => (FlatRecord::Trailer(self.take::<T>()?), self.details.is_none()),
+        };
+        let details = self.details;
+        self.details = match kind {
+            RecordKind::Header => Some(0),
+            RecordKind::Detail => details.map(|count| count + 1),
+            RecordKind::Trailer => None,
+        };
+        if misplaced {
+            return Err(FlatFileError::Unexpected { offset, kind });
+        }
+        if let (FlatRecord::Trailer(trailer), Some(count), Some(actual)) = (&record, self.trailer_count, details) {
+            let expected = count(trailer);
+            if expected != Some(actual) {
+                return Err(FlatFileError::CountMismatch {
+                    offset,
+                    expected,
+                    actual,
+                });
+            }
+        }
+        Ok(record)
+    }
+}
+
+impl<'a, H, D, T> Iterator for FlatFileReader<'a, H, D, T>
+where
+    H: FromBytes + Framing + 'a,
+    D: FromBytes + Framing + 'a,
+    T: FromBytes + Framing + 'a,
+{
+    type Item = Result<FlatRecord<'a, H, D, T>, FlatFileError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        if self.bytes.is_empty() {
+            self.done = true;
+            return self
+                .details
+                .map(|_| Err(FlatFileError::MissingTrailer { offset: self.offset }));
+        }
+        let Some(kind) = self.kind() else {
+            self.done = true;
+            return Some(Err(FlatFileError::UnknownRecordType {
+                offset: self.offset,
+                bytes: to_bytes_repr(&self.bytes[..D::FRAME.frame_len(D::SIZE).min(self.bytes.len())]),
+            }));
+        };
+        let result = self.read(kind);
+        if matches!(result, Err(FlatFileError::Frame { .. } | FlatFileError::Size(_))) {
+            self.done = true;
+        }
+        Some(result)
+    }
+}
+
+impl<'a, H, D, T> core::iter::FusedIterator for FlatFileReader<'a, H, D, T>
+where
+    H: FromBytes + Framing + 'a,
+    D: FromBytes + Framing + 'a,
+    T: FromBytes + Framing + 'a,
+{
+}
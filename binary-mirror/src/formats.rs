@@ -0,0 +1,41 @@
+//! Date and time formats chosen at runtime, for `to_native_with_formats`.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Formats replacing the `format` declared on `date`, `time` and `datetime` fields, by
+/// native field name
+///
+/// Fields of structs inlined with `flatten` are looked up by their own names.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FormatOverrides {
+    formats: Vec<(String, String)>,
+}
+
+impl FormatOverrides {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse `field` with `format`, replacing an earlier override of the field
+    pub fn with(mut self, field: &str, format: &str) -> Self {
+        self.set(field, format);
+        self
+    }
+
+    /// Parse `field` with `format`, replacing an earlier override of the field
+    pub fn set(&mut self, field: &str, format: &str) {
+        match self.formats.iter_mut().find(|(name, _)| name == field) {
+            Some((_, existing)) => *existing = String::from(format),
+            None => self.formats.push((String::from(field), String::from(format))),
+        }
+    }
+
+    /// The format overriding `field`, if any
+    pub fn get(&self, field: &str) -> Option<&str> {
+        self.formats
+            .iter()
+            .find(|(name, _)| name == field)
+            .map(|(_, format)| format.as_str())
+    }
+}
@@ -0,0 +1,186 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::to_bytes_repr;
+
+/// How each record is wrapped on the wire, chosen per type with `#[bm(frame = "...")]`
+///
+/// Records have a fixed size, so every framing adds a fixed number of bytes before and after
+/// the record and the whole frame has a fixed length too.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frame {
+    /// Records back to back, no framing
+    Fixed,
+    /// Record followed by a terminator, e.g. `\n` or `\r\n`
+    Delimited(&'static [u8]),
+    /// Record between a start and an end marker, e.g. SOH and ETX
+    Wrapped {
+        start: &'static [u8],
+        end: &'static [u8],
+    },
+    /// Record preceded by a length field counting the record bytes
+    LengthPrefixed { width: usize, encoding: LengthEncoding },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LengthEncoding {
+    /// Zero-padded ASCII digits, e.g. `0042`
+    Ascii,
+    /// Packed BCD, two digits per byte, e.g. `[0x00, 0x42]`
+    Bcd,
+}
+
+/// Implemented by `#[derive(BinaryMirror)]`, `Frame::Fixed` unless `frame` is given
+pub trait Framing {
+    const FRAME: Frame;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrameError {
+    pub(crate) message: String,
+    pub(crate) bytes: String,
+}
+
+impl fmt::Display for FrameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid frame: {}, content: \"{}\"", self.message, self.bytes)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FrameError {}
+
+impl Frame {
+    /// Number of bytes before the record
+    pub const fn prefix_len(&self) -> usize {
+        match self {
+            Self::Fixed | Self::Delimited(_) => 0,
+            Self::Wrapped { start, .. } => start.len(),
+            Self::LengthPrefixed { width, .. } => *width,
+        }
+    }
+
+    /// Number of bytes after the record
+    pub const fn suffix_len(&self) -> usize {
+        match self {
+            Self::Fixed | Self::LengthPrefixed { .. } => 0,
+            Self::Delimited(end) | Self::Wrapped { end, .. } => end.len(),
+        }
+    }
+
+    /// Length of a whole frame around a record of `size` bytes
+    pub const fn frame_len(&self, size: usize) -> usize {
+        self.prefix_len() + size + self.suffix_len()
+    }
+
+    /// Whether a record of `size` bytes can be framed, false when a length field has too
+    /// few digits for it
+    ///
+    /// `#[derive(BinaryMirror)]` checks this for the record size at compile time.
+    pub const fn fits(&self, size: usize) -> bool {
+        match self {
+            Self::LengthPrefixed { width, encoding } => {
+                let mut digits = match encoding {
+                    LengthEncoding::Ascii => *width,
+                    LengthEncoding::Bcd => *width * 2,
+                };
+                let mut len = size;
+                while digits > 0 && len > 0 {
+                    len /= 10;
+                    digits -= 1;
+                }
+                len == 0
+            }
+            _ => true,
+        }
+    }
+
+    /// Append the bytes that go before a record of `size` bytes
+    ///
+    /// A length field keeps only its last digits when `size` does not [`fit`](Self::fits).
+    pub fn write_prefix<E: Extend<u8>>(&self, size: usize, out: &mut E) {
+        match self {
+            Self::Fixed | Self::Delimited(_) => {}
+            Self::Wrapped { start, .. } => out.extend(start.iter().copied()),
+            Self::LengthPrefixed { width, encoding } => {
+                let digits = match encoding {
+                    LengthEncoding::Ascii => *width,
+                    LengthEncoding::Bcd => width * 2,
+                };
+                let mut len = size;
+                let mut prefix = vec![0u8; digits];
+                for digit in prefix.iter_mut().rev() {
+                    *digit = (len % 10) as u8;
+                    len /= 10;
+                }
+                match encoding {
+                    LengthEncoding::Ascii => out.extend(prefix.iter().map(|d| b'0' + d)),
+                    LengthEncoding::Bcd => out.extend(prefix.chunks(2).map(|d| (d[0] << 4) | d[1])),
+                }
+            }
+        }
+    }
+
+    /// The bytes that go after a record
+    pub const fn suffix(&self) -> &'static [u8] {
+        match self {
+            Self::Fixed | Self::LengthPrefixed { .. } => &[],
+            Self::Delimited(end) | Self::Wrapped { end, .. } => end,
+        }
+    }
+
+    /// Append the framed record to `out`
+    pub fn write(&self, record: &[u8], out: &mut Vec<u8>) {
+        self.write_prefix(record.len(), out);
+        out.extend_from_slice(record);
+        out.extend_from_slice(self.suffix());
+    }
+
+    /// Check the framing bytes of a whole frame and return the record inside
+    /// Returns Err if the frame length, markers or length field don't match a `size` byte record
+    pub fn strip<'a>(&self, frame: &'a [u8], size: usize) -> Result<&'a [u8], FrameError> {
+        let error = |message: String| FrameError {
+            message,
+            bytes: to_bytes_repr(frame),
+        };
+        if frame.len() != self.frame_len(size) {
+            return Err(error(format!(
+                "expected {} bytes but got {}",
+                self.frame_len(size),
+                frame.len()
+            )));
+        }
+        let (prefix, rest) = frame.split_at(self.prefix_len());
+        let (record, suffix) = rest.split_at(size);
+        match self {
+            Self::Fixed => {}
+            Self::Delimited(end) if suffix != *end => {
+                return Err(error(format!("expected terminator \"{}\"", to_bytes_repr(end))));
+            }
+            Self::Wrapped { start, end } if prefix != *start || suffix != *end => {
+                return Err(error(format!(
+                    "expected record between \"{}\" and \"{}\"",
+                    to_bytes_repr(start),
+                    to_bytes_repr(end)
+                )));
+            }
+            Self::Delimited(_) | Self::Wrapped { .. } => {}
+            Self::LengthPrefixed { encoding, .. } => {
+                let len = match encoding {
+                    LengthEncoding::Ascii => crate::parse_number::<usize>(prefix),
+                    LengthEncoding::Bcd => prefix.iter().try_fold(0usize, |len, &b| {
+                        let (high, low) = ((b >> 4) as usize, (b & 0x0f) as usize);
+                        (high < 10 && low < 10).then_some(len * 100 + high * 10 + low)
+                    }),
+                };
+                if len != Some(size) {
+                    return Err(error(format!("expected length field {}", size)));
+                }
+            }
+        }
+        Ok(record)
+    }
+}
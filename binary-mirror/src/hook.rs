@@ -0,0 +1,46 @@
+use core::sync::atomic::{AtomicPtr, Ordering};
+
+/// Called with the struct name, the field name and the raw bytes of a field that failed to parse
+pub type ParseFailureHook = fn(&'static str, &'static str, &[u8]);
+
+static HOOK: AtomicPtr<()> = AtomicPtr::new(core::ptr::null_mut());
+
+/// Register a function called whenever a `_with_warn` getter or `to_native` fails to
+/// parse a field, e.g. to count failures or raise an alert
+///
+/// Structs with `#[bm(on_error = "...")]` call their own function instead.
+pub fn set_parse_failure_hook(hook: ParseFailureHook) {
+    HOOK.store(hook as *mut (), Ordering::Release);
+}
+
+/// Remove the registered parse failure hook
+pub fn clear_parse_failure_hook() {
+    HOOK.store(core::ptr::null_mut(), Ordering::Release);
+}
+
+/// Called by the generated code when a field fails to parse
+#[doc(hidden)]
+pub fn parse_failure(struct_name: &'static str, field_name: &'static str, bytes: &[u8]) {
+    let hook = HOOK.load(Ordering::Acquire);
+    if !hook.is_null() {
+        // SAFETY: the only non-null values stored are `ParseFailureHook`s
+        let hook = unsafe { core::mem::transmute::<*mut (), ParseFailureHook>(hook) };
+        hook(struct_name, field_name, bytes);
+    }
+}
+
+/// Count a field that failed to parse in the `binary_mirror_parse_failures` counter,
+/// labeled by `struct` and `field`, when the `metrics` feature is enabled
+#[doc(hidden)]
+#[inline]
+pub fn count_parse_failure(struct_name: &'static str, field_name: &'static str) {
+    #[cfg(feature = "metrics")]
+    metrics::counter!(
+        "binary_mirror_parse_failures",
+        "struct" => struct_name,
+        "field" => field_name
+    )
+    .increment(1);
+    #[cfg(not(feature = "metrics"))]
+    let _ = (struct_name, field_name);
+}
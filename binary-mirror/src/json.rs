@@ -0,0 +1,6 @@
+//! JSON export for derived structs, enabled with the `json` feature
+//!
+//! Enable the `json` feature of `binary-mirror-derive` to have `to_json` and `write_json`
+//! generated, serializing the `SerdeView` of a record without building its native struct.
+
+pub use serde_json;
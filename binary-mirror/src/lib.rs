@@ -1,4 +1,102 @@
-use std::fmt;
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use alloc::borrow::Cow;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+
+#[cfg(feature = "arbitrary")]
+pub mod arbitrary;
+#[cfg(feature = "fake")]
+pub use rand;
+#[cfg(feature = "arrow")]
+pub mod arrow;
+mod accumulator;
+mod ascii;
+pub mod bytes_repr;
+mod c_header;
+pub mod checksum;
+pub mod compat;
+#[cfg(feature = "codec")]
+pub mod codec;
+#[cfg(feature = "dynamic")]
+mod dynamic;
+#[cfg(feature = "json")]
+pub mod json;
+mod flat_file;
+mod formats;
+mod frame;
+mod hook;
+#[cfg(feature = "mmap")]
+mod mmap;
+mod number;
+#[cfg(feature = "rayon")]
+mod par;
+#[cfg(feature = "parquet")]
+pub mod parquet;
+mod records;
+pub mod redact;
+mod reflect;
+mod schema;
+mod sql;
+mod text;
+#[cfg(feature = "async")]
+pub mod stream;
+#[cfg(feature = "yaml")]
+pub mod yaml;
+
+pub use accumulator::{Accumulator, Records};
+pub use ascii::{is_blank, trim_ascii, trim_ascii_end, trim_ascii_start};
+pub use c_header::{c_constant_name, c_enum_definition};
+#[cfg(feature = "dynamic")]
+pub use dynamic::{DynamicField, DynamicMirror, LayoutError};
+pub use flat_file::{FlatFileError, FlatFileReader, FlatRecord, RecordKind, RecordTypes};
+pub use formats::FormatOverrides;
+pub use frame::{Frame, FrameError, Framing, LengthEncoding};
+#[doc(hidden)]
+pub use hook::{count_parse_failure, parse_failure};
+pub use hook::{clear_parse_failure_hook, set_parse_failure_hook, ParseFailureHook};
+#[cfg(feature = "mmap")]
+pub use mmap::{Mmap, MmapRecords};
+pub use number::{
+    check_width, delocalize_number, fill_field, fill_number, format_fixed_point, localize_number, normalize_sign,
+    place_sign, trim_fill, write_fixed_point, write_localized, write_sign, Rounding, Sign,
+};
+pub use text::TextBuf;
+#[cfg(feature = "rayon")]
+pub use par::decode_par;
+pub use records::{CorruptReason, RecordIter, ResyncRecords, SkippedBytes};
+pub use reflect::{FieldDescriptor, FieldKind, Reflect, StructDescriptor};
+pub use schema::{Schema, SchemaError, SchemaField, SchemaMismatch};
+pub use sql::SqlValue;
+
+/// Not public API, the `alloc` names used by the generated code so it also
+/// compiles in `no_std` crates
+#[doc(hidden)]
+pub mod __private {
+    pub use alloc::boxed::Box;
+    pub use alloc::format;
+    pub use alloc::string::{String, ToString};
+    pub use alloc::vec;
+    pub use alloc::vec::Vec;
+
+    /// `let () = NonEmpty::<T>::CHECK;` fails the build for records of 0 bytes, which a
+    /// buffer can't be split into
+    pub struct NonEmpty<T: ?Sized>(core::marker::PhantomData<T>);
+
+    impl<T: crate::FromBytes + ?Sized> NonEmpty<T> {
+        pub const CHECK: () = assert!(T::SIZE > 0, "records of 0 bytes can't be split from a buffer");
+    }
+
+    #[cfg(feature = "regex")]
+    pub mod __regex {
+        pub use regex::Regex;
+        pub use std::sync::OnceLock;
+    }
+}
 
 #[derive(Debug)]
 pub struct BytesSizeError {
@@ -17,6 +115,7 @@ impl fmt::Display for BytesSizeError {
     }
 }
 
+#[cfg(feature = "std")]
 impl std::error::Error for BytesSizeError {}
 
 impl BytesSizeError {
@@ -29,6 +128,228 @@ impl BytesSizeError {
     }
 }
 
+/// Error of `write_to` when the buffer is shorter than the record
+#[derive(Debug)]
+pub struct BufferTooSmall {
+    pub(crate) needed: usize,
+    pub(crate) available: usize,
+}
+
+impl fmt::Display for BufferTooSmall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "buffer too small: needed {} bytes but only {} are available",
+            self.needed, self.available
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BufferTooSmall {}
+
+impl BufferTooSmall {
+    pub fn new(needed: usize, available: usize) -> Self {
+        Self { needed, available }
+    }
+
+    /// Length of the record
+    pub fn needed(&self) -> usize {
+        self.needed
+    }
+
+    /// Length of the buffer
+    pub fn available(&self) -> usize {
+        self.available
+    }
+}
+
+/// Error of the `decode` generated by `#[derive(BinaryMessage)]`
+#[derive(Debug)]
+pub enum DecodeError {
+    /// No variant's tag matches the message
+    UnknownTag { offset: usize, bytes: String },
+    /// The tag matched but the length doesn't match the variant's struct
+    Size(BytesSizeError),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownTag { offset, bytes } => {
+                write!(f, "unknown message tag at offset {}, content: \"{}\"", offset, bytes)
+            }
+            Self::Size(err) => err.fmt(f),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DecodeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::UnknownTag { .. } => None,
+            Self::Size(err) => Some(err),
+        }
+    }
+}
+
+impl From<BytesSizeError> for DecodeError {
+    fn from(err: BytesSizeError) -> Self {
+        Self::Size(err)
+    }
+}
+
+impl DecodeError {
+    pub fn unknown_tag(offset: usize, bytes: &[u8]) -> Self {
+        Self::UnknownTag {
+            offset,
+            bytes: to_bytes_repr(bytes),
+        }
+    }
+}
+
+/// Error of the `FromStr` generated by `#[derive(BinaryEnum)]`
+#[derive(Debug)]
+pub struct ParseEnumError {
+    pub(crate) enum_name: &'static str,
+    pub(crate) value: String,
+}
+
+impl fmt::Display for ParseEnumError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown {} value \"{}\"", self.enum_name, self.value)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseEnumError {}
+
+impl ParseEnumError {
+    pub fn new(enum_name: &'static str, value: &str) -> Self {
+        Self {
+            enum_name,
+            value: value.to_string(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ValidationError {
+    pub(crate) field: &'static str,
+    pub(crate) message: String,
+    pub(crate) bytes: String,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "validation failed for {}: {}, content: \"{}\"",
+            self.field, self.message, self.bytes
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ValidationError {}
+
+impl ValidationError {
+    pub fn new(field: &'static str, message: String, bytes: String) -> Self {
+        Self {
+            field,
+            message,
+            bytes,
+        }
+    }
+
+    /// Name of the field that failed validation
+    pub fn field(&self) -> &'static str {
+        self.field
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldErrorKind {
+    /// The field bytes could not be parsed into the native type
+    Parse,
+    /// No field with the given name exists, `field()` is then empty
+    UnknownField { name: String },
+    /// The bytes given for the field have the wrong length
+    SizeMismatch { expected: usize, actual: usize },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldError {
+    pub(crate) field: &'static str,
+    pub(crate) kind: FieldErrorKind,
+    pub(crate) bytes: String,
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            FieldErrorKind::Parse => {
+                write!(f, "failed to parse {}, content: \"{}\"", self.field, self.bytes)
+            }
+            FieldErrorKind::UnknownField { name } => write!(f, "unknown field {}", name),
+            FieldErrorKind::SizeMismatch { expected, actual } => write!(
+                f,
+                "field {} expects {} bytes but got {} bytes, content: \"{}\"",
+                self.field, expected, actual, self.bytes
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FieldError {}
+
+impl FieldError {
+    pub fn new(field: &'static str, bytes: String) -> Self {
+        Self {
+            field,
+            kind: FieldErrorKind::Parse,
+            bytes,
+        }
+    }
+
+    pub fn unknown_field(field: &str) -> Self {
+        Self {
+            field: "",
+            kind: FieldErrorKind::UnknownField { name: field.to_string() },
+            bytes: String::new(),
+        }
+    }
+
+    pub fn size_mismatch(field: &'static str, expected: usize, bytes: &[u8]) -> Self {
+        Self {
+            field,
+            kind: FieldErrorKind::SizeMismatch {
+                expected,
+                actual: bytes.len(),
+            },
+            bytes: to_bytes_repr(bytes),
+        }
+    }
+
+    /// Name of the field the error is about
+    ///
+    /// Empty for `FieldErrorKind::UnknownField`, which carries the unknown name instead
+    pub fn field(&self) -> &'static str {
+        self.field
+    }
+
+    pub fn kind(&self) -> &FieldErrorKind {
+        &self.kind
+    }
+
+    /// Raw bytes of the field in escaped form
+    pub fn bytes(&self) -> &str {
+        &self.bytes
+    }
+}
+
 pub fn to_hex_repr(bytes: &[u8]) -> String {
     bytes.iter().map(|b| format!("0x{:02x}", b)).collect::<Vec<_>>().join(", ")
 }
@@ -45,22 +366,208 @@ pub fn to_bytes_repr(bytes: &[u8]) -> String {
     }).collect::<Vec<String>>().join("")
 }
 
-#[derive(Debug, Clone)]
+/// Hexdump with offsets, hex and ASCII, one line per 16 bytes of each field, labelled with
+/// the field name on its first line
+pub fn hexdump(bytes: &[u8], fields: &[(&str, FieldSpec)]) -> String {
+    let mut out = String::new();
+    for (name, spec) in fields {
+        let field_bytes = bytes.get(spec.offset..spec.limit).unwrap_or_default();
+        for (i, chunk) in field_bytes.chunks(16).enumerate() {
+            let hex = chunk
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect::<Vec<_>>()
+                .join(" ");
+            let ascii = chunk
+                .iter()
+                .map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' })
+                .collect::<String>();
+            let label = if i == 0 { name } else { "" };
+            out.push_str(&format!(
+                "{:08x}  {:<47}  |{:<16}|  {}\n",
+                spec.offset + i * 16,
+                hex,
+                ascii,
+                label
+            ));
+        }
+    }
+    out
+}
+
+/// Quote a CSV field when it contains a delimiter, quote or line break (RFC 4180)
+pub fn csv_field(value: &str) -> Cow<'_, str> {
+    if value.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", value.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(value)
+    }
+}
+
+/// Parse an integer or float straight from ASCII bytes, without going through `str`
+pub fn parse_number<T: lexical_core::FromLexical>(bytes: &[u8]) -> Option<T> {
+    lexical_core::parse(bytes).ok()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct FieldSpec {
     pub offset: usize,
     pub limit: usize,
     pub size: usize,
+    /// The `type` declared in `#[bm(...)]`, None for untyped fields
+    pub type_name: Option<&'static str>,
+}
+
+/// A field whose bytes differ between two records, as returned by the generated `diff`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldDiff {
+    /// Name of the field in the raw struct
+    pub field: &'static str,
+    pub offset: usize,
+    pub size: usize,
+    /// Decoded value of the left record, or its escaped bytes when it can't be decoded
+    pub left: String,
+    /// Decoded value of the right record, or its escaped bytes when it can't be decoded
+    pub right: String,
+}
+
+impl fmt::Display for FieldDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} != {}", self.field, self.left, self.right)
+    }
+}
+
+/// A field of a record with its byte range, as returned by the generated `inspect_fields`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldInspect {
+    /// Name of the field in the raw struct
+    pub field: &'static str,
+    pub offset: usize,
+    pub limit: usize,
+    /// The bytes as `to_hex_repr`, `***` for a redacted field
+    pub hex: String,
+    /// Decoded value, `Error<bytes: "...">` when it can't be decoded and the escaped bytes
+    /// for fields without a type
+    pub value: String,
+}
+
+impl fmt::Display for FieldInspect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} @ [{}..{}] hex: [{}] value: {}",
+            self.field, self.offset, self.limit, self.hex, self.value
+        )
+    }
+}
+
+/// One line per field of `name`, `field @ [offset..limit]` followed by its hex and decoded
+/// value, with the names and ranges aligned
+pub fn inspect(name: &str, fields: &[FieldInspect]) -> String {
+    let ranges = fields
+        .iter()
+        .map(|field| format!("[{}..{}]", field.offset, field.limit))
+        .collect::<Vec<_>>();
+    let name_width = fields.iter().map(|field| field.field.len()).max().unwrap_or(0);
+    let range_width = ranges.iter().map(String::len).max().unwrap_or(0);
+    let mut out = format!("{} {{\n", name);
+    for (field, range) in fields.iter().zip(&ranges) {
+        out.push_str(&format!(
+            "    {:<name_width$} @ {:<range_width$}  hex: [{}]  value: {}\n",
+            field.field,
+            range,
+            field.hex,
+            field.value,
+            name_width = name_width,
+            range_width = range_width
+        ));
+    }
+    out.push('}');
+    out
+}
+
+/// The original bytes a `preserve_unknown` native struct was converted from
+///
+/// `from_native` writes them back for every field whose value is unchanged, so an
+/// untouched record, fields that failed to parse included, round-trips byte for byte.
+/// Like the bytes of a `carry_raw` field, they take part in comparing, ordering and
+/// hashing the native struct, so equal native structs always write the same bytes.
+#[derive(Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PreservedBytes(Option<Vec<u8>>);
+
+impl PreservedBytes {
+    pub fn new(bytes: &[u8]) -> Self {
+        Self(Some(bytes.to_vec()))
+    }
+
+    /// The carried bytes, None for a native struct built by hand
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        self.0.as_deref()
+    }
+
+    /// Replace the carried bytes, reusing the buffer
+    pub fn set(&mut self, bytes: &[u8]) {
+        let buf = self.0.get_or_insert_with(Vec::new);
+        buf.clear();
+        buf.extend_from_slice(bytes);
+    }
+
+    /// Drop the carried bytes, so every field is written from its native value
+    pub fn clear(&mut self) {
+        self.0 = None;
+    }
+}
+
+impl fmt::Debug for PreservedBytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            Some(bytes) => write!(f, "PreservedBytes({} bytes)", bytes.len()),
+            None => write!(f, "PreservedBytes(None)"),
+        }
+    }
 }
 
-pub trait FromBytes: Sized {
-    /// Get the size of the struct in bytes
+/// Structs ending in a `rest` field are unsized, they only support `from_bytes`
+pub trait FromBytes {
+    /// Get the size of the struct in bytes, the fixed prefix for structs with a `rest` field
     const SIZE: usize;
 
     /// Create a new instance from bytes
     /// Returns Err if the bytes length doesn't match the struct size
     fn from_bytes(bytes: &[u8]) -> Result<&Self, BytesSizeError>;
+
+    /// View a buffer of concatenated records as a slice of structs
+    /// Returns Err if the bytes length isn't a multiple of the struct size
+    fn from_bytes_many(bytes: &[u8]) -> Result<&[Self], BytesSizeError>
+    where
+        Self: Sized;
+
+    /// Copy the bytes into an owned instance that can outlive the input buffer
+    /// Returns Err if the bytes length doesn't match the struct size
+    fn from_bytes_owned(bytes: &[u8]) -> Result<Self, BytesSizeError>
+    where
+        Self: Sized;
+
+    /// Iterate over the records of a buffer, a trailing partial record is yielded as Err
+    fn iter_records(bytes: &[u8]) -> RecordIter<'_, Self>
+    where
+        Self: Sized,
+    {
+        RecordIter::new(bytes)
+    }
 }
 
+/// Marker for structs that are plain bytes, implemented by `#[derive(BinaryMirror)]`
+///
+/// The type parameters of a generic struct are bounded on it, `from_bytes` casts the record
+/// bytes to the struct and so to its payload.
+///
+/// # Safety
+///
+/// Every byte pattern of the type's size must be a valid value and its alignment must be 1.
+/// Implement it by deriving `BinaryMirror`, never by hand.
+pub unsafe trait RawRecord {}
+
 pub trait ToBytes {
     /// Convert the struct to its binary representation
     fn to_bytes(&self) -> &[u8];
@@ -69,6 +576,29 @@ pub trait ToBytes {
     fn to_bytes_owned(&self) -> Vec<u8> {
         self.to_bytes().to_vec()
     }
+
+    /// Copy the bytes to the start of `buf` without allocating, returning how many were written
+    /// Returns Err if `buf` is shorter than the struct
+    fn write_to(&self, buf: &mut [u8]) -> Result<usize, BufferTooSmall> {
+        let bytes = self.to_bytes();
+        if buf.len() < bytes.len() {
+            return Err(BufferTooSmall::new(bytes.len(), buf.len()));
+        }
+        buf[..bytes.len()].copy_from_slice(bytes);
+        Ok(bytes.len())
+    }
+
+    /// Write the bytes to `writer` without an intermediate Vec
+    #[cfg(feature = "std")]
+    fn to_bytes_into<W: std::io::Write>(&self, mut writer: W) -> std::io::Result<()> {
+        writer.write_all(self.to_bytes())
+    }
+}
+
+pub trait Validate {
+    /// Run the checks declared with `#[bm(validate(...))]`
+    /// Returns Err for the first field that fails
+    fn validate(&self) -> Result<(), ValidationError>;
 }
 
 pub trait ToNative {
@@ -76,6 +606,23 @@ pub trait ToNative {
     
     /// Convert to native type
     fn to_native(&self) -> Self::Native;
+
+    /// Convert to native type, collecting the fields that failed to parse
+    fn to_native_with_report(&self) -> (Self::Native, Vec<FieldError>) {
+        (self.to_native(), Vec::new())
+    }
+}
+
+/// A borrowed view of a raw struct that serializes like its native struct, without building it
+///
+/// `#[derive(BinaryMirror)]` implements it when the native struct derives `Serialize`.
+pub trait SerdeView {
+    type View<'a>: serde::Serialize
+    where
+        Self: 'a;
+
+    /// View the record for serialization
+    fn serde_view(&self) -> Self::View<'_>;
 }
 
 pub trait FromNative<T> {
@@ -0,0 +1,76 @@
+//! Memory-mapped view over a file of fixed-length records.
+
+use std::fs::File;
+use std::io;
+use std::marker::PhantomData;
+use std::ops::Deref;
+use std::path::Path;
+
+pub use memmap2::Mmap;
+
+use crate::{BytesSizeError, FromBytes};
+
+/// A file of concatenated records mapped into memory and viewed as `&[T]`
+///
+/// The length is checked once when the view is created, indexing and iterating go through
+/// `Deref<Target = [T]>` without copying the file into RAM.
+pub struct MmapRecords<T> {
+    mmap: Mmap,
+    _marker: PhantomData<T>,
+}
+
+impl<T: FromBytes> MmapRecords<T> {
+    /// Map the file at `path`
+    /// Returns Err if the file can't be mapped or its length isn't a multiple of the struct size
+    ///
+    /// # Safety
+    /// The file must not be modified or truncated while the view is alive, see [`Mmap::map`].
+    pub unsafe fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = File::open(path)?;
+        let mmap = unsafe { Mmap::map(&file)? };
+        Self::from_mmap(mmap).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    /// Wrap an existing mapping
+    /// Returns Err if its length isn't a multiple of the struct size
+    pub fn from_mmap(mmap: Mmap) -> Result<Self, BytesSizeError> {
+        T::from_bytes_many(&mmap)?;
+        Ok(Self {
+            mmap,
+            _marker: PhantomData,
+        })
+    }
+
+    /// The records of the file
+    pub fn as_slice(&self) -> &[T] {
+        T::from_bytes_many(&self.mmap).expect("length checked when the view was created")
+    }
+
+    /// The raw bytes of the file
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.mmap
+    }
+}
+
+impl<T: FromBytes> Deref for MmapRecords<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<'a, T: FromBytes> IntoIterator for &'a MmapRecords<T> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.as_slice().iter()
+    }
+}
+
+impl<T> std::fmt::Debug for MmapRecords<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MmapRecords").field("len", &self.mmap.len()).finish()
+    }
+}
@@ -0,0 +1,264 @@
+use alloc::string::String;
+use core::fmt;
+
+use crate::FieldError;
+
+/// How digits past the precision of a `precision = N` field are dropped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rounding {
+    /// Round a dropped 5 or more away from zero, 2.675 is 2.68
+    HalfUp,
+    /// Drop the extra digits, 2.679 is 2.67
+    Truncate,
+}
+
+/// Format the decimal text of a number, such as its `Display` output, with exactly
+/// `precision` digits after the point, zero-padded after the sign to `width`
+///
+/// Rounding works on the decimal digits rather than the binary value of a float, so
+/// `2.675_f64` rounds half up to `2.68`. A value rounding to zero loses its sign, and
+/// text that is not a plain decimal number, such as `NaN`, is returned as is.
+pub fn format_fixed_point(text: &str, precision: usize, rounding: Rounding, width: usize) -> String {
+    let mut out = String::with_capacity(width.max(text.len() + precision + 2));
+    let _ = write_fixed_point(&mut out, text, precision, rounding, width);
+    out
+}
+
+/// [`format_fixed_point`] into `out` without allocating
+pub fn write_fixed_point(
+    out: &mut impl fmt::Write,
+    text: &str,
+    precision: usize,
+    rounding: Rounding,
+    width: usize,
+) -> fmt::Result {
+    let (negative, unsigned) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text.strip_prefix('+').unwrap_or(text)),
+    };
+    let (int_part, frac_part) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+    let is_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    if int_part.is_empty() || !is_digits(int_part) || !is_digits(frac_part) {
+        return out.write_str(text);
+    }
+
+    // the integer digits then `precision` fraction digits, zero-extended
+    let point = int_part.len();
+    let count = point + precision;
+    let digit = |i: usize| match i.checked_sub(point) {
+        None => int_part.as_bytes()[i],
+        Some(i) => frac_part.as_bytes().get(i).copied().unwrap_or(b'0'),
+    };
+    let round_up = rounding == Rounding::HalfUp
+        && frac_part.as_bytes().get(precision).is_some_and(|b| *b >= b'5');
+    // rounding up adds one to the last digit that isn't a 9 and zeroes the 9s after it,
+    // all 9s carry into a new leading 1
+    let bumped = if round_up { (0..count).rev().find(|&i| digit(i) != b'9') } else { None };
+    let carried = round_up && bumped.is_none();
+    let rounded = |i: usize| match bumped {
+        _ if !round_up => digit(i),
+        Some(bumped) if i < bumped => digit(i),
+        Some(bumped) if i == bumped => digit(i) + 1,
+        _ => b'0',
+    };
+
+    let negative = negative && (carried || (0..count).any(|i| rounded(i) != b'0'));
+    let len = count + usize::from(carried) + usize::from(precision > 0) + usize::from(negative);
+    if negative {
+        out.write_char('-')?;
+    }
+    for _ in len..width {
+        out.write_char('0')?;
+    }
+    if carried {
+        out.write_char('1')?;
+    }
+    for i in 0..count {
+        if i == point {
+            out.write_char('.')?;
+        }
+        out.write_char(rounded(i) as char)?;
+    }
+    Ok(())
+}
+
+/// Where a numeric field keeps the sign of a negative value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sign {
+    /// `-123.45`, what `str::parse` and `to_string` use
+    Leading,
+    /// `123.45-`, common in mainframe and settlement files
+    Trailing,
+    /// `(123.45)`, the accounting convention
+    Paren,
+}
+
+/// Rewrite the sign of trimmed field bytes as a leading `-` in `buf`, ready for parsing
+///
+/// Bytes without the sign convention pass through unchanged, so a leading sign still
+/// parses. An unbalanced parenthesis, or a `buf` too small, is None.
+pub fn normalize_sign<'a>(bytes: &'a [u8], sign: Sign, buf: &'a mut [u8]) -> Option<&'a [u8]> {
+    let digits = match (sign, bytes) {
+        (Sign::Trailing, [rest @ .., b'-']) => crate::trim_ascii_end(rest),
+        (Sign::Trailing, [rest @ .., b'+']) => return Some(crate::trim_ascii_end(rest)),
+        (Sign::Paren, [b'(', rest @ .., b')']) => crate::trim_ascii(rest),
+        (Sign::Paren, [b'(', ..]) | (Sign::Paren, [.., b')']) => return None,
+        _ => return Some(bytes),
+    };
+    let out = buf.get_mut(..digits.len() + 1)?;
+    out[0] = b'-';
+    out[1..].copy_from_slice(digits);
+    Some(out)
+}
+
+/// Move the leading `-` of formatted text to where `sign` keeps it
+///
+/// Parentheses take the place of a padding zero when there is one, so zero-padded
+/// text keeps its width.
+pub fn place_sign(text: &str, sign: Sign) -> String {
+    let mut out = String::with_capacity(text.len() + 1);
+    let _ = write_sign(&mut out, text, sign);
+    out
+}
+
+/// [`place_sign`] into `out` without allocating
+pub fn write_sign(out: &mut impl fmt::Write, text: &str, sign: Sign) -> fmt::Result {
+    let digits = match text.strip_prefix('-') {
+        Some(digits) if sign != Sign::Leading => digits,
+        _ => return out.write_str(text),
+    };
+    match sign {
+        Sign::Trailing => {
+            out.write_str(digits)?;
+            out.write_char('-')
+        }
+        _ => {
+            let padded = digits.len() > 1
+                && digits.starts_with('0')
+                && digits.as_bytes()[1].is_ascii_digit();
+            out.write_char('(')?;
+            out.write_str(if padded { &digits[1..] } else { digits })?;
+            out.write_char(')')
+        }
+    }
+}
+
+/// Rewrite a number written with a locale's separators into `buf` with a `.` decimal
+/// point and no grouping, ready for parsing
+///
+/// `thousands` separators are dropped wherever they appear. A `buf` too small is None.
+pub fn delocalize_number<'a>(
+    bytes: &[u8],
+    thousands: Option<u8>,
+    decimal: u8,
+    buf: &'a mut [u8],
+) -> Option<&'a [u8]> {
+    let mut len = 0;
+    for &b in bytes {
+        if Some(b) == thousands {
+            continue;
+        }
+        *buf.get_mut(len)? = if b == decimal { b'.' } else { b };
+        len += 1;
+    }
+    Some(&buf[..len])
+}
+
+/// Write formatted text with a locale's decimal separator, and with `thousands`
+/// separators between groups of three integer digits
+///
+/// Grouping skips padding zeros and pads the result back to the width of the text,
+/// so `0001234.5` becomes `001,234.5`.
+pub fn localize_number(text: &str, thousands: Option<char>, decimal: char) -> String {
+    let mut out = String::with_capacity(text.len() + text.len() / 3);
+    let _ = write_localized(&mut out, text, thousands, decimal);
+    out
+}
+
+/// [`localize_number`] into `out` without allocating
+pub fn write_localized(
+    out: &mut impl fmt::Write,
+    text: &str,
+    thousands: Option<char>,
+    decimal: char,
+) -> fmt::Result {
+    let (sign, unsigned) = match text.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", text),
+    };
+    let (int_part, frac_part) = match unsigned.split_once('.') {
+        Some((int_part, frac_part)) => (int_part, Some(frac_part)),
+        None => (unsigned, None),
+    };
+    out.write_str(sign)?;
+    match thousands {
+        Some(separator) if int_part.bytes().all(|b| b.is_ascii_digit()) => {
+            let significant = match int_part.trim_start_matches('0') {
+                "" if !int_part.is_empty() => "0",
+                significant => significant,
+            };
+            let groups = significant.len().saturating_sub(1) / 3;
+            let grouped_len = significant.len() + groups * separator.len_utf8();
+            for _ in grouped_len..int_part.len() {
+                out.write_char('0')?;
+            }
+            for (i, c) in significant.chars().enumerate() {
+                if i > 0 && (significant.len() - i) % 3 == 0 {
+                    out.write_char(separator)?;
+                }
+                out.write_char(c)?;
+            }
+        }
+        _ => out.write_str(int_part)?,
+    }
+    if let Some(frac_part) = frac_part {
+        out.write_char(decimal)?;
+        out.write_str(frac_part)?;
+    }
+    Ok(())
+}
+
+/// Trimmed field bytes without their leading `fill` bytes, such as the asterisks that
+/// protect a printed amount
+///
+/// A zero fill is kept, since the digits parse as they are.
+pub fn trim_fill(bytes: &[u8], fill: u8) -> &[u8] {
+    if fill == b'0' {
+        return bytes;
+    }
+    let start = bytes.iter().position(|b| *b != fill).unwrap_or(bytes.len());
+    &bytes[start..]
+}
+
+/// Fail with a size mismatch when the formatted `text` of `field` is wider than its `width`
+pub fn check_width(field: &'static str, text: &str, width: usize) -> Result<(), FieldError> {
+    if text.len() > width {
+        return Err(FieldError::size_mismatch(field, width, text.as_bytes()));
+    }
+    Ok(())
+}
+
+/// Write formatted text right-aligned in `out`, padded on the left with `fill`
+///
+/// A zero fill goes after a leading `-` or `(`, so the number still parses. Text longer
+/// than `out` is cut to its width and returned as a size mismatch of `field`.
+pub fn fill_number(field: &'static str, text: &str, fill: u8, out: &mut [u8]) -> Result<(), FieldError> {
+    let result = check_width(field, text, out.len());
+    fill_field(text, fill, out);
+    result
+}
+
+/// [`fill_number`] without the size check, text longer than `out` is cut to its width
+pub fn fill_field(text: &str, fill: u8, out: &mut [u8]) {
+    let text = text.as_bytes();
+    if text.len() >= out.len() {
+        let len = out.len();
+        out.copy_from_slice(&text[..len]);
+        return;
+    }
+    let pad = out.len() - text.len();
+    let sign = usize::from(fill == b'0' && matches!(text.first(), Some(b'-' | b'(')));
+    out[..sign].copy_from_slice(&text[..sign]);
+    out[sign..sign + pad].fill(fill);
+    out[sign + pad..].copy_from_slice(&text[sign..]);
+}
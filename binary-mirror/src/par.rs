@@ -0,0 +1,16 @@
+use rayon::prelude::*;
+
+use crate::{BytesSizeError, FromBytes, ToNative};
+
+/// Convert a buffer of concatenated records to native structs in parallel
+/// Returns Err if the bytes length isn't a multiple of the struct size
+pub fn decode_par<T>(bytes: &[u8]) -> Result<Vec<T::Native>, BytesSizeError>
+where
+    T: FromBytes + ToNative + Sync,
+    T::Native: Send,
+{
+    Ok(T::from_bytes_many(bytes)?
+        .par_iter()
+        .map(ToNative::to_native)
+        .collect())
+}
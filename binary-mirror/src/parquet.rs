@@ -0,0 +1,52 @@
+//! Parquet output for derived structs, enabled with the `parquet` feature
+
+use std::borrow::Borrow;
+use std::io::Write;
+use std::sync::Arc;
+
+pub use ::parquet::errors::ParquetError;
+pub use ::parquet::file::properties::WriterProperties;
+
+use ::parquet::arrow::ArrowWriter;
+
+use crate::arrow::ToArrow;
+
+/// Number of records per row group written by `write_parquet`
+pub const DEFAULT_BATCH_SIZE: usize = 64 * 1024;
+
+/// Write raw records to a Parquet file, using the Arrow schema derived from the field attributes
+pub fn write_parquet<T, R, W>(writer: W, records: impl IntoIterator<Item = R>) -> Result<(), ParquetError>
+where
+    T: ToArrow,
+    R: Borrow<T>,
+    W: Write + Send,
+{
+    write_parquet_with(writer, records, DEFAULT_BATCH_SIZE, None)
+}
+
+/// Same as `write_parquet`, with the number of records per batch and writer properties
+pub fn write_parquet_with<T, R, W>(
+    writer: W,
+    records: impl IntoIterator<Item = R>,
+    batch_size: usize,
+    props: Option<WriterProperties>,
+) -> Result<(), ParquetError>
+where
+    T: ToArrow,
+    R: Borrow<T>,
+    W: Write + Send,
+{
+    let mut writer = ArrowWriter::try_new(writer, Arc::new(T::arrow_schema()), props)?;
+    let mut records = records.into_iter();
+    let mut batch = Vec::with_capacity(batch_size);
+    loop {
+        batch.extend(records.by_ref().take(batch_size.max(1)));
+        if batch.is_empty() {
+            break;
+        }
+        writer.write(&T::to_record_batch(batch.iter().map(Borrow::borrow))?)?;
+        batch.clear();
+    }
+    writer.close()?;
+    Ok(())
+}
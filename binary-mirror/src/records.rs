@@ -0,0 +1,173 @@
+use core::fmt;
+use core::iter::FusedIterator;
+use core::marker::PhantomData;
+
+use crate::{BytesSizeError, FrameError, Framing, FromBytes, Validate, ValidationError};
+
+/// Iterator over the records of a buffer, one `T::SIZE` chunk at a time
+///
+/// A trailing partial chunk is yielded once as Err, after every complete record.
+/// Records of 0 bytes fail the build in `new`.
+#[derive(Debug, Clone)]
+pub struct RecordIter<'a, T> {
+    bytes: &'a [u8],
+    _marker: PhantomData<&'a T>,
+}
+
+impl<'a, T: FromBytes> RecordIter<'a, T> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        let () = crate::__private::NonEmpty::<T>::CHECK;
+        Self {
+            bytes,
+            _marker: PhantomData,
+        }
+    }
+
+    /// The bytes not yet consumed
+    pub fn remaining(&self) -> &'a [u8] {
+        self.bytes
+    }
+}
+
+impl<'a, T: FromBytes + 'a> Iterator for RecordIter<'a, T> {
+    type Item = Result<&'a T, BytesSizeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.bytes.is_empty() {
+            return None;
+        }
+        let split = T::SIZE.min(self.bytes.len());
+        let (chunk, rest) = self.bytes.split_at(split);
+        self.bytes = rest;
+        Some(T::from_bytes(chunk))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = (self.bytes.len() + T::SIZE - 1) / T::SIZE;
+        (len, Some(len))
+    }
+}
+
+impl<'a, T: FromBytes + 'a> ExactSizeIterator for RecordIter<'a, T> {}
+
+impl<'a, T: FromBytes + 'a> FusedIterator for RecordIter<'a, T> {}
+
+/// Why the bytes at an offset are not a record
+#[derive(Debug)]
+pub enum CorruptReason {
+    /// The framing is wrong, or the buffer ends inside the frame
+    Frame(FrameError),
+    Size(BytesSizeError),
+    Validation(ValidationError),
+    /// The frame doesn't pass the `boundary` check
+    Boundary,
+}
+
+impl fmt::Display for CorruptReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Frame(err) => err.fmt(f),
+            Self::Size(err) => err.fmt(f),
+            Self::Validation(err) => err.fmt(f),
+            Self::Boundary => f.write_str("no record boundary"),
+        }
+    }
+}
+
+/// Bytes skipped by [`ResyncRecords`] to reach the next record
+#[derive(Debug)]
+pub struct SkippedBytes {
+    pub offset: usize,
+    pub len: usize,
+    /// Why the record at `offset` was rejected
+    pub reason: CorruptReason,
+}
+
+impl fmt::Display for SkippedBytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "skipped {} bytes at offset {}: {}", self.len, self.offset, self.reason)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SkippedBytes {}
+
+/// Iterator over the framed records of a buffer that steps over corrupt ones
+///
+/// A record whose frame, size or `Validate` checks fail is not the end of the buffer: the
+/// iterator scans forward one byte at a time for the next offset holding a valid record and
+/// yields the bytes in between as one `SkippedBytes` error. `boundary` narrows what counts as
+/// the start of a record, such as a sync byte or a known record-type code, so a mangled
+/// region is less likely to pass for a record.
+#[derive(Debug, Clone)]
+pub struct ResyncRecords<'a, T> {
+    bytes: &'a [u8],
+    offset: usize,
+    boundary: Option<fn(&[u8]) -> bool>,
+    _marker: PhantomData<&'a T>,
+}
+
+impl<'a, T: FromBytes + Framing + Validate + 'a> ResyncRecords<'a, T> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self {
+            bytes,
+            offset: 0,
+            boundary: None,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Only accept a record whose frame, of `T::FRAME.frame_len(T::SIZE)` bytes, passes `boundary`
+    pub fn boundary(mut self, boundary: fn(&[u8]) -> bool) -> Self {
+        self.boundary = Some(boundary);
+        self
+    }
+
+    /// The bytes not yet consumed
+    pub fn remaining(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    fn decode(&self, start: usize) -> Result<&'a T, CorruptReason> {
+        let frame_len = T::FRAME.frame_len(T::SIZE);
+        let frame = &self.bytes[start..(start + frame_len).min(self.bytes.len())];
+        let record = T::FRAME.strip(frame, T::SIZE).map_err(CorruptReason::Frame)?;
+        if self.boundary.is_some_and(|boundary| !boundary(frame)) {
+            return Err(CorruptReason::Boundary);
+        }
+        let record = T::from_bytes(record).map_err(CorruptReason::Size)?;
+        record.validate().map_err(CorruptReason::Validation)?;
+        Ok(record)
+    }
+
+    fn advance(&mut self, len: usize) {
+        self.bytes = &self.bytes[len..];
+        self.offset += len;
+    }
+}
+
+impl<'a, T: FromBytes + Framing + Validate + 'a> Iterator for ResyncRecords<'a, T> {
+    type Item = Result<&'a T, SkippedBytes>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.bytes.is_empty() {
+            return None;
+        }
+        match self.decode(0) {
+            Ok(record) => {
+                self.advance(T::FRAME.frame_len(T::SIZE));
+                Some(Ok(record))
+            }
+            Err(reason) => {
+                let len = (1..self.bytes.len())
+                    .find(|start| self.decode(*start).is_ok())
+                    .unwrap_or(self.bytes.len());
+                let offset = self.offset;
+                self.advance(len);
+                Some(Err(SkippedBytes { offset, len, reason }))
+            }
+        }
+    }
+}
+
+impl<'a, T: FromBytes + Framing + Validate + 'a> FusedIterator for ResyncRecords<'a, T> {}
@@ -0,0 +1,11 @@
+//! Serde adapter behind `#[bm(redact_serde = true)]`, writing `"***"` in place of the value.
+
+use serde::Serializer;
+
+/// The mask a redacted field shows in Debug, Display and serde output
+pub const MASK: &str = "***";
+
+/// Serialize any value as the mask, for `#[serde(serialize_with = "...")]`
+pub fn serialize<T: ?Sized, S: Serializer>(_value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(MASK)
+}
@@ -0,0 +1,162 @@
+//! Runtime description of the layout generated by `#[derive(BinaryMirror)]`.
+
+use crate::{Schema, SchemaError};
+
+/// The `type` a field is declared with in `#[bm(...)]`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FieldKind {
+    Str,
+    CompactStr,
+    Bytes,
+    I16,
+    I32,
+    I64,
+    U16,
+    U32,
+    U64,
+    F32,
+    F64,
+    Decimal,
+    DateTime,
+    Date,
+    Time,
+    Enum,
+    Custom,
+    Checksum,
+    /// Nested BinaryMirror struct declared with `flatten`
+    Flatten,
+    /// Variable-length trailing bytes declared with `rest`, its size is 0
+    Rest,
+    /// Unused filler declared with `pad`
+    Pad,
+    /// Field without a `#[bm(type = ...)]` attribute
+    Untyped,
+}
+
+impl FieldKind {
+    /// Look up the kind for a `type` name, e.g. `"i32"` or `"decimal"`
+    pub fn from_type_name(type_name: &str) -> Option<Self> {
+        Some(match type_name {
+            "str" => Self::Str,
+            "compact_str" => Self::CompactStr,
+            "bytes" => Self::Bytes,
+            "i16" => Self::I16,
+            "i32" => Self::I32,
+            "i64" => Self::I64,
+            "u16" => Self::U16,
+            "u32" => Self::U32,
+            "u64" => Self::U64,
+            "f32" => Self::F32,
+            "f64" => Self::F64,
+            "decimal" => Self::Decimal,
+            "datetime" => Self::DateTime,
+            "date" => Self::Date,
+            "time" => Self::Time,
+            "enum" => Self::Enum,
+            "custom" => Self::Custom,
+            "checksum" => Self::Checksum,
+            "flatten" => Self::Flatten,
+            "rest" => Self::Rest,
+            "pad" => Self::Pad,
+            _ => return None,
+        })
+    }
+
+    /// The `type` name used in `#[bm(...)]`, None for untyped fields
+    pub fn type_name(&self) -> Option<&'static str> {
+        Some(match self {
+            Self::Str => "str",
+            Self::CompactStr => "compact_str",
+            Self::Bytes => "bytes",
+            Self::I16 => "i16",
+            Self::I32 => "i32",
+            Self::I64 => "i64",
+            Self::U16 => "u16",
+            Self::U32 => "u32",
+            Self::U64 => "u64",
+            Self::F32 => "f32",
+            Self::F64 => "f64",
+            Self::Decimal => "decimal",
+            Self::DateTime => "datetime",
+            Self::Date => "date",
+            Self::Time => "time",
+            Self::Enum => "enum",
+            Self::Custom => "custom",
+            Self::Checksum => "checksum",
+            Self::Flatten => "flatten",
+            Self::Rest => "rest",
+            Self::Pad => "pad",
+            Self::Untyped => return None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FieldDescriptor {
+    /// Name of the field in the raw struct
+    pub name: &'static str,
+    /// Name given with `alias`, if any
+    pub alias: Option<&'static str>,
+    pub offset: usize,
+    pub size: usize,
+    pub kind: FieldKind,
+    /// The `format` attribute, if any
+    pub format: Option<&'static str>,
+    /// The field's `///` comments, lines joined by `\n`
+    pub doc: Option<&'static str>,
+    pub skip: bool,
+    pub skip_native: bool,
+    /// The layout of the struct of a `flatten` field
+    pub nested: Option<fn() -> &'static StructDescriptor>,
+}
+
+impl PartialEq for FieldDescriptor {
+    fn eq(&self, other: &Self) -> bool {
+        // Function addresses aren't unique, compare the nested layouts instead
+        self.name == other.name
+            && self.alias == other.alias
+            && self.offset == other.offset
+            && self.size == other.size
+            && self.kind == other.kind
+            && self.format == other.format
+            && self.doc == other.doc
+            && self.skip == other.skip
+            && self.skip_native == other.skip_native
+            && self.nested.map(|nested| nested()) == other.nested.map(|nested| nested())
+    }
+}
+
+impl Eq for FieldDescriptor {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StructDescriptor {
+    pub name: &'static str,
+    pub native_name: &'static str,
+    pub size: usize,
+    pub fields: &'static [FieldDescriptor],
+}
+
+pub trait Reflect {
+    /// Get the layout of the struct
+    fn descriptor() -> &'static StructDescriptor;
+
+    /// Get the descriptors of every field in layout order
+    fn fields() -> &'static [FieldDescriptor] {
+        Self::descriptor().fields
+    }
+
+    /// Find a field descriptor by its raw field name
+    fn field(name: &str) -> Option<&'static FieldDescriptor> {
+        Self::fields().iter().find(|field| field.name == name)
+    }
+
+    /// Get the layout as a serializable schema
+    fn schema() -> Schema {
+        Schema::from_descriptor(Self::descriptor())
+    }
+
+    /// Check a published schema, e.g. loaded from a venue's layout file, against the compiled layout
+    fn check_schema(published: &Schema) -> Result<(), SchemaError> {
+        Self::schema().check(published)
+    }
+}
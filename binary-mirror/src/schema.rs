@@ -0,0 +1,240 @@
+//! Serializable layout schemas, to publish a layout and to check a published one at startup.
+
+use core::fmt;
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+
+use crate::compat::{self, FieldChange};
+use crate::StructDescriptor;
+
+/// A layout as data, see [`Reflect::schema`](crate::Reflect::schema)
+///
+/// Serializes with any serde format, the `json` and `yaml` features add shortcuts.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Schema {
+    pub name: String,
+    pub size: usize,
+    pub fields: Vec<SchemaField>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SchemaField {
+    pub name: String,
+    pub offset: usize,
+    pub size: usize,
+    /// The `type` name of `#[bm(...)]`, None for untyped fields
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub type_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+}
+
+/// One difference between the compiled layout and a published schema
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaMismatch {
+    Size { expected: usize, actual: usize },
+    /// A compiled field the published schema doesn't have
+    MissingField { field: String },
+    /// A published field the compiled layout doesn't have
+    UnknownField { field: String },
+    Offset { field: String, expected: usize, actual: usize },
+    FieldSize { field: String, expected: usize, actual: usize },
+    Type {
+        field: String,
+        expected: Option<String>,
+        actual: Option<String>,
+    },
+    Format {
+        field: String,
+        expected: Option<String>,
+        actual: Option<String>,
+    },
+}
+
+impl SchemaMismatch {
+    /// The compiled layout is the old side of the change, the published schema the new one
+    fn from_change(change: FieldChange) -> Self {
+        match change {
+            FieldChange::Added { field, .. } => Self::UnknownField { field },
+            FieldChange::Removed { field, .. } => Self::MissingField { field },
+            FieldChange::Moved { field, from, to } => Self::Offset {
+                field,
+                expected: from,
+                actual: to,
+            },
+            FieldChange::Resized { field, from, to } => Self::FieldSize {
+                field,
+                expected: from,
+                actual: to,
+            },
+            FieldChange::Retyped { field, from, to } => Self::Type {
+                field,
+                expected: from,
+                actual: to,
+            },
+            FieldChange::Reformatted { field, from, to } => Self::Format {
+                field,
+                expected: from,
+                actual: to,
+            },
+        }
+    }
+}
+
+pub(crate) fn or_none(value: &Option<String>) -> &str {
+    value.as_deref().unwrap_or("none")
+}
+
+impl fmt::Display for SchemaMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Size { expected, actual } => write!(f, "size is {} instead of {}", actual, expected),
+            Self::MissingField { field } => write!(f, "field `{}` is missing", field),
+            Self::UnknownField { field } => write!(f, "field `{}` is unknown", field),
+            Self::Offset {
+                field,
+                expected,
+                actual,
+            } => write!(f, "field `{}` is at offset {} instead of {}", field, actual, expected),
+            Self::FieldSize {
+                field,
+                expected,
+                actual,
+            } => write!(f, "field `{}` is {} bytes instead of {}", field, actual, expected),
+            Self::Type {
+                field,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "field `{}` has type {} instead of {}",
+                field,
+                or_none(actual),
+                or_none(expected)
+            ),
+            Self::Format {
+                field,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "field `{}` has format {} instead of {}",
+                field,
+                or_none(actual),
+                or_none(expected)
+            ),
+        }
+    }
+}
+
+fn push_fields(fields: &mut Vec<SchemaField>, descriptor: &StructDescriptor, prefix: &str, offset: usize) {
+    for field in descriptor.fields {
+        let name = format!("{}{}", prefix, field.name);
+        if let Some(nested) = field.nested {
+            push_fields(fields, nested(), &format!("{}.", name), offset + field.offset);
+            continue;
+        }
+        fields.push(SchemaField {
+            name,
+            offset: offset + field.offset,
+            size: field.size,
+            type_name: field.kind.type_name().map(str::to_string),
+            format: field.format.map(str::to_string),
+        });
+    }
+}
+
+/// Every difference found by [`Schema::check`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaError {
+    pub(crate) name: String,
+    pub(crate) mismatches: Vec<SchemaMismatch>,
+}
+
+impl SchemaError {
+    pub fn mismatches(&self) -> &[SchemaMismatch] {
+        &self.mismatches
+    }
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "schema of {} doesn't match: ", self.name)?;
+        for (i, mismatch) in self.mismatches.iter().enumerate() {
+            if i > 0 {
+                f.write_str("; ")?;
+            }
+            mismatch.fmt(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SchemaError {}
+
+impl Schema {
+    /// Flattened fields are replaced by the fields of their struct, named `outer.inner`
+    pub fn from_descriptor(descriptor: &StructDescriptor) -> Self {
+        let mut fields = Vec::new();
+        push_fields(&mut fields, descriptor, "", 0);
+        Self {
+            name: descriptor.name.to_string(),
+            size: descriptor.size,
+            fields,
+        }
+    }
+
+    pub fn field(&self, name: &str) -> Option<&SchemaField> {
+        self.fields.iter().find(|field| field.name == name)
+    }
+
+    /// Check that a `published` schema describes this layout, matching fields by name
+    ///
+    /// The struct names may differ. Err lists every mismatch, in the order of this schema's
+    /// fields followed by the fields only `published` has.
+    pub fn check(&self, published: &Schema) -> Result<(), SchemaError> {
+        let report = compat::diff(self, published);
+        let mut mismatches = Vec::new();
+        if report.from_size != report.to_size {
+            mismatches.push(SchemaMismatch::Size {
+                expected: report.from_size,
+                actual: report.to_size,
+            });
+        }
+        mismatches.extend(report.changes.into_iter().map(SchemaMismatch::from_change));
+        if mismatches.is_empty() {
+            return Ok(());
+        }
+        Err(SchemaError {
+            name: self.name.clone(),
+            mismatches,
+        })
+    }
+
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> String {
+        // Only strings and integers, which always serialize
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    #[cfg(feature = "json")]
+    pub fn from_json(text: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(text)
+    }
+
+    #[cfg(feature = "yaml")]
+    pub fn to_yaml(&self) -> String {
+        // Only strings and integers, which always serialize
+        serde_yaml_ng::to_string(self).unwrap()
+    }
+
+    #[cfg(feature = "yaml")]
+    pub fn from_yaml(text: &str) -> Result<Self, serde_yaml_ng::Error> {
+        serde_yaml_ng::from_str(text)
+    }
+}
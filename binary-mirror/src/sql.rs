@@ -0,0 +1,56 @@
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+/// A native field value ready to bind as an SQL statement parameter
+#[derive(Debug, Clone, PartialEq)]
+pub enum SqlValue {
+    Null,
+    Int(i64),
+    Float(f64),
+    /// Text, and decimals, dates, times and enums in their text form
+    Text(String),
+    Bytes(Vec<u8>),
+}
+
+impl<T: Into<SqlValue>> From<Option<T>> for SqlValue {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(SqlValue::Null, Into::into)
+    }
+}
+
+macro_rules! impl_from_int {
+    ($($ty:ty),*) => {
+        $(impl From<$ty> for SqlValue {
+            fn from(value: $ty) -> Self {
+                SqlValue::Int(value as i64)
+            }
+        })*
+    };
+}
+
+impl_from_int!(i16, i32, i64, u16, u32);
+
+impl From<u64> for SqlValue {
+    fn from(value: u64) -> Self {
+        // Bound as text since it may not fit in a signed 64-bit column
+        SqlValue::Text(value.to_string())
+    }
+}
+
+impl From<f32> for SqlValue {
+    fn from(value: f32) -> Self {
+        SqlValue::Float(value as f64)
+    }
+}
+
+impl From<f64> for SqlValue {
+    fn from(value: f64) -> Self {
+        SqlValue::Float(value)
+    }
+}
+
+impl From<String> for SqlValue {
+    fn from(value: String) -> Self {
+        SqlValue::Text(value)
+    }
+}
@@ -0,0 +1,85 @@
+//! Async stream of fixed-length records read from a tokio `AsyncRead`.
+
+use std::io;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+
+pub use futures_core::Stream;
+use tokio::io::{AsyncRead, ReadBuf};
+
+use crate::{FromBytes, Framing};
+
+/// Stream of owned records read one frame at a time, see [`read_records`]
+pub struct RecordStream<R, T> {
+    reader: R,
+    buf: Vec<u8>,
+    filled: usize,
+    done: bool,
+    _marker: PhantomData<fn() -> T>,
+}
+
+/// Read records from `reader` until EOF
+///
+/// Partial reads are buffered until a whole frame (the record plus the framing of `T::FRAME`)
+/// is available. EOF in the middle of a frame yields one `UnexpectedEof` error before the
+/// stream ends.
+pub fn read_records<T: FromBytes + Framing, R: AsyncRead>(reader: R) -> RecordStream<R, T> {
+    RecordStream {
+        reader,
+        buf: vec![0; T::FRAME.frame_len(T::SIZE)],
+        filled: 0,
+        done: false,
+        _marker: PhantomData,
+    }
+}
+
+impl<R, T> RecordStream<R, T> {
+    /// Get back the underlying reader
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R: AsyncRead + Unpin, T: FromBytes + Framing> Stream for RecordStream<R, T> {
+    type Item = io::Result<T>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<io::Result<T>>> {
+        let this = self.get_mut();
+        if this.done {
+            return Poll::Ready(None);
+        }
+        while this.filled < this.buf.len() {
+            let mut read_buf = ReadBuf::new(&mut this.buf[this.filled..]);
+            if let Err(err) = ready!(Pin::new(&mut this.reader).poll_read(cx, &mut read_buf)) {
+                this.done = true;
+                return Poll::Ready(Some(Err(err)));
+            }
+            let read = read_buf.filled().len();
+            if read == 0 {
+                this.done = true;
+                if this.filled == 0 {
+                    return Poll::Ready(None);
+                }
+                return Poll::Ready(Some(Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    format!(
+                        "stream ended after {} of {} bytes, content: \"{}\"",
+                        this.filled,
+                        this.buf.len(),
+                        crate::to_bytes_repr(&this.buf[..this.filled])
+                    ),
+                ))));
+            }
+            this.filled += read;
+        }
+        this.filled = 0;
+        let record = match T::FRAME.strip(&this.buf, T::SIZE) {
+            Ok(record) => record,
+            Err(err) => return Poll::Ready(Some(Err(io::Error::new(io::ErrorKind::InvalidData, err)))),
+        };
+        Poll::Ready(Some(
+            T::from_bytes_owned(record).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err)),
+        ))
+    }
+}
@@ -0,0 +1,52 @@
+use core::fmt;
+
+/// Text formatted on the stack through `core::fmt::Write`, cut at `N` bytes
+///
+/// Setters, builders and `from_native` format numbers, dates and times into it before
+/// copying them into a field, so encoding a record doesn't allocate.
+#[derive(Clone, Copy)]
+pub struct TextBuf<const N: usize> {
+    bytes: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> TextBuf<N> {
+    pub const fn new() -> Self {
+        Self { bytes: [0; N], len: 0 }
+    }
+
+    /// The text written so far
+    pub fn as_str(&self) -> &str {
+        // writes are cut at a char boundary, so the bytes are always valid
+        core::str::from_utf8(&self.bytes[..self.len]).unwrap_or_default()
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+}
+
+impl<const N: usize> Default for TextBuf<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> fmt::Write for TextBuf<N> {
+    /// Append as much of `s` as fits, text past `N` bytes is dropped rather than an error
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let mut end = s.len().min(N - self.len);
+        while !s.is_char_boundary(end) {
+            end -= 1;
+        }
+        self.bytes[self.len..self.len + end].copy_from_slice(&s.as_bytes()[..end]);
+        self.len += end;
+        Ok(())
+    }
+}
+
+impl<const N: usize> fmt::Debug for TextBuf<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
@@ -0,0 +1,3 @@
+//! YAML schemas, enabled with the `yaml` feature
+
+pub use serde_yaml_ng;